@@ -0,0 +1,78 @@
+// Bullet hit testing with thousands of bullets flying through a field of
+// targets, the collision work that grows fastest with a busy screen. Run
+// with `cargo bench --bench collision`.
+
+mod common;
+
+use common::bench;
+use raylib::prelude::*;
+use spacegame::movement::Velocity;
+use spacegame::projectile::Projectile;
+use spacegame::team::Team;
+use spacegame::{Base2D, Health, UpdateSystem, World};
+
+const BULLETS: [usize; 3] = [1_000, 2_000, 5_000];
+const TARGETS: [usize; 2] = [50, 200];
+const DT: f32 = 1.0 / 60.0;
+/// Side of the square the targets and bullets are spread over.
+const FIELD: f32 = 2000.0;
+
+/// `targets` hostile ships on a grid and `bullets` friendly bullets flying
+/// up through them, spread evenly so a few hit every frame.
+fn field(targets: usize, bullets: usize) -> World {
+    let mut world = World::new();
+    let owner = world.new_entity();
+    world.team_components.push((owner, Team::Friendly));
+
+    let side = (targets as f32).sqrt().ceil() as usize;
+    for i in 0..targets {
+        let id = world.new_entity();
+        let pos = Vector2::new(
+            (i % side) as f32 / side as f32 * FIELD,
+            (i / side) as f32 / side as f32 * FIELD,
+        );
+        world
+            .base_components
+            .push((id, Base2D::new(pos, Vector2::new(32.0, 24.0))));
+        world.health_components.push((id, Health::new(1000)));
+        world.team_components.push((id, Team::Hostile));
+    }
+
+    let side = (bullets as f32).sqrt().ceil() as usize;
+    for i in 0..bullets {
+        let pos = Vector2::new(
+            (i % side) as f32 / side as f32 * FIELD,
+            (i / side) as f32 / side as f32 * FIELD,
+        );
+        Projectile::spawn(&mut world, owner, pos, Vector2::new(0.0, -600.0), 1, false);
+    }
+    world
+}
+
+fn main() {
+    for targets in TARGETS {
+        for bullets in BULLETS {
+            let name = format!("hit test, {} targets", targets);
+            bench(
+                &name,
+                bullets,
+                || field(targets, bullets),
+                |w| Projectile::update_system(w, DT),
+            );
+
+            let name = format!("3 frames move and hit, {} targets", targets);
+            bench(
+                &name,
+                bullets,
+                || field(targets, bullets),
+                |w| {
+                    for _ in 0..3 {
+                        Velocity::update_system(w, DT);
+                        Projectile::update_system(w, DT);
+                        w.end_frame();
+                    }
+                },
+            );
+        }
+    }
+}
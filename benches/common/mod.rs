@@ -0,0 +1,33 @@
+// A small stand-in for a benchmark framework, the benches only need a
+// mean time per run. Shared by the files in benches/, each of which is
+// a `harness = false` target run with `cargo bench --bench <name>`.
+
+use std::time::{Duration, Instant};
+
+/// Measured time each benchmark runs for, at least.
+const TARGET: Duration = Duration::from_millis(250);
+/// Fewest measured runs, however slow one is.
+const MIN_RUNS: u32 = 3;
+
+/// Times `run` on a fresh value from `setup` each time, only `run` is
+/// measured. Prints the mean time per run once it ran for `TARGET`.
+pub fn bench<S>(
+    name: &str,
+    count: usize,
+    mut setup: impl FnMut() -> S,
+    mut run: impl FnMut(&mut S),
+) {
+    // warm up
+    run(&mut setup());
+    let mut measured = Duration::ZERO;
+    let mut runs = 0;
+    while runs < MIN_RUNS || measured < TARGET {
+        let mut state = setup();
+        let start = Instant::now();
+        run(&mut state);
+        measured += start.elapsed();
+        runs += 1;
+    }
+    let per_run = measured.as_secs_f64() / runs as f64;
+    println!("{:<32} {:>6} {:>12.1} us", name, count, per_run * 1e6);
+}
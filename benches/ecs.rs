@@ -0,0 +1,91 @@
+// Entity spawn and despawn throughput and query iteration at a few world
+// sizes. Run with `cargo bench --bench ecs`.
+
+mod common;
+
+use common::bench;
+use raylib::prelude::*;
+use spacegame::movement::Velocity;
+use spacegame::plugin::GamePlugins;
+use spacegame::prefab::{Placement, Prefabs};
+use spacegame::projectile::Projectile;
+use spacegame::{BoundingBox2D, Engine, EntityID, UpdateSystem, World};
+
+const COUNTS: [usize; 3] = [1_000, 5_000, 10_000];
+const DT: f32 = 1.0 / 60.0;
+
+fn position(i: usize) -> Vector2 {
+    Vector2::new((i % 100) as f32 * 40.0, (i / 100) as f32 * 40.0)
+}
+
+/// `count` drones, moving.
+fn drones(count: usize) -> World {
+    let mut world = World::new();
+    for i in 0..count {
+        let pos = position(i);
+        let placement = Placement::new(BoundingBox2D::new(pos.x, pos.y, 0.0, 0.0));
+        if let Some(id) = Prefabs::spawn(&mut world, "Drone", &placement) {
+            let mut velocity = Velocity::new(0.0);
+            velocity.velocity = Vector2::new(20.0, 10.0);
+            world.velocity_components.push((id, velocity));
+        }
+    }
+    world
+}
+
+fn main() {
+    for count in COUNTS {
+        bench("spawn drones", count, World::new, |w| {
+            for i in 0..count {
+                let pos = position(i);
+                let placement = Placement::new(BoundingBox2D::new(pos.x, pos.y, 0.0, 0.0));
+                Prefabs::spawn(w, "Drone", &placement);
+            }
+        });
+
+        bench(
+            "despawn drones",
+            count,
+            || {
+                let w = drones(count);
+                let ids: Vec<EntityID> = w.base_components.iter().map(|b| b.0).collect();
+                (w, ids)
+            },
+            |(w, ids)| {
+                for id in ids.iter() {
+                    w.despawn(*id);
+                }
+            },
+        );
+
+        // the pools hand the parts of the first round to the second
+        bench("spawn and despawn bullets", count, World::new, |w| {
+            for _ in 0..2 {
+                let ids: Vec<EntityID> = (0..count)
+                    .map(|i| Projectile::spawn(w, 0, position(i), Vector2::zero(), 1, false))
+                    .collect();
+                for id in ids {
+                    w.despawn(id);
+                }
+            }
+        });
+
+        bench(
+            "Velocity system",
+            count,
+            || drones(count),
+            |w| Velocity::update_system(w, DT),
+        );
+    }
+
+    let mut engine = Engine::new();
+    engine.add_plugin(GamePlugins);
+    for count in [100, 1_000, 5_000] {
+        bench(
+            "frame of GamePlugins",
+            count,
+            || drones(count),
+            |w| engine.simulate(w, 1, DT),
+        );
+    }
+}
@@ -0,0 +1,89 @@
+// Base2D + Velocity iteration and Base2D lookups at 10k+ entities, the
+// old scan per entity against the sorted storage joins of `group`. Run
+// with `cargo bench --bench storage`.
+
+mod common;
+
+use std::hint::black_box;
+
+use common::bench;
+use raylib::prelude::*;
+use spacegame::group;
+use spacegame::movement::Velocity;
+use spacegame::{Base2D, EntityID, World};
+
+const COUNTS: [usize; 3] = [1_000, 10_000, 20_000];
+const DT: f32 = 1.0 / 60.0;
+
+/// `count` entities with a Base2D, every other one moving, the way ships
+/// and scenery end up mixed in a level.
+fn world(count: usize) -> World {
+    let mut world = World::new();
+    for i in 0..count {
+        let id = world.new_entity();
+        let pos = Vector2::new((i % 200) as f32 * 8.0, (i / 200) as f32 * 8.0);
+        world
+            .base_components
+            .push((id, Base2D::new(pos, Vector2::new(4.0, 4.0))));
+        if i % 2 == 0 {
+            let mut velocity = Velocity::new(0.0);
+            velocity.velocity = Vector2::new(10.0, 5.0);
+            world.velocity_components.push((id, velocity));
+        }
+    }
+    world
+}
+
+fn main() {
+    for count in COUNTS {
+        bench(
+            "move, scan per entity",
+            count,
+            || world(count),
+            |w| {
+                for (id, vel) in w.velocity_components.iter() {
+                    if let Some(b) = w.base_components.iter_mut().find(|b| b.0 == *id) {
+                        b.1.bounds.translate(vel.velocity * DT);
+                    }
+                }
+            },
+        );
+
+        bench(
+            "move, joined",
+            count,
+            || world(count),
+            |w| {
+                w.sort_groups();
+                group::join_mut(
+                    &mut w.velocity_components,
+                    &mut w.base_components,
+                    |_, vel, b| b.bounds.translate(vel.velocity * DT),
+                );
+            },
+        );
+
+        let w = world(count);
+        let ids: Vec<EntityID> = w.velocity_components.iter().map(|v| v.0).collect();
+        bench(
+            "lookup, scan",
+            count,
+            || (),
+            |_| {
+                for id in ids.iter() {
+                    black_box(w.base_components.iter().find(|b| b.0 == *id));
+                }
+            },
+        );
+        bench(
+            "lookup, binary search",
+            count,
+            || (),
+            |_| {
+                for id in ids.iter() {
+                    black_box(group::find(&w.base_components, *id));
+                }
+            },
+        );
+    }
+}
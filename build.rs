@@ -0,0 +1,40 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// With the `embed-assets` feature, lists every file under `assets/` for
+/// `pack.rs` to include in the executable. Without it the list is empty and
+/// the game reads loose files.
+fn main() {
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("embedded_assets.rs");
+    let mut files = Vec::new();
+    if env::var_os("CARGO_FEATURE_EMBED_ASSETS").is_some() {
+        println!("cargo:rerun-if-changed=assets");
+        collect(Path::new("assets"), &mut files);
+        files.sort();
+    }
+
+    let mut code = String::from("pub static EMBEDDED: &[(&str, &[u8])] = &[\n");
+    for file in files {
+        let key = file.to_string_lossy().replace('\\', "/");
+        let full = fs::canonicalize(&file).unwrap();
+        code += &format!("    ({:?}, include_bytes!({:?})),\n", key, full);
+    }
+    code += "];\n";
+    fs::write(out, code).unwrap();
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
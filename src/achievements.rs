@@ -0,0 +1,344 @@
+use std::fs;
+use std::path::PathBuf;
+
+use raylib::prelude::*;
+
+use crate::heat::HeatEvent;
+use crate::renderer::Renderer;
+use crate::team::Team;
+use crate::World;
+use crate::{t, warn};
+
+const TOAST_TIME: f32 = 4.0;
+const TOAST_SLIDE: f32 = 0.35;
+const TOAST_WIDTH: f32 = 220.0;
+const TOAST_HEIGHT: f32 = 44.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stat {
+    Kills,
+    ShotsFired,
+    Overheats,
+    /// Highest campaign wave number cleared without the player taking
+    /// damage during it.
+    UntouchedWave,
+}
+
+impl Stat {
+    fn values() -> Vec<Stat> {
+        vec![
+            Stat::Kills,
+            Stat::ShotsFired,
+            Stat::Overheats,
+            Stat::UntouchedWave,
+        ]
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Stat::Kills => "kills",
+            Stat::ShotsFired => "shots_fired",
+            Stat::Overheats => "overheats",
+            Stat::UntouchedWave => "untouched_wave",
+        }
+    }
+}
+
+/// Titled by the `achievements.<id>` string, described by
+/// `achievements.<id>_description`.
+struct Achievement {
+    id: &'static str,
+    stat: Stat,
+    goal: u32,
+}
+
+const ACHIEVEMENTS: [Achievement; 6] = [
+    Achievement {
+        id: "first_blood",
+        stat: Stat::Kills,
+        goal: 1,
+    },
+    Achievement {
+        id: "exterminator",
+        stat: Stat::Kills,
+        goal: 100,
+    },
+    Achievement {
+        id: "trigger_happy",
+        stat: Stat::ShotsFired,
+        goal: 1000,
+    },
+    Achievement {
+        id: "meltdown",
+        stat: Stat::Overheats,
+        goal: 1,
+    },
+    Achievement {
+        id: "slow_learner",
+        stat: Stat::Overheats,
+        goal: 25,
+    },
+    Achievement {
+        id: "untouchable",
+        stat: Stat::UntouchedWave,
+        goal: 10,
+    },
+];
+
+struct Toast {
+    id: &'static str,
+    age: f32,
+}
+
+/// Lifetime progress and unlocked achievements, kept in a small text file
+/// with one `stat <key> <value>` or `unlocked <id>` entry per line.
+pub struct Achievements {
+    path: PathBuf,
+    stats: Vec<(Stat, u32)>,
+    unlocked: Vec<&'static str>,
+    toasts: Vec<Toast>,
+    /// Campaign wave seen last frame, and the damage the player took in it.
+    wave: Option<usize>,
+    wave_damage: u32,
+}
+
+impl Achievements {
+    pub fn load(path: impl Into<PathBuf>) -> Achievements {
+        let mut achievements = Achievements {
+            path: path.into(),
+            stats: Stat::values().into_iter().map(|s| (s, 0)).collect(),
+            unlocked: Vec::new(),
+            toasts: Vec::new(),
+            wave: None,
+            wave_damage: 0,
+        };
+
+        let contents = fs::read_to_string(&achievements.path).unwrap_or_default();
+        for line in contents.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                ["stat", key, value] => {
+                    if let Some(s) = achievements.stats.iter_mut().find(|s| s.0.key() == *key) {
+                        s.1 = value.parse().unwrap_or(0);
+                    }
+                }
+                ["unlocked", id] => {
+                    if let Some(a) = ACHIEVEMENTS.iter().find(|a| a.id == *id) {
+                        achievements.unlocked.push(a.id);
+                    }
+                }
+                _ => (),
+            }
+        }
+        achievements
+    }
+
+    pub fn save(&self) {
+        let mut contents = String::new();
+        for (stat, value) in self.stats.iter() {
+            contents += &format!("stat {} {}\n", stat.key(), value);
+        }
+        for id in self.unlocked.iter() {
+            contents += &format!("unlocked {}\n", id);
+        }
+        if let Err(e) = fs::write(&self.path, contents) {
+            warn!(
+                "achievements",
+                "could not save achievements",
+                path = self.path.display(),
+                error = e
+            );
+        }
+    }
+
+    /// Every achievement's title and whether it's unlocked.
+    pub fn list(&self) -> Vec<(String, bool)> {
+        ACHIEVEMENTS
+            .iter()
+            .map(|a| (title(a.id), self.unlocked.contains(&a.id)))
+            .collect()
+    }
+
+    fn add(&mut self, stat: Stat, amount: u32) {
+        if let Some(s) = self.stats.iter_mut().find(|s| s.0 == stat) {
+            s.1 += amount;
+        }
+    }
+
+    /// For stats that keep a best rather than a total.
+    fn raise(&mut self, stat: Stat, value: u32) {
+        if let Some(s) = self.stats.iter_mut().find(|s| s.0 == stat) {
+            s.1 = s.1.max(value);
+        }
+    }
+
+    /// Adds up the damage taken in the wave under way, and counts the wave
+    /// as untouched if it's cleared without any.
+    fn track_wave(&mut self, wave: Option<usize>, damage_taken: u32) {
+        self.wave_damage += damage_taken;
+        if wave == self.wave {
+            return;
+        }
+        if let (Some(before), Some(now)) = (self.wave, wave) {
+            if now == before + 1 && self.wave_damage == 0 {
+                self.raise(Stat::UntouchedWave, now as u32);
+            }
+        }
+        self.wave = wave;
+        self.wave_damage = 0;
+    }
+
+    fn stat(&self, stat: Stat) -> u32 {
+        self.stats
+            .iter()
+            .find(|s| s.0 == stat)
+            .map(|s| s.1)
+            .unwrap_or(0)
+    }
+
+    pub fn update_system(&mut self, world: &World, dt: f32) {
+        let kills = world
+            .death_events
+            .iter()
+            .filter(|e| {
+                let by_player = e
+                    .killer
+                    .is_some_and(|k| world.player_components.iter().any(|p| p.0 == k));
+                let hostile = world
+                    .team_components
+                    .iter()
+                    .any(|t| t.0 == e.entity && t.1 == Team::Hostile);
+                by_player && hostile
+            })
+            .count();
+        let shots = world
+            .shot_events
+            .iter()
+            .filter(|e| world.player_components.iter().any(|p| p.0 == e.weapon))
+            .count();
+        let overheats = world
+            .heat_events
+            .iter()
+            .filter(|e| matches!(e.1, HeatEvent::Overheated))
+            .count();
+
+        self.add(Stat::Kills, kills as u32);
+        self.add(Stat::ShotsFired, shots as u32);
+        self.add(Stat::Overheats, overheats as u32);
+        // amounts are what was dealt by now, see `DamageEvent`
+        let damage_taken: u32 = world
+            .damage_events
+            .iter()
+            .filter(|e| world.player_components.iter().any(|p| p.0 == e.target))
+            .map(|e| e.amount as u32)
+            .sum();
+        self.track_wave(world.campaign.wave(), damage_taken);
+
+        let mut changed = false;
+        for achievement in ACHIEVEMENTS.iter() {
+            if self.unlocked.contains(&achievement.id)
+                || self.stat(achievement.stat) < achievement.goal
+            {
+                continue;
+            }
+            self.unlocked.push(achievement.id);
+            self.toasts.push(Toast {
+                id: achievement.id,
+                age: 0.0,
+            });
+            changed = true;
+        }
+        if changed {
+            self.save();
+        }
+
+        // only the oldest toast is on screen, the rest wait their turn
+        if let Some(toast) = self.toasts.first_mut() {
+            toast.age += dt;
+            if toast.age >= TOAST_TIME {
+                self.toasts.remove(0);
+            }
+        }
+    }
+
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        let toast = match self.toasts.first() {
+            Some(t) => t,
+            None => return,
+        };
+
+        let slide = (toast.age / TOAST_SLIDE)
+            .min((TOAST_TIME - toast.age) / TOAST_SLIDE)
+            .clamp(0.0, 1.0);
+        // ease out cubic
+        let shown = 1.0 - (1.0 - slide).powi(3);
+
+        let x = world.screen.width() - TOAST_WIDTH * shown;
+        let y = world.screen.height() - TOAST_HEIGHT - 10.0;
+        let rect = Rectangle::new(x, y, TOAST_WIDTH, TOAST_HEIGHT);
+        d.draw_rect(rect, Color::BLACK.fade(0.85));
+        d.draw_rect_lines(rect, 1, Color::GOLD);
+        d.draw_text(
+            &title(toast.id),
+            x as i32 + 10,
+            y as i32 + 6,
+            16,
+            Color::GOLD,
+        );
+        d.draw_text(
+            &t!(&format!("achievements.{}_description", toast.id)),
+            x as i32 + 10,
+            y as i32 + 26,
+            12,
+            Color::LIGHTGRAY,
+        );
+    }
+}
+
+fn title(id: &str) -> String {
+    t!(&format!("achievements.{}", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_waves_cleared_without_damage() {
+        let mut achievements = Achievements::load(PathBuf::new());
+        let untouched = |a: &Achievements| a.stat(Stat::UntouchedWave);
+
+        achievements.track_wave(Some(0), 0);
+        achievements.track_wave(Some(0), 0);
+        achievements.track_wave(Some(1), 0);
+        assert_eq!(untouched(&achievements), 1);
+
+        // hit during the second wave, even if only at its start
+        achievements.track_wave(Some(1), 3);
+        achievements.track_wave(Some(1), 0);
+        achievements.track_wave(Some(2), 0);
+        assert_eq!(untouched(&achievements), 1);
+        achievements.track_wave(Some(3), 0);
+        assert_eq!(untouched(&achievements), 3);
+
+        // a new level starting over doesn't lower the best
+        achievements.track_wave(None, 0);
+        achievements.track_wave(Some(0), 0);
+        achievements.track_wave(Some(1), 0);
+        assert_eq!(untouched(&achievements), 3);
+
+        for wave in 2..=10 {
+            achievements.track_wave(Some(wave), 0);
+        }
+        assert_eq!(untouched(&achievements), 10);
+    }
+
+    #[test]
+    fn every_achievement_has_strings() {
+        for a in ACHIEVEMENTS.iter() {
+            let description = format!("achievements.{}_description", a.id);
+            assert_ne!(title(a.id), format!("achievements.{}", a.id));
+            assert_ne!(t!(&description), description);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use raylib::prelude::*;
+
+use crate::atlas::Atlas;
+use crate::{EntityID, UpdateSystem, World};
+
+#[derive(Clone)]
+pub struct Clip {
+    pub frames: Vec<Rectangle>,
+    /// Seconds each frame stays up, one entry per frame.
+    pub durations: Vec<f32>,
+    pub looping: bool,
+}
+
+impl Clip {
+    /// Clip of `count` equally sized frames laid out left to right.
+    pub fn from_strip(
+        origin: Vector2,
+        frame_size: Vector2,
+        count: usize,
+        fps: f32,
+        looping: bool,
+    ) -> Clip {
+        let frames = (0..count)
+            .map(|i| {
+                Rectangle::new(
+                    origin.x + frame_size.x * i as f32,
+                    origin.y,
+                    frame_size.x,
+                    frame_size.y,
+                )
+            })
+            .collect();
+        let duration = if fps > 0.0 { 1.0 / fps } else { 0.0 };
+        Clip {
+            frames,
+            durations: vec![duration; count],
+            looping,
+        }
+    }
+}
+
+/// Named set of clips played onto the entity's Sprite. The first clip added
+/// is the default one that non-looping clips fall back to once finished.
+pub struct Animation {
+    clips: Vec<(String, Clip)>,
+    current: usize,
+    frame: usize,
+    timer: f32,
+}
+
+/// Asks the entity's Animation to switch to the named clip.
+pub struct AnimationEvent {
+    pub entity: EntityID,
+    pub clip: &'static str,
+}
+
+impl Animation {
+    pub fn new(name: &str, clip: Clip) -> Animation {
+        Animation {
+            clips: vec![(name.to_string(), clip)],
+            current: 0,
+            frame: 0,
+            timer: 0.0,
+        }
+    }
+
+    /// Animation with every clip of an atlas, e.g. the tags of an Aseprite
+    /// export. None when the atlas has no clips.
+    pub fn from_atlas(atlas: &Atlas) -> Option<Animation> {
+        let (first, rest) = atlas.clips().split_first()?;
+        let mut animation = Animation::new(&first.0, first.1.clone());
+        for (name, clip) in rest {
+            animation = animation.with_clip(name, clip.clone());
+        }
+        Some(animation)
+    }
+
+    pub fn with_clip(mut self, name: &str, clip: Clip) -> Animation {
+        self.clips.push((name.to_string(), clip));
+        self
+    }
+
+    /// Switches clips, restarting only when it isn't already playing. A
+    /// running one-shot clip can't be cut short by a looping one.
+    pub fn play(&mut self, name: &str) {
+        let index = match self.clips.iter().position(|c| c.0 == name) {
+            Some(i) => i,
+            None => return,
+        };
+        if !self.clips[self.current].1.looping && self.clips[index].1.looping {
+            return;
+        }
+        if index != self.current {
+            self.current = index;
+            self.frame = 0;
+            self.timer = 0.0;
+        }
+    }
+
+    pub fn current_frame(&self) -> Option<Rectangle> {
+        self.clips[self.current].1.frames.get(self.frame).copied()
+    }
+
+    fn advance(&mut self, dt: f32) {
+        let clip = &self.clips[self.current].1;
+        if clip.frames.is_empty() {
+            return;
+        }
+
+        self.timer += dt;
+        loop {
+            let frame_time = clip.durations.get(self.frame).copied().unwrap_or(0.0);
+            if frame_time <= 0.0 || self.timer < frame_time {
+                break;
+            }
+            self.timer -= frame_time;
+            if self.frame + 1 < clip.frames.len() {
+                self.frame += 1;
+            } else if clip.looping {
+                self.frame = 0;
+            } else {
+                self.current = 0;
+                self.frame = 0;
+                self.timer = 0.0;
+                break;
+            }
+        }
+    }
+}
+
+impl UpdateSystem for Animation {
+    fn update_system(world: &mut World, dt: f32) {
+        for event in world.animation_events.iter() {
+            if let Some(a) = world
+                .animation_components
+                .iter_mut()
+                .find(|a| a.0 == event.entity)
+            {
+                a.1.play(event.clip);
+            }
+        }
+
+        for (id, animation) in world.animation_components.iter_mut() {
+            animation.advance(dt);
+
+            let frame = match animation.current_frame() {
+                Some(f) => f,
+                None => continue,
+            };
+            if let Some(s) = world.sprite_components.iter_mut().find(|s| s.0 == *id) {
+                s.1.source = frame;
+            }
+        }
+    }
+}
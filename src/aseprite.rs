@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::animation::Clip;
+use crate::json::Json;
+use crate::pack;
+
+/// One frame of the sheet, `duration` in seconds.
+pub struct AsepriteFrame {
+    pub name: String,
+    pub rect: Rectangle,
+    pub duration: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+    PingPong,
+}
+
+/// Frame tag, `from` and `to` are inclusive frame indices.
+pub struct AsepriteTag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub direction: Direction,
+    pub looping: bool,
+}
+
+/// JSON data of an Aseprite "Export Sprite Sheet", in either the hash or
+/// the array layout. Export untrimmed, trimmed frames would need their
+/// offsets applied and don't get them.
+///
+/// The pivot comes from the first slice that has one set and is stored
+/// relative to the frame size, like `Sprite::origin`.
+pub struct AsepriteFile {
+    /// Path of the sheet image, relative to the working directory.
+    pub image: String,
+    pub frames: Vec<AsepriteFrame>,
+    pub tags: Vec<AsepriteTag>,
+    pub pivot: Option<Vector2>,
+}
+
+impl AsepriteFile {
+    pub fn load(path: &str) -> Result<AsepriteFile, String> {
+        let contents =
+            pack::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        AsepriteFile::parse(&contents, dir).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Parses the exported JSON, the image path is resolved against `dir`.
+    pub fn parse(contents: &str, dir: &Path) -> Result<AsepriteFile, String> {
+        let json = Json::parse(contents)?;
+        let meta = json.get("meta").ok_or("no meta")?;
+        let image = meta
+            .get("image")
+            .and_then(Json::as_str)
+            .ok_or("no meta.image")?;
+        let image = dir.join(image).to_string_lossy().into_owned();
+
+        // the hash layout keys frames by filename, the array one stores
+        // the filename inside each frame
+        let frames = match json.get("frames") {
+            Some(Json::Object(members)) => members
+                .iter()
+                .map(|(name, frame)| parse_frame(name, frame))
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(Json::Array(items)) => items
+                .iter()
+                .enumerate()
+                .map(|(i, frame)| {
+                    let name = frame
+                        .get("filename")
+                        .and_then(Json::as_str)
+                        .map(str::to_string)
+                        .unwrap_or(i.to_string());
+                    parse_frame(&name, frame)
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err("no frames".to_string()),
+        };
+
+        let mut tags = Vec::new();
+        for tag in meta
+            .get("frameTags")
+            .and_then(Json::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let name = tag.get("name").and_then(Json::as_str).ok_or("tag name")?;
+            let index = |key: &str| {
+                tag.get(key)
+                    .and_then(Json::as_i64)
+                    .filter(|i| *i >= 0 && (*i as usize) < frames.len())
+                    .map(|i| i as usize)
+                    .ok_or(format!("tag {}: bad {}", name, key))
+            };
+            let (from, to) = (index("from")?, index("to")?);
+            let direction = match tag.get("direction").and_then(Json::as_str) {
+                Some("reverse") => Direction::Reverse,
+                Some("pingpong") | Some("pingpong_reverse") => Direction::PingPong,
+                _ => Direction::Forward,
+            };
+            // newer exports have a repeat count, "0" or none means forever
+            let looping = tag
+                .get("repeat")
+                .and_then(Json::as_str)
+                .is_none_or(|r| r == "0");
+            tags.push(AsepriteTag {
+                name: name.to_string(),
+                from: from.min(to),
+                to: from.max(to),
+                direction,
+                looping,
+            });
+        }
+
+        let pivot = frames.first().and_then(|first| {
+            meta.get("slices")?.as_array()?.iter().find_map(|slice| {
+                let key = slice.get("keys")?.as_array()?.first()?;
+                let bounds = key.get("bounds")?;
+                let pivot = key.get("pivot")?;
+                let x = bounds.get("x")?.as_f32()? + pivot.get("x")?.as_f32()?;
+                let y = bounds.get("y")?.as_f32()? + pivot.get("y")?.as_f32()?;
+                Some(Vector2::new(x / first.rect.width, y / first.rect.height))
+            })
+        });
+
+        Ok(AsepriteFile {
+            image,
+            frames,
+            tags,
+            pivot,
+        })
+    }
+
+    /// One clip per tag. A sheet without tags plays all its frames as a
+    /// single looping "default" clip.
+    pub fn clips(&self) -> Vec<(String, Clip)> {
+        if self.tags.is_empty() {
+            if self.frames.is_empty() {
+                return Vec::new();
+            }
+            let all: Vec<usize> = (0..self.frames.len()).collect();
+            return vec![("default".to_string(), self.clip(&all, true))];
+        }
+
+        self.tags
+            .iter()
+            .map(|tag| {
+                let forward: Vec<usize> = (tag.from..=tag.to).collect();
+                let order = match tag.direction {
+                    Direction::Forward => forward,
+                    Direction::Reverse => forward.into_iter().rev().collect(),
+                    // there and back without repeating either end frame
+                    Direction::PingPong => {
+                        let back = forward.iter().rev().skip(1);
+                        let back: Vec<usize> = back
+                            .take(forward.len().saturating_sub(2))
+                            .copied()
+                            .collect();
+                        forward.into_iter().chain(back).collect()
+                    }
+                };
+                (tag.name.clone(), self.clip(&order, tag.looping))
+            })
+            .collect()
+    }
+
+    fn clip(&self, order: &[usize], looping: bool) -> Clip {
+        Clip {
+            frames: order.iter().map(|i| self.frames[*i].rect).collect(),
+            durations: order.iter().map(|i| self.frames[*i].duration).collect(),
+            looping,
+        }
+    }
+}
+
+fn parse_frame(name: &str, frame: &Json) -> Result<AsepriteFrame, String> {
+    let rect = frame
+        .get("frame")
+        .ok_or(format!("frame {}: no rect", name))?;
+    let field = |key: &str| {
+        rect.get(key)
+            .and_then(Json::as_f32)
+            .ok_or(format!("frame {}: no {}", name, key))
+    };
+    Ok(AsepriteFrame {
+        name: name.to_string(),
+        rect: Rectangle::new(field("x")?, field("y")?, field("w")?, field("h")?),
+        duration: frame
+            .get("duration")
+            .and_then(Json::as_f32)
+            .unwrap_or(100.0)
+            / 1000.0,
+    })
+}
@@ -0,0 +1,395 @@
+use std::marker::PhantomData;
+
+use raylib::prelude::*;
+
+use crate::aseprite::AsepriteFile;
+use crate::atlas::{self, Atlas, AtlasFile};
+use crate::audio::SoundClip;
+use crate::info;
+use crate::pack;
+use crate::scene::{Preload, Preloaded};
+
+/// Typed index into one of the Assets stores.
+pub struct Handle<T> {
+    index: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Handle<T> {
+    fn new(index: usize) -> Handle<T> {
+        Handle {
+            index,
+            _marker: PhantomData,
+        }
+    }
+}
+
+// derive would needlessly require T: Clone/PartialEq
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Handle<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Handle<T>) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+/// Orders by load order, which is only useful for grouping draws by texture.
+impl<T> PartialOrd for Handle<T> {
+    fn partial_cmp(&self, other: &Handle<T>) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    fn cmp(&self, other: &Handle<T>) -> std::cmp::Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({})", self.index)
+    }
+}
+
+/// Path keyed store, so every file is only loaded once. Unloaded slots stay
+/// empty and resolve to None.
+struct Store<T> {
+    entries: Vec<Option<(String, T)>>,
+}
+
+impl<T> Store<T> {
+    fn new() -> Store<T> {
+        Store {
+            entries: Vec::new(),
+        }
+    }
+
+    fn find(&self, path: &str) -> Option<Handle<T>> {
+        self.entries
+            .iter()
+            .position(|e| e.as_ref().is_some_and(|e| e.0 == path))
+            .map(Handle::new)
+    }
+
+    fn insert(&mut self, path: &str, asset: T) -> Handle<T> {
+        self.entries.push(Some((path.to_string(), asset)));
+        Handle::new(self.entries.len() - 1)
+    }
+
+    fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.entries
+            .get(handle.index)
+            .and_then(|e| e.as_ref())
+            .map(|e| &e.1)
+    }
+
+    /// Swaps the asset loaded from `path` for a new one, its handles now
+    /// resolve to that. False when nothing was loaded from there.
+    fn replace(&mut self, path: &str, asset: T) -> bool {
+        match self.entries.iter_mut().flatten().find(|e| e.0 == path) {
+            Some(entry) => {
+                entry.1 = asset;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove(&mut self, handle: Handle<T>) {
+        if let Some(e) = self.entries.get_mut(handle.index) {
+            *e = None;
+        }
+    }
+
+    /// Frees every entry from `start` on. The slots stay, so handles are
+    /// never reused for a different asset.
+    fn remove_from(&mut self, start: usize) {
+        for e in self.entries.iter_mut().skip(start) {
+            *e = None;
+        }
+    }
+}
+
+pub struct Assets {
+    textures: Store<Texture2D>,
+    atlases: Store<Atlas>,
+    fonts: Store<Font>,
+    sounds: Store<SoundClip>,
+    /// Font for text that doesn't pick its own.
+    pub ui_font: Option<Handle<Font>>,
+    /// Fonts tried in order for text the font it asks for can't show.
+    pub fallback_fonts: Vec<Handle<Font>>,
+    /// Files a scene read ahead, textures load from these instead of disk.
+    pub preloaded: Preloaded,
+    /// Where each store's entries for the current scene begin: textures,
+    /// atlases, fonts and sounds.
+    scene_start: [usize; 4],
+}
+
+impl Assets {
+    pub fn new() -> Assets {
+        Assets {
+            textures: Store::new(),
+            atlases: Store::new(),
+            fonts: Store::new(),
+            sounds: Store::new(),
+            ui_font: None,
+            fallback_fonts: Vec::new(),
+            preloaded: Vec::new(),
+            scene_start: [0; 4],
+        }
+    }
+
+    /// Everything loaded from now on belongs to the scene being built.
+    pub fn begin_scene(&mut self) {
+        self.scene_start = [
+            self.textures.entries.len(),
+            self.atlases.entries.len(),
+            self.fonts.entries.len(),
+            self.sounds.entries.len(),
+        ];
+    }
+
+    /// Frees what the current scene loaded, handles to it resolve to None.
+    pub fn end_scene(&mut self) {
+        let [textures, atlases, fonts, sounds] = self.scene_start;
+        self.textures.remove_from(textures);
+        self.atlases.remove_from(atlases);
+        self.fonts.remove_from(fonts);
+        self.sounds.remove_from(sounds);
+    }
+
+    pub fn load_texture(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: &str,
+    ) -> Result<Handle<Texture2D>, String> {
+        if let Some(handle) = self.textures.find(path) {
+            return Ok(handle);
+        }
+        let texture = match self.preloaded.iter().find(|p| p.0 == path) {
+            // decoded ahead by the scene's loading thread
+            Some((_, Preload::Image(image))) => rl.load_texture_from_image(thread, image)?,
+            _ => rl.load_texture_from_image(thread, &pack::load_image(path)?)?,
+        };
+        info!("assets", "texture loaded", path = path);
+        Ok(self.textures.insert(path, texture))
+    }
+
+    /// Loads the texture at `path` again if it's loaded, keeping its
+    /// handles. Returns whether it was.
+    pub fn reload_texture(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: &str,
+    ) -> Result<bool, String> {
+        if self.textures.find(path).is_none() {
+            return Ok(false);
+        }
+        let texture = rl.load_texture(thread, path)?;
+        Ok(self.textures.replace(path, texture))
+    }
+
+    pub fn texture(&self, handle: Handle<Texture2D>) -> Option<&Texture2D> {
+        self.textures.get(handle)
+    }
+
+    /// Frees the texture, handles to it resolve to None from now on.
+    pub fn unload_texture(&mut self, handle: Handle<Texture2D>) {
+        self.textures.remove(handle);
+    }
+
+    /// Loads an `.atlas` file and the image it points at.
+    pub fn load_atlas(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: &str,
+    ) -> Result<Handle<Atlas>, String> {
+        if let Some(handle) = self.atlases.find(path) {
+            return Ok(handle);
+        }
+        let file = AtlasFile::load(path)?;
+        let mut atlas = Atlas::new(self.load_texture(rl, thread, &file.image)?);
+        for (name, rect) in file.regions {
+            atlas.add_region(name, rect);
+        }
+        for (name, border) in file.borders {
+            atlas.set_border(name, border);
+        }
+        info!("assets", "atlas loaded", path = path);
+        Ok(self.atlases.insert(path, atlas))
+    }
+
+    /// Loads the JSON data of an Aseprite sprite sheet export and its image.
+    /// Every frame becomes a region and every tag a clip.
+    pub fn load_aseprite(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: &str,
+    ) -> Result<Handle<Atlas>, String> {
+        if let Some(handle) = self.atlases.find(path) {
+            return Ok(handle);
+        }
+        let file = AsepriteFile::load(path)?;
+        let mut atlas = Atlas::new(self.load_texture(rl, thread, &file.image)?);
+        atlas.pivot = file.pivot;
+        for (name, clip) in file.clips() {
+            atlas.add_clip(name, clip);
+        }
+        for frame in file.frames {
+            atlas.add_region(frame.name, frame.rect);
+        }
+        info!("assets", "aseprite sheet loaded", path = path);
+        Ok(self.atlases.insert(path, atlas))
+    }
+
+    /// Fallback for loose images: packs them into one texture at runtime,
+    /// stored under `name`.
+    pub fn pack_atlas(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        name: &str,
+        paths: &[&str],
+    ) -> Result<Handle<Atlas>, String> {
+        if let Some(handle) = self.atlases.find(name) {
+            return Ok(handle);
+        }
+        let (image, regions) = atlas::pack(paths)?;
+        let texture = rl.load_texture_from_image(thread, &image)?;
+        let mut atlas = Atlas::new(self.textures.insert(name, texture));
+        for (region, rect) in regions {
+            atlas.add_region(region, rect);
+        }
+        info!("assets", "atlas packed", name = name, images = paths.len());
+        Ok(self.atlases.insert(name, atlas))
+    }
+
+    pub fn atlas(&self, handle: Handle<Atlas>) -> Option<&Atlas> {
+        self.atlases.get(handle)
+    }
+
+    /// Loads a bitmap font (`.fnt`, or an XNA style `.png`) as is, or
+    /// rasterizes a `.ttf`/`.otf` at `size` pixels. Each size of an outline
+    /// font is its own asset.
+    pub fn load_font(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: &str,
+        size: i32,
+    ) -> Result<Handle<Font>, String> {
+        self.load_font_glyphs(rl, thread, path, size, &[])
+    }
+
+    /// Like `load_font`, but an outline font also gets the glyphs `text`
+    /// uses beyond the first 95, for languages outside of ASCII.
+    pub fn load_font_for(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: &str,
+        size: i32,
+        text: &str,
+    ) -> Result<Handle<Font>, String> {
+        let mut chars: Vec<i32> = (32..127).chain(text.chars().map(|c| c as i32)).collect();
+        chars.sort_unstable();
+        chars.dedup();
+        self.load_font_glyphs(rl, thread, path, size, &chars)
+    }
+
+    fn load_font_glyphs(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: &str,
+        size: i32,
+        chars: &[i32],
+    ) -> Result<Handle<Font>, String> {
+        let outline = path.ends_with(".ttf") || path.ends_with(".otf");
+        let key = if outline && !chars.is_empty() {
+            format!("{}@{}+{}", path, size, chars.len())
+        } else if outline {
+            format!("{}@{}", path, size)
+        } else {
+            path.to_string()
+        };
+        if let Some(handle) = self.fonts.find(&key) {
+            return Ok(handle);
+        }
+        let font = if outline && pack::get(path).is_some() {
+            pack::load_font(path, size, chars)?
+        } else if !std::path::Path::new(path).exists() {
+            // raylib quietly hands back its default font for missing files,
+            // and bitmap fonts name their page images by path so they're
+            // only read loose
+            return Err(format!("could not find font {}", path));
+        } else if outline && !chars.is_empty() {
+            rl.load_font_ex(thread, path, size, FontLoadEx::Chars(chars))?
+        } else if outline {
+            rl.load_font_ex(thread, path, size, FontLoadEx::Default(95))?
+        } else {
+            rl.load_font(thread, path)?
+        };
+        // point filtering so scaled bitmap glyphs stay sharp
+        // SAFETY: the texture is passed by value and was just loaded, the
+        // font keeps owning it.
+        unsafe {
+            ffi::SetTextureFilter(font.texture, TextureFilter::TEXTURE_FILTER_POINT as i32);
+        }
+        info!("assets", "font loaded", path = path, size = size);
+        Ok(self.fonts.insert(&key, font))
+    }
+
+    pub fn font(&self, handle: Handle<Font>) -> Option<&Font> {
+        self.fonts.get(handle)
+    }
+
+    /// Loads a sound effect, decoded fully into memory. Long tracks should
+    /// be streamed as Music instead.
+    pub fn load_sound(&mut self, path: &str) -> Result<Handle<SoundClip>, String> {
+        if let Some(handle) = self.sounds.find(path) {
+            return Ok(handle);
+        }
+        let sound = SoundClip::load(path)?;
+        info!("assets", "sound loaded", path = path);
+        Ok(self.sounds.insert(path, sound))
+    }
+
+    /// Loads the sound at `path` again if it's loaded, keeping its handles.
+    /// Returns whether it was.
+    pub fn reload_sound(&mut self, path: &str) -> Result<bool, String> {
+        if self.sounds.find(path).is_none() {
+            return Ok(false);
+        }
+        let sound = SoundClip::load(path)?;
+        Ok(self.sounds.replace(path, sound))
+    }
+
+    pub fn sound(&self, handle: Handle<SoundClip>) -> Option<&SoundClip> {
+        self.sounds.get(handle)
+    }
+
+    /// Frees everything, e.g. when the current scene is torn down.
+    pub fn clear(&mut self) {
+        self.textures = Store::new();
+        self.atlases = Store::new();
+        self.fonts = Store::new();
+        self.sounds = Store::new();
+        self.ui_font = None;
+        self.fallback_fonts.clear();
+    }
+}
@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::animation::Clip;
+use crate::assets::Handle;
+use crate::pack;
+
+/// Gap between packed images so filtering never bleeds into a neighbour.
+const PADDING: i32 = 1;
+const PACKED_WIDTH: i32 = 1024;
+
+/// One texture holding many images, each found by name. Atlases imported
+/// from animation tools also carry the clips built from those images.
+pub struct Atlas {
+    pub texture: Handle<Texture2D>,
+    /// Rotation pivot for sprites cut from this atlas, relative to their
+    /// size like `Sprite::origin`.
+    pub pivot: Option<Vector2>,
+    regions: Vec<(String, Rectangle)>,
+    /// Nine-slice borders of regions, left, top, right and bottom.
+    borders: Vec<(String, [i32; 4])>,
+    clips: Vec<(String, Clip)>,
+}
+
+impl Atlas {
+    pub fn new(texture: Handle<Texture2D>) -> Atlas {
+        Atlas {
+            texture,
+            pivot: None,
+            regions: Vec::new(),
+            borders: Vec::new(),
+            clips: Vec::new(),
+        }
+    }
+
+    pub fn region(&self, name: &str) -> Option<Rectangle> {
+        self.regions.iter().find(|r| r.0 == name).map(|r| r.1)
+    }
+
+    /// Region names in the order they were added.
+    pub fn region_names(&self) -> impl Iterator<Item = &str> {
+        self.regions.iter().map(|r| r.0.as_str())
+    }
+
+    pub fn add_region(&mut self, name: impl Into<String>, rect: Rectangle) {
+        self.regions.push((name.into(), rect));
+    }
+
+    pub fn border(&self, region: &str) -> Option<[i32; 4]> {
+        self.borders.iter().find(|b| b.0 == region).map(|b| b.1)
+    }
+
+    pub fn set_border(&mut self, region: impl Into<String>, border: [i32; 4]) {
+        let region = region.into();
+        self.borders.retain(|b| b.0 != region);
+        self.borders.push((region, border));
+    }
+
+    pub fn clips(&self) -> &[(String, Clip)] {
+        &self.clips
+    }
+
+    pub fn add_clip(&mut self, name: impl Into<String>, clip: Clip) {
+        self.clips.push((name.into(), clip));
+    }
+}
+
+/// Atlas description as read from an `.atlas` file, before the image is
+/// loaded. The format is one entry per line:
+///
+/// ```text
+/// # comment
+/// image ships.png
+/// region player 0 0 36 48
+/// region panel 40 0 24 24
+/// border panel 8 8 8 8
+/// ```
+///
+/// The image path is relative to the `.atlas` file. `border` lines give a
+/// region's nine-slice borders: left, top, right, bottom.
+pub struct AtlasFile {
+    pub image: String,
+    pub regions: Vec<(String, Rectangle)>,
+    pub borders: Vec<(String, [i32; 4])>,
+}
+
+impl AtlasFile {
+    pub fn load(path: &str) -> Result<AtlasFile, String> {
+        let contents =
+            pack::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+
+        let mut image = None;
+        let mut regions = Vec::new();
+        let mut borders = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                [] => (),
+                [comment, ..] if comment.starts_with('#') => (),
+                ["image", file] => image = Some(dir.join(file).to_string_lossy().into_owned()),
+                ["region", name, x, y, w, h] => {
+                    let parse = |v: &str| {
+                        v.parse::<f32>()
+                            .map_err(|_| format!("{}:{}: bad number {:?}", path, number + 1, v))
+                    };
+                    let rect = Rectangle::new(parse(x)?, parse(y)?, parse(w)?, parse(h)?);
+                    regions.push((name.to_string(), rect));
+                }
+                ["border", name, left, top, right, bottom] => {
+                    let parse = |v: &str| {
+                        v.parse::<i32>()
+                            .map_err(|_| format!("{}:{}: bad number {:?}", path, number + 1, v))
+                    };
+                    let border = [parse(left)?, parse(top)?, parse(right)?, parse(bottom)?];
+                    borders.push((name.to_string(), border));
+                }
+                _ => return Err(format!("{}:{}: can't parse {:?}", path, number + 1, line)),
+            }
+        }
+
+        let image = image.ok_or(format!("{}: no image line", path))?;
+        Ok(AtlasFile {
+            image,
+            regions,
+            borders,
+        })
+    }
+}
+
+/// Lays loose images out in rows, tallest first, and draws them into one
+/// image. Regions are named after the file stem, so `ship.png` becomes
+/// `ship`.
+pub fn pack(paths: &[&str]) -> Result<(Image, Vec<(String, Rectangle)>), String> {
+    let mut images = Vec::new();
+    for path in paths {
+        let image = pack::load_image(path)?;
+        let name = Path::new(path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or(path.to_string());
+        images.push((name, image));
+    }
+    images.sort_by_key(|(_, image)| -image.height());
+
+    // shelf packing, a new row starts whenever the current one is full
+    let mut regions = Vec::new();
+    let (mut x, mut y, mut row_height) = (PADDING, PADDING, 0);
+    for (name, image) in images.iter() {
+        if image.width() + 2 * PADDING > PACKED_WIDTH {
+            return Err(format!("{} is wider than the atlas", name));
+        }
+        if x + image.width() + PADDING > PACKED_WIDTH {
+            x = PADDING;
+            y += row_height + PADDING;
+            row_height = 0;
+        }
+        let rect = Rectangle::new(
+            x as f32,
+            y as f32,
+            image.width() as f32,
+            image.height() as f32,
+        );
+        regions.push((name.clone(), rect));
+        x += image.width() + PADDING;
+        row_height = row_height.max(image.height());
+    }
+    let height = y + row_height + PADDING;
+
+    let mut atlas = Image::gen_image_color(PACKED_WIDTH, height, Color::BLANK);
+    for ((_, image), (_, rect)) in images.iter().zip(regions.iter()) {
+        let source = Rectangle::new(0.0, 0.0, rect.width, rect.height);
+        atlas.draw(image, source, *rect, Color::WHITE);
+    }
+    Ok((atlas, regions))
+}
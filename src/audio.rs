@@ -0,0 +1,234 @@
+use raylib::prelude::*;
+
+use crate::assets::{Assets, Handle};
+use crate::pack;
+use crate::rng::Rng;
+use crate::World;
+
+/// Most sound effects playing at once. raylib can mix 16, but long before
+/// that stacked shots only add up to distorted noise.
+const MAX_VOICES: i32 = 8;
+/// Pre-panned copies of every clip, from hard left to hard right.
+const PAN_STEPS: usize = 5;
+/// Horizontal distance from the listener at which a sound is fully on one
+/// side, about half a screen.
+const PAN_DISTANCE: f32 = 320.0;
+/// Sounds closer than this play at full volume, then fade out linearly
+/// until they're out of earshot.
+const HEARING_NEAR: f32 = 250.0;
+const HEARING_FAR: f32 = 1000.0;
+
+/// Volume per category, each from 0 to 1. Master scales everything, music
+/// and sfx only their own kind.
+#[derive(Debug, Clone, Copy)]
+pub struct Volume {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Volume {
+    pub fn new() -> Volume {
+        Volume {
+            master: 1.0,
+            music: 1.0,
+            sfx: 1.0,
+        }
+    }
+}
+
+/// Sound effect loaded in a few stereo balances. raylib 3.7 can't pan a
+/// playing sound, so panning picks the closest pre-panned copy.
+pub struct SoundClip {
+    pans: Vec<Sound>,
+}
+
+impl SoundClip {
+    pub fn load(path: &str) -> Result<SoundClip, String> {
+        let mut wave = pack::load_wave(path)?;
+        let sample_rate = wave.smaple_rate() as i32;
+        // 32 bit float stereo, so the channels can be scaled in place
+        wave.wave_format(sample_rate, 32, 2);
+        let len = wave.sample_count() as usize;
+
+        let mut pans = Vec::with_capacity(PAN_STEPS);
+        for step in 0..PAN_STEPS {
+            let pan = step as f32 / (PAN_STEPS - 1) as f32 * 2.0 - 1.0;
+            // balance law: the center keeps both channels at full volume
+            let (left, right) = ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0));
+            let copy = wave.wave_copy();
+            if copy.data.is_null() {
+                return Err(format!("could not copy the samples of {}", path));
+            }
+            // SAFETY: the copy owns its own buffer of `len` samples, checked
+            // to be there above, and wave_format made them 32 bit floats.
+            // Nothing else points into it while the slice lives.
+            let samples = unsafe { std::slice::from_raw_parts_mut(copy.data as *mut f32, len) };
+            for frame in samples.chunks_mut(2) {
+                frame[0] *= left;
+                frame[1] *= right;
+            }
+            pans.push(Sound::load_sound_from_wave(&copy)?);
+        }
+        Ok(SoundClip { pans })
+    }
+
+    /// Copy closest to `pan`, -1 is hard left and 1 hard right.
+    fn panned(&self, pan: f32) -> &Sound {
+        let step = ((pan.clamp(-1.0, 1.0) + 1.0) * 0.5 * (PAN_STEPS - 1) as f32).round();
+        &self.pans[step as usize]
+    }
+}
+
+/// Request to play a sound effect once, collected in `World::sound_events`.
+pub struct PlaySound {
+    pub sound: Handle<SoundClip>,
+    pub volume: f32,
+    /// Playback speed, 1 is as recorded. Also shifts the pitch.
+    pub pitch: f32,
+    /// Where in the world the sound comes from, None for sounds that play
+    /// centered at full volume like UI feedback.
+    pub position: Option<Vector2>,
+}
+
+impl PlaySound {
+    pub fn new(sound: Handle<SoundClip>) -> PlaySound {
+        PlaySound {
+            sound,
+            volume: 1.0,
+            pitch: 1.0,
+            position: None,
+        }
+    }
+
+    pub fn at(mut self, position: Vector2) -> PlaySound {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn volume(mut self, volume: f32) -> PlaySound {
+        self.volume = volume;
+        self
+    }
+
+    pub fn pitch(mut self, pitch: f32) -> PlaySound {
+        self.pitch = pitch;
+        self
+    }
+}
+
+/// A sound effect that doesn't repeat itself exactly: each play takes the
+/// next of its clips in turn and a random pitch within a range, so rapid
+/// fire doesn't turn into one sample on a loop.
+pub struct SoundCue {
+    clips: Vec<Handle<SoundClip>>,
+    next: usize,
+    pub volume: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    rng: Rng,
+}
+
+impl SoundCue {
+    pub fn new(clips: Vec<Handle<SoundClip>>) -> SoundCue {
+        SoundCue {
+            clips,
+            next: 0,
+            volume: 1.0,
+            min_pitch: 1.0,
+            max_pitch: 1.0,
+            rng: Rng::new(0x50bd),
+        }
+    }
+
+    /// Loads every path that exists, None when none of them do.
+    pub fn load(assets: &mut Assets, paths: &[&str]) -> Option<SoundCue> {
+        let clips: Vec<_> = paths
+            .iter()
+            .filter_map(|p| assets.load_sound(p).ok())
+            .collect();
+        if clips.is_empty() {
+            return None;
+        }
+        Some(SoundCue::new(clips))
+    }
+
+    pub fn volume(mut self, volume: f32) -> SoundCue {
+        self.volume = volume;
+        self
+    }
+
+    /// Plays vary in pitch by up to `spread` either way, 0.05 is subtle.
+    pub fn pitch_spread(mut self, spread: f32) -> SoundCue {
+        self.min_pitch = 1.0 - spread;
+        self.max_pitch = 1.0 + spread;
+        self
+    }
+
+    /// Event for the next variation, None without clips.
+    pub fn play(&mut self) -> Option<PlaySound> {
+        let clip = *self.clips.get(self.next % self.clips.len().max(1))?;
+        self.next = (self.next + 1) % self.clips.len();
+        let pitch = self.rng.range(self.min_pitch, self.max_pitch);
+        Some(PlaySound::new(clip).volume(self.volume).pitch(pitch))
+    }
+}
+
+/// Volume and pan for a sound at `pos`, heard from the closest camera.
+/// Without cameras everything plays centered.
+fn spatialize(world: &World, pos: Vector2) -> (f32, f32) {
+    let listener = world
+        .camera_components
+        .iter()
+        .map(|c| c.1.target)
+        .min_by(|a, b| a.distance_to(pos).total_cmp(&b.distance_to(pos)));
+    let listener = match listener {
+        Some(l) => l,
+        None => return (1.0, 0.0),
+    };
+    let distance = listener.distance_to(pos);
+    let volume = 1.0 - (distance - HEARING_NEAR) / (HEARING_FAR - HEARING_NEAR);
+    let pan = (pos.x - listener.x) / PAN_DISTANCE;
+    (volume.clamp(0.0, 1.0), pan.clamp(-1.0, 1.0))
+}
+
+/// Plays this frame's sound events, attenuated and panned by where they
+/// happen. Requests for the same sound within a frame are merged into the
+/// loudest one, and nothing new starts while all voices are busy, so a
+/// burst of fire stays a burst and not a wall of noise.
+pub fn play_system(world: &World, audio: &mut RaylibAudio) {
+    // master goes through the device, so it reaches sounds already playing
+    audio.set_master_volume(world.volume.master);
+
+    // (sound, volume, pan, pitch)
+    let mut merged: Vec<(Handle<SoundClip>, f32, f32, f32)> = Vec::new();
+    for event in world.sound_events.iter() {
+        let (falloff, pan) = match event.position {
+            Some(pos) => spatialize(world, pos),
+            None => (1.0, 0.0),
+        };
+        let volume = event.volume * falloff;
+        if volume <= 0.0 {
+            continue;
+        }
+        match merged.iter_mut().find(|m| m.0 == event.sound) {
+            Some(m) if m.1 < volume => *m = (event.sound, volume, pan, event.pitch),
+            Some(_) => (),
+            None => merged.push((event.sound, volume, pan, event.pitch)),
+        }
+    }
+
+    for (sound, volume, pan, pitch) in merged {
+        if audio.get_sounds_playing() >= MAX_VOICES {
+            break;
+        }
+        let sound = match world.assets.sound(sound) {
+            Some(s) => s.panned(pan),
+            None => continue,
+        };
+        // multi playback copies the volume and pitch set right before
+        audio.set_sound_volume(sound, volume * world.volume.sfx);
+        audio.set_sound_pitch(sound, pitch);
+        audio.play_sound_multi(sound);
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+
+use crate::pack;
+
+/// Samples per energy measurement, about 20ms at 44.1kHz.
+const WINDOW: usize = 1024;
+/// Energy readings averaged to tell a beat from a loud passage, a bit over
+/// a second at 60 fps.
+const HISTORY: usize = 72;
+/// How far above the recent average the energy must spike to count.
+const BEAT_THRESHOLD: f32 = 1.35;
+/// Quietest energy that can still be a beat, keeps silence from ticking.
+const MIN_ENERGY: f32 = 0.01;
+/// Shortest gap between beats, caps detection at 240 bpm.
+const BEAT_COOLDOWN: f32 = 0.25;
+/// How fast the pulse dies down after a beat, per second.
+const PULSE_DECAY: f32 = 6.0;
+
+/// What the music is doing right now, for visuals to move along with.
+/// Everything rests at zero when no music is playing.
+pub struct Beat {
+    /// Loudness relative to the recent past, around 0..1.
+    pub level: f32,
+    /// Jumps to 1 on every beat and decays back to 0 in between.
+    pub pulse: f32,
+    /// True on the frame a beat was detected.
+    pub on_beat: bool,
+}
+
+impl Beat {
+    pub fn new() -> Beat {
+        Beat {
+            level: 0.0,
+            pulse: 0.0,
+            on_beat: false,
+        }
+    }
+}
+
+/// Estimates beats from the energy of the music around the playback
+/// position. raylib doesn't hand out the samples it streams, so the track
+/// is decoded a second time up front and read alongside the stream.
+pub struct BeatTracker {
+    /// Mono samples in [-1, 1].
+    samples: Vec<f32>,
+    sample_rate: f32,
+    history: VecDeque<f32>,
+    cooldown: f32,
+}
+
+impl BeatTracker {
+    pub fn load(path: &str) -> Result<BeatTracker, String> {
+        let wave = pack::load_wave(path)?;
+        let channels = wave.channels().max(1) as usize;
+        let sample_rate = wave.smaple_rate() as f32;
+        let interleaved = wave.load_wave_samples();
+        let samples = interleaved
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+        Ok(BeatTracker {
+            samples,
+            sample_rate,
+            history: VecDeque::with_capacity(HISTORY),
+            cooldown: 0.0,
+        })
+    }
+
+    /// Updates the beat resource for the music being `time` seconds in,
+    /// None when it isn't playing.
+    pub fn update(&mut self, beat: &mut Beat, time: Option<f32>, dt: f32) {
+        beat.pulse *= (-PULSE_DECAY * dt).exp();
+        beat.on_beat = false;
+        self.cooldown -= dt;
+
+        let time = match time {
+            Some(t) => t,
+            None => {
+                beat.level = 0.0;
+                return;
+            }
+        };
+        let start = ((time * self.sample_rate) as usize).min(self.samples.len());
+        let end = (start + WINDOW).min(self.samples.len());
+        let window = &self.samples[start..end];
+        if window.is_empty() {
+            return;
+        }
+        let energy = (window.iter().map(|s| s * s).sum::<f32>() / window.len() as f32).sqrt();
+
+        let average = if self.history.is_empty() {
+            energy
+        } else {
+            self.history.iter().sum::<f32>() / self.history.len() as f32
+        };
+        let peak = self.history.iter().copied().fold(energy, f32::max);
+        beat.level = if peak > 0.0 { energy / peak } else { 0.0 };
+
+        if energy > MIN_ENERGY && energy > average * BEAT_THRESHOLD && self.cooldown <= 0.0 {
+            beat.on_beat = true;
+            beat.pulse = 1.0;
+            self.cooldown = BEAT_COOLDOWN;
+        }
+
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(energy);
+    }
+}
@@ -0,0 +1,107 @@
+use raylib::core::input::key_from_i32;
+use raylib::prelude::*;
+
+use crate::t;
+
+/// Highest raylib key code, the keypad's equals key.
+const MAX_KEY_CODE: i32 = 336;
+
+/// Player controls that can be put on other keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Fire,
+}
+
+impl Action {
+    pub fn values() -> Vec<Action> {
+        vec![
+            Action::Up,
+            Action::Down,
+            Action::Left,
+            Action::Right,
+            Action::Fire,
+        ]
+    }
+
+    /// Name in the config file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Fire => "fire",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Action::Up => t!("action.up"),
+            Action::Down => t!("action.down"),
+            Action::Left => t!("action.left"),
+            Action::Right => t!("action.right"),
+            Action::Fire => t!("action.fire"),
+        }
+    }
+}
+
+/// Which key each action is on.
+#[derive(Debug, Clone)]
+pub struct Bindings {
+    keys: Vec<(Action, KeyboardKey)>,
+}
+
+impl Bindings {
+    pub fn new() -> Bindings {
+        Bindings {
+            keys: vec![
+                (Action::Up, KeyboardKey::KEY_W),
+                (Action::Down, KeyboardKey::KEY_S),
+                (Action::Left, KeyboardKey::KEY_A),
+                (Action::Right, KeyboardKey::KEY_D),
+                (Action::Fire, KeyboardKey::KEY_SPACE),
+            ],
+        }
+    }
+
+    pub fn key(&self, action: Action) -> KeyboardKey {
+        self.keys
+            .iter()
+            .find(|k| k.0 == action)
+            .map(|k| k.1)
+            .unwrap_or(KeyboardKey::KEY_NULL)
+    }
+
+    /// Puts `action` on `key`. An action already on that key swaps over to
+    /// the old key, so no key ends up doing two things.
+    pub fn set(&mut self, action: Action, key: KeyboardKey) {
+        let old = self.key(action);
+        for binding in self.keys.iter_mut() {
+            if binding.0 == action {
+                binding.1 = key;
+            } else if binding.1 == key {
+                binding.1 = old;
+            }
+        }
+    }
+
+    pub fn down(&self, rl: &RaylibHandle, action: Action) -> bool {
+        rl.is_key_down(self.key(action))
+    }
+}
+
+/// Short name of a key, like "W" or "LEFT_SHIFT".
+pub fn key_name(key: KeyboardKey) -> String {
+    let name = format!("{:?}", key);
+    name.trim_start_matches("KEY_").to_string()
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    (1..=MAX_KEY_CODE)
+        .filter_map(key_from_i32)
+        .find(|key| key_name(*key).eq_ignore_ascii_case(name))
+}
@@ -0,0 +1,216 @@
+use raylib::prelude::*;
+
+use crate::movement::rotated;
+use crate::tween::{self, Easing, Tween};
+use crate::{BoundingBox2D, EntityID, UpdateSystem, World};
+
+/// A pan in progress, see `Camera::pan_to`.
+#[derive(Debug, Clone, Copy)]
+pub struct Pan {
+    pub from: Vector2,
+    pub to: EntityID,
+}
+
+/// 2D view into the world. Every camera gets its own pass of the world draw
+/// systems, clipped to its viewport. The first camera is the active one,
+/// used by screen-space UI that needs a single view of the world.
+pub struct Camera {
+    /// Screen position the target is drawn at, usually the screen center.
+    pub offset: Vector2,
+    /// World position the camera looks at.
+    pub target: Vector2,
+    /// Rotation in degrees.
+    pub rotation: f32,
+    pub zoom: f32,
+    /// Extra zoom on top of `zoom`, as a share of it, from `zoom_punch`.
+    pub punch: f32,
+    pub pan: Option<Pan>,
+    pub follow: Option<EntityID>,
+    /// Half extents of the box around the target the followed entity can
+    /// move in without dragging the camera along.
+    pub deadzone: Vector2,
+    /// Seconds of velocity to lead the followed entity by.
+    pub look_ahead: f32,
+    /// How quickly the camera catches up, higher is snappier.
+    pub smoothing: f32,
+    /// Part of the screen this camera draws to, the whole screen when None.
+    pub viewport: Option<Rectangle>,
+}
+
+impl Camera {
+    pub fn new(offset: Vector2) -> Camera {
+        Camera {
+            offset,
+            target: Vector2::zero(),
+            rotation: 0.0,
+            zoom: 1.0,
+            punch: 0.0,
+            pan: None,
+            follow: None,
+            deadzone: Vector2::new(40.0, 30.0),
+            look_ahead: 0.25,
+            smoothing: 6.0,
+            viewport: None,
+        }
+    }
+
+    pub fn following(offset: Vector2, entity: EntityID) -> Camera {
+        Camera {
+            follow: Some(entity),
+            ..Camera::new(offset)
+        }
+    }
+
+    pub fn active(world: &World) -> Option<&Camera> {
+        world.camera_components.first().map(|c| &c.1)
+    }
+
+    /// The camera following `entity`, falling back to the active one.
+    pub fn of(world: &World, entity: EntityID) -> Option<&Camera> {
+        world
+            .camera_components
+            .iter()
+            .find(|c| c.1.follow == Some(entity))
+            .map(|c| &c.1)
+            .or(Camera::active(world))
+    }
+
+    /// Zooms every camera in by `amount`, 0.1 for 10%, and eases back out
+    /// over `duration` real seconds. For big explosions and the like, a
+    /// punch during another one starts from the bigger of the two.
+    pub fn zoom_punch(world: &mut World, amount: f32, duration: f32) {
+        let cameras: Vec<(EntityID, f32)> = world
+            .camera_components
+            .iter()
+            .map(|c| (c.0, c.1.punch))
+            .collect();
+        for (id, punch) in cameras {
+            tween::cancel(world, id, tween::camera_punch);
+            let from = punch.max(amount);
+            world.tween_components.push((
+                id,
+                Tween::new(tween::camera_punch, from, 0.0, duration, Easing::CubicOut).unscaled(),
+            ));
+        }
+    }
+
+    /// Pans the active camera over to `entity` in `duration` real seconds,
+    /// then follows it. Cutscenes pan to a boss and back to the player
+    /// with this.
+    pub fn pan_to(world: &mut World, entity: EntityID, duration: f32) {
+        let id = match world.camera_components.first_mut() {
+            Some((id, camera)) => {
+                camera.pan = Some(Pan {
+                    from: camera.target,
+                    to: entity,
+                });
+                // following would pull it back the other way
+                camera.follow = None;
+                *id
+            }
+            None => return,
+        };
+        tween::cancel(world, id, tween::camera_pan);
+        world.tween_components.push((
+            id,
+            Tween::new(tween::camera_pan, 0.0, 1.0, duration, Easing::SineInOut).unscaled(),
+        ));
+    }
+
+    /// `zoom` with the punch on top, what the world is drawn with.
+    pub fn view_zoom(&self) -> f32 {
+        self.zoom * (1.0 + self.punch)
+    }
+
+    /// Gives every camera an equal slice of the screen: side by side for
+    /// two, a grid for more. A lone camera gets the whole screen.
+    pub fn split_screen(world: &mut World) {
+        let (w, h) = (world.screen.width(), world.screen.height());
+        let count = world.camera_components.len();
+        let columns = if count <= 2 { count.max(1) } else { 2 };
+        let rows = count.div_ceil(columns).max(1);
+        let size = Vector2::new(w / columns as f32, h / rows as f32);
+
+        for (i, (_, camera)) in world.camera_components.iter_mut().enumerate() {
+            let viewport = Rectangle::new(
+                (i % columns) as f32 * size.x,
+                (i / columns) as f32 * size.y,
+                size.x,
+                size.y,
+            );
+            camera.offset = Vector2::new(
+                viewport.x + viewport.width * 0.5,
+                viewport.y + viewport.height * 0.5,
+            );
+            camera.viewport = if count > 1 { Some(viewport) } else { None };
+        }
+    }
+
+    pub fn as_raylib(&self) -> Camera2D {
+        Camera2D {
+            offset: self.offset,
+            target: self.target,
+            rotation: self.rotation,
+            zoom: self.view_zoom(),
+        }
+    }
+
+    pub fn world_to_screen(&self, pos: Vector2) -> Vector2 {
+        rotated(
+            (pos - self.target) * self.view_zoom(),
+            self.rotation.to_radians(),
+        ) + self.offset
+    }
+
+    pub fn screen_to_world(&self, pos: Vector2) -> Vector2 {
+        rotated(pos - self.offset, -self.rotation.to_radians()) / self.view_zoom() + self.target
+    }
+
+    /// World area visible in the viewport, or on a screen of the given size
+    /// without one. Rotation is ignored, so with a rotated camera this is
+    /// only an approximation.
+    pub fn view_bounds(&self, screen_width: f32, screen_height: f32) -> BoundingBox2D {
+        let area = self
+            .viewport
+            .unwrap_or(Rectangle::new(0.0, 0.0, screen_width, screen_height));
+        let top_left = self.screen_to_world(Vector2::new(area.x, area.y));
+        BoundingBox2D::new(
+            top_left.x,
+            top_left.y,
+            area.width / self.view_zoom(),
+            area.height / self.view_zoom(),
+        )
+    }
+}
+
+impl UpdateSystem for Camera {
+    fn update_system(world: &mut World, dt: f32) {
+        for (_, camera) in world.camera_components.iter_mut() {
+            let followed = match camera.follow {
+                Some(id) => id,
+                None => continue,
+            };
+            let center = match world.base_components.iter().find(|b| b.0 == followed) {
+                Some(b) => b.1.bounds.center(),
+                None => continue,
+            };
+            let velocity = world
+                .velocity_components
+                .iter()
+                .find(|v| v.0 == followed)
+                .map(|v| v.1.velocity)
+                .unwrap_or(Vector2::zero());
+
+            let desired = center + velocity * camera.look_ahead;
+            let diff = desired - camera.target;
+            let outside = Vector2::new(
+                (diff.x.abs() - camera.deadzone.x).max(0.0) * diff.x.signum(),
+                (diff.y.abs() - camera.deadzone.y).max(0.0) * diff.y.signum(),
+            );
+
+            // framerate independent exponential smoothing
+            let t = 1.0 - (-camera.smoothing * dt).exp();
+            camera.target += outside * t;
+        }
+    }
+}
@@ -0,0 +1,698 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use raylib::prelude::*;
+
+use crate::cutscene::{self, Cue};
+use crate::difficulty::Difficulty;
+use crate::json::Json;
+use crate::pack;
+use crate::prefab::{self, Placement, Prefabs};
+use crate::renderer::Renderer;
+use crate::starfield::Backdrop;
+use crate::team::Team;
+use crate::{info, t, warn};
+use crate::{BoundingBox2D, EntityID, UIBarStyle, World};
+
+/// Seconds the level's name shows at its start.
+const TITLE_TIME: f32 = 2.5;
+/// Seconds the results show before the next level loads.
+const RESULTS_TIME: f32 = 4.0;
+
+/// One or more copies of a prefab, some time into a wave.
+#[derive(Debug, Clone)]
+pub struct SpawnDef {
+    /// Seconds after the wave starts.
+    pub at: f32,
+    pub prefab: String,
+    pub pos: Vector2,
+    pub count: usize,
+    /// Offset from one copy to the next.
+    pub spacing: Vector2,
+}
+
+#[derive(Debug, Clone)]
+pub struct WaveDef {
+    /// Seconds of quiet after the previous wave is cleared.
+    pub delay: f32,
+    /// Sorted by `at`.
+    pub spawns: Vec<SpawnDef>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BossDef {
+    pub prefab: String,
+    pub pos: Vector2,
+    /// Replaces the prefab's health.
+    pub health: Option<u16>,
+    /// Cutscene played as it spawns, `cutscene::boss_intro` unless the
+    /// level has one.
+    pub intro: Vec<Cue>,
+}
+
+/// Image scrolled behind the stars, see `Backdrop`.
+#[derive(Debug, Clone)]
+pub struct BackgroundDef {
+    pub texture: String,
+    pub parallax: f32,
+    pub tint: Color,
+}
+
+/// A campaign level described in a JSON file in `assets/levels/`, named
+/// after the file. Levels are played in the order of their file names.
+///
+/// ```json
+/// {
+///     "name": "Outskirts",
+///     "map": "Level_0",
+///     "music": "assets/music/outskirts.ogg",
+///     "par_time": 180,
+///     "background": [
+///         { "texture": "assets/textures/dust.png", "parallax": 0.2, "tint": "#ffffff80" }
+///     ],
+///     "waves": [
+///         {
+///             "delay": 2,
+///             "spawns": [
+///                 { "at": 0, "prefab": "Drone", "pos": [300, 100] },
+///                 { "at": 4, "prefab": "Drone", "pos": [200, 80], "count": 3, "spacing": [40, 0] }
+///             ]
+///         }
+///     ],
+///     "boss": {
+///         "prefab": "Drone",
+///         "pos": [300, 60],
+///         "health": 120,
+///         "intro": [{ "at": 0, "do": "banner", "text": "Drone Prime", "time": 2 }]
+///     }
+/// }
+/// ```
+///
+/// Only `name` is needed. `map` names the LDtk level to build, `music`
+/// replaces the gameplay track. The boss comes after the last wave is
+/// cleared, with its intro cutscene, see `Cue::parse`, an empty one for
+/// none. Beating it, or clearing the last wave when there's none,
+/// completes the level.
+#[derive(Debug, Clone)]
+pub struct LevelDef {
+    pub id: String,
+    pub name: String,
+    pub map: Option<String>,
+    pub music: Option<String>,
+    /// Seconds a good run takes.
+    pub par_time: Option<f32>,
+    pub background: Vec<BackgroundDef>,
+    pub waves: Vec<WaveDef>,
+    pub boss: Option<BossDef>,
+}
+
+impl LevelDef {
+    pub fn load(path: &Path) -> Result<LevelDef, String> {
+        let contents = pack::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        let id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        LevelDef::parse(id, &contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(id: &str, contents: &str) -> Result<LevelDef, String> {
+        let json = Json::parse(contents)?;
+        let fields = json.as_object().ok_or("expected an object")?;
+        let mut def = LevelDef {
+            id: id.to_string(),
+            name: String::new(),
+            map: None,
+            music: None,
+            par_time: None,
+            background: Vec::new(),
+            waves: Vec::new(),
+            boss: None,
+        };
+        for (key, value) in fields.iter() {
+            let bad = || format!("bad value for '{}': {}", key, value);
+            match key.as_str() {
+                "name" => def.name = value.as_str().ok_or_else(bad)?.to_string(),
+                "map" => def.map = Some(value.as_str().ok_or_else(bad)?.to_string()),
+                "music" => def.music = Some(value.as_str().ok_or_else(bad)?.to_string()),
+                "par_time" => def.par_time = Some(value.as_f32().ok_or_else(bad)?),
+                "background" => {
+                    for layer in value.as_array().ok_or_else(bad)? {
+                        def.background.push(background(layer).ok_or_else(bad)?);
+                    }
+                }
+                "waves" => {
+                    for wave in value.as_array().ok_or_else(bad)? {
+                        def.waves.push(parse_wave(wave).ok_or_else(bad)?);
+                    }
+                }
+                "boss" => {
+                    def.boss = Some(BossDef {
+                        prefab: value
+                            .get("prefab")
+                            .and_then(Json::as_str)
+                            .ok_or_else(bad)?
+                            .to_string(),
+                        pos: value.get("pos").and_then(prefab::vector).ok_or_else(bad)?,
+                        health: prefab::number(value, "health").map(|h| h as u16),
+                        intro: match value.get("intro") {
+                            Some(intro) => cutscene::parse_cues(intro).ok_or_else(bad)?,
+                            None => cutscene::boss_intro(),
+                        },
+                    })
+                }
+                // a typo would otherwise quietly drop part of the level
+                _ => return Err(format!("unknown key '{}'", key)),
+            }
+        }
+        if def.name.is_empty() {
+            return Err("no name".to_string());
+        }
+        Ok(def)
+    }
+}
+
+fn background(json: &Json) -> Option<BackgroundDef> {
+    Some(BackgroundDef {
+        texture: json.get("texture")?.as_str()?.to_string(),
+        parallax: prefab::number(json, "parallax").unwrap_or(0.1),
+        tint: match json.get("tint") {
+            Some(tint) => prefab::color(tint)?,
+            None => Color::WHITE,
+        },
+    })
+}
+
+fn parse_wave(json: &Json) -> Option<WaveDef> {
+    let mut spawns = Vec::new();
+    for spawn in json.get("spawns")?.as_array()? {
+        spawns.push(SpawnDef {
+            at: prefab::number(spawn, "at").unwrap_or(0.0),
+            prefab: spawn.get("prefab")?.as_str()?.to_string(),
+            pos: prefab::vector(spawn.get("pos")?)?,
+            count: prefab::number(spawn, "count").unwrap_or(1.0).max(1.0) as usize,
+            spacing: match spawn.get("spacing") {
+                Some(spacing) => prefab::vector(spacing)?,
+                None => Vector2::zero(),
+            },
+        });
+    }
+    spawns.sort_by(|a, b| a.at.total_cmp(&b.at));
+    Some(WaveDef {
+        delay: prefab::number(json, "delay").unwrap_or(0.0),
+        spawns,
+    })
+}
+
+/// How the level being played is going.
+struct Run {
+    /// Index of the wave under way, the waves' count once the last is
+    /// cleared.
+    wave: usize,
+    /// Seconds since the wave was due, its delay included.
+    wave_time: f32,
+    /// Spawns of the wave already made.
+    spawned: usize,
+    /// What the wave spawned, it's cleared once they're all gone.
+    hostiles: Vec<EntityID>,
+    boss: Option<EntityID>,
+    /// Set once the boss was spawned, or failed to, so an unknown prefab
+    /// is warned about once and doesn't keep the level from finishing.
+    boss_spawned: bool,
+    elapsed: f32,
+    /// Seconds since the level was completed.
+    finished: Option<f32>,
+}
+
+/// The campaign's levels, which are unlocked, and the one being played.
+/// Progress is stored in its own file, as `unlocked <id>` and
+/// `best <id> <seconds>` lines.
+pub struct Campaign {
+    pub levels: Vec<LevelDef>,
+    /// Level played next.
+    pub current: usize,
+    path: PathBuf,
+    unlocked: Vec<String>,
+    best_times: Vec<(String, f32)>,
+    run: Option<Run>,
+    /// Set when the results are done showing, for the game to load what
+    /// comes next.
+    advance: bool,
+}
+
+impl Campaign {
+    pub fn new() -> Campaign {
+        Campaign {
+            levels: Vec::new(),
+            current: 0,
+            path: PathBuf::new(),
+            unlocked: Vec::new(),
+            best_times: Vec::new(),
+            run: None,
+            advance: false,
+        }
+    }
+
+    /// Loads every level in `dir`, returns how many. Files that fail are
+    /// skipped with a warning.
+    pub fn load_dir(&mut self, dir: &str) -> usize {
+        let mut paths: Vec<PathBuf> = pack::files_in(dir)
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            match LevelDef::load(&path) {
+                Ok(def) => {
+                    self.levels.retain(|l| l.id != def.id);
+                    self.levels.push(def);
+                }
+                Err(e) => warn!("campaign", "could not load level", error = e),
+            }
+        }
+        self.levels.sort_by(|a, b| a.id.cmp(&b.id));
+        info!("campaign", "levels loaded", count = self.levels.len());
+        self.levels.len()
+    }
+
+    /// Reads the progress file at `path`, and picks the furthest unlocked
+    /// level to play.
+    pub fn load_progress(&mut self, path: impl Into<PathBuf>) {
+        self.path = path.into();
+        let contents = fs::read_to_string(&self.path).unwrap_or_default();
+        for line in contents.lines() {
+            match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+                ["unlocked", id] => self.unlocked.push(id.to_string()),
+                ["best", id, time] => {
+                    if let Ok(time) = time.parse() {
+                        self.best_times.push((id.to_string(), time));
+                    }
+                }
+                _ => (),
+            }
+        }
+        self.current = (0..self.levels.len())
+            .rev()
+            .find(|i| self.is_unlocked(*i))
+            .unwrap_or(0);
+    }
+
+    fn save_progress(&self) {
+        let mut contents = String::new();
+        for id in self.unlocked.iter() {
+            contents += &format!("unlocked {}\n", id);
+        }
+        for (id, time) in self.best_times.iter() {
+            contents += &format!("best {} {}\n", id, time);
+        }
+        if let Err(e) = fs::write(&self.path, contents) {
+            warn!(
+                "campaign",
+                "could not save progress",
+                path = self.path.display(),
+                error = e
+            );
+        }
+    }
+
+    /// The first level always is.
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        index == 0
+            || self
+                .levels
+                .get(index)
+                .is_some_and(|l| self.unlocked.contains(&l.id))
+    }
+
+    pub fn best_time(&self, id: &str) -> Option<f32> {
+        self.best_times.iter().find(|b| b.0 == id).map(|b| b.1)
+    }
+
+    /// The level played next, none without a campaign.
+    pub fn level(&self) -> Option<&LevelDef> {
+        self.levels.get(self.current)
+    }
+
+    /// Index of the wave under way in the level being played, the waves'
+    /// count once the last is cleared. None between levels.
+    pub fn wave(&self) -> Option<usize> {
+        self.run.as_ref().map(|r| r.wave)
+    }
+
+    /// Starts the current level's waves and puts its background up. Called
+    /// by the scene that builds the level.
+    pub fn begin(world: &mut World, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let level = match world.campaign.level() {
+            Some(level) => level.clone(),
+            None => return,
+        };
+        for layer in level.background.iter() {
+            match world.assets.load_texture(rl, thread, &layer.texture) {
+                Ok(texture) => world.starfield.backdrops.push(Backdrop {
+                    texture,
+                    parallax: layer.parallax,
+                    tint: layer.tint,
+                }),
+                Err(e) => warn!("campaign", "could not load background", error = e),
+            }
+        }
+        world.campaign.run = Some(Run {
+            wave: 0,
+            wave_time: 0.0,
+            spawned: 0,
+            hostiles: Vec::new(),
+            boss: None,
+            boss_spawned: false,
+            elapsed: 0.0,
+            finished: None,
+        });
+        world.campaign.advance = false;
+        info!("campaign", "level started", level = level.id);
+    }
+
+    /// Forgets the level being played, the progress stays.
+    pub fn stop(&mut self) {
+        self.run = None;
+        self.advance = false;
+    }
+
+    /// True once, when the finished level's results are done showing. The
+    /// current level is the next one by then, or the first again after the
+    /// last.
+    pub fn take_advance(&mut self) -> bool {
+        std::mem::replace(&mut self.advance, false)
+    }
+
+    /// Spawns the waves as they come due and the boss after them, and
+    /// completes the level once it's all beaten.
+    pub fn update_system(world: &mut World, dt: f32) {
+        let level = match (world.campaign.level(), world.campaign.run.as_ref()) {
+            (Some(level), Some(_)) => level.clone(),
+            _ => return,
+        };
+        let alive = |world: &World, id: &EntityID| world.base_components.iter().any(|b| b.0 == *id);
+        let mut spawns = Vec::new();
+        let mut boss_due = false;
+        {
+            let run = world.campaign.run.as_mut().unwrap();
+            if let Some(finished) = run.finished.as_mut() {
+                *finished += dt;
+                if *finished >= RESULTS_TIME {
+                    world.campaign.run = None;
+                    world.campaign.current =
+                        (world.campaign.current + 1) % world.campaign.levels.len();
+                    world.campaign.advance = true;
+                }
+                return;
+            }
+            run.elapsed += dt;
+            if let Some(wave) = level.waves.get(run.wave) {
+                run.wave_time += dt;
+                let due = wave.spawns[run.spawned..]
+                    .iter()
+                    .take_while(|s| s.at + wave.delay <= run.wave_time)
+                    .count();
+                spawns.extend(wave.spawns[run.spawned..run.spawned + due].iter().cloned());
+                run.spawned += due;
+            } else if !run.boss_spawned {
+                boss_due = level.boss.is_some();
+                run.boss_spawned = true;
+            }
+        }
+
+        let mut spawned = Vec::new();
+        for spawn in spawns.iter() {
+            for i in 0..spawn.count {
+                let pos = spawn.pos + spawn.spacing * i as f32;
+                let placement = Placement::new(BoundingBox2D::new(pos.x, pos.y, 0.0, 0.0));
+                match Prefabs::spawn(world, &spawn.prefab, &placement) {
+                    Some(id) => spawned.push(id),
+                    None => warn!("campaign", "unknown prefab", prefab = spawn.prefab),
+                }
+            }
+        }
+        let mut boss = None;
+        if let Some(def) = level.boss.as_ref().filter(|_| boss_due) {
+            let placement = Placement::new(BoundingBox2D::new(def.pos.x, def.pos.y, 0.0, 0.0));
+            boss = Prefabs::spawn(world, &def.prefab, &placement);
+            match boss {
+                Some(id) => {
+                    if !world.team_components.iter().any(|t| t.0 == id) {
+                        world.team_components.push((id, Team::Hostile));
+                        // wasn't hostile yet when it spawned
+                        Prefabs::toughen(world, id);
+                    }
+                    let scale = world.difficulty.enemy_health();
+                    if let Some(h) = world.health_components.iter_mut().find(|h| h.0 == id) {
+                        if let Some(health) = def.health {
+                            let health = Difficulty::scale(health, scale);
+                            h.1.max_health = health;
+                            h.1.health = health;
+                        }
+                        h.1.bar_style = UIBarStyle::BOSS;
+                    }
+                    if !def.intro.is_empty() {
+                        world.cutscene.play(def.intro.clone(), id);
+                    }
+                }
+                None => warn!("campaign", "unknown prefab", prefab = def.prefab),
+            }
+        }
+
+        let hostiles: Vec<EntityID> = {
+            let run = world.campaign.run.as_ref().unwrap();
+            run.hostiles
+                .iter()
+                .chain(spawned.iter())
+                .filter(|id| alive(world, id))
+                .copied()
+                .collect()
+        };
+        let boss_alive = world
+            .campaign
+            .run
+            .as_ref()
+            .unwrap()
+            .boss
+            .or(boss)
+            .is_some_and(|id| alive(world, &id));
+
+        let run = world.campaign.run.as_mut().unwrap();
+        run.hostiles = hostiles;
+        if boss.is_some() {
+            run.boss = boss;
+        }
+        let wave_done = level
+            .waves
+            .get(run.wave)
+            .is_some_and(|w| run.spawned == w.spawns.len() && run.hostiles.is_empty());
+        if wave_done {
+            run.wave += 1;
+            run.wave_time = 0.0;
+            run.spawned = 0;
+        }
+        let beaten = run.wave >= level.waves.len()
+            && !boss_alive
+            && (level.boss.is_none() || run.boss_spawned);
+        if beaten {
+            run.finished = Some(0.0);
+            let time = run.elapsed;
+            world.campaign.complete(&level, time);
+        }
+    }
+
+    /// Counts `child` as part of the wave or boss fight `parent` is in, so
+    /// the wave isn't cleared while what its scripts spawned is around.
+    pub fn adopt(&mut self, parent: EntityID, child: EntityID) {
+        if let Some(run) = self.run.as_mut() {
+            if run.hostiles.contains(&parent) || run.boss == Some(parent) {
+                run.hostiles.push(child);
+            }
+        }
+    }
+
+    /// Unlocks the level after `level` and keeps the time if it's a best.
+    fn complete(&mut self, level: &LevelDef, time: f32) {
+        info!("campaign", "level completed", level = level.id, time = time);
+        if let Some(next) = self.levels.get(self.current + 1) {
+            if !self.unlocked.contains(&next.id) {
+                info!("campaign", "level unlocked", level = next.id);
+                self.unlocked.push(next.id.clone());
+            }
+        }
+        match self.best_times.iter_mut().find(|b| b.0 == level.id) {
+            Some(best) => best.1 = best.1.min(time),
+            None => self.best_times.push((level.id.clone(), time)),
+        }
+        self.save_progress();
+    }
+
+    /// The level's name as it starts, the wave count while it's played and
+    /// the time against par once it's beaten.
+    pub fn draw_system(world: &World, d: &mut dyn Renderer) {
+        let campaign = &world.campaign;
+        let screen = world.screen.canvas;
+        let (level, run) = match (campaign.level(), campaign.run.as_ref()) {
+            (Some(level), Some(run)) => (level, run),
+            _ => return,
+        };
+        let center = |d: &mut dyn Renderer, text: &str, y: i32, size: i32, color: Color| {
+            let x = screen.x as i32 / 2 - measure_text(text, size) / 2;
+            d.draw_text(text, x, y, size, color);
+        };
+
+        if let Some(finished) = run.finished {
+            let alpha = (finished / 0.5).min(1.0);
+            let y = screen.y as i32 / 2 - 40;
+            center(d, &t!("campaign.complete"), y, 24, Color::GOLD.fade(alpha));
+            let time = t!("campaign.time", time = clock(run.elapsed));
+            center(d, &time, y + 34, 14, Color::WHITE.fade(alpha));
+            if let Some(par) = level.par_time {
+                let color = if run.elapsed <= par {
+                    Color::GREEN
+                } else {
+                    Color::LIGHTGRAY
+                };
+                let par = t!("campaign.par", time = clock(par));
+                center(d, &par, y + 52, 14, color.fade(alpha));
+            }
+            if let Some(best) = campaign.best_time(&level.id) {
+                let best = t!("campaign.best", time = clock(best));
+                center(d, &best, y + 70, 14, Color::GRAY.fade(alpha));
+            }
+            return;
+        }
+
+        if run.elapsed < TITLE_TIME {
+            let alpha = ((TITLE_TIME - run.elapsed) / 0.5).min(1.0);
+            let title = t!(
+                "campaign.level",
+                number = campaign.current + 1,
+                name = level.name
+            );
+            center(d, &title, screen.y as i32 / 3, 20, Color::WHITE.fade(alpha));
+        }
+        if !level.waves.is_empty() {
+            let wave = run.wave.min(level.waves.len() - 1) + 1;
+            let text = t!("campaign.wave", wave = wave, count = level.waves.len());
+            center(d, &text, 24, 10, Color::LIGHTGRAY);
+        }
+    }
+}
+
+/// `m:ss`.
+fn clock(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL: &str = r##"{
+        "name": "Outskirts",
+        "map": "Level_0",
+        "par_time": 180,
+        "background": [{ "texture": "dust.png", "tint": "#ffffff80" }],
+        "waves": [
+            {
+                "delay": 2,
+                "spawns": [
+                    { "at": 4, "prefab": "Drone", "pos": [200, 80], "count": 3, "spacing": [40, 0] },
+                    { "at": 0, "prefab": "Drone", "pos": [300, 100], "count": 0 }
+                ]
+            },
+            { "spawns": [] }
+        ],
+        "boss": { "prefab": "Drone", "pos": [300, 60], "health": 120, "intro": [] }
+    }"##;
+
+    #[test]
+    fn reads_a_level() {
+        let def = LevelDef::parse("01_outskirts", LEVEL).unwrap();
+        assert_eq!(def.id, "01_outskirts");
+        assert_eq!(def.name, "Outskirts");
+        assert_eq!(def.map.as_deref(), Some("Level_0"));
+        assert_eq!(def.music, None);
+        assert_eq!(def.par_time, Some(180.0));
+
+        assert_eq!(def.background.len(), 1);
+        assert_eq!(def.background[0].parallax, 0.1);
+        assert_eq!(def.background[0].tint, Color::new(255, 255, 255, 128));
+
+        assert_eq!(def.waves.len(), 2);
+        let wave = &def.waves[0];
+        assert_eq!(wave.delay, 2.0);
+        // sorted by when they spawn, with at least one copy each
+        let spawns: Vec<(f32, usize)> = wave.spawns.iter().map(|s| (s.at, s.count)).collect();
+        assert_eq!(spawns, [(0.0, 1), (4.0, 3)]);
+        assert_eq!(wave.spawns[0].spacing, Vector2::zero());
+        assert_eq!(wave.spawns[1].spacing, Vector2::new(40.0, 0.0));
+        assert_eq!(def.waves[1].delay, 0.0);
+
+        let boss = def.boss.unwrap();
+        assert_eq!(boss.prefab, "Drone");
+        assert_eq!(boss.pos, Vector2::new(300.0, 60.0));
+        assert_eq!(boss.health, Some(120));
+        assert!(boss.intro.is_empty());
+    }
+
+    #[test]
+    fn only_needs_a_name() {
+        let def = LevelDef::parse("a", r#"{ "name": "A" }"#).unwrap();
+        assert!(def.waves.is_empty() && def.background.is_empty());
+        assert!(def.boss.is_none());
+
+        let boss = r#"{ "name": "A", "boss": { "prefab": "Drone", "pos": [0, 0] } }"#;
+        let boss = LevelDef::parse("a", boss).unwrap().boss.unwrap();
+        assert_eq!(boss.health, None);
+        assert!(!boss.intro.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_levels() {
+        let err = |json: &str| LevelDef::parse("a", json).unwrap_err();
+        assert_eq!(err("{}"), "no name");
+        assert_eq!(err("[]"), "expected an object");
+        assert_eq!(err(r#"{ "name": "A", "wave": [] }"#), "unknown key 'wave'");
+        assert!(err(r#"{ "name": 3 }"#).starts_with("bad value for 'name'"));
+        assert!(err(r#"{ "name": "A", "waves": [{}] }"#).starts_with("bad value for 'waves'"));
+        let no_pos = r#"{ "name": "A", "waves": [{ "spawns": [{ "prefab": "Drone" }] }] }"#;
+        assert!(err(no_pos).starts_with("bad value for 'waves'"));
+        let no_prefab = r#"{ "name": "A", "boss": { "pos": [0, 0] } }"#;
+        assert!(err(no_prefab).starts_with("bad value for 'boss'"));
+        let bad_tint = r#"{ "name": "A", "background": [{ "texture": "a.png", "tint": "red" }] }"#;
+        assert!(err(bad_tint).starts_with("bad value for 'background'"));
+    }
+
+    #[test]
+    fn finishes_when_the_boss_cant_spawn() {
+        let json = r#"{ "name": "A", "boss": { "prefab": "NoSuchBoss", "pos": [0, 0] } }"#;
+        let mut world = World::new();
+        world
+            .campaign
+            .levels
+            .push(LevelDef::parse("a", json).unwrap());
+        world.campaign.path = std::env::temp_dir().join("spacegame-campaign-test.txt");
+        world.campaign.run = Some(Run {
+            wave: 0,
+            wave_time: 0.0,
+            spawned: 0,
+            hostiles: Vec::new(),
+            boss: None,
+            boss_spawned: false,
+            elapsed: 0.0,
+            finished: None,
+        });
+
+        Campaign::update_system(&mut world, 1.0 / 60.0);
+        let run = world.campaign.run.as_ref().unwrap();
+        assert!(run.boss_spawned);
+        assert_eq!(run.boss, None);
+        assert_eq!(run.finished, Some(0.0));
+        assert!(world.campaign.best_time("a").is_some());
+        fs::remove_file(&world.campaign.path).ok();
+    }
+}
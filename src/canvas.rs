@@ -0,0 +1,81 @@
+use raylib::prelude::*;
+
+use crate::screen::ScreenSize;
+
+/// Fixed size render target the whole game is drawn into, then scaled up to
+/// the window by a whole number and centered with black bars around it. The
+/// game only ever sees the canvas, whatever the window does.
+pub struct Canvas {
+    target: RenderTexture2D,
+    /// Scratch target for presenting the canvas at a lower resolution.
+    pixelated: RenderTexture2D,
+}
+
+impl Canvas {
+    pub fn new(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        width: u32,
+        height: u32,
+    ) -> Result<Canvas, String> {
+        let target = rl.load_render_texture(thread, width, height)?;
+        let pixelated = rl.load_render_texture(thread, width, height)?;
+        Ok(Canvas { target, pixelated })
+    }
+
+    /// Redirects drawing into the canvas until `present`.
+    pub fn begin(&mut self, _d: &mut RaylibDrawHandle) {
+        // same as render.rs, the safe wrapper returns a handle the draw
+        // systems can't take
+        let target: &ffi::RenderTexture2D = self.target.as_ref();
+        // SAFETY: the target lives as long as the canvas, and the draw
+        // handle means a frame is being drawn. `present` ends the mode.
+        unsafe {
+            ffi::BeginTextureMode(*target);
+        }
+    }
+
+    /// Stops drawing into the canvas and blits it onto the window, in blocks
+    /// of `pixelation` canvas pixels when that's above 1.
+    pub fn present(&self, d: &mut RaylibDrawHandle, screen: &ScreenSize, pixelation: u32) {
+        // SAFETY: ends the texture mode `begin` started, no arguments.
+        unsafe {
+            ffi::EndTextureMode();
+        }
+        let texture = self.target.texture();
+        let size = Vector2::new(texture.width() as f32, texture.height() as f32);
+        // render textures are stored upside down
+        let mut source = Rectangle::new(0.0, 0.0, size.x, -size.y);
+        let mut texture = texture;
+
+        if pixelation > 1 {
+            // shrink into the scratch target, the point filtered upscale
+            // below turns every texel into a block
+            let small = Rectangle::new(
+                0.0,
+                0.0,
+                (size.x / pixelation as f32).ceil(),
+                (size.y / pixelation as f32).ceil(),
+            );
+            let scratch: &ffi::RenderTexture2D = self.pixelated.as_ref();
+            // SAFETY: the canvas was just unbound, so this doesn't nest, and
+            // the scratch target lives as long as the canvas.
+            unsafe {
+                ffi::BeginTextureMode(*scratch);
+            }
+            d.clear_background(Color::BLACK);
+            d.draw_texture_pro(texture, source, small, Vector2::zero(), 0.0, Color::WHITE);
+            // SAFETY: ends the scratch target's mode from just above.
+            unsafe {
+                ffi::EndTextureMode();
+            }
+            texture = self.pixelated.texture();
+            source = Rectangle::new(0.0, size.y - small.height, small.width, -small.height);
+        }
+
+        d.clear_background(Color::BLACK);
+        let (scale, offset) = screen.letterbox();
+        let dest = Rectangle::new(offset.x, offset.y, size.x * scale, size.y * scale);
+        d.draw_texture_pro(texture, source, dest, Vector2::zero(), 0.0, Color::WHITE);
+    }
+}
@@ -0,0 +1,130 @@
+use raylib::core::input::key_from_i32;
+use raylib::prelude::*;
+
+use crate::damage::Damage;
+use crate::renderer::Renderer;
+use crate::state::GameState;
+use crate::{info, t, World};
+
+/// Letters remembered, enough for the longest code.
+const MEMORY: usize = 16;
+const MESSAGE_TIME: f32 = 2.0;
+
+/// Marks an entity that damage passes through.
+pub struct GodMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cheat {
+    GodMode,
+    AllWeapons,
+    SkipWave,
+}
+
+impl Cheat {
+    fn values() -> Vec<Cheat> {
+        vec![Cheat::GodMode, Cheat::AllWeapons, Cheat::SkipWave]
+    }
+
+    /// Typed during play to trigger the cheat.
+    fn code(&self) -> &'static str {
+        match self {
+            Cheat::GodMode => "IDDQD",
+            Cheat::AllWeapons => "IDKFA",
+            Cheat::SkipWave => "SKIPWAVE",
+        }
+    }
+}
+
+/// Watches the letters typed while playing for cheat codes. Only active in
+/// debug builds, or with `cheats = true` in the config.
+pub struct Cheats {
+    enabled: bool,
+    typed: String,
+    message: Option<(String, f32)>,
+}
+
+impl Cheats {
+    pub fn new(enabled: bool) -> Cheats {
+        Cheats {
+            enabled,
+            typed: String::new(),
+            message: None,
+        }
+    }
+
+    pub fn input_system(&mut self, world: &mut World, rl: &mut RaylibHandle) {
+        if !self.enabled || !world.state.is(GameState::Playing) {
+            self.typed.clear();
+            return;
+        }
+        // polled one by one, taking from the key queue would starve the
+        // settings menu waiting for a key to rebind
+        let letters = (KeyboardKey::KEY_A as i32..=KeyboardKey::KEY_Z as i32)
+            .filter_map(key_from_i32)
+            .filter(|key| rl.is_key_pressed(*key));
+        for key in letters {
+            self.typed.push(char::from_u32(key as u32).unwrap_or(' '));
+            if self.typed.len() > MEMORY {
+                self.typed.remove(0);
+            }
+            if let Some(cheat) = Cheat::values()
+                .into_iter()
+                .find(|c| self.typed.ends_with(c.code()))
+            {
+                self.typed.clear();
+                let message = Cheats::apply(world, cheat);
+                info!("cheats", "cheat used", cheat = format!("{:?}", cheat));
+                self.message = Some((message, MESSAGE_TIME));
+            }
+        }
+    }
+
+    fn apply(world: &mut World, cheat: Cheat) -> String {
+        let player = match world.player_components.first() {
+            Some(p) => p.0,
+            None => return t!("cheats.no_player"),
+        };
+        match cheat {
+            Cheat::GodMode => {
+                if world.god_mode_components.iter().any(|g| g.0 == player) {
+                    world.god_mode_components.retain(|g| g.0 != player);
+                    t!("cheats.god_mode_off")
+                } else {
+                    world.god_mode_components.push((player, GodMode));
+                    t!("cheats.god_mode_on")
+                }
+            }
+            Cheat::AllWeapons => {
+                if let Some(w) = world.weapon_components.iter_mut().find(|w| w.0 == player) {
+                    while w.1.upgrade() {}
+                }
+                if let Some(b) = world.bomb_components.iter_mut().find(|b| b.0 == player) {
+                    b.1.count = b.1.capacity;
+                }
+                t!("cheats.all_weapons")
+            }
+            Cheat::SkipWave => {
+                let killed = Damage::kill_hostiles(world);
+                t!("cheats.skip_wave", killed = killed)
+            }
+        }
+    }
+
+    pub fn update_system(&mut self, dt: f32) {
+        if let Some((_, timer)) = self.message.as_mut() {
+            *timer -= dt;
+            if *timer <= 0.0 {
+                self.message = None;
+            }
+        }
+    }
+
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if let Some((message, timer)) = self.message.as_ref() {
+            let text = t!("cheats.message", message = message);
+            let x = world.screen.width() as i32 / 2 - measure_text(&text, 10) / 2;
+            let alpha = (timer / 0.5).min(1.0);
+            d.draw_text(&text, x, 40, 10, Color::GOLD.fade(alpha));
+        }
+    }
+}
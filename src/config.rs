@@ -0,0 +1,444 @@
+use std::fs;
+use std::path::PathBuf;
+
+use raylib::prelude::*;
+
+use crate::audio::Volume;
+use crate::bindings::{self, Action, Bindings};
+use crate::crosshair::CrosshairStyle;
+use crate::difficulty::Difficulty;
+use crate::palette::ColorMode;
+use crate::screen::WindowMode;
+use crate::warn;
+
+/// User settings, stored as `key = value` lines (a small subset of TOML).
+/// Unknown keys and bad values are ignored so an old or hand-edited file
+/// never stops the game from starting.
+///
+/// Settings come in layers: the defaults, then the file, then the command
+/// line (see `args`). What the command line forces is for this run only,
+/// saving writes back the file's value unless the player changed it since.
+pub struct Config {
+    path: PathBuf,
+    pub window_mode: WindowMode,
+    /// Windowed size as a multiple of the canvas resolution.
+    pub window_scale: u32,
+    /// Frames per second to cap the game at, 0 runs uncapped.
+    pub target_fps: u32,
+    pub vsync: bool,
+    pub crosshair: CrosshairStyle,
+    pub crosshair_scale: f32,
+    /// Volumes from 0 to 1, master scales the other two.
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    /// Multiplies every screen shake, 0 turns it off.
+    pub screen_shake: f32,
+    pub color_mode: ColorMode,
+    pub high_contrast_bullets: bool,
+    /// Code of the UI language, the name of its file in `assets/lang`.
+    pub language: String,
+    /// Stored as `key_<action> = "<key>"`, like `key_fire = "SPACE"`.
+    pub bindings: Bindings,
+    /// Cheat codes work outside of debug builds too.
+    pub cheats: bool,
+    /// Picked when starting a new game, so it's saved with the slot.
+    pub difficulty: Difficulty,
+
+    /// Seeds the world's random effects, for runs that play out the same.
+    /// Command line only, like the rest below.
+    pub seed: Option<u64>,
+    /// Level to start in, counting from 1: the campaign's, or the LDtk
+    /// project's when there's no campaign.
+    pub level: Option<usize>,
+    /// Exact window size, instead of a multiple of the canvas.
+    pub window_size: Option<(i32, i32)>,
+    pub msaa: bool,
+    /// Settings the command line forced: key, its value from the file, and
+    /// the value given.
+    overrides: Vec<(String, String, String)>,
+}
+
+impl Config {
+    /// The defaults, saving to `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Config {
+        Config {
+            path: path.into(),
+            window_mode: WindowMode::Windowed,
+            window_scale: 2,
+            target_fps: 60,
+            vsync: true,
+            crosshair: CrosshairStyle::Cross,
+            crosshair_scale: 1.0,
+            master_volume: 1.0,
+            music_volume: 0.8,
+            sfx_volume: 1.0,
+            screen_shake: 1.0,
+            color_mode: ColorMode::Normal,
+            high_contrast_bullets: false,
+            language: "en".to_string(),
+            bindings: Bindings::new(),
+            cheats: false,
+            difficulty: Difficulty::Normal,
+            seed: None,
+            level: None,
+            window_size: None,
+            msaa: false,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// The defaults with the file at `path` on top.
+    pub fn load(path: impl Into<PathBuf>) -> Config {
+        let mut config = Config::new(path);
+        let contents = fs::read_to_string(&config.path).unwrap_or_default();
+        config.read(&contents);
+        config
+    }
+
+    /// Sets everything in the contents of a config file.
+    fn read(&mut self, contents: &str) {
+        for line in contents.lines() {
+            if let Some((key, value)) = without_comment(line).split_once('=') {
+                self.set(key.trim(), value.trim());
+            }
+        }
+    }
+
+    /// Sets one setting from its key and value as written in the file.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "window_mode" => {
+                self.window_mode =
+                    WindowMode::from_name(value.trim_matches('"')).unwrap_or(self.window_mode);
+            }
+            "window_scale" => {
+                self.window_scale = value
+                    .parse::<u32>()
+                    .map(|s| s.clamp(1, 4))
+                    .unwrap_or(self.window_scale);
+            }
+            "target_fps" => self.target_fps = value.parse().unwrap_or(self.target_fps),
+            "vsync" => self.vsync = value.parse().unwrap_or(self.vsync),
+            "crosshair" => {
+                self.crosshair =
+                    CrosshairStyle::from_name(value.trim_matches('"')).unwrap_or(self.crosshair);
+            }
+            "crosshair_scale" => {
+                self.crosshair_scale = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|s| !s.is_nan())
+                    .map_or(self.crosshair_scale, |s| s.clamp(0.5, 4.0));
+            }
+            "master_volume" => self.master_volume = fraction(value, self.master_volume),
+            "music_volume" => self.music_volume = fraction(value, self.music_volume),
+            "sfx_volume" => self.sfx_volume = fraction(value, self.sfx_volume),
+            "screen_shake" => self.screen_shake = fraction(value, self.screen_shake),
+            "color_mode" => {
+                self.color_mode =
+                    ColorMode::from_name(value.trim_matches('"')).unwrap_or(self.color_mode);
+            }
+            "high_contrast_bullets" => {
+                self.high_contrast_bullets = value.parse().unwrap_or(self.high_contrast_bullets)
+            }
+            "language" => self.language = value.trim_matches('"').to_string(),
+            "cheats" => self.cheats = value.parse().unwrap_or(self.cheats),
+            "difficulty" => {
+                self.difficulty =
+                    Difficulty::from_name(value.trim_matches('"')).unwrap_or(self.difficulty);
+            }
+            _ => {
+                let action = key
+                    .strip_prefix("key_")
+                    .and_then(|name| Action::values().into_iter().find(|a| a.name() == name));
+                let bound = bindings::key_from_name(value.trim_matches('"'));
+                if let (Some(action), Some(bound)) = (action, bound) {
+                    self.bindings.set(action, bound);
+                }
+            }
+        }
+    }
+
+    /// Applies the command line, without the program name, on top:
+    ///
+    /// - `--windowed`, `--borderless`, `--fullscreen`
+    /// - `--scale N`, `--size WxH`, `--fps N`, `--vsync`, `--no-vsync`,
+    ///   `--msaa`
+    /// - `--seed N`, `--level N`, `--cheats`
+    /// - `--set key=value` for any setting in the file
+    ///
+    /// Like the file, unknown options and bad values are skipped with a
+    /// warning.
+    pub fn args(&mut self, args: impl IntoIterator<Item = String>) {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mode = arg
+                .strip_prefix("--")
+                .and_then(WindowMode::from_name)
+                .map(|m| format!("\"{}\"", m.name()));
+            if let Some(mode) = mode {
+                self.force("window_mode", &mode);
+                continue;
+            }
+            match arg.as_str() {
+                "--vsync" => {
+                    self.force("vsync", "true");
+                }
+                "--no-vsync" => {
+                    self.force("vsync", "false");
+                }
+                "--cheats" => {
+                    self.force("cheats", "true");
+                }
+                "--msaa" => self.msaa = true,
+                "--scale" | "--fps" | "--seed" | "--level" | "--size" | "--set" => {
+                    let value = args.next().unwrap_or_default();
+                    if !self.option(&arg, &value) {
+                        warn!(
+                            "config",
+                            "bad command line value",
+                            option = arg,
+                            value = value
+                        );
+                    }
+                }
+                _ => warn!("config", "unknown command line option", option = arg),
+            }
+        }
+    }
+
+    /// One option that takes a value, false when the value is no good.
+    fn option(&mut self, option: &str, value: &str) -> bool {
+        match option {
+            "--scale" => value.parse::<u32>().is_ok() && self.force("window_scale", value),
+            "--fps" => value.parse::<u32>().is_ok() && self.force("target_fps", value),
+            "--seed" => {
+                self.seed = value.parse().ok();
+                self.seed.is_some()
+            }
+            "--level" => {
+                self.level = value.parse().ok().filter(|l| *l > 0);
+                self.level.is_some()
+            }
+            "--size" => {
+                self.window_size = value.split_once('x').and_then(|(w, h)| {
+                    Some((w.parse().ok()?, h.parse().ok()?)).filter(|(w, h)| *w > 0 && *h > 0)
+                });
+                self.window_size.is_some()
+            }
+            "--set" => match value.split_once('=') {
+                Some((key, value)) => self.force(key.trim(), value.trim()),
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Sets `key` for this run only. False when there's no such setting.
+    fn force(&mut self, key: &str, value: &str) -> bool {
+        let file = match self.entry(key) {
+            Some(file) => file,
+            None => return false,
+        };
+        // an earlier override of the same key keeps the file's value
+        let file = match self.overrides.iter().position(|o| o.0 == key) {
+            Some(i) => self.overrides.remove(i).1,
+            None => file,
+        };
+        self.set(key, value);
+        let given = self.entry(key).unwrap_or_default();
+        self.overrides.push((key.to_string(), file, given));
+        true
+    }
+
+    fn entry(&self, key: &str) -> Option<String> {
+        self.entries().into_iter().find(|e| e.0 == key).map(|e| e.1)
+    }
+
+    /// Every setting as a key and its value as written in the file.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![
+            ("window_mode", format!("\"{}\"", self.window_mode.name())),
+            ("window_scale", self.window_scale.to_string()),
+            ("target_fps", self.target_fps.to_string()),
+            ("vsync", self.vsync.to_string()),
+            ("crosshair", format!("\"{}\"", self.crosshair.name())),
+            ("crosshair_scale", self.crosshair_scale.to_string()),
+            ("master_volume", self.master_volume.to_string()),
+            ("music_volume", self.music_volume.to_string()),
+            ("sfx_volume", self.sfx_volume.to_string()),
+            ("screen_shake", self.screen_shake.to_string()),
+            ("color_mode", format!("\"{}\"", self.color_mode.name())),
+            (
+                "high_contrast_bullets",
+                self.high_contrast_bullets.to_string(),
+            ),
+            ("language", format!("\"{}\"", self.language)),
+            ("cheats", self.cheats.to_string()),
+            ("difficulty", format!("\"{}\"", self.difficulty.name())),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect::<Vec<(String, String)>>();
+        for action in Action::values() {
+            let key = bindings::key_name(self.bindings.key(action));
+            entries.push((format!("key_{}", action.name()), format!("\"{}\"", key)));
+        }
+        entries
+    }
+
+    pub fn save(&self) {
+        let contents: String = self
+            .entries()
+            .into_iter()
+            .map(|(key, value)| {
+                // still what the command line said, so not the player's choice
+                let value = match self.overrides.iter().find(|o| o.0 == key) {
+                    Some((_, file, given)) if *given == value => file.clone(),
+                    _ => value,
+                };
+                format!("{} = {}\n", key, value)
+            })
+            .collect();
+        if let Err(e) = fs::write(&self.path, contents) {
+            warn!(
+                "config",
+                "could not save config",
+                path = self.path.display(),
+                error = e
+            );
+        }
+    }
+
+    pub fn volume(&self) -> Volume {
+        Volume {
+            master: self.master_volume,
+            music: self.music_volume,
+            sfx: self.sfx_volume,
+        }
+    }
+
+    pub fn set_volume(&mut self, volume: &Volume) {
+        self.master_volume = volume.master;
+        self.music_volume = volume.music;
+        self.sfx_volume = volume.sfx;
+    }
+
+    /// Applies the frame cap and vsync to a running window, for when they
+    /// change after startup.
+    pub fn apply_video(&self, rl: &mut RaylibHandle) {
+        rl.set_target_fps(self.target_fps);
+        let vsync = WindowState::default().set_vsync_hint(true);
+        if self.vsync {
+            rl.set_window_state(vsync);
+        } else {
+            rl.clear_window_state(vsync);
+        }
+    }
+}
+
+/// A number from 0 to 1, clamped. `NaN` parses, but isn't one.
+fn fraction(value: &str, default: f32) -> f32 {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|v| !v.is_nan())
+        .map_or(default, |v| v.clamp(0.0, 1.0))
+}
+
+/// The line up to a `#` that isn't inside a quoted value.
+fn without_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(config: &Config) -> String {
+        config
+            .entries()
+            .into_iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value))
+            .collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut config = Config::new("unused.toml");
+        config.window_mode = WindowMode::Borderless;
+        config.window_scale = 3;
+        config.vsync = false;
+        config.crosshair_scale = 1.5;
+        config.music_volume = 0.25;
+        config.language = "de".to_string();
+        config.difficulty = Difficulty::Hard;
+        config.bindings.set(Action::Fire, KeyboardKey::KEY_ENTER);
+
+        let mut read = Config::new("unused.toml");
+        read.read(&file(&config));
+        assert_eq!(read.entries(), config.entries());
+    }
+
+    #[test]
+    fn skips_what_it_cant_read() {
+        let mut config = Config::new("unused.toml");
+        config.read(
+            "# a comment\n\
+             window_scale = 99\n\
+             target_fps = -5\n\
+             master_volume = NaN\n\
+             music_volume = 3\n\
+             crosshair_scale = nan\n\
+             vsync = yes\n\
+             difficulty = \"impossible\"\n\
+             key_fire = \"NOT_A_KEY\"\n\
+             key_jump = \"SPACE\"\n\
+             no value here\n\
+             unknown = 1\n\
+             \n\
+             sfx_volume = 0.5 # quieter\n",
+        );
+        let defaults = Config::new("unused.toml");
+        assert_eq!(config.window_scale, 4);
+        assert_eq!(config.target_fps, defaults.target_fps);
+        assert_eq!(config.master_volume, defaults.master_volume);
+        assert_eq!(config.music_volume, 1.0);
+        assert_eq!(config.crosshair_scale, defaults.crosshair_scale);
+        assert_eq!(config.vsync, defaults.vsync);
+        assert_eq!(config.difficulty, defaults.difficulty);
+        assert_eq!(
+            config.bindings.key(Action::Fire),
+            defaults.bindings.key(Action::Fire)
+        );
+        assert_eq!(config.sfx_volume, 0.5);
+    }
+
+    #[test]
+    fn keeps_a_hash_inside_quotes() {
+        let mut config = Config::new("unused.toml");
+        config.read("language = \"x#y\" # not this\n");
+        assert_eq!(config.language, "x#y");
+    }
+
+    #[test]
+    fn saves_the_files_value_for_what_the_command_line_forced() {
+        let mut config = Config::new("unused.toml");
+        config.read("window_scale = 3\n");
+        config.args(["--scale", "1", "--set", "vsync=false"].map(String::from));
+        assert_eq!(config.window_scale, 1);
+        assert!(!config.vsync);
+        let forced: Vec<&str> = config.overrides.iter().map(|o| o.2.as_str()).collect();
+        assert_eq!(forced, ["1", "false"]);
+        assert_eq!(config.overrides[0].1, "3");
+    }
+}
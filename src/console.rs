@@ -0,0 +1,414 @@
+use std::fs;
+
+use raylib::prelude::*;
+
+use crate::damage::Damage;
+use crate::input;
+use crate::json::Json;
+use crate::prefab::{Placement, Prefabs};
+use crate::renderer::Renderer;
+use crate::{BoundingBox2D, World};
+
+const MAX_LOG: usize = 200;
+const MAX_HISTORY: usize = 50;
+const LINE_HEIGHT: i32 = 12;
+/// Part of the screen the console covers when down.
+const HEIGHT: f32 = 0.45;
+
+/// Runs a command with its arguments, returning what to print.
+pub type Run = fn(&mut World, &[&str]) -> Result<String, String>;
+/// Candidates for a command's first argument, for autocompletion.
+pub type Complete = fn(&World) -> Vec<String>;
+
+struct Command {
+    name: &'static str,
+    usage: &'static str,
+    run: Run,
+    complete: Option<Complete>,
+}
+
+/// Drop-down developer console, toggled with the tilde key. Commands come
+/// from a registry, Tab completes command names and first arguments, Up and
+/// Down walk through earlier input.
+pub struct Console {
+    open: bool,
+    input: String,
+    commands: Vec<Command>,
+    log: Vec<String>,
+    history: Vec<String>,
+    /// Position while walking the history, None when typing something new.
+    browsing: Option<usize>,
+}
+
+impl Console {
+    pub fn new() -> Console {
+        Console {
+            open: false,
+            input: String::new(),
+            commands: Vec::new(),
+            log: Vec::new(),
+            history: Vec::new(),
+            browsing: None,
+        }
+    }
+
+    /// A console with the game's own commands.
+    pub fn builtin() -> Console {
+        let mut console = Console::new();
+        console.register("spawn", "spawn <prefab> [x y]", spawn, Some(prefab_names));
+        console.register(
+            "give",
+            "give <credits|bombs|health> [amount]",
+            give,
+            Some(gifts),
+        );
+        console.register("killall", "killall", kill_all, None);
+        console.register("dump", "dump [file]", dump, None);
+        console.register("restore", "restore [file]", restore, None);
+        console.register("set", "set <timescale|shake> <value>", set, Some(settings));
+        console.register(
+            "toggle",
+            "toggle <debugdraw|renderstats>",
+            toggle,
+            Some(toggles),
+        );
+        console
+    }
+
+    /// Adds a command, replacing any with the same name.
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        run: Run,
+        complete: Option<Complete>,
+    ) {
+        self.commands.retain(|c| c.name != name);
+        self.commands.push(Command {
+            name,
+            usage,
+            run,
+            complete,
+        });
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Whether `pos` is on the console while it's down.
+    pub fn covers(&self, world: &World, pos: Vector2) -> bool {
+        self.open && pos.y < (world.screen.height() * HEIGHT).floor()
+    }
+
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        if self.log.len() > MAX_LOG {
+            self.log.remove(0);
+        }
+    }
+
+    /// Runs one line of input. `help` and `clear` are the console's own.
+    pub fn execute(&mut self, world: &mut World, line: &str) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, args) = match words.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        self.print(format!("> {}", line));
+        match *name {
+            "help" => {
+                let usages: Vec<&str> = self.commands.iter().map(|c| c.usage).collect();
+                for usage in usages {
+                    self.print(usage);
+                }
+                self.print("clear");
+            }
+            "clear" => self.log.clear(),
+            _ => {
+                let command = match self.commands.iter().find(|c| c.name == *name) {
+                    Some(c) => c,
+                    None => {
+                        self.print(format!("Unknown command {}, try help", name));
+                        return;
+                    }
+                };
+                let usage = command.usage;
+                match (command.run)(world, args) {
+                    Ok(output) if output.is_empty() => (),
+                    Ok(output) => self.print(output),
+                    Err(e) => {
+                        self.print(e);
+                        self.print(format!("usage: {}", usage));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Completes the word being typed, as far as all candidates agree.
+    /// Lists them when that's no further.
+    fn complete(&mut self, world: &World) {
+        let words: Vec<&str> = self.input.split(' ').collect();
+        let candidates: Vec<String> = match words.as_slice() {
+            [word] => {
+                let mut names: Vec<String> =
+                    self.commands.iter().map(|c| c.name.to_string()).collect();
+                names.push("help".to_string());
+                names.push("clear".to_string());
+                names.into_iter().filter(|n| n.starts_with(word)).collect()
+            }
+            [name, word] => self
+                .commands
+                .iter()
+                .find(|c| c.name == *name)
+                .and_then(|c| c.complete)
+                .map(|complete| complete(world))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|n| n.starts_with(word))
+                .collect(),
+            _ => return,
+        };
+        let first = match candidates.first() {
+            Some(f) => f,
+            None => return,
+        };
+        let common = candidates.iter().fold(first.as_str(), |common, c| {
+            let len = common
+                .chars()
+                .zip(c.chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            &common[..len]
+        });
+        let typed = words.last().map(|w| w.len()).unwrap_or(0);
+        if candidates.len() == 1 {
+            self.input = format!("{}{} ", &self.input[..self.input.len() - typed], common);
+        } else if common.len() > typed {
+            self.input = format!("{}{}", &self.input[..self.input.len() - typed], common);
+        } else {
+            self.print(candidates.join("  "));
+        }
+    }
+
+    fn browse(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let last = self.history.len() - 1;
+        self.browsing = match (self.browsing, older) {
+            (None, true) => Some(last),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i < last => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.input = self
+            .browsing
+            .map(|i| self.history[i].clone())
+            .unwrap_or_default();
+    }
+
+    /// Toggles the console and, while it's down, takes all typing. The main
+    /// loop leaves the game's own input alone meanwhile.
+    pub fn input_system(&mut self, world: &mut World, rl: &RaylibHandle) {
+        if rl.is_key_pressed(KeyboardKey::KEY_GRAVE) {
+            self.open = !self.open;
+            // eat the tilde so it doesn't end up in the input
+            input::typed(rl);
+            return;
+        }
+        if !self.open {
+            return;
+        }
+
+        self.input
+            .extend(input::typed(rl).into_iter().filter(|c| !c.is_control()));
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            self.input.pop();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            self.complete(world);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.browse(true);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.browse(false);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            self.open = false;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            let line = std::mem::take(&mut self.input);
+            self.browsing = None;
+            if !line.trim().is_empty() && self.history.last() != Some(&line) {
+                self.history.push(line.clone());
+                if self.history.len() > MAX_HISTORY {
+                    self.history.remove(0);
+                }
+            }
+            self.execute(world, &line);
+        }
+    }
+
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if !self.open {
+            return;
+        }
+        let w = world.screen.width();
+        let h = (world.screen.height() * HEIGHT).floor();
+        d.draw_rect(Rectangle::new(0.0, 0.0, w, h), Color::BLACK.fade(0.85));
+        d.draw_line(Vector2::new(0.0, h), Vector2::new(w, h), 1.0, Color::GRAY);
+
+        let input_y = h as i32 - LINE_HEIGHT - 4;
+        d.draw_text(&format!("> {}_", self.input), 6, input_y, 10, Color::YELLOW);
+        let mut y = input_y - LINE_HEIGHT - 2;
+        for line in self.log.iter().rev() {
+            if y < 0 {
+                break;
+            }
+            d.draw_text(line, 6, y, 10, Color::RAYWHITE);
+            y -= LINE_HEIGHT;
+        }
+    }
+}
+
+/// Where things go when a command isn't told: on the player, or the middle
+/// of the view without one.
+fn here(world: &World) -> Vector2 {
+    world
+        .player_components
+        .first()
+        .and_then(|p| world.base_components.iter().find(|b| b.0 == p.0))
+        .map(|b| b.1.bounds.center())
+        .or_else(|| world.camera_components.first().map(|c| c.1.target))
+        .unwrap_or_default()
+}
+
+fn number<T: std::str::FromStr>(arg: Option<&&str>, default: T) -> Result<T, String> {
+    match arg {
+        Some(a) => a.parse().map_err(|_| format!("{} is not a number", a)),
+        None => Ok(default),
+    }
+}
+
+fn spawn(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = args.first().ok_or("Spawn what?")?;
+    let around = here(world);
+    let x = number(args.get(1), around.x)?;
+    let y = number(args.get(2), around.y)?;
+    let placement = Placement::new(BoundingBox2D::new(x, y, 0.0, 0.0));
+    match Prefabs::spawn(world, name, &placement) {
+        Some(id) => Ok(format!("Spawned {} as {}", name, id)),
+        None => Err(format!("No prefab called {}", name)),
+    }
+}
+
+fn prefab_names(world: &World) -> Vec<String> {
+    world.prefabs.names().map(|n| n.to_string()).collect()
+}
+
+fn give(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let player = world
+        .player_components
+        .first()
+        .map(|p| p.0)
+        .ok_or("No player")?;
+    match args.first() {
+        Some(&"credits") => {
+            let amount = number(args.get(1), 1000)?;
+            if let Some(p) = world.player_components.iter_mut().find(|p| p.0 == player) {
+                p.1.credits += amount;
+            }
+            Ok(format!("Gave {} credits", amount))
+        }
+        Some(&"bombs") => {
+            let bombs = world.bomb_components.iter_mut().find(|b| b.0 == player);
+            let bombs = bombs.ok_or("The player carries no bombs")?;
+            bombs.1.count = bombs.1.capacity;
+            Ok("Bombs refilled".to_string())
+        }
+        Some(&"health") => {
+            let health = world.health_components.iter_mut().find(|h| h.0 == player);
+            let health = health.ok_or("The player has no health")?;
+            health.1.health = health.1.max_health;
+            Ok("Hull repaired".to_string())
+        }
+        Some(other) => Err(format!("Can't give {}", other)),
+        None => Err("Give what?".to_string()),
+    }
+}
+
+fn gifts(_: &World) -> Vec<String> {
+    ["credits", "bombs", "health"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Kills every hostile through the damage system, so deaths count and
+/// explode like any other.
+fn kill_all(world: &mut World, _: &[&str]) -> Result<String, String> {
+    Ok(format!("Killed {} hostiles", Damage::kill_hostiles(world)))
+}
+
+/// Writes `World::to_json` to a file, `world.json` unless told.
+fn dump(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let path = args.first().unwrap_or(&"world.json");
+    fs::write(path, world.to_json().to_string()).map_err(|e| e.to_string())?;
+    Ok(format!("World written to {}", path))
+}
+
+fn restore(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let path = args.first().unwrap_or(&"world.json");
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    world.from_json(&Json::parse(&contents)?)?;
+    Ok(format!("World restored from {}", path))
+}
+
+fn set(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let value: f32 = match args.get(1) {
+        Some(_) => number(args.get(1), 0.0)?,
+        None => return Err("Set it to what?".to_string()),
+    };
+    match args.first() {
+        Some(&"timescale") => world.time.time_scale = value.clamp(0.0, 10.0),
+        Some(&"shake") => world.juice.intensity = value.clamp(0.0, 1.0),
+        Some(other) => return Err(format!("Can't set {}", other)),
+        None => return Err("Set what?".to_string()),
+    }
+    Ok(String::new())
+}
+
+fn settings(_: &World) -> Vec<String> {
+    ["timescale", "shake"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn toggle(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let on = match args.first() {
+        Some(&"debugdraw") => {
+            world.debug.enabled = !world.debug.enabled;
+            world.gizmos.enabled = world.debug.enabled;
+            world.debug.enabled
+        }
+        Some(&"renderstats") => {
+            world.render_stats.enabled = !world.render_stats.enabled;
+            world.render_stats.enabled
+        }
+        Some(other) => return Err(format!("Can't toggle {}", other)),
+        None => return Err("Toggle what?".to_string()),
+    };
+    Ok(format!("{} {}", args[0], if on { "on" } else { "off" }))
+}
+
+fn toggles(_: &World) -> Vec<String> {
+    ["debugdraw", "renderstats"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
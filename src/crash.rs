@@ -0,0 +1,172 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use raylib::prelude::*;
+
+use crate::log;
+use crate::state::GameState;
+use crate::{EntityID, World};
+
+/// Log records included in a crash report.
+const LOG_LINES: usize = 50;
+/// Characters per line of the panic message on the crash screen.
+const WRAP: usize = 70;
+
+/// What the world looked like at the end of the last frame. Kept up to date
+/// by `snapshot`, since the panic hook can't reach the world itself.
+struct WorldStats {
+    frame: u64,
+    elapsed: f32,
+    state: GameState,
+    scene: Option<&'static str>,
+    last_entity: EntityID,
+    components: [(&'static str, usize); 12],
+}
+
+struct Crash {
+    message: String,
+    report: Option<PathBuf>,
+}
+
+static WORLD: Mutex<Option<WorldStats>> = Mutex::new(None);
+static CRASH: Mutex<Option<Crash>> = Mutex::new(None);
+
+/// Replaces the default panic output with a crash report in `dir`: the
+/// panic message and where it happened, a backtrace, the last log records
+/// and the last world snapshot. The default output still goes to stderr.
+pub fn install(dir: &'static str) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let message = panic_message(info);
+        let report = write_report(dir, &message);
+        if let Ok(mut crash) = CRASH.lock() {
+            *crash = Some(Crash { message, report });
+        }
+    }));
+}
+
+/// Remembers the world's vitals for the crash report, once per frame.
+pub fn snapshot(world: &World, scene: Option<&'static str>) {
+    let stats = WorldStats {
+        frame: world.time.frame(),
+        elapsed: world.time.elapsed(),
+        state: world.state.current(),
+        scene,
+        last_entity: world.last_entity,
+        components: [
+            ("base", world.base_components.len()),
+            ("health", world.health_components.len()),
+            ("player", world.player_components.len()),
+            ("velocity", world.velocity_components.len()),
+            ("team", world.team_components.len()),
+            ("projectile", world.projectile_components.len()),
+            ("sprite", world.sprite_components.len()),
+            ("emitter", world.emitter_components.len()),
+            ("light", world.light_components.len()),
+            ("text", world.text_components.len()),
+            ("ui", world.ui_components.len()),
+            ("widget", world.widget_components.len()),
+        ],
+    };
+    if let Ok(mut world) = WORLD.lock() {
+        *world = Some(stats);
+    }
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    match info.location() {
+        Some(l) => format!("{} at {}:{}", payload, l.file(), l.line()),
+        None => payload,
+    }
+}
+
+fn write_report(dir: &str, message: &str) -> Option<PathBuf> {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |t| t.as_secs());
+    let mut contents = format!(
+        "SpaceGame crashed\n\n{}\nthread: {}\n\n",
+        message,
+        std::thread::current().name().unwrap_or("unnamed")
+    );
+
+    contents += "World\n";
+    // the lock is only ever held for a moment, a busy one means the panic
+    // came from inside it
+    match WORLD.try_lock().ok().as_ref().and_then(|w| w.as_ref()) {
+        Some(w) => {
+            contents += &format!(
+                "frame {}, {:.2}s in, state {:?}, scene {}\nlast entity {}\n",
+                w.frame,
+                w.elapsed,
+                w.state,
+                w.scene.unwrap_or("none"),
+                w.last_entity
+            );
+            for (name, count) in w.components.iter() {
+                contents += &format!("  {:<12} {}\n", name, count);
+            }
+        }
+        None => contents += "  no snapshot\n",
+    }
+
+    contents += "\nLog\n";
+    for record in log::try_recent(LOG_LINES) {
+        contents += &format!("{}\n", record);
+    }
+    contents += &format!("\nBacktrace\n{}\n", Backtrace::force_capture());
+
+    fs::create_dir_all(dir).ok()?;
+    let path = PathBuf::from(dir).join(format!("crash_{}.txt", time));
+    fs::write(&path, contents).ok()?;
+    Some(path)
+}
+
+/// After a panic took the game down, opens a small window saying so and
+/// where the report went, until a key is pressed or it's closed. Does
+/// nothing when there was no panic, or in the browser, where the panic
+/// went to the console and there's no second window to open.
+pub fn show_screen() {
+    if cfg!(target_os = "emscripten") {
+        return;
+    }
+    let crash = match CRASH.lock().ok().and_then(|mut c| c.take()) {
+        Some(c) => c,
+        None => return,
+    };
+    let mut lines = vec!["SpaceGame ran into a problem and had to close.".to_string()];
+    lines.push(String::new());
+    let chars: Vec<char> = crash.message.chars().collect();
+    lines.extend(chars.chunks(WRAP).take(4).map(|c| c.iter().collect()));
+    lines.push(String::new());
+    lines.push(match crash.report.as_ref() {
+        Some(p) => format!("A crash report was saved to {}", p.display()),
+        None => "The crash report could not be saved.".to_string(),
+    });
+    lines.push("Press any key to close.".to_string());
+
+    let (mut rl, thread) = raylib::init()
+        .size(480, 40 + lines.len() as i32 * 16)
+        .title("SpaceGame crashed")
+        .build();
+    rl.set_target_fps(30);
+    while !rl.window_should_close() && rl.get_key_pressed().is_none() {
+        let mut d = rl.begin_drawing(&thread);
+        d.clear_background(Color::new(40, 20, 30, 255));
+        for (i, line) in lines.iter().enumerate() {
+            let color = if i == 0 { Color::GOLD } else { Color::RAYWHITE };
+            d.draw_text(line, 20, 20 + i as i32 * 16, 10, color);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use raylib::prelude::*;
+
+use crate::font::{self, TextStyle};
+use crate::pack;
+use crate::renderer::Renderer;
+use crate::state::GameState;
+use crate::{Anchor, World};
+
+/// Pixels per second the credits roll at.
+const SCROLL_SPEED: f32 = 30.0;
+const LINE_HEIGHT: f32 = 18.0;
+/// Used when there's no credits file, same format.
+const DEFAULT_CREDITS: &str = "# Spacegame
+bramtechs
+
+# Built with
+Rust
+raylib
+
+# Thanks for playing";
+
+/// Rolling credits shown in the Credits state, from the title screen's
+/// Credits button. Each line of the text is a name, lines starting with
+/// `# ` are headings. Any key or click goes back to the title screen, as
+/// does reaching the end.
+pub struct Credits {
+    lines: Vec<String>,
+    scroll: f32,
+}
+
+impl Credits {
+    /// Reads the credits from `path`, falling back to the built-in ones.
+    pub fn load(path: &str) -> Credits {
+        let text = pack::read_to_string(path).unwrap_or_else(|_| DEFAULT_CREDITS.to_string());
+        Credits {
+            lines: text.lines().map(|l| l.trim_end().to_string()).collect(),
+            scroll: 0.0,
+        }
+    }
+
+    pub fn input_system(&mut self, world: &mut World, rl: &mut RaylibHandle) {
+        if !world.state.is(GameState::Credits) {
+            return;
+        }
+        let clicked = rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
+        if rl.get_key_pressed().is_some() || clicked {
+            world.state.set(GameState::Menu);
+        }
+    }
+
+    pub fn update_system(&mut self, world: &mut World, dt: f32) {
+        if !world.state.is(GameState::Credits) {
+            self.scroll = 0.0;
+            return;
+        }
+        self.scroll += SCROLL_SPEED * dt;
+        // the last line has left the top of the screen
+        let length = self.lines.len() as f32 * LINE_HEIGHT;
+        if self.scroll > world.screen.height() + length {
+            world.state.set(GameState::Menu);
+        }
+    }
+
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if !world.state.is(GameState::Credits) {
+            return;
+        }
+        let (w, h) = (world.screen.width(), world.screen.height());
+        d.draw_rect(Rectangle::new(0.0, 0.0, w, h), Color::BLACK.fade(0.6));
+        for (i, line) in self.lines.iter().enumerate() {
+            let y = h - self.scroll + i as f32 * LINE_HEIGHT;
+            if !(-LINE_HEIGHT..h + LINE_HEIGHT).contains(&y) {
+                continue;
+            }
+            let (text, style) = match line.strip_prefix("# ") {
+                Some(heading) => (heading, TextStyle::new(18.0, Color::GOLD)),
+                None => (line.as_str(), TextStyle::new(14.0, Color::RAYWHITE)),
+            };
+            let style = style.aligned(Anchor::Center).outlined(Color::BLACK);
+            font::draw(&world.assets, d, text, Vector2::new(w * 0.5, y), &style);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use raylib::prelude::*;
+
+use crate::assets::Handle;
+use crate::renderer::Renderer;
+use crate::{DrawSystem, UpdateSystem, World};
+
+/// Seconds the hit marker stays up after a confirmed hit.
+const HIT_MARKER_TIME: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrosshairStyle {
+    Cross,
+    Dot,
+    Circle,
+    /// The crosshair texture, falls back to Cross when it isn't loaded.
+    Sprite,
+}
+
+impl CrosshairStyle {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrosshairStyle::Cross => "cross",
+            CrosshairStyle::Dot => "dot",
+            CrosshairStyle::Circle => "circle",
+            CrosshairStyle::Sprite => "sprite",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CrosshairStyle> {
+        match name {
+            "cross" => Some(CrosshairStyle::Cross),
+            "dot" => Some(CrosshairStyle::Dot),
+            "circle" => Some(CrosshairStyle::Circle),
+            "sprite" => Some(CrosshairStyle::Sprite),
+            _ => None,
+        }
+    }
+}
+
+/// Game cursor replacing the OS one, drawn on top of everything at the
+/// mouse position. Flashes a hit marker when the player's shots land.
+pub struct Crosshair {
+    pub style: CrosshairStyle,
+    pub scale: f32,
+    pub texture: Option<Handle<Texture2D>>,
+    pos: Vector2,
+    hit_timer: f32,
+    crit: bool,
+}
+
+impl Crosshair {
+    pub fn new(style: CrosshairStyle, scale: f32) -> Crosshair {
+        Crosshair {
+            style,
+            scale,
+            texture: None,
+            pos: Vector2::zero(),
+            hit_timer: 0.0,
+            crit: false,
+        }
+    }
+
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        world.crosshair.pos = world.screen.window_to_canvas(rl.get_mouse_position());
+    }
+}
+
+impl UpdateSystem for Crosshair {
+    fn update_system(world: &mut World, dt: f32) {
+        let crosshair = &mut world.crosshair;
+        crosshair.hit_timer = (crosshair.hit_timer - dt).max(0.0);
+
+        let hits = world.damage_events.iter().filter(|e| {
+            e.source
+                .is_some_and(|s| world.player_components.iter().any(|p| p.0 == s))
+        });
+        for hit in hits {
+            crosshair.hit_timer = HIT_MARKER_TIME;
+            crosshair.crit |= hit.crit;
+        }
+        if crosshair.hit_timer == 0.0 {
+            crosshair.crit = false;
+        }
+    }
+}
+
+impl DrawSystem for Crosshair {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        let crosshair = &world.crosshair;
+        let pos = crosshair.pos;
+        let s = crosshair.scale;
+        let color = Color::WHITE;
+
+        let texture = crosshair.texture.and_then(|t| world.assets.texture(t));
+        match (crosshair.style, texture) {
+            (CrosshairStyle::Sprite, Some(t)) => {
+                let size = Vector2::new(t.width() as f32, t.height() as f32) * s;
+                let source = Rectangle::new(0.0, 0.0, t.width() as f32, t.height() as f32);
+                let dest =
+                    Rectangle::new(pos.x - size.x * 0.5, pos.y - size.y * 0.5, size.x, size.y);
+                d.draw_sprite(t, source, dest, Vector2::zero(), 0.0, color);
+            }
+            (CrosshairStyle::Dot, _) => {
+                d.draw_circle(pos, 2.0 * s, color);
+            }
+            (CrosshairStyle::Circle, _) => {
+                d.draw_circle_outline(pos, 6.0 * s, color);
+                d.draw_circle(pos, 1.0 * s, color);
+            }
+            // the gap in the middle keeps the target visible
+            (CrosshairStyle::Cross, _) | (CrosshairStyle::Sprite, None) => {
+                for dir in [
+                    Vector2::new(1.0, 0.0),
+                    Vector2::new(-1.0, 0.0),
+                    Vector2::new(0.0, 1.0),
+                    Vector2::new(0.0, -1.0),
+                ] {
+                    d.draw_line(pos + dir * 3.0 * s, pos + dir * 8.0 * s, s.max(1.0), color);
+                }
+            }
+        }
+
+        if crosshair.hit_timer > 0.0 {
+            // diagonal ticks that spread out and fade as the marker expires
+            let t = crosshair.hit_timer / HIT_MARKER_TIME;
+            let spread = (6.0 + (1.0 - t) * 4.0) * s;
+            let marker = if crosshair.crit {
+                Color::GOLD
+            } else {
+                Color::WHITE
+            };
+            for (dx, dy) in [(1.0, 1.0), (-1.0, 1.0), (1.0, -1.0), (-1.0, -1.0)] {
+                let dir = Vector2::new(dx, dy).normalized();
+                d.draw_line(
+                    pos + dir * spread,
+                    pos + dir * (spread + 4.0 * s),
+                    2.0,
+                    marker.fade(t),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use raylib::prelude::*;
+
+use crate::audio::PlaySound;
+use crate::camera::Camera;
+use crate::json::Json;
+use crate::prefab;
+use crate::renderer::Renderer;
+use crate::{warn, EntityID, World};
+
+/// Seconds the banner takes to fade in and out.
+const BANNER_FADE: f32 = 0.3;
+
+/// Who a pan goes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// The entity the cutscene was played for, the boss of a boss intro.
+    Subject,
+    Player,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Takes the controls from the player, and every weapon holds fire so
+    /// nobody gets a free shot in.
+    Lock,
+    Unlock,
+    /// Pans the camera to the target in the given seconds, it follows the
+    /// target from there.
+    Pan(Target, f32),
+    /// Shows a banner across the screen for the given seconds, with the
+    /// subject's name when there's no text.
+    Banner(Option<String>, f32),
+    /// Plays the sound at the path.
+    Sound(String),
+    /// `Camera::zoom_punch` with an amount and seconds.
+    Punch(f32, f32),
+}
+
+/// A step some seconds into a cutscene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub at: f32,
+    pub step: Step,
+}
+
+impl Cue {
+    pub fn new(at: f32, step: Step) -> Cue {
+        Cue { at, step }
+    }
+
+    /// Reads a cue of a cutscene's list, `do` saying which step it is:
+    ///
+    /// ```json
+    /// { "at": 0, "do": "lock" }
+    /// { "at": 0, "do": "pan", "to": "subject", "time": 1.2 }
+    /// { "at": 1.2, "do": "banner", "text": "The Warden", "time": 2 }
+    /// { "at": 1.2, "do": "sound", "path": "assets/sfx/boss_roar.wav" }
+    /// { "at": 1.2, "do": "punch", "amount": 0.1, "time": 0.5 }
+    /// { "at": 4, "do": "unlock" }
+    /// ```
+    pub fn parse(json: &Json) -> Option<Cue> {
+        let time = || prefab::number(json, "time");
+        let step = match json.get("do")?.as_str()? {
+            "lock" => Step::Lock,
+            "unlock" => Step::Unlock,
+            "pan" => {
+                let target = match json.get("to")?.as_str()? {
+                    "subject" => Target::Subject,
+                    "player" => Target::Player,
+                    _ => return None,
+                };
+                Step::Pan(target, time()?)
+            }
+            "banner" => {
+                let text = match json.get("text") {
+                    Some(text) => Some(text.as_str()?.to_string()),
+                    None => None,
+                };
+                Step::Banner(text, time()?)
+            }
+            "sound" => Step::Sound(json.get("path")?.as_str()?.to_string()),
+            "punch" => Step::Punch(prefab::number(json, "amount")?, time()?),
+            _ => return None,
+        };
+        Some(Cue::new(prefab::number(json, "at").unwrap_or(0.0), step))
+    }
+}
+
+/// Reads a list of cues, sorted by when they come.
+pub fn parse_cues(json: &Json) -> Option<Vec<Cue>> {
+    let mut cues = json
+        .as_array()?
+        .iter()
+        .map(Cue::parse)
+        .collect::<Option<Vec<_>>>()?;
+    cues.sort_by(|a, b| a.at.total_cmp(&b.at));
+    Some(cues)
+}
+
+/// What a boss without an intro of its own gets: the camera goes over to
+/// it, its name comes up with a roar and the camera goes back.
+pub fn boss_intro() -> Vec<Cue> {
+    vec![
+        Cue::new(0.0, Step::Lock),
+        Cue::new(0.0, Step::Pan(Target::Subject, 1.0)),
+        Cue::new(1.0, Step::Banner(None, 2.0)),
+        Cue::new(1.0, Step::Sound("assets/sfx/boss_roar.wav".to_string())),
+        Cue::new(1.0, Step::Punch(0.1, 0.5)),
+        Cue::new(3.0, Step::Pan(Target::Player, 0.8)),
+        Cue::new(3.8, Step::Unlock),
+    ]
+}
+
+struct Banner {
+    text: String,
+    duration: f32,
+    age: f32,
+}
+
+/// Plays one cutscene at a time: a list of cues, each step taken once the
+/// cutscene has run for its `at` seconds. Boss intros are played by the
+/// campaign when the boss spawns.
+pub struct Cutscene {
+    cues: Vec<Cue>,
+    /// Index of the next cue to take.
+    next: usize,
+    elapsed: f32,
+    subject: Option<EntityID>,
+    locked: bool,
+    banner: Option<Banner>,
+}
+
+impl Cutscene {
+    pub fn new() -> Cutscene {
+        Cutscene {
+            cues: Vec::new(),
+            next: 0,
+            elapsed: 0.0,
+            subject: None,
+            locked: false,
+            banner: None,
+        }
+    }
+
+    /// Starts `cues`, sorted by `at`, about `subject`. Cuts off whatever
+    /// was playing.
+    pub fn play(&mut self, cues: Vec<Cue>, subject: EntityID) {
+        *self = Cutscene {
+            cues,
+            subject: Some(subject),
+            ..Cutscene::new()
+        };
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.next < self.cues.len() || self.banner.is_some()
+    }
+
+    /// True between a lock step and an unlock, or the cutscene's end.
+    pub fn locks_input(&self) -> bool {
+        self.locked
+    }
+
+    /// Takes the steps that came due.
+    pub fn update_system(world: &mut World, dt: f32) {
+        if !world.cutscene.is_playing() {
+            return;
+        }
+        // on real time like the camera tweens it starts, so a slow motion
+        // kill just before doesn't drag it out
+        let dt = if dt > 0.0 {
+            world.time.unscaled_delta()
+        } else {
+            0.0
+        };
+        let cutscene = &mut world.cutscene;
+        cutscene.elapsed += dt;
+        if let Some(banner) = cutscene.banner.as_mut() {
+            banner.age += dt;
+            if banner.age >= banner.duration {
+                cutscene.banner = None;
+            }
+        }
+        let due = cutscene.cues[cutscene.next..]
+            .iter()
+            .take_while(|c| c.at <= cutscene.elapsed)
+            .count();
+        let steps: Vec<Step> = cutscene.cues[cutscene.next..cutscene.next + due]
+            .iter()
+            .map(|c| c.step.clone())
+            .collect();
+        cutscene.next += due;
+
+        for step in steps {
+            Cutscene::take(world, step);
+        }
+        if !world.cutscene.is_playing() {
+            world.cutscene.locked = false;
+        }
+    }
+
+    fn take(world: &mut World, step: Step) {
+        let subject = world.cutscene.subject;
+        match step {
+            Step::Lock => world.cutscene.locked = true,
+            Step::Unlock => world.cutscene.locked = false,
+            Step::Pan(target, duration) => {
+                let entity = match target {
+                    Target::Subject => subject,
+                    Target::Player => world.player_components.first().map(|p| p.0),
+                };
+                if let Some(entity) = entity {
+                    Camera::pan_to(world, entity, duration);
+                }
+            }
+            Step::Banner(text, duration) => {
+                let text = text.or_else(|| {
+                    world
+                        .base_components
+                        .iter()
+                        .find(|b| Some(b.0) == subject)
+                        .map(|b| b.1.name.clone())
+                });
+                world.cutscene.banner = text.map(|text| Banner {
+                    text,
+                    duration,
+                    age: 0.0,
+                });
+            }
+            Step::Sound(path) => match world.assets.load_sound(&path) {
+                Ok(sound) => world.sound_events.push(PlaySound::new(sound)),
+                Err(e) => warn!("cutscene", "could not load sound", error = e),
+            },
+            Step::Punch(amount, duration) => Camera::zoom_punch(world, amount, duration),
+        }
+    }
+
+    /// The banner, a dark strip across the middle of the screen with the
+    /// text on it.
+    pub fn draw_system(world: &World, d: &mut dyn Renderer) {
+        let banner = match world.cutscene.banner.as_ref() {
+            Some(banner) => banner,
+            None => return,
+        };
+        let fade_in = banner.age / BANNER_FADE;
+        let fade_out = (banner.duration - banner.age) / BANNER_FADE;
+        let alpha = fade_in.min(fade_out).clamp(0.0, 1.0);
+
+        let screen = world.screen.canvas;
+        let height = 48.0;
+        // slides open from the middle as it fades in
+        let width = screen.x * alpha;
+        let strip = Rectangle::new(
+            (screen.x - width) * 0.5,
+            screen.y * 0.5 - height * 0.5,
+            width,
+            height,
+        );
+        d.draw_rect(strip, Color::BLACK.fade(0.8 * alpha));
+        d.draw_line(
+            Vector2::new(strip.x, strip.y),
+            Vector2::new(strip.x + strip.width, strip.y),
+            2.0,
+            Color::RED.fade(alpha),
+        );
+        d.draw_line(
+            Vector2::new(strip.x, strip.y + height),
+            Vector2::new(strip.x + strip.width, strip.y + height),
+            2.0,
+            Color::RED.fade(alpha),
+        );
+
+        let size = 24;
+        let x = screen.x as i32 / 2 - measure_text(&banner.text, size) / 2;
+        let y = (strip.y + height * 0.5) as i32 - size / 2;
+        d.draw_text(&banner.text, x, y, size, Color::WHITE.fade(alpha));
+    }
+}
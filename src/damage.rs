@@ -0,0 +1,227 @@
+use raylib::prelude::*;
+
+use crate::animation::AnimationEvent;
+use crate::camera::Camera;
+use crate::damage_number::DamageNumber;
+use crate::difficulty::Difficulty;
+use crate::light::Light;
+use crate::particles::EmitterConfig;
+use crate::team::Team;
+use crate::{Anchor, EntityID, UIBarStyle, UpdateSystem, World};
+
+/// Kills of anything at least this tough slow the game down for a moment.
+const BIG_KILL_HEALTH: u16 = 20;
+/// Most times over the explosion gets, for overkill of twice the health.
+const MAX_VIOLENCE: f32 = 3.0;
+
+/// Request to hurt an entity, applied to its Health by the damage system.
+pub struct DamageEvent {
+    pub target: EntityID,
+    pub source: Option<EntityID>,
+    /// Set to what was dealt by the damage system, armor included, so
+    /// systems after it don't count hits that were absorbed or overkill.
+    pub amount: u16,
+    /// Critical hits get a louder damage number.
+    pub crit: bool,
+}
+
+/// Emitted once when an entity's health reaches zero.
+pub struct DeathEvent {
+    pub entity: EntityID,
+    pub killer: Option<EntityID>,
+    /// Damage of the killing hit past what was left of the entity, armor
+    /// included.
+    pub overkill: u16,
+}
+
+/// Request to heal an entity's hull, applied by the damage system after
+/// the damage so a heal can't save what died this frame. Never heals past
+/// max_health.
+pub struct HealEvent {
+    pub target: EntityID,
+    pub source: Option<EntityID>,
+    pub amount: u16,
+}
+
+pub struct Damage;
+
+impl Damage {
+    /// Deals every living hostile its remaining health, returns how many.
+    pub fn kill_hostiles(world: &mut World) -> usize {
+        let hostiles: Vec<(EntityID, u16)> = world
+            .health_components
+            .iter()
+            .filter(|h| h.1.health > 0)
+            .filter(|h| {
+                world
+                    .team_components
+                    .iter()
+                    .any(|t| t.0 == h.0 && t.1 == Team::Hostile)
+            })
+            .map(|h| (h.0, h.1.health))
+            .collect();
+        for (target, amount) in hostiles.iter() {
+            world.damage_events.push(DamageEvent {
+                target: *target,
+                source: None,
+                amount: *amount,
+                crit: false,
+            });
+        }
+        hostiles.len()
+    }
+}
+
+impl UpdateSystem for Damage {
+    fn update_system(world: &mut World, _dt: f32) {
+        let mut numbers = Vec::new();
+        let mut armor_numbers = Vec::new();
+        let mut explosions = Vec::new();
+        let mut punch: f32 = 0.0;
+
+        // the amounts are changed to what was dealt, armor included, for
+        // the stats and observers reading them after
+        for event in world.damage_events.iter_mut() {
+            if world
+                .god_mode_components
+                .iter()
+                .any(|g| g.0 == event.target)
+            {
+                event.amount = 0;
+                continue;
+            }
+            let player = world
+                .player_components
+                .iter_mut()
+                .find(|p| p.0 == event.target)
+                .map(|p| &mut p.1);
+            if player.as_ref().is_some_and(|p| p.invulnerable > 0.0) {
+                event.amount = 0;
+                continue;
+            }
+            let hostile = event.source.is_some_and(|source| {
+                world
+                    .team_components
+                    .iter()
+                    .any(|t| t.0 == source && t.1 == Team::Hostile)
+            });
+            if hostile {
+                event.amount = Difficulty::scale(event.amount, world.difficulty.enemy_damage());
+            }
+            let health = match world
+                .health_components
+                .iter_mut()
+                .find(|h| h.0 == event.target)
+            {
+                Some(h) => &mut h.1,
+                None => {
+                    event.amount = 0;
+                    continue;
+                }
+            };
+
+            if health.health == 0 {
+                event.amount = 0;
+                continue;
+            }
+            let hit = health.take_damage(event.amount);
+            let overkill = event.amount - hit.armor - hit.hull;
+            event.amount = hit.armor + hit.hull;
+            let center = world
+                .base_components
+                .iter()
+                .find(|b| b.0 == event.target)
+                .map(|b| (b.1.bounds.calc(Anchor::TopCenter), b.1.bounds.center()));
+            // armor rings off with sparks, the hull takes the real hit
+            if hit.armor > 0 {
+                if let Some((top, center)) = center {
+                    armor_numbers.push((top, hit.armor));
+                    world.particles.burst(&EmitterConfig::sparks(), center);
+                }
+                world.juice.shake(1.0, 0.08);
+            }
+            if hit.hull == 0 {
+                continue;
+            }
+            if let Some((top, _)) = center {
+                numbers.push((top, hit.hull, event.crit));
+            }
+            // hits on the player should be felt, hits on others just seen
+            if let Some(player) = player {
+                player.invulnerable = world.difficulty.player_iframes();
+                world.juice.shake(4.0, 0.2);
+                world.juice.hitstop(4);
+            } else {
+                world.juice.shake(1.5, 0.1);
+            }
+            world.animation_events.push(AnimationEvent {
+                entity: event.target,
+                clip: "hit",
+            });
+            if health.health == 0 {
+                if let Some(b) = world.base_components.iter().find(|b| b.0 == event.target) {
+                    // a massive hit blows it apart harder
+                    let violence =
+                        (1.0 + overkill as f32 / health.max_health.max(1) as f32).min(MAX_VIOLENCE);
+                    let mut explosion = EmitterConfig::explosion();
+                    explosion.burst = (explosion.burst as f32 * violence) as u32;
+                    explosion.speed = (explosion.speed.0, explosion.speed.1 * violence);
+                    world.particles.burst(&explosion, b.1.bounds.center());
+                    explosions.push(b.1.bounds.center());
+                }
+                world.juice.shake(8.0, 0.4);
+                world.juice.hitstop(6);
+                if matches!(health.bar_style, UIBarStyle::BOSS) {
+                    world.time.slow_motion(0.2, 2.0);
+                    punch = punch.max(0.15);
+                } else if health.max_health >= BIG_KILL_HEALTH {
+                    world.time.slow_motion(0.5, 0.6);
+                    punch = punch.max(0.06);
+                }
+                world.death_events.push(DeathEvent {
+                    entity: event.target,
+                    killer: event.source,
+                    overkill,
+                });
+            }
+        }
+
+        for (pos, amount) in armor_numbers {
+            DamageNumber::spawn_armor(world, pos, amount);
+        }
+        for (pos, amount, crit) in numbers {
+            DamageNumber::spawn(world, pos, amount, crit);
+        }
+        for pos in explosions {
+            Light::flash(world, pos, Light::new(140.0, Color::ORANGE, 1.5), 0.5);
+        }
+
+        let mut heals = Vec::new();
+        for event in world.heal_events.iter() {
+            let health = match world
+                .health_components
+                .iter_mut()
+                .find(|h| h.0 == event.target)
+            {
+                Some(h) => &mut h.1,
+                None => continue,
+            };
+            if health.health == 0 {
+                continue;
+            }
+            let healed = health.heal(event.amount);
+            if healed == 0 {
+                continue;
+            }
+            if let Some(b) = world.base_components.iter().find(|b| b.0 == event.target) {
+                heals.push((b.1.bounds.calc(Anchor::TopCenter), healed));
+            }
+        }
+        for (pos, amount) in heals {
+            DamageNumber::spawn_heal(world, pos, amount);
+        }
+        if punch > 0.0 {
+            Camera::zoom_punch(world, punch, 0.5);
+        }
+    }
+}
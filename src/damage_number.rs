@@ -0,0 +1,125 @@
+use std::fmt::Write;
+
+use raylib::prelude::*;
+
+use crate::font::TextStyle;
+use crate::lifetime::Lifetime;
+use crate::movement::Velocity;
+use crate::pool;
+use crate::render::{Layer, RenderLayer};
+use crate::text::Text2D;
+use crate::{Anchor, Base2D, BoundingBox2D, EntityID, UpdateSystem, World};
+
+const RISE_SPEED: f32 = 60.0;
+const DURATION: f32 = 0.8;
+
+/// Short-lived number popping up where damage landed. Fades its Text2D out
+/// as its lifetime runs down.
+pub struct DamageNumber {
+    color: Color,
+}
+
+impl DamageNumber {
+    /// Spawns a number at `pos` that rises and fades until its lifetime ends.
+    pub fn spawn(world: &mut World, pos: Vector2, amount: u16, crit: bool) {
+        let (size, color) = if crit {
+            (24, Color::GOLD)
+        } else {
+            (14, Color::WHITE)
+        };
+        DamageNumber::spawn_styled(world, pos, "", amount, size, color);
+    }
+
+    /// A smaller grey number for damage armor took, so it reads apart from
+    /// hull damage.
+    pub fn spawn_armor(world: &mut World, pos: Vector2, amount: u16) {
+        DamageNumber::spawn_styled(world, pos, "", amount, 10, Color::LIGHTGRAY);
+    }
+
+    /// A green `+amount` for health that came back.
+    pub fn spawn_heal(world: &mut World, pos: Vector2, amount: u16) {
+        DamageNumber::spawn_styled(world, pos, "+", amount, 14, Color::LIME);
+    }
+
+    fn spawn_styled(
+        world: &mut World,
+        pos: Vector2,
+        sign: &str,
+        amount: u16,
+        size: i32,
+        color: Color,
+    ) {
+        let id = world.new_entity();
+        let mut parts = world.pools.damage_numbers.take_or(DamageNumberParts::new);
+
+        parts.base.bounds = BoundingBox2D::new_v(pos, Vector2::zero());
+        parts.base.visible = true;
+        world.base_components.push((id, parts.base));
+
+        let mut velocity = Velocity::new(2.0);
+        velocity.velocity = Vector2::new(0.0, -RISE_SPEED);
+        world.velocity_components.push((id, velocity));
+        world
+            .lifetime_components
+            .push((id, Lifetime::new(DURATION)));
+        world
+            .layer_components
+            .push((id, Layer::new(RenderLayer::Ui, 0)));
+
+        let mut text = parts.text;
+        text.text.clear();
+        let _ = write!(text.text, "{}{}", sign, amount);
+        text.style = TextStyle::new(size as f32, color)
+            .aligned(Anchor::Center)
+            .outlined(Color::BLACK);
+        world.text_components.push((id, text));
+        world
+            .damage_number_components
+            .push((id, DamageNumber { color }));
+    }
+}
+
+/// What a damage number allocates, kept in `world.pools` between them.
+pub struct DamageNumberParts {
+    base: Base2D,
+    text: Text2D,
+}
+
+impl DamageNumberParts {
+    fn new() -> DamageNumberParts {
+        let mut base = Base2D::new(Vector2::zero(), Vector2::zero());
+        base.name = "Damage number".to_string();
+        base.tint = Color::BLANK;
+        DamageNumberParts {
+            base,
+            text: Text2D::new(String::new(), 14, Color::WHITE),
+        }
+    }
+
+    /// Takes a damage number's parts out of the world, None when `id`
+    /// isn't one.
+    pub fn take(world: &mut World, id: EntityID) -> Option<DamageNumberParts> {
+        pool::take(&mut world.damage_number_components, id)?;
+        Some(DamageNumberParts {
+            base: pool::take(&mut world.base_components, id)?,
+            text: pool::take(&mut world.text_components, id)?,
+        })
+    }
+}
+
+impl UpdateSystem for DamageNumber {
+    fn update_system(world: &mut World, _dt: f32) {
+        for (id, number) in world.damage_number_components.iter() {
+            let alpha = world
+                .lifetime_components
+                .iter()
+                .find(|l| l.0 == *id)
+                .map(|l| 1.0 - l.1.progress())
+                .unwrap_or(1.0);
+            if let Some(text) = world.text_components.iter_mut().find(|t| t.0 == *id) {
+                text.1.style.color = number.color.fade(alpha);
+                text.1.style.outline = Some(Color::BLACK.fade(alpha));
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use raylib::prelude::*;
+
+use crate::render;
+use crate::renderer::Renderer;
+use crate::{Anchor, DrawSystem, World};
+
+/// Developer overlay toggled with F3: collision bounds with their anchor
+/// points, trigger volumes, velocity vectors and what the pilots are after,
+/// drawn over everything else in world space, along with the gizmos systems
+/// added this frame.
+pub struct DebugDraw {
+    pub enabled: bool,
+}
+
+impl DebugDraw {
+    pub fn new() -> DebugDraw {
+        DebugDraw { enabled: false }
+    }
+
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        if rl.is_key_pressed(KeyboardKey::KEY_F3) {
+            world.debug.enabled = !world.debug.enabled;
+            world.gizmos.enabled = world.debug.enabled;
+        }
+    }
+}
+
+impl DrawSystem for DebugDraw {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        if !world.debug.enabled {
+            return;
+        }
+
+        for (id, base) in world.base_components.iter() {
+            if !render::in_view(world, *id) {
+                continue;
+            }
+
+            let trigger = world.trigger_components.iter().any(|t| t.0 == *id);
+            let color = if trigger { Color::SKYBLUE } else { Color::LIME };
+            d.draw_rect_lines(base.bounds.into(), 1, color);
+            for anchor in Anchor::values() {
+                d.draw_circle(base.bounds.calc(anchor), 2.0, Color::RED);
+            }
+
+            if let Some(v) = world.velocity_components.iter().find(|v| v.0 == *id) {
+                let center = base.bounds.center();
+                // a quarter second ahead, long enough to read at bullet speeds
+                d.draw_line(center, center + v.1.velocity * 0.25, 1.0, Color::MAGENTA);
+            }
+        }
+
+        // a line to each pilot's target, the range it keeps from it and
+        // where it aims while firing
+        for (id, pilot) in world.pilot_components.iter() {
+            let center = match world.base_components.iter().find(|b| b.0 == *id) {
+                Some(b) => b.1.bounds.center(),
+                None => continue,
+            };
+            let target = pilot
+                .target
+                .and_then(|t| world.base_components.iter().find(|b| b.0 == t))
+                .map(|b| b.1.bounds.center());
+            if !render::in_view(world, *id) && target.is_none() {
+                continue;
+            }
+            if let Some(target) = target {
+                d.draw_line(center, target, 1.0, Color::ORANGE.fade(0.6));
+                d.draw_circle_outline(target, pilot.range, Color::ORANGE.fade(0.3));
+            }
+            if pilot.firing {
+                d.draw_circle(pilot.aim, 3.0, Color::ORANGE);
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use raylib::prelude::*;
+
+use crate::console::Console;
+use crate::inspector::Inspector;
+use crate::log::LogPanel;
+use crate::profiler::Profiler;
+use crate::renderer::Renderer;
+use crate::World;
+
+/// What the developer panels took of this frame's input. Game input the
+/// panels might also want looks here first, so a click on the inspector
+/// doesn't also press the menu button behind it.
+pub struct InputCapture {
+    pub mouse: bool,
+    pub keyboard: bool,
+}
+
+impl InputCapture {
+    pub fn new() -> InputCapture {
+        InputCapture {
+            mouse: false,
+            keyboard: false,
+        }
+    }
+}
+
+/// The console, inspector, profiler and log panels, with one place that
+/// decides who gets the input. Only there with the `dev-tools` feature, on
+/// by default, a build without it never opens them.
+pub struct DevTools {
+    pub console: Console,
+    pub inspector: Inspector,
+    pub log_panel: LogPanel,
+    enabled: bool,
+}
+
+impl DevTools {
+    pub fn new() -> DevTools {
+        DevTools {
+            console: Console::builtin(),
+            inspector: Inspector::new(),
+            log_panel: LogPanel::new(),
+            enabled: cfg!(feature = "dev-tools"),
+        }
+    }
+
+    /// Runs the panels' input and fills in `world.capture`. Comes before
+    /// the game's own input, which should leave the keyboard alone while
+    /// it's captured.
+    pub fn input_system(&mut self, world: &mut World, rl: &RaylibHandle) {
+        world.capture = InputCapture::new();
+        if !self.enabled {
+            return;
+        }
+        self.console.input_system(world, rl);
+        // the console takes the keyboard while it's down
+        world.capture.keyboard = self.console.is_open();
+        if !world.capture.keyboard {
+            self.inspector.input_system(world, rl);
+            self.log_panel.input_system(rl);
+            Profiler::input_system(world, rl);
+        }
+
+        let mouse = world.screen.window_to_canvas(rl.get_mouse_position());
+        // the inspector picks entities by clicking the world, so it wants
+        // the mouse everywhere
+        world.capture.mouse = self.inspector.is_open()
+            || self.console.covers(world, mouse)
+            || self.log_panel.covers(world, mouse)
+            || Profiler::panel(world).is_some_and(|p| p.check_collision_point_rec(mouse));
+    }
+
+    /// The panels that draw over everything, the profiler draws with the
+    /// other overlays.
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        self.inspector.draw_system(world, d);
+        self.log_panel.draw_system(world, d);
+        self.console.draw_system(world, d);
+    }
+}
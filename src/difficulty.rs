@@ -0,0 +1,117 @@
+/// How hard the game is, picked when starting a new game and kept with the
+/// save slot. Spawners scale the health of hostiles with it, the damage
+/// system what hostiles deal and how long the player can't be hit again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl Difficulty {
+    pub fn values() -> Vec<Difficulty> {
+        vec![
+            Difficulty::Easy,
+            Difficulty::Normal,
+            Difficulty::Hard,
+            Difficulty::Nightmare,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Nightmare => "nightmare",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::values().into_iter().find(|d| d.name() == name)
+    }
+
+    /// Multiplies the health and armor of hostiles.
+    pub fn enemy_health(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+            Difficulty::Nightmare => 2.0,
+        }
+    }
+
+    /// Multiplies the damage hostiles deal.
+    pub fn enemy_damage(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+            Difficulty::Nightmare => 1.5,
+        }
+    }
+
+    /// Seconds the player can't be hurt after a hit to the hull.
+    pub fn player_iframes(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.0,
+            Difficulty::Normal => 0.5,
+            Difficulty::Hard => 0.25,
+            Difficulty::Nightmare => 0.0,
+        }
+    }
+
+    /// `amount` times `multiplier`, rounded, and never scaled down to
+    /// nothing.
+    pub fn scale(amount: u16, multiplier: f32) -> u16 {
+        if amount == 0 {
+            return 0;
+        }
+        (amount as f32 * multiplier)
+            .round()
+            .clamp(1.0, u16::MAX as f32) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_each_difficulty() {
+        // difficulty, health, damage and iframe multipliers, then a 6 health
+        // hostile, its 3 damage shot and a 1 damage graze scaled by them
+        let table = [
+            (Difficulty::Easy, 0.75, 0.5, 1.0, 5, 2, 1),
+            (Difficulty::Normal, 1.0, 1.0, 0.5, 6, 3, 1),
+            (Difficulty::Hard, 1.5, 1.25, 0.25, 9, 4, 1),
+            (Difficulty::Nightmare, 2.0, 1.5, 0.0, 12, 5, 2),
+        ];
+        assert_eq!(table.len(), Difficulty::values().len());
+        for (difficulty, health, damage, iframes, hostile, shot, graze) in table {
+            assert_eq!(difficulty.enemy_health(), health, "{:?}", difficulty);
+            assert_eq!(difficulty.enemy_damage(), damage, "{:?}", difficulty);
+            assert_eq!(difficulty.player_iframes(), iframes, "{:?}", difficulty);
+            assert_eq!(Difficulty::scale(6, health), hostile, "{:?}", difficulty);
+            assert_eq!(Difficulty::scale(3, damage), shot, "{:?}", difficulty);
+            assert_eq!(Difficulty::scale(1, damage), graze, "{:?}", difficulty);
+            assert_eq!(Difficulty::from_name(difficulty.name()), Some(difficulty));
+        }
+        assert_eq!(Difficulty::from_name("impossible"), None);
+    }
+
+    #[test]
+    fn rounds_halves_up_and_never_to_nothing() {
+        assert_eq!(Difficulty::scale(5, 0.5), 3);
+        assert_eq!(Difficulty::scale(6, 0.25), 2);
+        assert_eq!(Difficulty::scale(5, 0.25), 1);
+        assert_eq!(Difficulty::scale(7, 0.25), 2);
+        assert_eq!(Difficulty::scale(1, 0.1), 1);
+        assert_eq!(Difficulty::scale(7, 0.0), 1);
+        // nothing stays nothing
+        assert_eq!(Difficulty::scale(0, 2.0), 0);
+        assert_eq!(Difficulty::scale(40000, 2.0), u16::MAX);
+        assert_eq!(Difficulty::scale(40000, 1.5), 60000);
+    }
+}
@@ -0,0 +1,694 @@
+use raylib::prelude::*;
+
+use crate::animation::{Animation, AnimationEvent};
+use crate::assets::Assets;
+use crate::audio::{PlaySound, Volume};
+use crate::beat::Beat;
+use crate::bindings::Bindings;
+use crate::camera::Camera;
+use crate::campaign::Campaign;
+use crate::cheats::GodMode;
+use crate::config::Config;
+use crate::crosshair::{Crosshair, CrosshairStyle};
+use crate::cutscene::Cutscene;
+use crate::damage::{DamageEvent, DeathEvent, HealEvent};
+use crate::damage_number::DamageNumber;
+use crate::debug::DebugDraw;
+use crate::dev_tools::InputCapture;
+use crate::difficulty::Difficulty;
+use crate::editor::LevelEntity;
+use crate::flash::DamageFlash;
+use crate::focus::Focus;
+use crate::gizmos::Gizmos;
+use crate::grid::SpatialGrid;
+use crate::group;
+use crate::hazard::Hazard;
+use crate::heat::{Heat, HeatEvent};
+use crate::hot_reload::AssetReloaded;
+use crate::hud::HudItem;
+use crate::indicator::Indicator;
+use crate::inspector::COMPONENTS;
+use crate::json::Json;
+use crate::juice::Juice;
+use crate::lifetime::Lifetime;
+use crate::light::Light;
+use crate::movement::Velocity;
+use crate::observer::{self, Observers};
+use crate::palette::Palette;
+use crate::particles::{Emitter, Particles};
+use crate::path::PathFollow;
+use crate::pilot::Pilot;
+use crate::player::Player;
+use crate::pool::Pools;
+use crate::portal::Portal;
+use crate::prefab::Prefabs;
+use crate::profiler::Profiler;
+use crate::projectile::Projectile;
+use crate::regen::Regen;
+use crate::render::Layer;
+use crate::render_stats::RenderStats;
+use crate::renderer::Renderer;
+use crate::screen::ScreenSize;
+use crate::script::{Script, ScriptEvent, Scripts};
+use crate::sprite::Sprite;
+use crate::starfield::Starfield;
+use crate::state::{GameState, StateMachine};
+use crate::station::{Bombs, Station};
+use crate::team::Team;
+use crate::telegraph::Telegraph;
+use crate::text::Text2D;
+use crate::tilemap::Tilemap;
+use crate::time::Time;
+use crate::trail::Trail;
+use crate::transition::Transition;
+use crate::trigger::{Trigger, TriggerEvent};
+use crate::tutorial::TutorialPrompt;
+use crate::tween::Tween;
+use crate::ui::UiElement;
+use crate::weapon::{ShotEvent, Weapon};
+use crate::widget::{UiEvent, Widget};
+use crate::{debug, Base2D, BoundingBox2D, Health, HEIGHT, WIDTH};
+
+pub trait UpdateSystem {
+    fn update_system(world: &mut World, dt: f32);
+}
+
+pub trait DrawSystem {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer);
+}
+
+pub type EntityID = u64;
+
+pub struct World {
+    pub last_entity: EntityID,
+    pub base_components: Vec<(EntityID, Base2D)>,
+    pub health_components: Vec<(EntityID, Health)>,
+    pub player_components: Vec<(EntityID, Player)>,
+    pub pilot_components: Vec<(EntityID, Pilot)>,
+    pub weapon_components: Vec<(EntityID, Weapon)>,
+    pub heat_components: Vec<(EntityID, Heat)>,
+    pub velocity_components: Vec<(EntityID, Velocity)>,
+    pub trigger_components: Vec<(EntityID, Trigger)>,
+    pub hazard_components: Vec<(EntityID, Hazard)>,
+    pub portal_components: Vec<(EntityID, Portal)>,
+    pub station_components: Vec<(EntityID, Station)>,
+    pub bomb_components: Vec<(EntityID, Bombs)>,
+    pub god_mode_components: Vec<(EntityID, GodMode)>,
+    pub indicator_components: Vec<(EntityID, Indicator)>,
+    pub team_components: Vec<(EntityID, Team)>,
+    pub projectile_components: Vec<(EntityID, Projectile)>,
+    pub tutorial_components: Vec<(EntityID, TutorialPrompt)>,
+    pub sprite_components: Vec<(EntityID, Sprite)>,
+    pub animation_components: Vec<(EntityID, Animation)>,
+    pub layer_components: Vec<(EntityID, Layer)>,
+    pub camera_components: Vec<(EntityID, Camera)>,
+    pub emitter_components: Vec<(EntityID, Emitter)>,
+    pub lifetime_components: Vec<(EntityID, Lifetime)>,
+    pub damage_number_components: Vec<(EntityID, DamageNumber)>,
+    pub text_components: Vec<(EntityID, Text2D)>,
+    pub ui_components: Vec<(EntityID, UiElement)>,
+    pub hud_components: Vec<(EntityID, HudItem)>,
+    pub widget_components: Vec<(EntityID, Widget)>,
+    pub tween_components: Vec<(EntityID, Tween)>,
+    pub flash_components: Vec<(EntityID, DamageFlash)>,
+    pub trail_components: Vec<(EntityID, Trail)>,
+    pub light_components: Vec<(EntityID, Light)>,
+    pub tilemap_components: Vec<(EntityID, Tilemap)>,
+    pub script_components: Vec<(EntityID, Script)>,
+    pub level_entity_components: Vec<(EntityID, LevelEntity)>,
+    pub path_components: Vec<(EntityID, PathFollow)>,
+    pub telegraph_components: Vec<(EntityID, Telegraph)>,
+    pub regen_components: Vec<(EntityID, Regen)>,
+
+    pub shot_events: Vec<ShotEvent>,
+    pub heat_events: Vec<(EntityID, HeatEvent)>,
+    pub trigger_events: Vec<TriggerEvent>,
+    pub damage_events: Vec<DamageEvent>,
+    pub death_events: Vec<DeathEvent>,
+    pub heal_events: Vec<HealEvent>,
+    pub animation_events: Vec<AnimationEvent>,
+    pub ui_events: Vec<UiEvent>,
+    pub sound_events: Vec<PlaySound>,
+    pub reload_events: Vec<AssetReloaded>,
+    pub script_events: Vec<ScriptEvent>,
+
+    pub assets: Assets,
+    pub prefabs: Prefabs,
+    pub scripts: Scripts,
+    pub campaign: Campaign,
+    pub cutscene: Cutscene,
+    pub config: Config,
+    pub difficulty: Difficulty,
+    pub starfield: Starfield,
+    pub particles: Particles,
+    pub grid: SpatialGrid,
+    pub pools: Pools,
+    pub observers: Observers,
+    pub juice: Juice,
+    pub time: Time,
+    pub transition: Transition,
+    pub state: StateMachine,
+    pub beat: Beat,
+    pub volume: Volume,
+    pub palette: Palette,
+    pub bindings: Bindings,
+    pub crosshair: Crosshair,
+    pub render_stats: RenderStats,
+    pub profiler: Profiler,
+    pub focus: Focus,
+    pub capture: InputCapture,
+    pub screen: ScreenSize,
+    /// Sorted ids of the entities drawn this frame, see render::cull_system.
+    pub in_view: Vec<EntityID>,
+    /// World area of the view being drawn, None when drawing everything.
+    pub view: Option<BoundingBox2D>,
+    pub debug: DebugDraw,
+    pub gizmos: Gizmos,
+}
+
+impl World {
+    pub fn new() -> World {
+        World {
+            last_entity: 0,
+            base_components: Vec::new(),
+            health_components: Vec::new(),
+            player_components: Vec::new(),
+            pilot_components: Vec::new(),
+            weapon_components: Vec::new(),
+            heat_components: Vec::new(),
+            velocity_components: Vec::new(),
+            trigger_components: Vec::new(),
+            hazard_components: Vec::new(),
+            portal_components: Vec::new(),
+            station_components: Vec::new(),
+            bomb_components: Vec::new(),
+            god_mode_components: Vec::new(),
+            indicator_components: Vec::new(),
+            team_components: Vec::new(),
+            projectile_components: Vec::new(),
+            tutorial_components: Vec::new(),
+            sprite_components: Vec::new(),
+            animation_components: Vec::new(),
+            layer_components: Vec::new(),
+            camera_components: Vec::new(),
+            emitter_components: Vec::new(),
+            lifetime_components: Vec::new(),
+            damage_number_components: Vec::new(),
+            text_components: Vec::new(),
+            ui_components: Vec::new(),
+            hud_components: Vec::new(),
+            widget_components: Vec::new(),
+            tween_components: Vec::new(),
+            flash_components: Vec::new(),
+            trail_components: Vec::new(),
+            light_components: Vec::new(),
+            tilemap_components: Vec::new(),
+            script_components: Vec::new(),
+            level_entity_components: Vec::new(),
+            path_components: Vec::new(),
+            telegraph_components: Vec::new(),
+            regen_components: Vec::new(),
+
+            shot_events: Vec::new(),
+            heat_events: Vec::new(),
+            trigger_events: Vec::new(),
+            damage_events: Vec::new(),
+            death_events: Vec::new(),
+            heal_events: Vec::new(),
+            animation_events: Vec::new(),
+            ui_events: Vec::new(),
+            sound_events: Vec::new(),
+            reload_events: Vec::new(),
+            script_events: Vec::new(),
+
+            assets: Assets::new(),
+            prefabs: Prefabs::builtin(),
+            scripts: Scripts::new(),
+            campaign: Campaign::new(),
+            cutscene: Cutscene::new(),
+            config: Config::new("config.toml"),
+            difficulty: Difficulty::Normal,
+            starfield: Starfield::new(0x5eed),
+            particles: Particles::new(0x9a27),
+            grid: SpatialGrid::new(),
+            pools: Pools::new(),
+            observers: Observers::new(),
+            juice: Juice::new(0x5a4e),
+            time: Time::new(),
+            transition: Transition::new(),
+            state: StateMachine::new(GameState::Menu),
+            beat: Beat::new(),
+            volume: Volume::new(),
+            palette: Palette::new(),
+            bindings: Bindings::new(),
+            crosshair: Crosshair::new(CrosshairStyle::Cross, 1.0),
+            render_stats: RenderStats::new(),
+            profiler: Profiler::new(),
+            focus: Focus::new(),
+            capture: InputCapture::new(),
+            // the window size is picked up on the first frame
+            screen: ScreenSize::new(
+                Vector2::new(WIDTH as f32, HEIGHT as f32),
+                Vector2::new(WIDTH as f32, HEIGHT as f32),
+            ),
+            in_view: Vec::new(),
+            view: None,
+            debug: DebugDraw::new(),
+            gizmos: Gizmos::new(),
+        }
+    }
+
+    /// Empties the world for a new scene. Loaded assets, settings and the
+    /// state machine carry over, and entity ids keep counting up so ids
+    /// held by menus never point at something new.
+    pub fn reset(&mut self) {
+        let old = std::mem::replace(self, World::new());
+        self.last_entity = old.last_entity;
+        self.assets = old.assets;
+        self.prefabs = old.prefabs;
+        self.pools = old.pools;
+        self.observers = old.observers;
+        self.scripts = old.scripts;
+        self.campaign = old.campaign;
+        self.campaign.stop();
+        self.config = old.config;
+        self.difficulty = old.difficulty;
+        self.volume = old.volume;
+        self.palette = old.palette;
+        self.bindings = old.bindings;
+        self.juice.intensity = old.juice.intensity;
+        self.time = old.time;
+        self.crosshair = old.crosshair;
+        self.state = old.state;
+        self.render_stats = old.render_stats;
+        self.profiler = old.profiler;
+        self.screen = old.screen;
+        self.debug = old.debug;
+        self.gizmos.enabled = old.gizmos.enabled;
+        self.reseed();
+    }
+
+    /// Mixes the config's `--seed` into the random effects, so a run with
+    /// another seed looks different but plays the same every time.
+    pub fn reseed(&mut self) {
+        if let Some(seed) = self.config.seed {
+            self.starfield = Starfield::new(0x5eed ^ seed);
+            self.particles = Particles::new(0x9a27 ^ seed);
+            let intensity = self.juice.intensity;
+            self.juice = Juice::new(0x5a4e ^ seed);
+            self.juice.intensity = intensity;
+        }
+    }
+
+    /// Every entity with a reflected component, as JSON for tools and bug
+    /// reports:
+    ///
+    /// ```json
+    /// {
+    ///     "last_entity": 12,
+    ///     "entities": [
+    ///         { "id": 3, "name": "Player", "Base2D": { "x": 40, "y": 80, ... }, "Health": { ... } }
+    ///     ]
+    /// }
+    /// ```
+    ///
+    /// Only what the inspector can reflect is in it, see `Inspect`.
+    pub fn to_json(&self) -> Json {
+        let mut ids: Vec<EntityID> = COMPONENTS.iter().flat_map(|c| (c.ids)(self)).collect();
+        ids.sort();
+        ids.dedup();
+
+        let entities = ids
+            .into_iter()
+            .map(|id| {
+                let mut members = vec![("id".to_string(), Json::Number(id as f64))];
+                if let Some(base) = self.base_components.iter().find(|b| b.0 == id) {
+                    members.push(("name".to_string(), Json::String(base.1.name.clone())));
+                }
+                for component in COMPONENTS.iter() {
+                    if let Some(c) = (component.get)(self, id) {
+                        let fields = c
+                            .fields()
+                            .into_iter()
+                            .map(|f| (f.name.to_string(), Json::Number(f.value as f64)))
+                            .collect();
+                        members.push((component.name.to_string(), Json::Object(fields)));
+                    }
+                }
+                Json::Object(members)
+            })
+            .collect();
+        Json::Object(vec![
+            (
+                "last_entity".to_string(),
+                Json::Number(self.last_entity as f64),
+            ),
+            ("entities".to_string(), Json::Array(entities)),
+        ])
+    }
+
+    /// Puts the world back the way `to_json` found it: reflected fields
+    /// are set, missing components added and entities the snapshot doesn't
+    /// have despawned. Components reflection doesn't know stay as they are.
+    /// Nothing changes when the snapshot doesn't make sense.
+    pub fn from_json(&mut self, json: &Json) -> Result<(), String> {
+        let entities = json
+            .get("entities")
+            .and_then(Json::as_array)
+            .ok_or("expected an entities array")?;
+        let mut restored = Vec::new();
+        for entity in entities {
+            let members = entity.as_object().ok_or("expected an entity object")?;
+            let id = entity
+                .get("id")
+                .and_then(Json::as_i64)
+                .filter(|id| *id > 0)
+                .ok_or("entity without an id")? as EntityID;
+            let mut name = None;
+            let mut components = Vec::new();
+            for (key, value) in members.iter() {
+                match key.as_str() {
+                    "id" => (),
+                    "name" => name = Some(value.as_str().ok_or("bad name")?.to_string()),
+                    _ => {
+                        let component = COMPONENTS
+                            .iter()
+                            .find(|c| c.name == key)
+                            .ok_or_else(|| format!("unknown component '{}'", key))?;
+                        let mut fields = Vec::new();
+                        for (field, value) in value.as_object().ok_or("bad component")? {
+                            let value = value.as_f32().ok_or("bad field")?;
+                            fields.push((field.clone(), value));
+                        }
+                        components.push((component, fields));
+                    }
+                }
+            }
+            restored.push((id, name, components));
+        }
+
+        let kept: Vec<EntityID> = restored.iter().map(|e| e.0).collect();
+        let mut gone: Vec<EntityID> = COMPONENTS.iter().flat_map(|c| (c.ids)(self)).collect();
+        gone.retain(|id| !kept.contains(id));
+        for id in gone {
+            self.despawn(id);
+        }
+        for (id, name, components) in restored {
+            for (component, fields) in components {
+                if (component.get)(self, id).is_none() {
+                    (component.insert)(self, id);
+                }
+                let c = (component.get_mut)(self, id).unwrap();
+                // twice, fields limited by others (health by max health)
+                // may come first
+                for _ in 0..2 {
+                    for (field, value) in fields.iter() {
+                        c.set_field(field, *value);
+                    }
+                }
+            }
+            if let Some(name) = name {
+                if let Some(base) = self.base_components.iter_mut().find(|b| b.0 == id) {
+                    base.1.name = name;
+                }
+            }
+            self.last_entity = self.last_entity.max(id);
+        }
+        let last = json.get("last_entity").and_then(Json::as_i64).unwrap_or(0);
+        self.last_entity = self.last_entity.max(last as EntityID);
+        Ok(())
+    }
+
+    /// Hands out the next id, one higher than any before it. An entity is
+    /// nothing but its id until components are pushed for it:
+    ///
+    /// ```
+    /// use spacegame::{Health, World};
+    ///
+    /// let mut world = World::new();
+    /// let ship = world.new_entity();
+    /// world.health_components.push((ship, Health::new(20)));
+    /// assert!(world.new_entity() > ship);
+    /// ```
+    pub fn new_entity(&mut self) -> EntityID {
+        self.last_entity += 1;
+        debug!("ecs", "entity spawned", id = self.last_entity);
+        self.last_entity
+    }
+
+    /// Calls `handler` with every `E` event from now on, at the end of the
+    /// frame it happened in. Kept across `reset`.
+    pub fn on_event<E: observer::Event>(&mut self, handler: observer::Handler<E>) {
+        E::handlers_mut(&mut self.observers).push(handler);
+    }
+
+    /// Hands the events to their observers, removes the entities that died
+    /// this frame and empties the event queues, once every system has seen
+    /// them.
+    ///
+    /// ```
+    /// use spacegame::damage::DeathEvent;
+    /// use spacegame::{Health, World};
+    ///
+    /// let mut world = World::new();
+    /// let ship = world.new_entity();
+    /// world.health_components.push((ship, Health::new(20)));
+    /// world.death_events.push(DeathEvent { entity: ship, killer: None, overkill: 0 });
+    ///
+    /// world.end_frame();
+    /// assert!(world.health_components.is_empty());
+    /// assert!(world.death_events.is_empty());
+    /// ```
+    pub fn end_frame(&mut self) {
+        Observers::dispatch(self);
+        let dead: Vec<EntityID> = self.death_events.iter().map(|e| e.entity).collect();
+        for id in dead {
+            self.despawn(id);
+        }
+
+        self.shot_events.clear();
+        self.heat_events.clear();
+        self.trigger_events.clear();
+        self.damage_events.clear();
+        self.death_events.clear();
+        self.heal_events.clear();
+        self.animation_events.clear();
+        self.ui_events.clear();
+        self.sound_events.clear();
+        self.reload_events.clear();
+        self.script_events.clear();
+    }
+
+    /// Sorts the storages of the hot joins, Base2D with Velocity and with
+    /// Sprite, by entity id. New entities get higher ids and are pushed to
+    /// the back, so these are nearly always sorted already. Sorted, finding
+    /// an entity's component is a binary search and joining two storages
+    /// one walk down both, see `group`, instead of a scan of one storage
+    /// for every entity of the other.
+    ///
+    /// ```
+    /// use raylib::prelude::*;
+    /// use spacegame::movement::Velocity;
+    /// use spacegame::{group, Base2D, World};
+    ///
+    /// let mut world = World::new();
+    /// let (a, b) = (world.new_entity(), world.new_entity());
+    /// world.velocity_components.push((a, Velocity::new(0.0)));
+    /// // b's Base2D before a's puts the storage out of order
+    /// for id in [b, a] {
+    ///     let base = Base2D::new(Vector2::zero(), Vector2::one());
+    ///     world.base_components.push((id, base));
+    /// }
+    ///
+    /// world.sort_groups();
+    /// assert_eq!(group::find(&world.base_components, a), Some(0));
+    /// let moving: Vec<_> = group::join(&world.base_components, &world.velocity_components)
+    ///     .map(|(id, _, _)| id)
+    ///     .collect();
+    /// assert_eq!(moving, [a]);
+    /// ```
+    pub fn sort_groups(&mut self) {
+        group::sort(&mut self.base_components);
+        group::sort(&mut self.velocity_components);
+        group::sort(&mut self.sprite_components);
+    }
+
+    /// Removes every component belonging to `id`, and what other entities
+    /// knew of it: cameras stop following it, pilots targeting it and a
+    /// player docked at it let go. Bullets and the like leave their
+    /// components in `pools` for the next one.
+    ///
+    /// ```
+    /// use raylib::prelude::*;
+    /// use spacegame::camera::Camera;
+    /// use spacegame::{Health, World};
+    ///
+    /// let mut world = World::new();
+    /// let ship = world.new_entity();
+    /// world.health_components.push((ship, Health::new(20)));
+    /// let camera = world.new_entity();
+    /// world.camera_components.push((camera, Camera::following(Vector2::zero(), ship)));
+    ///
+    /// world.despawn(ship);
+    /// assert!(world.health_components.is_empty());
+    /// assert_eq!(world.camera_components[0].1.follow, None);
+    /// ```
+    pub fn despawn(&mut self, id: EntityID) {
+        Pools::recycle(self, id);
+        self.base_components.retain(|c| c.0 != id);
+        self.health_components.retain(|c| c.0 != id);
+        self.player_components.retain(|c| c.0 != id);
+        self.pilot_components.retain(|c| c.0 != id);
+        self.weapon_components.retain(|c| c.0 != id);
+        self.heat_components.retain(|c| c.0 != id);
+        self.velocity_components.retain(|c| c.0 != id);
+        self.trigger_components.retain(|c| c.0 != id);
+        self.hazard_components.retain(|c| c.0 != id);
+        self.portal_components.retain(|c| c.0 != id);
+        self.station_components.retain(|c| c.0 != id);
+        self.bomb_components.retain(|c| c.0 != id);
+        self.god_mode_components.retain(|c| c.0 != id);
+        self.indicator_components.retain(|c| c.0 != id);
+        self.team_components.retain(|c| c.0 != id);
+        self.projectile_components.retain(|c| c.0 != id);
+        self.tutorial_components.retain(|c| c.0 != id);
+        self.sprite_components.retain(|c| c.0 != id);
+        self.animation_components.retain(|c| c.0 != id);
+        self.layer_components.retain(|c| c.0 != id);
+        self.camera_components.retain(|c| c.0 != id);
+        self.emitter_components.retain(|c| c.0 != id);
+        self.lifetime_components.retain(|c| c.0 != id);
+        self.damage_number_components.retain(|c| c.0 != id);
+        self.text_components.retain(|c| c.0 != id);
+        self.ui_components.retain(|c| c.0 != id);
+        self.hud_components.retain(|c| c.0 != id);
+        self.widget_components.retain(|c| c.0 != id);
+        self.tween_components.retain(|c| c.0 != id);
+        self.flash_components.retain(|c| c.0 != id);
+        self.trail_components.retain(|c| c.0 != id);
+        self.light_components.retain(|c| c.0 != id);
+        self.tilemap_components.retain(|c| c.0 != id);
+        self.script_components.retain(|c| c.0 != id);
+        self.level_entity_components.retain(|c| c.0 != id);
+        self.path_components.retain(|c| c.0 != id);
+        self.telegraph_components.retain(|c| c.0 != id);
+        self.regen_components.retain(|c| c.0 != id);
+
+        for (_, camera) in self.camera_components.iter_mut() {
+            if camera.follow == Some(id) {
+                camera.follow = None;
+            }
+        }
+        for (_, pilot) in self.pilot_components.iter_mut() {
+            if pilot.target == Some(id) {
+                pilot.target = None;
+            }
+        }
+        for (_, player) in self.player_components.iter_mut() {
+            if player.docked_at == Some(id) {
+                player.docked_at = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::{DamageEvent, DeathEvent};
+    use crate::movement::Velocity;
+    use crate::pilot::Pilot;
+    use crate::player::Player;
+
+    fn ship(world: &mut World) -> EntityID {
+        let id = world.new_entity();
+        world
+            .base_components
+            .push((id, Base2D::new(Vector2::zero(), Vector2::one())));
+        world.health_components.push((id, Health::new(10)));
+        world.velocity_components.push((id, Velocity::new(0.0)));
+        id
+    }
+
+    #[test]
+    fn ids_are_never_handed_out_twice() {
+        let mut world = World::new();
+        let first = world.new_entity();
+        let second = world.new_entity();
+        assert!(first > 0 && second > first);
+        world.despawn(second);
+        // a reset keeps counting, so nothing left over mistakes a new id
+        world.reset();
+        assert!(world.new_entity() > second);
+    }
+
+    #[test]
+    fn despawn_takes_only_that_entity() {
+        let mut world = World::new();
+        let (a, b) = (ship(&mut world), ship(&mut world));
+        world.despawn(a);
+        assert_eq!(world.base_components.len(), 1);
+        assert_eq!(world.health_components[0].0, b);
+        assert_eq!(world.velocity_components[0].0, b);
+        // one that's gone already, or never was, changes nothing
+        world.despawn(a);
+        world.despawn(1000);
+        assert_eq!(world.base_components[0].0, b);
+    }
+
+    #[test]
+    fn despawn_lets_go_of_references() {
+        let mut world = World::new();
+        let target = ship(&mut world);
+        let hunter = ship(&mut world);
+        let mut pilot = Pilot::new(100.0, 1);
+        pilot.target = Some(target);
+        world.pilot_components.push((hunter, pilot));
+        let mut player = Player::new();
+        player.docked_at = Some(target);
+        world.player_components.push((hunter, player));
+
+        world.despawn(target);
+        assert_eq!(world.pilot_components[0].1.target, None);
+        assert_eq!(world.player_components[0].1.docked_at, None);
+    }
+
+    #[test]
+    fn end_frame_removes_the_dead_and_clears_events() {
+        let mut world = World::new();
+        let (dead, alive) = (ship(&mut world), ship(&mut world));
+        world.damage_events.push(DamageEvent {
+            target: alive,
+            source: None,
+            amount: 1,
+            crit: false,
+        });
+        world.death_events.push(DeathEvent {
+            entity: dead,
+            killer: None,
+            overkill: 0,
+        });
+
+        world.end_frame();
+        let left: Vec<EntityID> = world.base_components.iter().map(|b| b.0).collect();
+        assert_eq!(left, [alive]);
+        assert!(world.damage_events.is_empty());
+        assert!(world.death_events.is_empty());
+    }
+
+    #[test]
+    fn sort_groups_orders_the_joined_storages() {
+        let mut world = World::new();
+        let (a, b, c) = (ship(&mut world), ship(&mut world), ship(&mut world));
+        // components added to older entities land at the back
+        world.base_components.rotate_left(1);
+        world.velocity_components.reverse();
+        world.sort_groups();
+        let order: Vec<EntityID> = world.base_components.iter().map(|b| b.0).collect();
+        assert_eq!(order, [a, b, c]);
+        let joined: Vec<EntityID> = group::join(&world.base_components, &world.velocity_components)
+            .map(|(id, _, _)| id)
+            .collect();
+        assert_eq!(joined, [a, b, c]);
+    }
+}
@@ -0,0 +1,701 @@
+use std::fs;
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::camera::Camera;
+use crate::input;
+use crate::inspector::Inspector;
+use crate::json::Json;
+use crate::pack;
+use crate::path::{Curve, PathFollow};
+use crate::prefab::{Placement, Prefabs};
+use crate::renderer::Renderer;
+use crate::state::GameState;
+use crate::tween::Repeat;
+use crate::{info, warn};
+use crate::{BoundingBox2D, EntityID, World};
+
+/// World units per second the camera pans at, at zoom 1.
+const PAN_SPEED: f32 = 400.0;
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.0;
+/// Placed and dragged things snap to this, Shift places freely.
+const GRID: f32 = 8.0;
+/// Screen pixels around a selection's bottom right corner that resize it,
+/// and around path points that move them.
+const HANDLE: f32 = 6.0;
+/// Speed a newly laid path starts with, +/- changes it by `SPEED_STEP`.
+const PATH_SPEED: f32 = 60.0;
+const SPEED_STEP: f32 = 10.0;
+const PALETTE_HEIGHT: f32 = 16.0;
+const PALETTE_CELL: f32 = 90.0;
+
+/// Marks an entity as part of the editor's level, with the prefab it was
+/// made from. These are what the editor saves.
+pub struct LevelEntity {
+    pub prefab: String,
+}
+
+/// The editor's level file, prefabs with where they go and the paths they
+/// follow, in world coordinates:
+///
+/// ```json
+/// {
+///     "entities": [
+///         { "prefab": "Drone", "name": "Left guard", "bounds": [260, 300, 32, 24] },
+///         {
+///             "prefab": "Drone", "name": "Runner", "bounds": [40, 40, 32, 24],
+///             "path": { "points": [[56, 52], [300, 52]], "speed": 80, "repeat": "PingPong" }
+///         }
+///     ]
+/// }
+/// ```
+pub struct EditorLevel {
+    pub entities: Vec<(String, Placement, Option<PathFollow>)>,
+}
+
+impl EditorLevel {
+    pub fn load(path: &Path) -> Result<EditorLevel, String> {
+        let contents = pack::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        EditorLevel::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(contents: &str) -> Result<EditorLevel, String> {
+        let json = Json::parse(contents)?;
+        let mut entities = Vec::new();
+        let list = json.get("entities").and_then(Json::as_array);
+        for entity in list.ok_or("expected an entities array")? {
+            let bad = || format!("bad entity: {}", entity);
+            let prefab = entity.get("prefab").and_then(Json::as_str);
+            let bounds = entity.get("bounds").and_then(bounds).ok_or_else(bad)?;
+            let mut placement = Placement::new(bounds);
+            if let Some(name) = entity.get("name").and_then(Json::as_str) {
+                placement.name = name.to_string();
+            }
+            let path = match entity.get("path") {
+                Some(path) => Some(PathFollow::parse(path).ok_or_else(bad)?),
+                None => None,
+            };
+            entities.push((prefab.ok_or_else(bad)?.to_string(), placement, path));
+        }
+        Ok(EditorLevel { entities })
+    }
+
+    /// The level's entities in `world` as they are now.
+    pub fn from_world(world: &World) -> EditorLevel {
+        let entities = world
+            .level_entity_components
+            .iter()
+            .filter_map(|(id, level)| {
+                let base = world.base_components.iter().find(|b| b.0 == *id)?;
+                let mut placement = Placement::new(base.1.bounds);
+                placement.name = base.1.name.clone();
+                let path = world.path_components.iter().find(|p| p.0 == *id);
+                Some((level.prefab.clone(), placement, path.map(|p| p.1.clone())))
+            })
+            .collect();
+        EditorLevel { entities }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let entities = self
+            .entities
+            .iter()
+            .map(|(prefab, placement, path)| {
+                let b = placement.bounds;
+                let bounds = [b.x1, b.y1, b.width(), b.height()]
+                    .iter()
+                    .map(|n| Json::Number(*n as f64))
+                    .collect();
+                let mut fields = vec![
+                    ("prefab".to_string(), Json::String(prefab.clone())),
+                    ("name".to_string(), Json::String(placement.name.clone())),
+                    ("bounds".to_string(), Json::Array(bounds)),
+                ];
+                if let Some(path) = path {
+                    fields.push(("path".to_string(), path.to_json()));
+                }
+                Json::Object(fields)
+            })
+            .collect();
+        let json = Json::Object(vec![("entities".to_string(), Json::Array(entities))]);
+        fs::write(path, json.to_string()).map_err(|e| e.to_string())
+    }
+
+    /// Spawns the level's prefabs, unknown ones are skipped with a warning.
+    /// A path laid in the editor replaces the one the prefab comes with.
+    pub fn spawn(&self, world: &mut World) {
+        for (prefab, placement, path) in self.entities.iter() {
+            let id = match spawn(world, prefab, placement) {
+                Some(id) => id,
+                None => {
+                    warn!("editor", "unknown prefab", prefab = prefab);
+                    continue;
+                }
+            };
+            if let Some(path) = path {
+                world.path_components.retain(|p| p.0 != id);
+                world.path_components.push((id, path.clone()));
+            }
+        }
+    }
+}
+
+/// Spawns a prefab as part of the editor's level.
+fn spawn(world: &mut World, prefab: &str, placement: &Placement) -> Option<EntityID> {
+    let id = Prefabs::spawn(world, prefab, placement)?;
+    world.level_entity_components.push((
+        id,
+        LevelEntity {
+            prefab: prefab.to_string(),
+        },
+    ));
+    Some(id)
+}
+
+/// Camera as it was before the editor took it over.
+struct Borrowed {
+    camera: EntityID,
+    follow: Option<EntityID>,
+    target: Vector2,
+    zoom: f32,
+}
+
+enum Drag {
+    Move {
+        grab: Vector2,
+    },
+    Resize,
+    /// A point of the selection's path, by index.
+    Point(usize),
+}
+
+/// Level editor, toggled with F1 while playing or paused. The world
+/// freezes and the camera flies free like in photo mode. Pick a prefab
+/// from the palette along the bottom and click to place it, Right click
+/// puts the prefab down again. Without one, clicking selects and dragging
+/// moves, or resizes from the corner. The selection shows in the
+/// inspector, N renames it and Delete removes it. P lays a path for the
+/// selection to follow: clicks add points, dragging moves them, Backspace
+/// takes the last one back, B switches between waypoints and Bezier
+/// curves, R cycles how it repeats and +/- change its speed. Ctrl+S saves
+/// the level file, Ctrl+L loads it again.
+pub struct Editor {
+    path: String,
+    previous: GameState,
+    borrowed: Option<Borrowed>,
+    pan: Vector2,
+    zoom: f32,
+    /// Prefab placed by clicking.
+    brush: Option<String>,
+    pub selected: Option<EntityID>,
+    drag: Option<Drag>,
+    /// Name being typed for the selection.
+    renaming: Option<String>,
+    /// Laying the selection's path.
+    laying: bool,
+}
+
+impl Editor {
+    pub fn new(path: &str) -> Editor {
+        Editor {
+            path: path.to_string(),
+            previous: GameState::Playing,
+            borrowed: None,
+            pan: Vector2::zero(),
+            zoom: 0.0,
+            brush: None,
+            selected: None,
+            drag: None,
+            renaming: None,
+            laying: false,
+        }
+    }
+
+    fn palette(world: &World) -> Vec<String> {
+        let mut names: Vec<String> = world.prefabs.names().map(|n| n.to_string()).collect();
+        names.sort();
+        names
+    }
+
+    fn palette_top(world: &World) -> f32 {
+        world.screen.height() - PALETTE_HEIGHT
+    }
+
+    pub fn input_system(
+        &mut self,
+        world: &mut World,
+        rl: &RaylibHandle,
+        inspector: &mut Inspector,
+    ) {
+        let state = world.state.current();
+        if rl.is_key_pressed(KeyboardKey::KEY_F1) && self.renaming.is_none() {
+            match state {
+                GameState::Playing | GameState::Paused => {
+                    self.previous = state;
+                    world.state.set(GameState::Editor);
+                }
+                GameState::Editor => world.state.set(self.previous),
+                _ => (),
+            }
+        }
+        if state != GameState::Editor {
+            return;
+        }
+        if self
+            .selected
+            .is_some_and(|id| !world.base_components.iter().any(|b| b.0 == id))
+        {
+            self.selected = None;
+        }
+        if self.selected.is_none() {
+            self.laying = false;
+        }
+
+        if let Some(name) = self.renaming.as_mut() {
+            name.extend(input::typed(rl).into_iter().filter(|c| !c.is_control()));
+            if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                name.pop();
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                let id = self.selected;
+                if let Some(b) = world.base_components.iter_mut().find(|b| Some(b.0) == id) {
+                    b.1.name = std::mem::take(name);
+                }
+                self.renaming = None;
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                self.renaming = None;
+            }
+            return;
+        }
+
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_S) {
+            self.save(world);
+        }
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_L) {
+            self.load(world);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            if self.laying {
+                self.laying = false;
+            } else {
+                world.state.set(self.previous);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_P) {
+            if let Some(id) = self.selected {
+                self.laying = !self.laying;
+                self.brush = None;
+                if self.laying && !world.path_components.iter().any(|p| p.0 == id) {
+                    let start = world.base_components.iter().find(|b| b.0 == id);
+                    let points = start.map(|b| b.1.bounds.center()).into_iter().collect();
+                    let path = crate::path::Path::new(Curve::Waypoints, points);
+                    let follow = PathFollow::new(path, PATH_SPEED).repeat(Repeat::Loop);
+                    world.path_components.push((id, follow));
+                }
+            }
+        }
+        if self.laying {
+            let id = self.selected;
+            if let Some((_, follow)) = world.path_components.iter_mut().find(|p| Some(p.0) == id) {
+                if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                    follow.path.points.pop();
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_B) {
+                    follow.path.curve = match follow.path.curve {
+                        Curve::Waypoints => Curve::Bezier,
+                        Curve::Bezier => Curve::Waypoints,
+                    };
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_R) {
+                    follow.repeat = match follow.repeat {
+                        Repeat::Once => Repeat::Loop,
+                        Repeat::Loop => Repeat::PingPong,
+                        Repeat::PingPong => Repeat::Once,
+                    };
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) {
+                    follow.speed += SPEED_STEP;
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_MINUS) {
+                    follow.speed = (follow.speed - SPEED_STEP).max(SPEED_STEP);
+                }
+                follow.path.rebuild();
+            }
+            // taking back every point takes the path away
+            world
+                .path_components
+                .retain(|p| Some(p.0) != id || !p.1.path.points.is_empty());
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_N) && self.selected.is_some() {
+            // eat the N so it doesn't start the name
+            input::typed(rl);
+            self.renaming = Some(String::new());
+            return;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DELETE) {
+            if let Some(id) = self.selected.take() {
+                world.despawn(id);
+                inspector.select(None);
+            }
+        }
+
+        self.pan = Vector2::zero();
+        if !ctrl {
+            let held = |keys: [KeyboardKey; 2]| keys.iter().any(|k| rl.is_key_down(*k));
+            if held([KeyboardKey::KEY_W, KeyboardKey::KEY_UP]) {
+                self.pan.y -= 1.0;
+            }
+            if held([KeyboardKey::KEY_S, KeyboardKey::KEY_DOWN]) {
+                self.pan.y += 1.0;
+            }
+            if held([KeyboardKey::KEY_A, KeyboardKey::KEY_LEFT]) {
+                self.pan.x -= 1.0;
+            }
+            if held([KeyboardKey::KEY_D, KeyboardKey::KEY_RIGHT]) {
+                self.pan.x += 1.0;
+            }
+            if shift {
+                self.pan *= 3.0;
+            }
+        }
+
+        // the developer panels over the world get the mouse first
+        if world.capture.mouse {
+            self.zoom = 0.0;
+            self.drag = None;
+            return;
+        }
+        self.zoom = rl.get_mouse_wheel_move();
+        let mouse = world.screen.window_to_canvas(rl.get_mouse_position());
+        let camera = Camera::active(world);
+        let to_world = |pos: Vector2| camera.map_or(pos, |c| c.screen_to_world(pos));
+        let to_screen = |pos: Vector2| camera.map_or(pos, |c| c.world_to_screen(pos));
+        let pos = to_world(mouse);
+        let snapped = if shift {
+            pos
+        } else {
+            Vector2::new((pos.x / GRID).round() * GRID, (pos.y / GRID).round() * GRID)
+        };
+
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_RIGHT_BUTTON) {
+            self.brush = None;
+        }
+        if rl.is_mouse_button_released(MouseButton::MOUSE_LEFT_BUTTON) {
+            self.drag = None;
+        }
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            if mouse.y >= Editor::palette_top(world) {
+                let index = (mouse.x / PALETTE_CELL) as usize;
+                self.brush = Editor::palette(world).get(index).cloned();
+                return;
+            }
+            if self.laying {
+                let id = self.selected;
+                let hit = world
+                    .path_components
+                    .iter()
+                    .find(|p| Some(p.0) == id)
+                    .and_then(|p| {
+                        let mut points = p.1.path.points.iter();
+                        points.rposition(|p| to_screen(*p).distance_to(mouse) <= HANDLE)
+                    });
+                if let Some(i) = hit {
+                    self.drag = Some(Drag::Point(i));
+                } else if let Some(p) = world.path_components.iter_mut().find(|p| Some(p.0) == id) {
+                    p.1.path.points.push(snapped);
+                    p.1.path.rebuild();
+                }
+                return;
+            }
+            if let Some(prefab) = self.brush.clone() {
+                let placement = Placement::new(BoundingBox2D::new(snapped.x, snapped.y, 0.0, 0.0));
+                match spawn(world, &prefab, &placement) {
+                    Some(id) => {
+                        self.selected = Some(id);
+                        inspector.select(Some(id));
+                    }
+                    None => warn!("editor", "unknown prefab", prefab = prefab),
+                }
+                return;
+            }
+
+            let selection = self
+                .selected
+                .and_then(|id| world.base_components.iter().find(|b| b.0 == id))
+                .map(|b| b.1.bounds);
+            let on_handle = selection
+                .is_some_and(|b| to_screen(Vector2::new(b.x2, b.y2)).distance_to(mouse) <= HANDLE);
+            if on_handle {
+                self.drag = Some(Drag::Resize);
+                return;
+            }
+            let hit = world
+                .base_components
+                .iter()
+                .filter(|b| b.1.bounds.contains(pos))
+                .min_by(|a, b| {
+                    let area = |b: &BoundingBox2D| b.width() * b.height();
+                    area(&a.1.bounds).total_cmp(&area(&b.1.bounds))
+                });
+            self.selected = hit.map(|b| b.0);
+            self.drag = hit.map(|b| Drag::Move {
+                grab: pos - Vector2::new(b.1.bounds.x1, b.1.bounds.y1),
+            });
+            inspector.select(self.selected);
+        }
+
+        let (id, drag) = match (self.selected, self.drag.as_ref()) {
+            (Some(id), Some(drag)) => (id, drag),
+            _ => return,
+        };
+        if let Drag::Point(i) = drag {
+            if let Some((_, follow)) = world.path_components.iter_mut().find(|p| p.0 == id) {
+                if let Some(point) = follow.path.points.get_mut(*i) {
+                    *point = snapped;
+                    follow.path.rebuild();
+                }
+            }
+            return;
+        }
+        if let Some(b) = world.base_components.iter_mut().find(|b| b.0 == id) {
+            let b = &mut b.1.bounds;
+            match drag {
+                Drag::Move { grab } => {
+                    let mut corner = pos - *grab;
+                    if !shift {
+                        corner.x = (corner.x / GRID).round() * GRID;
+                        corner.y = (corner.y / GRID).round() * GRID;
+                    }
+                    b.translate(corner - Vector2::new(b.x1, b.y1));
+                }
+                Drag::Resize => {
+                    b.x2 = snapped.x.max(b.x1 + 1.0);
+                    b.y2 = snapped.y.max(b.y1 + 1.0);
+                }
+                Drag::Point(_) => (),
+            }
+        }
+    }
+
+    fn save(&self, world: &World) {
+        let path = Path::new(&self.path);
+        match EditorLevel::from_world(world).save(path) {
+            Ok(()) => info!("editor", "level saved", path = self.path),
+            Err(e) => warn!(
+                "editor",
+                "could not save level",
+                path = self.path,
+                error = e
+            ),
+        }
+    }
+
+    /// Replaces the level's entities in the world with the file's.
+    fn load(&mut self, world: &mut World) {
+        let level = match EditorLevel::load(Path::new(&self.path)) {
+            Ok(level) => level,
+            Err(e) => {
+                warn!("editor", "could not load level", error = e);
+                return;
+            }
+        };
+        let old: Vec<EntityID> = world.level_entity_components.iter().map(|l| l.0).collect();
+        for id in old {
+            world.despawn(id);
+        }
+        level.spawn(world);
+        self.selected = None;
+        info!("editor", "level loaded", path = self.path);
+    }
+
+    /// Borrows the first camera on entering the editor and hands it back on
+    /// leaving, like photo mode.
+    pub fn update_system(&mut self, world: &mut World, dt: f32) {
+        let editing = world.state.is(GameState::Editor);
+        if editing && self.borrowed.is_none() {
+            self.borrowed = world.camera_components.first().map(|(id, c)| Borrowed {
+                camera: *id,
+                follow: c.follow,
+                target: c.target,
+                zoom: c.zoom,
+            });
+        } else if !editing {
+            if let Some(b) = self.borrowed.take() {
+                if let Some(c) = world.camera_components.iter_mut().find(|c| c.0 == b.camera) {
+                    c.1.follow = b.follow;
+                    c.1.target = b.target;
+                    c.1.zoom = b.zoom;
+                }
+            }
+            self.drag = None;
+            self.renaming = None;
+            self.laying = false;
+            return;
+        }
+
+        let id = match self.borrowed.as_ref() {
+            Some(b) => b.camera,
+            None => return,
+        };
+        if let Some((_, camera)) = world.camera_components.iter_mut().find(|c| c.0 == id) {
+            camera.follow = None;
+            camera.zoom = (camera.zoom * (1.0 + self.zoom * 0.1)).clamp(MIN_ZOOM, MAX_ZOOM);
+            camera.target += self.pan * (PAN_SPEED / camera.zoom * dt);
+        }
+    }
+
+    /// The selection with its resize handle, the palette and what the keys
+    /// do.
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if !world.state.is(GameState::Editor) {
+            return;
+        }
+        let camera = Camera::active(world);
+        let to_screen = |pos: Vector2| camera.map_or(pos, |c| c.world_to_screen(pos));
+        for (id, _) in world.level_entity_components.iter() {
+            if let Some(b) = world.base_components.iter().find(|b| b.0 == *id) {
+                let b = b.1.bounds;
+                let top_left = to_screen(Vector2::new(b.x1, b.y1));
+                let size = to_screen(Vector2::new(b.x2, b.y2)) - top_left;
+                let rect = Rectangle::new(top_left.x, top_left.y, size.x, size.y);
+                let color = if self.selected == Some(*id) {
+                    Color::YELLOW
+                } else {
+                    Color::SKYBLUE.fade(0.5)
+                };
+                d.draw_rect_lines(rect, 1, color);
+            }
+        }
+        let selection = self
+            .selected
+            .and_then(|id| world.base_components.iter().find(|b| b.0 == id));
+        if let Some(b) = selection {
+            let b = b.1.bounds;
+            let top_left = to_screen(Vector2::new(b.x1, b.y1));
+            let bottom_right = to_screen(Vector2::new(b.x2, b.y2));
+            let size = bottom_right - top_left;
+            d.draw_rect_lines(
+                Rectangle::new(top_left.x, top_left.y, size.x, size.y),
+                1,
+                Color::YELLOW,
+            );
+            d.draw_rect(
+                Rectangle::new(
+                    bottom_right.x - HANDLE / 2.0,
+                    bottom_right.y - HANDLE / 2.0,
+                    HANDLE,
+                    HANDLE,
+                ),
+                Color::YELLOW,
+            );
+        }
+
+        for (id, follow) in world.path_components.iter() {
+            let laying = self.laying && self.selected == Some(*id);
+            let color = if laying {
+                Color::ORANGE
+            } else {
+                Color::ORANGE.fade(0.4)
+            };
+            for pair in follow.path.samples().windows(2) {
+                d.draw_line(to_screen(pair[0]), to_screen(pair[1]), 1.0, color);
+            }
+            if !laying {
+                continue;
+            }
+            let points = &follow.path.points;
+            if follow.path.curve == Curve::Bezier {
+                // each control point hangs off the point it bends away from
+                for (i, point) in points.iter().enumerate() {
+                    let anchor = match i % 3 {
+                        1 => points[i - 1],
+                        2 => match points.get(i + 1) {
+                            Some(p) => *p,
+                            None => continue,
+                        },
+                        _ => continue,
+                    };
+                    d.draw_line(to_screen(anchor), to_screen(*point), 1.0, Color::GRAY);
+                }
+            }
+            for (i, point) in points.iter().enumerate() {
+                let pos = to_screen(*point);
+                let control = follow.path.curve == Curve::Bezier && i % 3 != 0;
+                let rect =
+                    Rectangle::new(pos.x - HANDLE / 2.0, pos.y - HANDLE / 2.0, HANDLE, HANDLE);
+                if control {
+                    d.draw_rect_lines(rect, 1, Color::ORANGE);
+                } else {
+                    d.draw_rect(rect, Color::ORANGE);
+                }
+            }
+        }
+
+        let w = world.screen.width();
+        d.draw_rect(Rectangle::new(0.0, 0.0, w, 14.0), Color::BLACK.fade(0.8));
+        let laying = self
+            .selected
+            .and_then(|id| world.path_components.iter().find(|p| p.0 == id))
+            .filter(|_| self.laying);
+        let help = match (self.renaming.as_ref(), laying, self.brush.as_ref()) {
+            (Some(name), _, _) => format!("Name: {}_   Enter to keep, Escape to cancel", name),
+            (None, Some((_, follow)), _) => {
+                let curve = match follow.path.curve {
+                    Curve::Waypoints => "lines",
+                    Curve::Bezier => "bezier",
+                };
+                format!(
+                    "Path: Click add  Drag move  Bksp undo  B {}  R {:?}  +/- speed {}  P done",
+                    curve, follow.repeat, follow.speed
+                )
+            }
+            (None, None, Some(brush)) => format!("Placing {}   Right click to stop", brush),
+            (None, None, None) => {
+                "F1 exit  Click select  Drag move  N rename  P path  Del delete  Ctrl+S save  Ctrl+L load"
+                    .to_string()
+            }
+        };
+        d.draw_text(&help, 4, 2, 10, Color::RAYWHITE);
+
+        let top = Editor::palette_top(world);
+        d.draw_rect(
+            Rectangle::new(0.0, top, w, PALETTE_HEIGHT),
+            Color::BLACK.fade(0.8),
+        );
+        for (i, name) in Editor::palette(world).iter().enumerate() {
+            let x = i as f32 * PALETTE_CELL;
+            let color = if self.brush.as_ref() == Some(name) {
+                Color::YELLOW
+            } else {
+                Color::RAYWHITE
+            };
+            let label = if measure_text(name, 10) < PALETTE_CELL as i32 - 6 {
+                name.clone()
+            } else {
+                format!("{}..", name.chars().take(10).collect::<String>())
+            };
+            d.draw_text(&label, x as i32 + 4, top as i32 + 3, 10, color);
+        }
+    }
+}
+
+/// `[x, y, width, height]`.
+fn bounds(json: &Json) -> Option<BoundingBox2D> {
+    match json.as_array()?.as_slice() {
+        [x, y, w, h] => Some(BoundingBox2D::new(
+            x.as_f32()?,
+            y.as_f32()?,
+            w.as_f32()?,
+            h.as_f32()?,
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,318 @@
+use raylib::prelude::*;
+
+use crate::config::Config;
+use crate::pack;
+use crate::plugin::{GamePlugins, Plugin};
+use crate::{info, profile, warn, World};
+
+/// An update system: its name in the profiler and what it runs.
+type System = (&'static str, fn(&mut World, f32));
+type StartupSystem = fn(&mut World, &mut RaylibHandle, &RaylibThread);
+
+/// The systems plugins added. Needs no window, `Engine::builder` opens one
+/// alongside.
+pub struct Engine {
+    systems: Vec<System>,
+    startup: Vec<StartupSystem>,
+}
+
+impl Engine {
+    pub fn new() -> Engine {
+        Engine {
+            systems: Vec::new(),
+            startup: Vec::new(),
+        }
+    }
+
+    pub fn add_plugin(&mut self, plugin: impl Plugin) -> &mut Engine {
+        plugin.build(self);
+        self
+    }
+
+    /// Runs `system` every simulated frame, after the ones added before it.
+    pub fn add_system(&mut self, name: &'static str, system: fn(&mut World, f32)) -> &mut Engine {
+        self.systems.push((name, system));
+        self
+    }
+
+    /// Runs `system` once from `startup`, to set up resources and load
+    /// assets.
+    pub fn add_startup_system(&mut self, system: StartupSystem) -> &mut Engine {
+        self.startup.push(system);
+        self
+    }
+
+    /// Runs the startup systems, once the window is open.
+    pub fn startup(&self, world: &mut World, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        for system in self.startup.iter() {
+            system(world, rl, thread);
+        }
+    }
+
+    /// Moves the world on by this frame's game time, `world.time.delta()`:
+    /// every update system, in order. Nothing runs while frame-stepping
+    /// holds the world still.
+    pub fn update(&self, world: &mut World) {
+        if world.time.frozen() {
+            return;
+        }
+        let dt = world.time.delta();
+        for (name, system) in self.systems.iter() {
+            profile!(world, name, system(world, dt));
+        }
+    }
+
+    /// Runs `frames` fixed updates of `dt` without a window or input, for
+    /// tests and tools. Dead entities go and events clear after each one,
+    /// like at the end of a real frame.
+    pub fn simulate(&self, world: &mut World, frames: u32, dt: f32) {
+        for _ in 0..frames {
+            self.frame(world, dt);
+            world.end_frame();
+        }
+    }
+
+    /// One fixed update of `dt` like `simulate` takes, with the frame-step
+    /// mode of `world.time` heeded, and the frame not ended so its events
+    /// can still be looked at.
+    pub fn frame(&self, world: &mut World, dt: f32) {
+        let game_dt = world.time.simulation_dt(dt).unwrap_or(0.0);
+        world.time.advance(dt, game_dt);
+        self.update(world);
+    }
+
+    /// Starts from a 2x window at the canvas resolution, vsynced and capped
+    /// at 60 fps. Apply `config` to take the player's settings.
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder {
+            title: "SpaceGame".to_string(),
+            width: WIDTH * 2,
+            height: HEIGHT * 2,
+            vsync: true,
+            resizable: false,
+            msaa: false,
+            target_fps: 60,
+            icon: None,
+            error: None,
+        }
+    }
+}
+
+/// Window and startup options, see `Engine::builder`. Later calls win, so
+/// set the defaults first, then the config.
+pub struct EngineBuilder {
+    title: String,
+    width: i32,
+    height: i32,
+    vsync: bool,
+    resizable: bool,
+    msaa: bool,
+    /// Frames per second to cap the game at, 0 runs uncapped.
+    target_fps: u32,
+    icon: Option<String>,
+    /// First bad option given, reported by `build`.
+    error: Option<String>,
+}
+
+impl EngineBuilder {
+    pub fn title(mut self, title: &str) -> EngineBuilder {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Window size in pixels, the canvas is scaled to fit it.
+    pub fn size(mut self, width: i32, height: i32) -> EngineBuilder {
+        if width <= 0 || height <= 0 {
+            self.fail(format!("bad window size {}x{}", width, height));
+        }
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> EngineBuilder {
+        self.vsync = vsync;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> EngineBuilder {
+        self.resizable = resizable;
+        self
+    }
+
+    /// 4x multisampling, smooths the edges of shapes but not textures.
+    pub fn msaa(mut self, msaa: bool) -> EngineBuilder {
+        self.msaa = msaa;
+        self
+    }
+
+    pub fn target_fps(mut self, fps: u32) -> EngineBuilder {
+        self.target_fps = fps;
+        self
+    }
+
+    /// Image shown in the title bar and task bar. One that fails to load
+    /// leaves the default icon.
+    pub fn icon(mut self, path: &str) -> EngineBuilder {
+        self.icon = Some(path.to_string());
+        self
+    }
+
+    /// Takes the window size, frame cap, vsync and multisampling from the
+    /// config, command line included.
+    pub fn config(self, config: &Config) -> EngineBuilder {
+        let scale = config.window_scale.max(1) as i32;
+        let (width, height) = config
+            .window_size
+            .unwrap_or((WIDTH * scale, HEIGHT * scale));
+        self.size(width, height)
+            .vsync(config.vsync)
+            .target_fps(config.target_fps)
+            .msaa(config.msaa)
+    }
+
+    fn fail(&mut self, error: String) {
+        self.error.get_or_insert(error);
+    }
+
+    /// Opens the window, next to an engine without plugins. Fails on a bad
+    /// option, or when there's no window to be had.
+    pub fn build(self) -> Result<(Engine, RaylibHandle, RaylibThread), String> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let mut builder = raylib::init();
+        builder.size(self.width, self.height).title(&self.title);
+        if self.vsync {
+            builder.vsync();
+        }
+        if self.resizable {
+            builder.resizable();
+        }
+        if self.msaa {
+            builder.msaa_4x();
+        }
+        let (mut rl, thread) = builder.build();
+        if !rl.is_window_ready() {
+            return Err("could not open a window".to_string());
+        }
+        rl.set_target_fps(self.target_fps);
+
+        if let Some(path) = self.icon {
+            match pack::load_image(&path) {
+                Ok(icon) => rl.set_window_icon(&icon),
+                Err(e) => warn!(
+                    "engine",
+                    "could not load the window icon",
+                    path = path,
+                    error = e
+                ),
+            }
+        }
+        info!(
+            "engine",
+            "window opened",
+            width = self.width,
+            height = self.height,
+            vsync = self.vsync,
+            msaa = self.msaa
+        );
+        Ok((Engine::new(), rl, thread))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+
+    CenterLeft,
+    Center,
+    CenterRight,
+
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    pub fn values() -> Vec<Anchor> {
+        vec![
+            Anchor::TopLeft,
+            Anchor::TopCenter,
+            Anchor::TopRight,
+            Anchor::CenterLeft,
+            Anchor::Center,
+            Anchor::CenterRight,
+            Anchor::BottomLeft,
+            Anchor::BottomCenter,
+            Anchor::BottomRight,
+        ]
+    }
+}
+
+/// Internal resolution everything is drawn at, see Canvas.
+pub const WIDTH: i32 = 640;
+pub const HEIGHT: i32 = 360;
+
+/// Longest frame the game will simulate. Dragging or resizing the window
+/// stalls the loop, and a frame that long would throw everything through
+/// walls and pile up work for the frames after it, so the game slows down
+/// instead.
+pub const MAX_FRAME_TIME: f32 = 0.1;
+
+/// Real time the last frame took, clamped to `MAX_FRAME_TIME`. Read once at
+/// the top of the frame and handed to `Time::advance`.
+pub fn frame_time(rl: &RaylibHandle) -> f32 {
+    rl.get_frame_time().clamp(0.0, MAX_FRAME_TIME)
+}
+
+/// Calls `frame` once per frame until it returns false.
+#[cfg(not(target_os = "emscripten"))]
+pub fn run_loop<F: FnMut() -> bool + 'static>(mut frame: F) {
+    while frame() {}
+}
+
+/// In the browser the page owns the loop and a frame that never returns
+/// hangs the tab, so `frame` is handed to emscripten to call once per
+/// animation frame. This never returns, which is why the frame has to own
+/// everything it uses.
+#[cfg(target_os = "emscripten")]
+pub fn run_loop<F: FnMut() -> bool + 'static>(frame: F) {
+    use std::os::raw::{c_int, c_void};
+
+    extern "C" {
+        fn emscripten_set_main_loop_arg(
+            func: extern "C" fn(*mut c_void),
+            arg: *mut c_void,
+            fps: c_int,
+            simulate_infinite_loop: c_int,
+        );
+        fn emscripten_cancel_main_loop();
+    }
+
+    extern "C" fn call<F: FnMut() -> bool>(arg: *mut c_void) {
+        // SAFETY: only ever called with the frame leaked below, which is
+        // never freed, and on the page's one thread
+        let frame = unsafe { &mut *(arg as *mut F) };
+        if !frame() {
+            // SAFETY: no arguments, stops the loop this is called from
+            unsafe { emscripten_cancel_main_loop() };
+        }
+    }
+
+    // lives as long as the page, the loop keeps calling it
+    let frame = Box::into_raw(Box::new(frame));
+    // SAFETY: `call` matches the frame's type and the pointer stays valid
+    // for good. fps 0 follows the browser's requestAnimationFrame.
+    unsafe { emscripten_set_main_loop_arg(call::<F>, frame as *mut c_void, 0, 1) };
+}
+
+/// Runs `frames` fixed updates of the whole game, see `Engine::simulate`.
+pub fn simulate(world: &mut World, frames: u32, dt: f32) {
+    let mut engine = Engine::new();
+    engine.add_plugin(GamePlugins);
+    engine.simulate(world, frames, dt);
+}
@@ -0,0 +1,67 @@
+use raylib::prelude::*;
+
+use crate::particles::lerp_color;
+use crate::tween::{Easing, Tween};
+use crate::{EntityID, UpdateSystem, World};
+
+/// Tints the entity when it takes damage and fades back to its own tint
+/// over `duration` seconds.
+pub struct DamageFlash {
+    pub color: Color,
+    pub duration: f32,
+    /// Tint to return to, captured when a flash starts.
+    original: Color,
+    flashing: bool,
+}
+
+impl DamageFlash {
+    pub fn new(color: Color, duration: f32) -> DamageFlash {
+        DamageFlash {
+            color,
+            duration,
+            original: Color::WHITE,
+            flashing: false,
+        }
+    }
+}
+
+impl UpdateSystem for DamageFlash {
+    fn update_system(world: &mut World, _dt: f32) {
+        for event in world.damage_events.iter() {
+            let flash = match world
+                .flash_components
+                .iter_mut()
+                .find(|f| f.0 == event.target)
+            {
+                Some(f) => &mut f.1,
+                None => continue,
+            };
+            let base = match world.base_components.iter().find(|b| b.0 == event.target) {
+                Some(b) => &b.1,
+                None => continue,
+            };
+
+            // a hit during a flash restarts it without losing the real tint
+            if !flash.flashing {
+                flash.original = base.tint;
+                flash.flashing = true;
+            }
+            world.tween_components.push((
+                event.target,
+                Tween::new(flash_tint, 1.0, 0.0, flash.duration, Easing::QuadIn),
+            ));
+        }
+    }
+}
+
+/// Tween setter blending from the original tint (0) to the flash color (1).
+fn flash_tint(world: &mut World, id: EntityID, value: f32) {
+    let flash = match world.flash_components.iter_mut().find(|f| f.0 == id) {
+        Some(f) => &mut f.1,
+        None => return,
+    };
+    if let Some(b) = world.base_components.iter_mut().find(|b| b.0 == id) {
+        b.1.tint = lerp_color(flash.original, flash.color, value);
+    }
+    flash.flashing = value > 0.0;
+}
@@ -0,0 +1,138 @@
+use raylib::prelude::*;
+
+use crate::particles::lerp_color;
+use crate::renderer::Renderer;
+use crate::widget::{UiEvent, UiEventKind, WidgetKind, WidgetState};
+use crate::{DrawSystem, EntityID, World};
+
+/// Share of a slider's range one key press moves it by.
+const SLIDER_STEP: f32 = 0.05;
+
+/// Which widget keyboard and gamepad input goes to, so menus work without a
+/// mouse. Up/down move between widgets in spawn order and wrap around,
+/// left/right nudge sliders and Enter/A activates.
+pub struct Focus {
+    pub focused: Option<EntityID>,
+}
+
+fn pressed(rl: &RaylibHandle, key: KeyboardKey, button: GamepadButton) -> bool {
+    rl.is_key_pressed(key)
+        || (rl.is_gamepad_available(0) && rl.is_gamepad_button_pressed(0, button))
+}
+
+impl Focus {
+    pub fn new() -> Focus {
+        Focus { focused: None }
+    }
+
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        let focusable: Vec<EntityID> = world
+            .widget_components
+            .iter()
+            .filter(|w| !matches!(w.1.kind, WidgetKind::Panel))
+            .map(|w| w.0)
+            .collect();
+        if focusable.is_empty() {
+            world.focus.focused = None;
+            return;
+        }
+
+        // clicking a widget moves the focus along with it
+        if let Some(w) = world
+            .widget_components
+            .iter()
+            .find(|w| w.1.state == WidgetState::Pressed)
+        {
+            world.focus.focused = Some(w.0);
+        }
+
+        let len = focusable.len();
+        let current = world
+            .focus
+            .focused
+            .and_then(|f| focusable.iter().position(|id| *id == f));
+        let up = pressed(
+            rl,
+            KeyboardKey::KEY_UP,
+            GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP,
+        );
+        let down = pressed(
+            rl,
+            KeyboardKey::KEY_DOWN,
+            GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN,
+        );
+        let index = if up {
+            Some(current.map(|i| (i + len - 1) % len).unwrap_or(len - 1))
+        } else if down {
+            Some(current.map(|i| (i + 1) % len).unwrap_or(0))
+        } else {
+            current
+        };
+        world.focus.focused = index.map(|i| focusable[i]);
+
+        let focused = match world.focus.focused {
+            Some(id) => id,
+            None => return,
+        };
+        let widget = match world.widget_components.iter_mut().find(|w| w.0 == focused) {
+            Some(w) => &mut w.1,
+            None => return,
+        };
+
+        let confirm = pressed(
+            rl,
+            KeyboardKey::KEY_ENTER,
+            GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+        );
+        let left = pressed(
+            rl,
+            KeyboardKey::KEY_LEFT,
+            GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT,
+        );
+        let right = pressed(
+            rl,
+            KeyboardKey::KEY_RIGHT,
+            GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT,
+        );
+
+        let event = match &mut widget.kind {
+            WidgetKind::Button { .. } if confirm => Some(UiEventKind::Clicked),
+            WidgetKind::Checkbox { checked, .. } if confirm => {
+                *checked = !*checked;
+                Some(UiEventKind::Toggled(*checked))
+            }
+            WidgetKind::Slider {
+                value, min, max, ..
+            } if left || right => {
+                let step = (*max - *min) * SLIDER_STEP;
+                let step = if left { -step } else { step };
+                *value = (*value + step).clamp(*min, *max);
+                Some(UiEventKind::Changed(*value))
+            }
+            _ => None,
+        };
+
+        if let Some(kind) = event {
+            world.ui_events.push(UiEvent {
+                widget: focused,
+                kind,
+            });
+        }
+    }
+}
+
+impl DrawSystem for Focus {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        let bounds = match world
+            .focus
+            .focused
+            .and_then(|f| world.ui_components.iter().find(|u| u.0 == f))
+        {
+            Some(u) => u.1.bounds.inset(-3.0 - world.beat.pulse * 2.0),
+            None => return,
+        };
+        // breathes with the music so menus don't sit still
+        let color = lerp_color(Color::YELLOW, Color::WHITE, world.beat.pulse * 0.6);
+        d.draw_rect_lines(bounds.into(), 2, color);
+    }
+}
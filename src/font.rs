@@ -0,0 +1,214 @@
+use raylib::prelude::*;
+
+use crate::assets::{Assets, Handle};
+use crate::renderer::Renderer;
+use crate::{Anchor, BoundingBox2D};
+
+/// Gap between lines, as a fraction of the font size.
+const LINE_GAP: f32 = 0.2;
+
+/// How a piece of text is drawn. Without a font the assets' UI font is
+/// used, and raylib's built-in one when that isn't loaded either. Text the
+/// font has no glyphs for goes to the first fallback font that has them.
+#[derive(Debug, Clone, Copy)]
+pub struct TextStyle {
+    pub font: Option<Handle<Font>>,
+    pub size: f32,
+    /// Extra room between characters, in pixels.
+    pub spacing: f32,
+    pub color: Color,
+    /// Which point of the text block sits on the draw position.
+    pub align: Anchor,
+    /// 1 pixel outline around every glyph.
+    pub outline: Option<Color>,
+    /// Copy of the text drawn behind it, shifted by the offset.
+    pub shadow: Option<(Vector2, Color)>,
+    /// Wraps lines at word boundaries to stay within this width.
+    pub wrap_width: Option<f32>,
+}
+
+impl TextStyle {
+    pub fn new(size: f32, color: Color) -> TextStyle {
+        TextStyle {
+            font: None,
+            size,
+            spacing: 1.0,
+            color,
+            align: Anchor::TopLeft,
+            outline: None,
+            shadow: None,
+            wrap_width: None,
+        }
+    }
+
+    pub fn with_font(mut self, font: Handle<Font>) -> TextStyle {
+        self.font = Some(font);
+        self
+    }
+
+    pub fn aligned(mut self, align: Anchor) -> TextStyle {
+        self.align = align;
+        self
+    }
+
+    pub fn outlined(mut self, color: Color) -> TextStyle {
+        self.outline = Some(color);
+        self
+    }
+
+    pub fn shadowed(mut self, offset: Vector2, color: Color) -> TextStyle {
+        self.shadow = Some((offset, color));
+        self
+    }
+
+    pub fn wrapped(mut self, width: f32) -> TextStyle {
+        self.wrap_width = Some(width);
+        self
+    }
+
+    fn line_height(&self) -> f32 {
+        self.size * (1.0 + LINE_GAP)
+    }
+}
+
+/// Lets a raw font go through the safe drawing functions.
+#[derive(Clone, Copy)]
+struct RawFont(ffi::Font);
+
+impl AsRef<ffi::Font> for RawFont {
+    fn as_ref(&self) -> &ffi::Font {
+        &self.0
+    }
+}
+
+fn resolve(assets: &Assets, style: &TextStyle, text: &str) -> RawFont {
+    let font = match style.font.or(assets.ui_font).and_then(|f| assets.font(f)) {
+        Some(font) => RawFont(*font.as_ref()),
+        // SAFETY: no arguments, the default font lives as long as the
+        // window and isn't freed here
+        None => RawFont(unsafe { ffi::GetFontDefault() }),
+    };
+    if text.is_ascii() || covers(font, text) {
+        return font;
+    }
+    assets
+        .fallback_fonts
+        .iter()
+        .filter_map(|f| assets.font(*f))
+        .map(|f| RawFont(*f.as_ref()))
+        .find(|f| covers(*f, text))
+        .unwrap_or(font)
+}
+
+/// Whether the font has a glyph for every character of the text.
+fn covers(font: RawFont, text: &str) -> bool {
+    if font.0.chars.is_null() {
+        return false;
+    }
+    // SAFETY: a loaded font's chars point to charsCount glyphs, null was
+    // ruled out above, and the font outlives this call.
+    let glyphs = unsafe { std::slice::from_raw_parts(font.0.chars, font.0.charsCount as usize) };
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| glyphs.iter().any(|g| g.value == c as i32))
+}
+
+fn line_width(font: RawFont, line: &str, style: &TextStyle) -> f32 {
+    // without a window the default font isn't loaded and raylib would read
+    // its missing glyphs, headless text takes no room
+    if font.0.chars.is_null() {
+        return 0.0;
+    }
+    measure_text_ex(font, line, style.size, style.spacing).x
+}
+
+/// Splits the text into the lines it's drawn as: on newlines, and on word
+/// boundaries past the style's wrap width. Single words wider than the
+/// wrap width get a line of their own rather than being cut.
+pub fn layout(assets: &Assets, text: &str, style: &TextStyle) -> Vec<String> {
+    let font = resolve(assets, style, text);
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let max = match style.wrap_width {
+            Some(max) => max,
+            None => {
+                lines.push(paragraph.to_string());
+                continue;
+            }
+        };
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if !line.is_empty() && line_width(font, &candidate, style) > max {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Size of the laid out text block.
+pub fn measure(assets: &Assets, text: &str, style: &TextStyle) -> Vector2 {
+    let font = resolve(assets, style, text);
+    let lines = layout(assets, text, style);
+    let width = lines
+        .iter()
+        .map(|l| line_width(font, l, style))
+        .fold(0.0, f32::max);
+    let height = style.size + style.line_height() * lines.len().saturating_sub(1) as f32;
+    Vector2::new(width, height)
+}
+
+/// Draws the text with the style's align point on `pos`. Lines are aligned
+/// to the same side of the block as the align anchor.
+pub fn draw(assets: &Assets, d: &mut dyn Renderer, text: &str, pos: Vector2, style: &TextStyle) {
+    let font = resolve(assets, style, text);
+    let size = measure(assets, text, style);
+    let block = BoundingBox2D::new(0.0, 0.0, size.x, size.y);
+    let top_left = pos - block.calc(style.align);
+
+    for (i, line) in layout(assets, text, style).iter().enumerate() {
+        let width = line_width(font, line, style);
+        let row = BoundingBox2D::new(0.0, 0.0, size.x - width, 0.0);
+        let x = top_left.x + row.calc(style.align).x;
+        // whole pixels keep bitmap fonts crisp
+        let y = top_left.y + style.line_height() * i as f32;
+        let origin = Vector2::new(x.round(), y.round());
+
+        let mut draw_at = |offset: Vector2, color: Color| {
+            d.draw_text_font(
+                font.as_ref(),
+                line,
+                origin + offset,
+                style.size,
+                style.spacing,
+                color,
+            );
+        };
+        if let Some((offset, color)) = style.shadow {
+            draw_at(offset, color);
+        }
+        if let Some(color) = style.outline {
+            for (dx, dy) in [
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ] {
+                draw_at(Vector2::new(dx as f32, dy as f32), color);
+            }
+        }
+        draw_at(Vector2::zero(), style.color);
+    }
+}
@@ -0,0 +1,126 @@
+use raylib::prelude::*;
+
+use crate::achievements::Achievements;
+use crate::renderer::Renderer;
+use crate::save::SaveSlots;
+use crate::state::GameState;
+use crate::stats::Stats;
+use crate::t;
+use crate::ui::UiElement;
+use crate::widget::{UiEventKind, Widget};
+use crate::{Anchor, EntityID, World};
+
+const COLUMN_WIDTH: f32 = 190.0;
+const PANEL_HEIGHT: f32 = 250.0;
+
+/// Shown in the Gallery state, from the title screen's Gallery button: the
+/// ships unlocked on any save slot, the enemies met so far and every
+/// achievement, locked ones greyed out.
+pub struct Gallery {
+    back: Option<EntityID>,
+}
+
+impl Gallery {
+    pub fn new() -> Gallery {
+        Gallery { back: None }
+    }
+
+    /// Escape goes back like the Back button does.
+    pub fn input_system(&mut self, world: &mut World, rl: &RaylibHandle) {
+        if world.state.is(GameState::Gallery) && rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            world.state.set(GameState::Menu);
+        }
+    }
+
+    pub fn update_system(&mut self, world: &mut World) {
+        let shown = world.state.is(GameState::Gallery);
+        if shown && self.back.is_none() {
+            let back = Widget::spawn(
+                world,
+                Widget::button(t!("menu.back")),
+                UiElement::new(
+                    Anchor::BottomCenter,
+                    Vector2::new(0.0, -16.0),
+                    Vector2::new(100.0, 22.0),
+                ),
+            );
+            self.back = Some(back);
+            world.focus.focused = Some(back);
+        } else if !shown {
+            if let Some(back) = self.back.take() {
+                world.despawn(back);
+            }
+            return;
+        }
+
+        let clicked = world
+            .ui_events
+            .iter()
+            .any(|e| e.kind == UiEventKind::Clicked && Some(e.widget) == self.back);
+        if clicked {
+            world.state.set(GameState::Menu);
+        }
+    }
+
+    pub fn draw_system(
+        &self,
+        world: &World,
+        d: &mut dyn Renderer,
+        slots: &SaveSlots,
+        stats: &Stats,
+        achievements: &Achievements,
+    ) {
+        if !world.state.is(GameState::Gallery) {
+            return;
+        }
+        let ships: Vec<(String, bool)> = slots
+            .ships()
+            .into_iter()
+            .map(|(ship, unlocked)| (ship.to_string(), unlocked))
+            .collect();
+        let enemies: Vec<(String, bool)> = stats
+            .kills()
+            .into_iter()
+            .map(|(kind, kills)| (format!("{} x{}", kind, kills), true))
+            .collect();
+        let trophies = achievements.list();
+
+        let center = world.screen.bounds().center();
+        let left = center.x - COLUMN_WIDTH * 1.5;
+        let top = center.y - PANEL_HEIGHT * 0.5 - 14.0;
+        for (i, (title, entries)) in [
+            (t!("gallery.ships"), ships),
+            (t!("gallery.enemies"), enemies),
+            (t!("gallery.achievements"), trophies),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let panel = Rectangle::new(
+                left + i as f32 * COLUMN_WIDTH + 2.0,
+                top,
+                COLUMN_WIDTH - 4.0,
+                PANEL_HEIGHT,
+            );
+            d.draw_rect(panel, Color::BLACK.fade(0.9));
+            d.draw_rect_lines(panel, 1, Color::WHITE);
+
+            let x = panel.x as i32 + 10;
+            let mut y = panel.y as i32 + 10;
+            d.draw_text(&title, x, y, 16, Color::GOLD);
+            y += 24;
+            if entries.is_empty() {
+                d.draw_text(&t!("gallery.empty"), x, y, 12, Color::GRAY);
+            }
+            for (entry, unlocked) in entries {
+                let color = if unlocked {
+                    Color::RAYWHITE
+                } else {
+                    Color::DARKGRAY
+                };
+                d.draw_text(&entry, x, y, 12, color);
+                y += 16;
+            }
+        }
+    }
+}
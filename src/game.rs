@@ -0,0 +1,438 @@
+use std::time::Instant;
+
+use raylib::prelude::*;
+
+use crate::achievements::Achievements;
+use crate::audio;
+use crate::beat::BeatTracker;
+use crate::campaign::Campaign;
+use crate::canvas::Canvas;
+use crate::cheats::Cheats;
+use crate::config::Config;
+use crate::crash;
+use crate::credits::Credits;
+use crate::crosshair::Crosshair;
+use crate::cutscene::Cutscene;
+use crate::debug::DebugDraw;
+use crate::dev_tools::DevTools;
+use crate::editor::Editor;
+use crate::engine::{self, Engine};
+use crate::focus::Focus;
+use crate::gallery::Gallery;
+use crate::gameover::GameOverScreen;
+use crate::heat::HeatSounds;
+use crate::hot_reload::HotReload;
+use crate::light::LightMap;
+use crate::menu::MainMenu;
+use crate::minimap::Minimap;
+use crate::music::{MusicCue, MusicPlayer};
+use crate::pause::PauseMenu;
+use crate::photo::PhotoMode;
+use crate::player::Player;
+use crate::plugin::{CampaignPlugin, GamePlugins, PrefabPlugin, UiPlugin};
+use crate::profiler::Profiler;
+use crate::render;
+use crate::render_stats::RenderStats;
+use crate::save::SaveSlots;
+use crate::scene::Scenes;
+use crate::screen::ScreenSize;
+use crate::sector::{self, EDITOR_LEVEL};
+use crate::settings::SettingsMenu;
+use crate::state::{GameState, StateMachine};
+use crate::station::Station;
+use crate::stats::Stats;
+use crate::time::Time;
+use crate::transition::Transition;
+use crate::tutorial::Tutorial;
+use crate::ui::UiElement;
+use crate::widget::Widget;
+use crate::{profile, warn, DrawSystem, UIBarStyle, UpdateSystem, World, HEIGHT, WIDTH};
+
+const MENU_MUSIC: &str = "assets/music/menu.ogg";
+/// Gameplay track, streamed and analyzed for the beat.
+const MUSIC: &str = "assets/music/ambient.ogg";
+const BOSS_MUSIC: &str = "assets/music/boss.ogg";
+/// Played once before the boss track starts looping.
+const BOSS_MUSIC_INTRO: &str = "assets/music/boss_intro.ogg";
+
+/// The running game: the window, the world, and everything around it that
+/// lives outside the world, menus, music and render targets. Each frame
+/// goes through input, update and draw, see `frame`.
+pub struct Game {
+    engine: Engine,
+    rl: RaylibHandle,
+    thread: RaylibThread,
+    world: World,
+    scenes: Scenes,
+
+    audio: RaylibAudio,
+    music: MusicPlayer,
+    beat_tracker: Option<BeatTracker>,
+    heat_sounds: HeatSounds,
+
+    canvas: Canvas,
+    minimap: Minimap,
+    lights: LightMap,
+
+    achievements: Achievements,
+    stats: Stats,
+    tutorial: Tutorial,
+    cheats: Cheats,
+
+    settings: SettingsMenu,
+    menu: MainMenu,
+    slots: SaveSlots,
+    pause: PauseMenu,
+    game_over: GameOverScreen,
+    credits: Credits,
+    gallery: Gallery,
+    photo: PhotoMode,
+    dev_tools: DevTools,
+    editor: Editor,
+    hot_reload: HotReload,
+}
+
+impl Game {
+    /// Sets the game up in the window `engine` was built with, with the
+    /// player's settings, and loads the title screen.
+    pub fn new(
+        mut engine: Engine,
+        mut rl: RaylibHandle,
+        thread: RaylibThread,
+        config: Config,
+    ) -> Game {
+        engine
+            .add_plugin(GamePlugins)
+            .add_plugin(PrefabPlugin)
+            .add_plugin(CampaignPlugin)
+            .add_plugin(UiPlugin);
+        let audio = RaylibAudio::init_audio_device();
+        // tracks that fail to load leave their cue silent
+        let mut music = MusicPlayer::new();
+        music.load(&thread, MusicCue::Menu, None, MENU_MUSIC).ok();
+        music.load(&thread, MusicCue::Gameplay, None, MUSIC).ok();
+        music
+            .load(&thread, MusicCue::Boss, Some(BOSS_MUSIC_INTRO), BOSS_MUSIC)
+            .ok();
+        let canvas = Canvas::new(&mut rl, &thread, WIDTH as u32, HEIGHT as u32)
+            .expect("could not create the canvas");
+        let minimap = Minimap::new(&mut rl, &thread);
+        let lights = LightMap::new(&mut rl, &thread, WIDTH as u32, HEIGHT as u32)
+            .expect("could not create the light map");
+
+        let mut world = World::new();
+        world.volume = config.volume();
+        world.bindings = config.bindings.clone();
+        world.juice.intensity = config.screen_shake;
+        world.palette.mode = config.color_mode;
+        world.palette.high_contrast_bullets = config.high_contrast_bullets;
+        world.difficulty = config.difficulty;
+        world.screen.set_mode(&mut rl, config.window_mode);
+        world.crosshair = Crosshair::new(config.crosshair, config.crosshair_scale);
+        let cheats = Cheats::new(cfg!(debug_assertions) || config.cheats);
+        world.config = config;
+        world.reseed();
+        let mut menu = MainMenu::new();
+        menu.logo = world
+            .assets
+            .load_texture(&mut rl, &thread, "assets/textures/logo.png")
+            .ok();
+        let heat_sounds = HeatSounds::load(&mut world.assets);
+        world.crosshair.texture = world
+            .assets
+            .load_texture(&mut rl, &thread, "assets/textures/crosshair.png")
+            .ok();
+        rl.hide_cursor();
+        // Escape pauses instead of closing the window
+        rl.set_exit_key(None);
+        engine.startup(&mut world, &mut rl, &thread);
+
+        let mut scenes = sector::scenes();
+        scenes.load("title");
+
+        Game {
+            engine,
+            rl,
+            thread,
+            world,
+            scenes,
+            audio,
+            music,
+            beat_tracker: BeatTracker::load(MUSIC).ok(),
+            heat_sounds,
+            canvas,
+            minimap,
+            lights,
+            achievements: Achievements::load("achievements.txt"),
+            stats: Stats::load("stats.txt"),
+            tutorial: Tutorial::load("tutorial.txt"),
+            cheats,
+            settings: SettingsMenu::new(),
+            menu,
+            slots: SaveSlots::load("save"),
+            pause: PauseMenu::new(),
+            game_over: GameOverScreen::new("highscores.txt"),
+            credits: Credits::load("assets/credits.txt"),
+            gallery: Gallery::new(),
+            photo: PhotoMode::new(),
+            dev_tools: DevTools::new(),
+            editor: Editor::new(EDITOR_LEVEL),
+            hot_reload: HotReload::new("assets"),
+        }
+    }
+
+    /// Runs one frame, false once the game should close. Hand it to
+    /// `engine::run_loop`.
+    pub fn frame(&mut self) -> bool {
+        if self.rl.window_should_close() || self.menu.quit {
+            self.achievements.save();
+            return false;
+        }
+        let dt = engine::frame_time(&self.rl);
+        self.music_system(dt);
+        let world = &mut self.world;
+        world.render_stats.frame(dt);
+        // the world runs on game time, which stops outside of the simulated
+        // states or between frame steps, and slows down for hitstop, slow
+        // motion and the debug speed. Menus, transitions and the cursor stay on real time.
+        let hitstop_dt = match world.time.simulation_dt(dt) {
+            Some(sim_dt) if world.state.current().simulates() => world.juice.update(sim_dt),
+            _ => 0.0,
+        };
+        world.time.advance(dt, hitstop_dt);
+
+        let stage_start = Instant::now();
+        self.world.profiler.begin_stage("input");
+        self.input();
+        self.world
+            .render_stats
+            .stage("input", stage_start.elapsed());
+
+        let stage_start = Instant::now();
+        self.world.profiler.begin_stage("update");
+        self.update(dt);
+        self.world
+            .render_stats
+            .stage("update", stage_start.elapsed());
+
+        let stage_start = Instant::now();
+        self.world.profiler.begin_stage("draw");
+        self.draw();
+        // shown next frame, and without the wait for the buffer swap
+        self.world.render_stats.stage("draw", stage_start.elapsed());
+        self.world.profiler.end_frame();
+        self.world.gizmos.clear();
+        crash::snapshot(&self.world, self.scenes.current());
+        true
+    }
+
+    /// Picks the track for what's going on and keeps it and the beat
+    /// going. Music keeps real time, hitstop or not.
+    fn music_system(&mut self, dt: f32) {
+        let world = &mut self.world;
+        let boss_alive = world
+            .health_components
+            .iter()
+            .any(|h| matches!(h.1.bar_style, UIBarStyle::BOSS));
+        let cue = if world.state.current().is_title() {
+            MusicCue::Menu
+        } else if boss_alive {
+            MusicCue::Boss
+        } else {
+            MusicCue::Gameplay
+        };
+        self.music.volume = world.volume.music;
+        self.music.play(&mut self.audio, cue);
+        self.music.update(&mut self.audio, dt);
+        if let Some(tracker) = self.beat_tracker.as_mut() {
+            let time = self.music.position(&self.audio, MusicCue::Gameplay);
+            tracker.update(&mut world.beat, time, dt);
+        }
+    }
+
+    fn input(&mut self) {
+        let world = &mut self.world;
+        let rl = &mut self.rl;
+        self.dev_tools.input_system(world, rl);
+        ScreenSize::update_system(world, rl);
+        UiElement::layout_system(world);
+        if !world.capture.keyboard {
+            ScreenSize::input_system(world, rl);
+            DebugDraw::input_system(world, rl);
+            RenderStats::input_system(world, rl);
+            Time::input_system(world, rl);
+            StateMachine::input_system(world, rl);
+            self.photo.input_system(world, rl);
+            self.editor
+                .input_system(world, rl, &mut self.dev_tools.inspector);
+            self.credits.input_system(world, rl);
+            self.gallery.input_system(world, rl);
+            self.cheats.input_system(world, rl);
+            if world.state.is(GameState::Playing) {
+                profile!(world, "Player", Player::input_system(world, rl));
+                profile!(world, "Station", Station::input_system(world, rl));
+            }
+            self.minimap.input_system(rl);
+            profile!(world, "Widget", Widget::input_system(world, rl));
+            Focus::input_system(world, rl);
+            self.settings.input_system(world, rl);
+            self.game_over.input_system(rl);
+        }
+        Crosshair::input_system(world, rl);
+    }
+
+    fn update(&mut self, dt: f32) {
+        let world = &mut self.world;
+        let game_dt = world.time.delta();
+        // the world only moves while simulated, menus and overlays keep going
+        let simulating = world.state.current().simulates() && !world.time.frozen();
+        if simulating {
+            self.engine.update(world);
+            profile!(world, "HeatSounds", self.heat_sounds.update_system(world));
+        }
+        // progress only counts in a real game, not in the attract mode
+        if simulating && world.state.is(GameState::Playing) {
+            profile!(
+                world,
+                "Achievements",
+                self.achievements.update_system(world, game_dt)
+            );
+            profile!(world, "Stats", self.stats.update_system(world, game_dt));
+        }
+        if world.state.is(GameState::Playing) {
+            self.tutorial.update_system(world);
+            StateMachine::update_system(world);
+        }
+        Crosshair::update_system(world, dt);
+        self.hot_reload
+            .update_system(world, &mut self.rl, &self.thread, dt);
+        self.cheats.update_system(dt);
+        self.credits.update_system(world, dt);
+        self.photo.update_system(world, dt);
+        self.editor.update_system(world, dt);
+        Transition::update_system(world, dt);
+        self.settings.update_system(world, &mut self.rl);
+        // menus wait for the scene they belong to
+        if !self.scenes.loading() {
+            profile!(
+                world,
+                "MainMenu",
+                self.menu
+                    .update_system(world, &mut self.settings, &mut self.slots)
+            );
+            self.slots.update_system(world, &self.stats, dt);
+            self.pause.update_system(world, &mut self.settings);
+            self.game_over.update_system(world, &self.stats, dt);
+            self.gallery.update_system(world);
+        }
+        profile!(world, "Audio", audio::play_system(world, &mut self.audio));
+
+        // let the boss going down be heard over the music
+        let boss_died = world.death_events.iter().any(|e| {
+            world
+                .health_components
+                .iter()
+                .any(|h| h.0 == e.entity && matches!(h.1.bar_style, UIBarStyle::BOSS))
+        });
+        if boss_died {
+            self.music.duck(0.25, 2.0);
+        }
+
+        world.end_frame();
+        self.scene_system(dt);
+        StateMachine::apply_system(&mut self.world);
+    }
+
+    /// Loads the scene a menu or the campaign asks for, and sets a game up
+    /// once its scene is in.
+    fn scene_system(&mut self, dt: f32) {
+        let world = &mut self.world;
+        let scenes = &mut self.scenes;
+        let new_game = self
+            .slots
+            .new_game
+            .take()
+            .or(self.pause.new_game.take())
+            .or(self.game_over.new_game.take());
+        match new_game {
+            Some(GameState::Menu) => scenes.load("title"),
+            Some(_) => scenes.load("sector"),
+            None => (),
+        }
+        // on to the next level, or back to the title after the last
+        if world.campaign.take_advance() {
+            match world.campaign.current {
+                0 => scenes.load("title"),
+                _ => scenes.load("sector"),
+            }
+        }
+        let entered = profile!(
+            world,
+            "Scenes",
+            scenes.update_system(world, &mut self.rl, &self.thread, dt)
+        );
+        if let Some(scene) = entered {
+            self.stats.start_run();
+            if scene == "sector" {
+                self.slots.restore(world);
+                let track = world.campaign.level().and_then(|l| l.music.clone());
+                if let Some(track) = track {
+                    match self
+                        .music
+                        .load(&self.thread, MusicCue::Gameplay, None, &track)
+                    {
+                        Ok(()) => self.beat_tracker = BeatTracker::load(&track).ok(),
+                        Err(e) => warn!("campaign", "could not load level music", error = e),
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self) {
+        let world = &mut self.world;
+        let mut d = self.rl.begin_drawing(&self.thread);
+        profile!(
+            world,
+            "Minimap",
+            self.minimap.render(world, &mut d, &self.thread)
+        );
+        profile!(world, "LightMap", self.lights.render(world, &mut d));
+
+        self.canvas.begin(&mut d);
+        render::draw_world(world, &mut d, &self.lights);
+
+        // screen space overlays, always on top of the world
+        // photos get the world alone
+        if !world.state.is(GameState::Photo) {
+            self.minimap.draw_system(world, &mut d);
+            self.menu.draw_system(world, &mut d);
+            self.achievements.draw_system(world, &mut d);
+            self.stats.draw_system(world, &mut d);
+            Campaign::draw_system(world, &mut d);
+            Cutscene::draw_system(world, &mut d);
+            self.game_over.draw_system(world, &mut d);
+            self.credits.draw_system(world, &mut d);
+            self.gallery
+                .draw_system(world, &mut d, &self.slots, &self.stats, &self.achievements);
+            d.draw_fps(10, 10);
+            RenderStats::draw_system(world, &mut d);
+            Profiler::draw_system(world, &mut d);
+            Time::draw_system(world, &mut d);
+            self.cheats.draw_system(world, &mut d);
+            Crosshair::draw_system(world, &mut d);
+        }
+        self.photo.draw_system(world, &mut d);
+        self.editor.draw_system(world, &mut d);
+        self.dev_tools.draw_system(world, &mut d);
+        Transition::draw_system(world, &mut d);
+        self.scenes.draw_system(world, &mut d);
+        profile!(
+            world,
+            "Canvas",
+            self.canvas
+                .present(&mut d, &world.screen, world.transition.pixelation())
+        );
+        self.photo.capture_system(&mut d);
+    }
+}
@@ -0,0 +1,237 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::PathBuf;
+
+use raylib::prelude::*;
+
+use crate::input;
+use crate::renderer::Renderer;
+use crate::state::GameState;
+use crate::stats::Stats;
+use crate::t;
+use crate::ui::UiElement;
+use crate::warn;
+use crate::widget::{UiEventKind, Widget};
+use crate::{Anchor, EntityID, World};
+
+/// Entries kept on the high score table.
+const MAX_SCORES: usize = 8;
+const MAX_NAME_LENGTH: usize = 12;
+
+/// Best scores, kept on disk as one `<score> <name>` line each, best first.
+struct HighScores {
+    path: PathBuf,
+    entries: Vec<(u32, String)>,
+}
+
+impl HighScores {
+    fn load(path: impl Into<PathBuf>) -> HighScores {
+        let path = path.into();
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+        let mut entries: Vec<(u32, String)> = contents
+            .lines()
+            .filter_map(|line| {
+                let (score, name) = line.split_once(' ')?;
+                Some((score.parse().ok()?, name.to_string()))
+            })
+            .collect();
+        entries.sort_by_key(|e| Reverse(e.0));
+        entries.truncate(MAX_SCORES);
+        HighScores { path, entries }
+    }
+
+    fn save(&self) {
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|(score, name)| format!("{} {}\n", score, name))
+            .collect();
+        if let Err(e) = fs::write(&self.path, contents) {
+            warn!(
+                "gameover",
+                "could not save high scores",
+                path = self.path.display(),
+                error = e
+            );
+        }
+    }
+
+    fn qualifies(&self, score: u32) -> bool {
+        score > 0 && (self.entries.len() < MAX_SCORES || self.entries.iter().any(|e| score > e.0))
+    }
+
+    /// Adds the score below any equal ones, returns its row.
+    fn insert(&mut self, score: u32, name: String) -> usize {
+        let row = self
+            .entries
+            .iter()
+            .position(|e| score > e.0)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(row, (score, name));
+        self.entries.truncate(MAX_SCORES);
+        row
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GameOverButton {
+    Restart,
+    Menu,
+}
+
+/// Shown in the GameOver state next to the run's stats: the score, the
+/// high score table and, once any new high score has a name, buttons to
+/// play again or go back to the title screen.
+pub struct GameOverScreen {
+    /// Set when the game should start over, like PauseMenu's.
+    pub new_game: Option<GameState>,
+    scores: HighScores,
+    /// Score of the run, Some while the screen is up.
+    score: Option<u32>,
+    /// Name being typed for a score that made the table.
+    name: Option<String>,
+    /// Row the run's score went into.
+    placed: Option<usize>,
+    buttons: Vec<(EntityID, GameOverButton)>,
+    blink: f32,
+}
+
+impl GameOverScreen {
+    pub fn new(path: impl Into<PathBuf>) -> GameOverScreen {
+        GameOverScreen {
+            new_game: None,
+            scores: HighScores::load(path),
+            score: None,
+            name: None,
+            placed: None,
+            buttons: Vec::new(),
+            blink: 0.0,
+        }
+    }
+
+    fn spawn_buttons(&mut self, world: &mut World) {
+        for (button, label, x) in [
+            (GameOverButton::Restart, t!("gameover.restart"), -80.0),
+            (GameOverButton::Menu, t!("gameover.menu"), 80.0),
+        ] {
+            let id = Widget::spawn(
+                world,
+                Widget::button(label),
+                UiElement::new(
+                    Anchor::BottomCenter,
+                    Vector2::new(x, -16.0),
+                    Vector2::new(140.0, 24.0),
+                ),
+            );
+            self.buttons.push((id, button));
+        }
+        world.focus.focused = self.buttons.first().map(|b| b.0);
+    }
+
+    /// Types the name for a new high score, Enter puts it on the table.
+    pub fn input_system(&mut self, rl: &RaylibHandle) {
+        let name = match self.name.as_mut() {
+            Some(n) => n,
+            None => return,
+        };
+        // the table is a plain text file, keep names to one printable line
+        for c in input::typed(rl) {
+            if c.is_ascii_graphic() && name.len() < MAX_NAME_LENGTH {
+                name.push(c);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            name.pop();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            let name = match self.name.take() {
+                Some(n) if !n.is_empty() => n,
+                _ => "Pilot".to_string(),
+            };
+            if let Some(score) = self.score {
+                self.placed = Some(self.scores.insert(score, name));
+                self.scores.save();
+            }
+        }
+    }
+
+    /// Opens with the GameOver state and closes when it's left. The buttons
+    /// wait for the name, so Enter can't restart the game by accident.
+    pub fn update_system(&mut self, world: &mut World, stats: &Stats, dt: f32) {
+        let game_over = world.state.is(GameState::GameOver);
+        if game_over && self.score.is_none() {
+            let score = stats.score();
+            self.score = Some(score);
+            self.placed = None;
+            if self.scores.qualifies(score) {
+                self.name = Some(String::new());
+            }
+        } else if !game_over && self.score.is_some() {
+            self.score = None;
+            self.name = None;
+            for (id, _) in self.buttons.drain(..) {
+                world.despawn(id);
+            }
+        }
+        if !game_over {
+            return;
+        }
+        self.blink = (self.blink + dt) % 1.0;
+        if self.name.is_none() && self.buttons.is_empty() {
+            self.spawn_buttons(world);
+        }
+
+        for event in world.ui_events.iter() {
+            if event.kind != UiEventKind::Clicked {
+                continue;
+            }
+            match self.buttons.iter().find(|b| b.0 == event.widget) {
+                Some((_, GameOverButton::Restart)) => self.new_game = Some(GameState::Playing),
+                Some((_, GameOverButton::Menu)) => self.new_game = Some(GameState::Menu),
+                None => (),
+            }
+        }
+    }
+
+    /// Draws the right half of the game over screen, Stats draws the left.
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        let score = match self.score {
+            Some(s) => s,
+            None => return,
+        };
+
+        let w = 300.0;
+        let h = 260.0;
+        let center = world.screen.bounds().center();
+        let panel = Rectangle::new(center.x + 5.0, center.y - h * 0.5 - 14.0, w, h);
+        d.draw_rect(panel, Color::BLACK.fade(0.9));
+        d.draw_rect_lines(panel, 1, Color::WHITE);
+
+        let x = panel.x as i32 + 14;
+        let mut y = panel.y as i32 + 12;
+        d.draw_text(&t!("gameover.score", score = score), x, y, 24, Color::GOLD);
+        y += 34;
+
+        if let Some(name) = self.name.as_ref() {
+            d.draw_text(&t!("gameover.new_high_score"), x, y, 14, Color::WHITE);
+            y += 18;
+            let cursor = if self.blink < 0.5 { "_" } else { "" };
+            d.draw_text(&format!("{}{}", name, cursor), x, y, 14, Color::YELLOW);
+            y += 26;
+        }
+
+        d.draw_text(&t!("gameover.high_scores"), x, y, 14, Color::WHITE);
+        for (i, (entry_score, name)) in self.scores.entries.iter().enumerate() {
+            y += 16;
+            let color = if self.placed == Some(i) {
+                Color::YELLOW
+            } else {
+                Color::LIGHTGRAY
+            };
+            d.draw_text(&format!("{}. {}", i + 1, name), x, y, 12, color);
+            let text = entry_score.to_string();
+            let text_x = panel.x as i32 + w as i32 - 14 - measure_text(&text, 12);
+            d.draw_text(&text, text_x, y, 12, color);
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use raylib::prelude::*;
+
+use crate::regen::Regen;
+use crate::render;
+use crate::renderer::Renderer;
+use crate::{Anchor, DrawSystem, World};
+
+#[allow(clippy::upper_case_acronyms)]
+pub enum UIBarStyle {
+    HIDDEN,
+    INLINE,
+    BOSS,
+}
+
+/// Gap between the pips of a segmented bar.
+const SEGMENT_GAP: f32 = 2.0;
+/// Height of the armor strip that goes with a bar, with its gap to it.
+pub const ARMOR_HEIGHT: f32 = 4.0;
+
+/// What a hit took off each layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit {
+    pub armor: u16,
+    pub hull: u16,
+}
+
+pub struct Health {
+    pub max_health: u16,
+    pub health: u16,
+    /// Taken before the hull, shown as a strip along the health bar.
+    pub armor: u16,
+    pub max_armor: u16,
+    /// Pips the bar is split in, a plain bar when 0.
+    pub segments: u16,
+    pub bar_style: UIBarStyle,
+}
+
+impl Health {
+    pub fn new(health: u16) -> Health {
+        Health {
+            max_health: health,
+            health,
+            armor: 0,
+            max_armor: 0,
+            segments: 0,
+            bar_style: UIBarStyle::INLINE,
+        }
+    }
+
+    pub fn armored(mut self, armor: u16) -> Health {
+        self.armor = armor;
+        self.max_armor = armor;
+        self
+    }
+
+    pub fn segmented(mut self, segments: u16) -> Health {
+        self.segments = segments;
+        self
+    }
+
+    /// Armor soaks up the hit first, what's left of it goes to the hull.
+    pub fn take_damage(&mut self, amount: u16) -> Hit {
+        let armor = amount.min(self.armor);
+        self.armor -= armor;
+        let hull = (amount - armor).min(self.health);
+        self.health -= hull;
+        Hit { armor, hull }
+    }
+
+    /// Adds up to `amount` to the hull, never past max_health, and returns
+    /// how much it took.
+    pub fn heal(&mut self, amount: u16) -> u16 {
+        let healed = amount.min(self.max_health.saturating_sub(self.health));
+        self.health += healed;
+        healed
+    }
+
+    /// Pips with health left in them, a sliver counts as a full one.
+    pub fn filled_segments(&self) -> u16 {
+        if self.max_health == 0 {
+            return 0;
+        }
+        (self.health as u32 * self.segments as u32).div_ceil(self.max_health as u32) as u16
+    }
+
+    /// Fills `bar` up to the health left, pip by pip for a segmented bar.
+    pub fn draw_fill(&self, d: &mut dyn Renderer, bar: Rectangle, color: Color) {
+        if self.max_health == 0 {
+            return;
+        }
+        if self.segments == 0 {
+            let ratio = self.health as f32 / self.max_health as f32;
+            d.draw_rect(
+                Rectangle::new(bar.x, bar.y, bar.width * ratio, bar.height),
+                color,
+            );
+            return;
+        }
+        let count = self.segments as f32;
+        let width = (bar.width - SEGMENT_GAP * (count + 1.0)) / count;
+        for i in 0..self.filled_segments() {
+            let x = bar.x + SEGMENT_GAP + i as f32 * (width + SEGMENT_GAP);
+            d.draw_rect(
+                Rectangle::new(
+                    x,
+                    bar.y + SEGMENT_GAP,
+                    width,
+                    bar.height - SEGMENT_GAP * 2.0,
+                ),
+                color,
+            );
+        }
+    }
+
+    /// The armor left as a strip at `y`, as wide as `bar`, leaving a pixel
+    /// of `ARMOR_HEIGHT` free towards the bar. Nothing without armor.
+    pub fn draw_armor(&self, d: &mut dyn Renderer, bar: Rectangle, y: f32) {
+        if self.max_armor == 0 {
+            return;
+        }
+        let strip = Rectangle::new(bar.x, y, bar.width, ARMOR_HEIGHT - 1.0);
+        let ratio = self.armor as f32 / self.max_armor as f32;
+        d.draw_rect(strip, Color::DARKGRAY);
+        d.draw_rect(
+            Rectangle::new(strip.x, strip.y, strip.width * ratio, strip.height),
+            Color::LIGHTGRAY,
+        );
+    }
+}
+
+/// Soft pulsing green glow around the bar of something regenerating.
+pub fn draw_regen_glow(d: &mut dyn Renderer, bar: Rectangle, time: f32) {
+    let pulse = (time * 4.0).sin() * 0.5 + 0.5;
+    for (grow, alpha) in [(4.0, 0.15), (2.0, 0.25)] {
+        let glow = Rectangle::new(
+            bar.x - grow,
+            bar.y - grow,
+            bar.width + grow * 2.0,
+            bar.height + grow * 2.0,
+        );
+        d.draw_rect(glow, Color::LIME.fade(alpha * (0.5 + pulse * 0.5)));
+    }
+}
+
+impl DrawSystem for Health {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        world
+            .base_components
+            .iter()
+            .filter(|b| render::in_view(world, b.0))
+            .filter_map(|b| {
+                world
+                    .health_components
+                    .iter()
+                    .find(|h| b.0 == h.0)
+                    .map(|h| (b.0, &b.1, &h.1))
+            })
+            .for_each(|(id, b, health)| match health.bar_style {
+                UIBarStyle::INLINE => {
+                    let w = 80.0;
+                    let h = 10.0;
+                    let mut top_center = b.bounds.calc(Anchor::TopCenter);
+                    top_center -= Vector2::new(0.0, 20.0);
+
+                    let rect = Rectangle::new(top_center.x - w * 0.5, top_center.y - h * 0.5, w, h);
+                    if Regen::of(world, id).is_some_and(Regen::regenerating) {
+                        draw_regen_glow(d, rect, world.time.elapsed());
+                    }
+                    health.draw_fill(d, rect, Color::MAROON);
+                    d.draw_rect_lines(rect, 1, Color::WHITE);
+                    // above the bar, the heat bar goes below it
+                    health.draw_armor(d, rect, rect.y - ARMOR_HEIGHT);
+                }
+                // boss bars live in the HUD
+                UIBarStyle::BOSS => (),
+                _ => (),
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn armor_takes_hits_first() {
+        let mut health = Health::new(10).armored(5);
+        assert_eq!(health.take_damage(3), Hit { armor: 3, hull: 0 });
+        assert_eq!((health.armor, health.health), (2, 10));
+
+        // what the armor can't soak up goes through to the hull
+        assert_eq!(health.take_damage(6), Hit { armor: 2, hull: 4 });
+        assert_eq!((health.armor, health.health), (0, 6));
+        assert_eq!(health.take_damage(2), Hit { armor: 0, hull: 2 });
+
+        // past the hull is overkill, not dealt
+        assert_eq!(health.take_damage(9), Hit { armor: 0, hull: 4 });
+        assert_eq!(health.health, 0);
+        assert_eq!(health.take_damage(1), Hit { armor: 0, hull: 0 });
+    }
+
+    #[test]
+    fn heals_up_to_max_health() {
+        let mut health = Health::new(10).armored(5);
+        health.take_damage(9);
+        assert_eq!(health.heal(3), 3);
+        assert_eq!(health.heal(3), 1);
+        assert_eq!(health.health, 10);
+        // armor isn't healed
+        assert_eq!(health.armor, 0);
+    }
+
+    #[test]
+    fn fills_segments_with_health_left() {
+        let mut health = Health::new(20).segmented(4);
+        assert_eq!(health.filled_segments(), 4);
+        // right at a boundary the pip is gone, a point more keeps it
+        health.take_damage(5);
+        assert_eq!(health.filled_segments(), 3);
+        health.take_damage(4);
+        assert_eq!(health.filled_segments(), 3);
+        health.take_damage(1);
+        assert_eq!(health.filled_segments(), 2);
+        health.take_damage(9);
+        assert_eq!(health.filled_segments(), 1);
+        health.take_damage(1);
+        assert_eq!(health.filled_segments(), 0);
+
+        assert_eq!(Health::new(20).filled_segments(), 0);
+        assert_eq!(Health::new(0).segmented(4).filled_segments(), 0);
+    }
+}
@@ -0,0 +1,83 @@
+use raylib::prelude::*;
+
+use crate::renderer::Renderer;
+use crate::World;
+
+/// Length of an arrow's head.
+const ARROW_HEAD: f32 = 6.0;
+
+enum Shape {
+    Line(Vector2, Vector2),
+    Circle(Vector2, f32),
+    Rect(Rectangle),
+    Arrow(Vector2, Vector2),
+    Text(Vector2, String),
+}
+
+/// Immediate mode debug shapes in world space. Any system can add some
+/// while the debug overlay (F3) is on, they're drawn over the world at the
+/// end of the frame and then forgotten. Calls do nothing while it's off.
+pub struct Gizmos {
+    pub enabled: bool,
+    shapes: Vec<(Shape, Color)>,
+}
+
+impl Gizmos {
+    pub fn new() -> Gizmos {
+        Gizmos {
+            enabled: false,
+            shapes: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, shape: Shape, color: Color) {
+        if self.enabled {
+            self.shapes.push((shape, color));
+        }
+    }
+
+    pub fn line(&mut self, from: Vector2, to: Vector2, color: Color) {
+        self.push(Shape::Line(from, to), color);
+    }
+
+    pub fn circle(&mut self, center: Vector2, radius: f32, color: Color) {
+        self.push(Shape::Circle(center, radius), color);
+    }
+
+    pub fn rect(&mut self, rect: Rectangle, color: Color) {
+        self.push(Shape::Rect(rect), color);
+    }
+
+    pub fn arrow(&mut self, from: Vector2, to: Vector2, color: Color) {
+        self.push(Shape::Arrow(from, to), color);
+    }
+
+    pub fn text(&mut self, pos: Vector2, text: impl Into<String>, color: Color) {
+        self.push(Shape::Text(pos, text.into()), color);
+    }
+
+    /// Forgets this frame's shapes, once everything is drawn.
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    /// Draws the shapes, inside the camera's 2D mode.
+    pub fn draw_system(world: &World, d: &mut dyn Renderer) {
+        for (shape, color) in world.gizmos.shapes.iter() {
+            let color = *color;
+            match shape {
+                Shape::Line(from, to) => d.draw_line(*from, *to, 1.0, color),
+                Shape::Circle(center, radius) => d.draw_circle_outline(*center, *radius, color),
+                Shape::Rect(rect) => d.draw_rect_lines(*rect, 1, color),
+                Shape::Arrow(from, to) => {
+                    d.draw_line(*from, *to, 1.0, color);
+                    let back = (*from - *to).normalized() * ARROW_HEAD;
+                    let side = Vector2::new(-back.y, back.x) * 0.5;
+                    d.draw_line(*to, *to + back + side, 1.0, color);
+                    d.draw_line(*to, *to + back - side, 1.0, color);
+                }
+                Shape::Text(pos, text) => d.draw_text(text, pos.x as i32, pos.y as i32, 10, color),
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use crate::physics::BoundingBox2D;
+use crate::{EntityID, UpdateSystem, World};
+
+/// Width and height of a grid cell in world units, a few ships across.
+pub const CELL_SIZE: f32 = 64.0;
+
+/// Entities covering more cells than this, tilemaps and big trigger zones,
+/// are kept in one list every query returns instead of in each cell.
+const MAX_CELLS: i64 = 64;
+
+/// Uniform grid over every Base2D, rebuilt once a fixed update after
+/// movement. Collision checks ask it for the entities near a box instead of
+/// testing every entity in the world. It only narrows things down: callers
+/// still test the bounds they look up, as anything spawned or moved after
+/// the rebuild is found in its old cells, or not at all, until the next one.
+pub struct SpatialGrid {
+    cells: HashMap<(i32, i32), Vec<EntityID>>,
+    large: Vec<EntityID>,
+}
+
+impl SpatialGrid {
+    pub fn new() -> SpatialGrid {
+        SpatialGrid {
+            cells: HashMap::new(),
+            large: Vec::new(),
+        }
+    }
+
+    /// Cells touched by `bounds` as inclusive column and row ranges.
+    pub fn cells_of(bounds: &BoundingBox2D) -> ((i32, i32), (i32, i32)) {
+        let cell = |v: f32| (v / CELL_SIZE).floor() as i32;
+        (
+            (cell(bounds.x1), cell(bounds.x2)),
+            (cell(bounds.y1), cell(bounds.y2)),
+        )
+    }
+
+    /// Empties the grid and puts each entity in the cells its bounds touch.
+    pub fn rebuild<'a>(&mut self, bases: impl Iterator<Item = (EntityID, &'a BoundingBox2D)>) {
+        // keep the cell vectors around, the same cells fill up again
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.large.clear();
+
+        for (id, bounds) in bases {
+            let ((x1, x2), (y1, y2)) = SpatialGrid::cells_of(bounds);
+            let count = (x2 as i64 - x1 as i64 + 1) * (y2 as i64 - y1 as i64 + 1);
+            if count > MAX_CELLS {
+                self.large.push(id);
+                continue;
+            }
+            for y in y1..=y2 {
+                for x in x1..=x2 {
+                    self.cells.entry((x, y)).or_default().push(id);
+                }
+            }
+        }
+        self.cells.retain(|_, cell| !cell.is_empty());
+    }
+
+    /// Entities that might overlap `bounds`, each once and in id order.
+    pub fn query(&self, bounds: &BoundingBox2D) -> Vec<EntityID> {
+        let ((x1, x2), (y1, y2)) = SpatialGrid::cells_of(bounds);
+        let mut found = self.large.clone();
+        if (x2 as i64 - x1 as i64 + 1) * (y2 as i64 - y1 as i64 + 1) > MAX_CELLS {
+            // a huge area touches most of what's filled anyway
+            found.extend(self.cells.values().flatten());
+        } else {
+            for y in y1..=y2 {
+                for x in x1..=x2 {
+                    if let Some(cell) = self.cells.get(&(x, y)) {
+                        found.extend(cell);
+                    }
+                }
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    /// Entities put in the cell at column `x`, row `y`.
+    pub fn cell(&self, x: i32, y: i32) -> &[EntityID] {
+        self.cells.get(&(x, y)).map_or(&[], |c| c.as_slice())
+    }
+
+    /// Entities too big to be put in cells.
+    pub fn large(&self) -> &[EntityID] {
+        &self.large
+    }
+}
+
+impl UpdateSystem for SpatialGrid {
+    fn update_system(world: &mut World, _dt: f32) {
+        let bases = world.base_components.iter().map(|b| (b.0, &b.1.bounds));
+        world.grid.rebuild(bases);
+    }
+}
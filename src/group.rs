@@ -0,0 +1,141 @@
+use crate::EntityID;
+
+/// Sorts `components` by entity id unless it already is, which takes one
+/// pass. Only components added to older entities put a storage out of
+/// order, the stable sort keeps the rest as it was.
+///
+/// ```
+/// let mut storage = vec![(3, 'c'), (1, 'a'), (2, 'b')];
+/// spacegame::group::sort(&mut storage);
+/// assert_eq!(storage, [(1, 'a'), (2, 'b'), (3, 'c')]);
+/// ```
+pub fn sort<T>(components: &mut [(EntityID, T)]) {
+    if !components.is_sorted_by_key(|c| c.0) {
+        components.sort_by_key(|c| c.0);
+    }
+}
+
+/// Index of `id`'s component in a sorted storage.
+///
+/// ```
+/// let storage = [(1, 'a'), (4, 'b'), (9, 'c')];
+/// assert_eq!(spacegame::group::find(&storage, 4), Some(1));
+/// assert_eq!(spacegame::group::find(&storage, 5), None);
+/// ```
+pub fn find<T>(components: &[(EntityID, T)], id: EntityID) -> Option<usize> {
+    components.binary_search_by_key(&id, |c| c.0).ok()
+}
+
+/// The entities in both sorted storages with both their components, in id
+/// order.
+///
+/// ```
+/// let names = [(1, "drone"), (2, "rock"), (5, "station")];
+/// let speeds = [(1, 30.0), (3, 12.0), (5, 0.0)];
+/// let joined: Vec<_> = spacegame::group::join(&names, &speeds).collect();
+/// assert_eq!(joined, [(1, &"drone", &30.0), (5, &"station", &0.0)]);
+/// ```
+pub fn join<'a, A, B>(
+    a: &'a [(EntityID, A)],
+    b: &'a [(EntityID, B)],
+) -> impl Iterator<Item = (EntityID, &'a A, &'a B)> {
+    let (mut i, mut j) = (0, 0);
+    std::iter::from_fn(move || {
+        while i < a.len() && j < b.len() {
+            let (ia, ib) = (a[i].0, b[j].0);
+            if ia < ib {
+                i += 1;
+            } else if ib < ia {
+                j += 1;
+            } else {
+                let pair = (ia, &a[i].1, &b[j].1);
+                i += 1;
+                j += 1;
+                return Some(pair);
+            }
+        }
+        None
+    })
+}
+
+/// `join` for changing the components, `f` is called for every entity in
+/// both sorted storages.
+///
+/// ```
+/// let mut positions = [(1, 0.0), (2, 0.0), (3, 0.0)];
+/// let mut speeds = [(2, 4.0), (3, -1.0)];
+/// spacegame::group::join_mut(&mut positions, &mut speeds, |_, pos, speed| *pos += *speed);
+/// assert_eq!(positions, [(1, 0.0), (2, 4.0), (3, -1.0)]);
+/// ```
+pub fn join_mut<A, B>(
+    a: &mut [(EntityID, A)],
+    b: &mut [(EntityID, B)],
+    mut f: impl FnMut(EntityID, &mut A, &mut B),
+) {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let (ia, ib) = (a[i].0, b[j].0);
+        if ia < ib {
+            i += 1;
+        } else if ib < ia {
+            j += 1;
+        } else {
+            f(ia, &mut a[i].1, &mut b[j].1);
+            i += 1;
+            j += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_keeps_equal_ids_in_order() {
+        let mut storage = vec![(2, 'x'), (1, 'a'), (2, 'y'), (1, 'b')];
+        sort(&mut storage);
+        assert_eq!(storage, [(1, 'a'), (1, 'b'), (2, 'x'), (2, 'y')]);
+    }
+
+    #[test]
+    fn find_in_empty_and_at_the_ends() {
+        let empty: [(EntityID, ()); 0] = [];
+        assert_eq!(find(&empty, 1), None);
+        let storage = [(2, ()), (7, ())];
+        assert_eq!(find(&storage, 2), Some(0));
+        assert_eq!(find(&storage, 7), Some(1));
+        assert_eq!(find(&storage, 1), None);
+        assert_eq!(find(&storage, 8), None);
+    }
+
+    #[test]
+    fn join_matches_a_scan() {
+        // every mix of five ids in each storage
+        for mask_a in 0..32u32 {
+            for mask_b in 0..32u32 {
+                let a: Vec<(EntityID, u32)> = (0..5)
+                    .filter(|i| mask_a & (1 << i) != 0)
+                    .map(|i| (i as EntityID, i))
+                    .collect();
+                let b: Vec<(EntityID, u32)> = (0..5)
+                    .filter(|i| mask_b & (1 << i) != 0)
+                    .map(|i| (i as EntityID, i * 10))
+                    .collect();
+                let scanned: Vec<(EntityID, u32, u32)> = a
+                    .iter()
+                    .filter_map(|x| b.iter().find(|y| y.0 == x.0).map(|y| (x.0, x.1, y.1)))
+                    .collect();
+
+                let joined: Vec<(EntityID, u32, u32)> =
+                    join(&a, &b).map(|(id, x, y)| (id, *x, *y)).collect();
+                assert_eq!(joined, scanned, "{:b} {:b}", mask_a, mask_b);
+
+                let mut visited = Vec::new();
+                let (mut a, mut b) = (a.clone(), b.clone());
+                join_mut(&mut a, &mut b, |id, x, y| visited.push((id, *x, *y)));
+                assert_eq!(visited, scanned, "{:b} {:b}", mask_a, mask_b);
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::damage::DamageEvent;
+use crate::render;
+use crate::renderer::Renderer;
+use crate::trigger::TriggerEventKind;
+use crate::{DrawSystem, EntityID, UpdateSystem, World};
+
+/// Area effect applied to everything inside the entity's trigger volume.
+/// Nebulae slow ships down and blind sensors, radiation fields burn hull.
+pub struct Hazard {
+    damage_per_second: f32,
+    jams_sensors: bool,
+    extra_drag: f32,
+    /// Occupants and their not yet applied fractional damage.
+    occupants: Vec<(EntityID, f32)>,
+}
+
+impl Hazard {
+    pub fn nebula() -> Hazard {
+        Hazard {
+            damage_per_second: 0.0,
+            jams_sensors: true,
+            extra_drag: 1.5,
+            occupants: Vec::new(),
+        }
+    }
+
+    pub fn radiation(damage_per_second: f32) -> Hazard {
+        Hazard {
+            damage_per_second,
+            jams_sensors: false,
+            extra_drag: 0.0,
+            occupants: Vec::new(),
+        }
+    }
+
+    /// Whether `entity` currently sits inside a sensor jamming hazard.
+    pub fn sensors_jammed(world: &World, entity: EntityID) -> bool {
+        world
+            .hazard_components
+            .iter()
+            .any(|h| h.1.jams_sensors && h.1.occupants.iter().any(|o| o.0 == entity))
+    }
+}
+
+impl UpdateSystem for Hazard {
+    fn update_system(world: &mut World, dt: f32) {
+        for event in world.trigger_events.iter() {
+            let hazard = match world
+                .hazard_components
+                .iter_mut()
+                .find(|h| h.0 == event.trigger)
+            {
+                Some(h) => &mut h.1,
+                None => continue,
+            };
+
+            let drag_change = match event.kind {
+                TriggerEventKind::Enter => {
+                    hazard.occupants.push((event.other, 0.0));
+                    hazard.extra_drag
+                }
+                TriggerEventKind::Exit => {
+                    hazard.occupants.retain(|o| o.0 != event.other);
+                    -hazard.extra_drag
+                }
+            };
+
+            if let Some(v) = world
+                .velocity_components
+                .iter_mut()
+                .find(|v| v.0 == event.other)
+            {
+                v.1.drag += drag_change;
+            }
+        }
+
+        for (id, hazard) in world.hazard_components.iter_mut() {
+            for (occupant, pending) in hazard.occupants.iter_mut() {
+                *pending += hazard.damage_per_second * dt;
+                if *pending < 1.0 {
+                    continue;
+                }
+
+                let damage = pending.floor();
+                *pending -= damage;
+                world.damage_events.push(DamageEvent {
+                    target: *occupant,
+                    source: Some(*id),
+                    amount: damage as u16,
+                    crit: false,
+                });
+            }
+        }
+    }
+}
+
+impl DrawSystem for Hazard {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        world
+            .base_components
+            .iter()
+            .filter(|b| render::in_view(world, b.0))
+            .filter(|b| world.hazard_components.iter().any(|h| h.0 == b.0))
+            .for_each(|b| d.draw_rect(b.1.bounds.into(), world.palette.remap(b.1.tint).fade(0.25)));
+    }
+}
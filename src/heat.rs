@@ -0,0 +1,240 @@
+use raylib::prelude::*;
+
+use crate::assets::{Assets, Handle};
+use crate::audio::{PlaySound, SoundClip};
+use crate::particles::EmitterConfig;
+use crate::render;
+use crate::renderer::Renderer;
+use crate::{Anchor, DrawSystem, UpdateSystem, World};
+
+/// Per-weapon heat. Every shot adds heat, which bleeds off over time. Going
+/// over the limit jams the weapon for `jam_duration` seconds.
+pub struct Heat {
+    heat: f32,
+    max_heat: f32,
+    heat_per_shot: f32,
+    cooling_rate: f32,
+    jam_duration: f32,
+    jam_timer: f32,
+    warned: bool,
+}
+
+pub enum HeatEvent {
+    /// Heat crossed the warning threshold.
+    Warning,
+    /// Weapon overheated and is now jammed.
+    Overheated,
+    /// Jam penalty is over, weapon can fire again.
+    Vented,
+}
+
+const WARNING_THRESHOLD: f32 = 0.75;
+
+impl Heat {
+    pub fn new(max_heat: f32, heat_per_shot: f32, cooling_rate: f32) -> Heat {
+        Heat {
+            heat: 0.0,
+            max_heat,
+            heat_per_shot,
+            cooling_rate,
+            jam_duration: 2.0,
+            jam_timer: 0.0,
+            warned: false,
+        }
+    }
+
+    pub fn jammed(&self) -> bool {
+        self.jam_timer > 0.0
+    }
+
+    pub fn ratio(&self) -> f32 {
+        self.heat / self.max_heat
+    }
+
+    /// Registers a shot, returns false when the weapon is jammed.
+    pub fn add_shot(&mut self) -> bool {
+        if self.jammed() {
+            return false;
+        }
+        self.heat += self.heat_per_shot;
+        true
+    }
+}
+
+impl UpdateSystem for Heat {
+    fn update_system(world: &mut World, dt: f32) {
+        for (id, heat) in world.heat_components.iter_mut() {
+            if heat.jammed() {
+                heat.jam_timer -= dt;
+                if !heat.jammed() {
+                    heat.heat = 0.0;
+                    heat.warned = false;
+                    world.heat_events.push((*id, HeatEvent::Vented));
+                }
+                continue;
+            }
+
+            if heat.heat >= heat.max_heat {
+                heat.heat = heat.max_heat;
+                heat.jam_timer = heat.jam_duration;
+                world.heat_events.push((*id, HeatEvent::Overheated));
+                if let Some(b) = world.base_components.iter().find(|b| b.0 == *id) {
+                    world
+                        .particles
+                        .burst(&EmitterConfig::sparks(), b.1.bounds.center());
+                }
+                continue;
+            }
+
+            if !heat.warned && heat.ratio() >= WARNING_THRESHOLD {
+                heat.warned = true;
+                world.heat_events.push((*id, HeatEvent::Warning));
+            } else if heat.warned && heat.ratio() < WARNING_THRESHOLD {
+                heat.warned = false;
+            }
+
+            heat.heat = (heat.heat - heat.cooling_rate * dt).max(0.0);
+        }
+    }
+}
+
+impl DrawSystem for Heat {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        world
+            .base_components
+            .iter()
+            .filter(|b| render::in_view(world, b.0))
+            .filter_map(|b| {
+                world
+                    .heat_components
+                    .iter()
+                    .find(|h| b.0 == h.0)
+                    .map(|h| (&b.1, &h.1))
+            })
+            .for_each(|(b, heat)| {
+                // sits right below the inline health bar
+                let w = 80.0;
+                let h = 4.0;
+                let mut top_center = b.bounds.calc(Anchor::TopCenter);
+                top_center -= Vector2::new(0.0, 10.0);
+
+                let rect = Rectangle::new(top_center.x - w * 0.5, top_center.y - h * 0.5, w, h);
+                let color = if heat.jammed() {
+                    Color::RED
+                } else if heat.ratio() >= WARNING_THRESHOLD {
+                    Color::ORANGE
+                } else {
+                    Color::GOLD
+                };
+
+                let fill = Rectangle::new(rect.x, rect.y, w * heat.ratio(), h);
+                d.draw_rect(fill, color);
+                d.draw_rect_lines(rect, 1, Color::GRAY);
+            });
+    }
+}
+
+/// Warning and vent cues. Missing files just leave the cue silent.
+pub struct HeatSounds {
+    warning: Option<Handle<SoundClip>>,
+    vent: Option<Handle<SoundClip>>,
+}
+
+impl HeatSounds {
+    pub fn load(assets: &mut Assets) -> HeatSounds {
+        HeatSounds {
+            warning: assets.load_sound("assets/sfx/heat_warning.wav").ok(),
+            vent: assets.load_sound("assets/sfx/heat_vent.wav").ok(),
+        }
+    }
+
+    /// Turns this frame's heat events into sound events.
+    pub fn update_system(&self, world: &mut World) {
+        for (id, event) in world.heat_events.iter() {
+            let sound = match event {
+                HeatEvent::Warning | HeatEvent::Overheated => self.warning,
+                HeatEvent::Vented => self.vent,
+            };
+            let pos = world
+                .base_components
+                .iter()
+                .find(|b| b.0 == *id)
+                .map(|b| b.1.bounds.center());
+            if let Some(sound) = sound {
+                let mut event = PlaySound::new(sound);
+                event.position = pos;
+                world.sound_events.push(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(world: &World) -> Vec<&'static str> {
+        world
+            .heat_events
+            .iter()
+            .map(|e| match e.1 {
+                HeatEvent::Warning => "warning",
+                HeatEvent::Overheated => "overheated",
+                HeatEvent::Vented => "vented",
+            })
+            .collect()
+    }
+
+    fn step(world: &mut World, dt: f32) -> Vec<&'static str> {
+        world.heat_events.clear();
+        Heat::update_system(world, dt);
+        events(world)
+    }
+
+    fn heat(world: &mut World) -> &mut Heat {
+        &mut world.heat_components[0].1
+    }
+
+    #[test]
+    fn jams_when_overheated_and_vents_after() {
+        let mut world = World::new();
+        world.heat_components.push((1, Heat::new(10.0, 3.0, 2.0)));
+
+        assert!(heat(&mut world).add_shot());
+        assert!(heat(&mut world).add_shot());
+        assert_eq!(step(&mut world, 0.0), Vec::<&str>::new());
+        assert!(heat(&mut world).add_shot());
+        assert_eq!(step(&mut world, 0.0), ["warning"]);
+        // warned once while it stays hot
+        assert_eq!(step(&mut world, 0.0), Vec::<&str>::new());
+
+        assert!(heat(&mut world).add_shot());
+        assert_eq!(step(&mut world, 0.0), ["overheated"]);
+        assert!(heat(&mut world).jammed());
+        assert_eq!(heat(&mut world).ratio(), 1.0);
+        assert!(!heat(&mut world).add_shot());
+
+        // no cooling while jammed, just the jam running out
+        assert_eq!(step(&mut world, 1.5), Vec::<&str>::new());
+        assert!(heat(&mut world).jammed());
+        assert_eq!(heat(&mut world).ratio(), 1.0);
+        assert_eq!(step(&mut world, 0.5), ["vented"]);
+        assert!(!heat(&mut world).jammed());
+        assert_eq!(heat(&mut world).ratio(), 0.0);
+        assert!(heat(&mut world).add_shot());
+    }
+
+    #[test]
+    fn cools_down_over_time() {
+        let mut world = World::new();
+        world.heat_components.push((1, Heat::new(10.0, 8.0, 4.0)));
+        world.heat_components[0].1.add_shot();
+        assert_eq!(step(&mut world, 1.0), ["warning"]);
+        assert_eq!(world.heat_components[0].1.ratio(), 0.4);
+        // cooled under the threshold, so crossing it again warns again
+        assert_eq!(step(&mut world, 1.0), Vec::<&str>::new());
+        world.heat_components[0].1.add_shot();
+        assert_eq!(step(&mut world, 10.0), ["warning"]);
+        assert_eq!(world.heat_components[0].1.ratio(), 0.0);
+    }
+}
@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use raylib::prelude::*;
+
+use crate::i18n;
+use crate::prefab::EntityDef;
+use crate::script::Program;
+use crate::{info, warn, World};
+
+/// Seconds between looks at the assets folder.
+const POLL_INTERVAL: f32 = 0.5;
+
+/// A file behind a loaded asset changed and was loaded again. Handles stay
+/// the same, systems that copied something out of the asset (a texture's
+/// size, a prefab's tuning) can refresh it.
+pub struct AssetReloaded {
+    pub path: String,
+}
+
+/// Watches a folder while the game runs and reloads what changed in place:
+/// textures, sounds, entity definitions, scripts and languages. Only on in
+/// debug builds, the release game never looks.
+pub struct HotReload {
+    dir: PathBuf,
+    enabled: bool,
+    timer: f32,
+    /// When each file was last seen changed.
+    stamps: Vec<(PathBuf, SystemTime)>,
+}
+
+impl HotReload {
+    pub fn new(dir: &str) -> HotReload {
+        let mut hot_reload = HotReload {
+            dir: PathBuf::from(dir),
+            enabled: cfg!(debug_assertions),
+            timer: 0.0,
+            stamps: Vec::new(),
+        };
+        if hot_reload.enabled {
+            hot_reload.stamps = scan(&hot_reload.dir);
+        }
+        hot_reload
+    }
+
+    pub fn update_system(
+        &mut self,
+        world: &mut World,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        dt: f32,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        self.timer -= dt;
+        if self.timer > 0.0 {
+            return;
+        }
+        self.timer = POLL_INTERVAL;
+
+        let stamps = scan(&self.dir);
+        for (path, modified) in stamps.iter() {
+            let changed = self
+                .stamps
+                .iter()
+                .find(|s| s.0 == *path)
+                .is_some_and(|s| s.1 != *modified);
+            if changed {
+                HotReload::reload(world, rl, thread, path);
+            }
+        }
+        self.stamps = stamps;
+    }
+
+    fn reload(world: &mut World, rl: &mut RaylibHandle, thread: &RaylibThread, path: &Path) {
+        // assets are keyed by the path they were loaded with
+        let key = path.to_string_lossy().replace('\\', "/");
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let result = match extension {
+            "png" | "jpg" | "bmp" | "gif" => world.assets.reload_texture(rl, thread, &key),
+            "wav" | "ogg" | "mp3" | "flac" => world.assets.reload_sound(&key),
+            "json" if path.parent().is_some_and(|p| p.ends_with("entities")) => {
+                EntityDef::load(path).map(|def| {
+                    world.prefabs.register_def(def);
+                    true
+                })
+            }
+            "script" => Program::load(path).map(|program| {
+                world.scripts.add(program);
+                true
+            }),
+            "toml" if path.parent().is_some_and(|p| p.ends_with("lang")) => {
+                let code = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                fs::read_to_string(path)
+                    .map(|contents| {
+                        i18n::load(code, &contents);
+                        true
+                    })
+                    .map_err(|e| e.to_string())
+            }
+            _ => Ok(false),
+        };
+        match result {
+            Ok(true) => {
+                info!("assets", "asset reloaded", path = key);
+                world.reload_events.push(AssetReloaded { path: key });
+            }
+            // not loaded, nothing to refresh
+            Ok(false) => (),
+            // the old version stays until the file is fixed
+            Err(e) => warn!("assets", "could not reload asset", path = key, error = e),
+        }
+    }
+}
+
+/// Every file under `dir` with the time it was last modified.
+fn scan(dir: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let mut stamps = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            match entry.metadata() {
+                Ok(meta) if meta.is_dir() => dirs.push(path),
+                Ok(meta) => {
+                    if let Ok(modified) = meta.modified() {
+                        stamps.push((path, modified));
+                    }
+                }
+                Err(_) => (),
+            }
+        }
+    }
+    stamps
+}
@@ -0,0 +1,89 @@
+use raylib::prelude::*;
+
+use crate::font::{self, TextStyle};
+use crate::gameplay::{self, ARMOR_HEIGHT};
+use crate::regen::Regen;
+use crate::renderer::Renderer;
+use crate::t;
+use crate::{Anchor, DrawSystem, UIBarStyle, World};
+
+/// What a HUD element shows. Drawn inside the entity's UiElement bounds.
+pub enum HudItem {
+    /// Player credits, right aligned.
+    Credits,
+    /// Player bombs left.
+    Bombs,
+    /// Health of the first entity with a boss style health bar.
+    BossBar,
+}
+
+impl DrawSystem for HudItem {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        let player = world.player_components.first();
+
+        for (id, item) in world.hud_components.iter() {
+            let bounds = match world.ui_components.iter().find(|u| u.0 == *id) {
+                Some(u) => u.1.bounds,
+                None => continue,
+            };
+            let size = bounds.height();
+
+            match item {
+                HudItem::Credits => {
+                    if let Some((_, p)) = player {
+                        let text = t!("hud.credits", credits = p.credits);
+                        let style = TextStyle::new(size, Color::GOLD).aligned(Anchor::TopRight);
+                        font::draw(
+                            &world.assets,
+                            d,
+                            &text,
+                            bounds.calc(Anchor::TopRight),
+                            &style,
+                        );
+                    }
+                }
+                HudItem::Bombs => {
+                    let bombs =
+                        player.and_then(|p| world.bomb_components.iter().find(|b| b.0 == p.0));
+                    if let Some((_, b)) = bombs {
+                        let text = t!("hud.bombs", count = b.count, capacity = b.capacity);
+                        let style = TextStyle::new(size, Color::WHITE);
+                        font::draw(
+                            &world.assets,
+                            d,
+                            &text,
+                            bounds.calc(Anchor::TopLeft),
+                            &style,
+                        );
+                    }
+                }
+                HudItem::BossBar => {
+                    let boss = world
+                        .health_components
+                        .iter()
+                        .find(|h| matches!(h.1.bar_style, UIBarStyle::BOSS));
+                    if let Some((boss, h)) = boss {
+                        if Regen::of(world, *boss).is_some_and(Regen::regenerating) {
+                            gameplay::draw_regen_glow(d, bounds.into(), world.time.elapsed());
+                        }
+                        h.draw_fill(d, bounds.into(), Color::MAROON);
+                        d.draw_rect_lines(bounds.into(), 1, Color::WHITE);
+                        h.draw_armor(d, bounds.into(), bounds.y2 + 1.0);
+                        let armor = if h.max_armor > 0 { ARMOR_HEIGHT } else { 0.0 };
+
+                        let name = world
+                            .base_components
+                            .iter()
+                            .find(|b| b.0 == *boss)
+                            .map(|b| b.1.name.as_str())
+                            .unwrap_or_default();
+                        let style = TextStyle::new(10.0, Color::WHITE).aligned(Anchor::TopCenter);
+                        let pos =
+                            bounds.calc(Anchor::BottomCenter) + Vector2::new(0.0, 4.0 + armor);
+                        font::draw(&world.assets, d, name, pos, &style);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use std::sync::Mutex;
+
+use crate::pack;
+use crate::{info, warn};
+
+/// The game's own strings, in English. Language files only need the keys
+/// they translate, anything missing falls back to these.
+const ENGLISH: &str = r#"
+[language]
+name = "English"
+
+[menu]
+start = "Start"
+gallery = "Gallery"
+settings = "Settings"
+credits = "Credits"
+quit = "Quit"
+back = "Back"
+
+[pause]
+resume = "Resume"
+restart = "Restart"
+quit = "Quit to Menu"
+
+[settings]
+video = "Video"
+audio = "Audio"
+controls = "Controls"
+accessibility = "Access"
+window = "Window: {mode}"
+windowed = "Windowed"
+borderless = "Borderless"
+fullscreen = "Fullscreen"
+resolution = "Resolution: {width}x{height}"
+vsync = "VSync"
+frame_cap = "Frame cap: {fps}"
+frame_cap_off = "Frame cap: off"
+language = "Language: {name}"
+master = "Master"
+music = "Music"
+effects = "Effects"
+shake = "Shake"
+colors = "Colors: {mode}"
+colors_normal = "Normal"
+deuteranopia = "Deuteranopia"
+protanopia = "Protanopia"
+high_contrast = "High contrast bullets"
+key = "{action}: {key}"
+press_key = "{action}: press a key"
+
+[action]
+up = "Up"
+down = "Down"
+left = "Left"
+right = "Right"
+fire = "Fire"
+
+[slots]
+empty = "Slot {slot}: empty"
+newer = "Slot {slot}: newer version"
+progress = "Slot {slot}: best {score}, {hours}h{minutes}m"
+difficulty = "Difficulty: {difficulty}"
+easy = "Easy"
+normal = "Normal"
+hard = "Hard"
+nightmare = "Nightmare"
+
+[hud]
+credits = "CR {credits}"
+bombs = "BOMBS {count}/{capacity}"
+no_signal = "NO SIGNAL"
+
+[station]
+docked = "DOCKED"
+credits = "Credits: {credits}"
+bombs = "Bombs: {count}/{capacity}"
+repair = "Repair hull"
+refill = "Refill bombs"
+upgrade = "Upgrade weapon"
+undock = "Undock"
+slow_down = "Slow down to dock"
+
+[tutorial]
+move = "WASD to steer"
+fire = "Hold SPACE to fire"
+dock = "Coast in slowly to dock"
+station = "UP/DOWN to pick, ENTER to buy"
+
+[gameover]
+score = "SCORE {score}"
+new_high_score = "New high score! Your name:"
+high_scores = "High scores"
+restart = "Restart"
+menu = "Menu"
+
+[stats]
+game_over = "GAME OVER"
+time = "Time survived  {seconds}s"
+shots = "Shots fired    {shots}"
+accuracy = "Accuracy       {percent}%"
+dealt = "Damage dealt   {damage}"
+taken = "Damage taken   {damage}"
+overkill = "Overkill       {damage}"
+kills = "Kills"
+lifetime = "Lifetime: {runs} runs, {seconds}s, {percent}% accuracy"
+
+[campaign]
+level = "Level {number}: {name}"
+wave = "Wave {wave}/{count}"
+complete = "LEVEL COMPLETE"
+time = "Time {time}"
+par = "Par {time}"
+best = "Best {time}"
+
+[gallery]
+ships = "Ships"
+enemies = "Enemies met"
+achievements = "Achievements"
+empty = "Nothing yet"
+
+[achievements]
+first_blood = "First Blood"
+first_blood_description = "Destroy an enemy"
+exterminator = "Exterminator"
+exterminator_description = "Destroy 100 enemies"
+trigger_happy = "Trigger Happy"
+trigger_happy_description = "Fire 1000 shots"
+meltdown = "Meltdown"
+meltdown_description = "Overheat a weapon"
+slow_learner = "Slow Learner"
+slow_learner_description = "Overheat a weapon 25 times"
+untouchable = "Untouchable"
+untouchable_description = "Clear wave 10 without taking damage"
+
+[cheats]
+message = "Cheat: {message}"
+no_player = "No player"
+god_mode_on = "God mode on"
+god_mode_off = "God mode off"
+all_weapons = "Weapons maxed, bombs refilled"
+skip_wave = "Skipped, {killed} hostiles destroyed"
+
+[photo]
+help = "WASD move  Wheel zoom  F12 capture  F9 exit"
+
+[scene]
+loading = "Loading"
+"#;
+
+/// One language's strings, keyed by `section.key`.
+struct Table {
+    /// File name without the extension, `en`, `de`, ...
+    code: String,
+    strings: Vec<(String, String)>,
+}
+
+impl Table {
+    /// Reads `key = "value"` lines under `[section]` headers. Values may
+    /// use `\n`, `\"` and `\\`, lines starting with `#` are comments.
+    fn parse(code: &str, contents: &str) -> Table {
+        let mut section = String::new();
+        let mut strings = Vec::new();
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() => (key, value),
+                _ => {
+                    warn!(
+                        "i18n",
+                        "line without a key and value",
+                        language = code,
+                        line = line
+                    );
+                    continue;
+                }
+            };
+            let value = value.trim();
+            let value = unescape(
+                value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value),
+            );
+            strings.push((format!("{}.{}", section, key.trim()), value));
+        }
+        Table {
+            code: code.to_string(),
+            strings,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.strings
+            .iter()
+            .find(|s| s.0 == key)
+            .map(|s| s.1.as_str())
+    }
+}
+
+/// Escapes in one pass, so `\\n` is a backslash and an `n`. A backslash
+/// before anything else is kept.
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+struct Strings {
+    /// English first, it's what every other language falls back to.
+    tables: Vec<Table>,
+    current: usize,
+}
+
+static STRINGS: Mutex<Option<Strings>> = Mutex::new(None);
+
+fn with_strings<T>(f: impl FnOnce(&mut Strings) -> T) -> T {
+    let mut guard = STRINGS.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(|| Strings {
+        tables: vec![Table::parse("en", ENGLISH)],
+        current: 0,
+    }))
+}
+
+/// Adds a language per `.toml` file in `dir`, named after the file: a
+/// `de.toml` is German. Its `[language]` section holds the `name` shown in
+/// the settings and optionally a `font` with the glyphs it needs. An
+/// `en.toml` changes the built-in English strings. Returns how many files
+/// were read.
+pub fn load_dir(dir: &str) -> usize {
+    let mut loaded = 0;
+    for path in pack::files_in(dir) {
+        if path.extension().is_none_or(|e| e != "toml") {
+            continue;
+        }
+        let code = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(code) => code.to_string(),
+            None => continue,
+        };
+        match pack::read_to_string(&path) {
+            Ok(contents) => {
+                add(Table::parse(&code, &contents));
+                loaded += 1;
+            }
+            Err(e) => warn!(
+                "i18n",
+                "could not read language",
+                path = path.display(),
+                error = e
+            ),
+        }
+    }
+    info!("i18n", "languages loaded", count = loaded);
+    loaded
+}
+
+/// Adds a language from the contents of its file, or updates it when
+/// already known.
+pub fn load(code: &str, contents: &str) {
+    add(Table::parse(code, contents));
+}
+
+fn add(table: Table) {
+    with_strings(|strings| {
+        match strings.tables.iter_mut().find(|t| t.code == table.code) {
+            // new keys first, so they win over the old ones
+            Some(old) => {
+                let mut merged = table.strings;
+                merged.append(&mut old.strings);
+                old.strings = merged;
+            }
+            None => strings.tables.push(table),
+        }
+    });
+}
+
+/// Codes of the known languages, English first.
+pub fn languages() -> Vec<String> {
+    with_strings(|strings| strings.tables.iter().map(|t| t.code.clone()).collect())
+}
+
+/// What a language calls itself, its code when it doesn't say.
+pub fn name(code: &str) -> String {
+    with_strings(|strings| {
+        let table = strings.tables.iter().find(|t| t.code == code);
+        table
+            .and_then(|t| t.get("language.name"))
+            .unwrap_or(code)
+            .to_string()
+    })
+}
+
+/// Code of the language strings come in.
+pub fn language() -> String {
+    with_strings(|strings| strings.tables[strings.current].code.clone())
+}
+
+/// Switches the language, false when there's no such language.
+pub fn set_language(code: &str) -> bool {
+    with_strings(
+        |strings| match strings.tables.iter().position(|t| t.code == code) {
+            Some(i) => {
+                strings.current = i;
+                true
+            }
+            None => false,
+        },
+    )
+}
+
+/// Fonts languages ask for, with every string of the language so the font
+/// can be loaded with the glyphs they need. For scripts the UI font
+/// doesn't cover, text falls back to these.
+pub fn fonts() -> Vec<(String, String)> {
+    with_strings(|strings| {
+        strings
+            .tables
+            .iter()
+            .filter_map(|t| {
+                let font = t.get("language.font")?;
+                let text: String = t.strings.iter().map(|s| s.1.as_str()).collect();
+                Some((font.to_string(), text))
+            })
+            .collect()
+    })
+}
+
+/// The string for `key` in the current language, with each `{name}` in it
+/// replaced by its value. Falls back to English, then to the key itself so
+/// a missing string shows up on screen. Use `t!` instead.
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    let text = with_strings(|strings| {
+        strings.tables[strings.current]
+            .get(key)
+            .or_else(|| strings.tables[0].get(key))
+            .map(str::to_string)
+    });
+    let mut text = text.unwrap_or_else(|| key.to_string());
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+/// Looks up a UI string in the current language:
+/// `t!("hud.bombs", count = 2, capacity = 3)`.
+#[macro_export]
+macro_rules! t {
+    ($key:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::translate($key, &[$((stringify!($name), $value.to_string())),*])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_sections_and_keys() {
+        let table = Table::parse(
+            "xx",
+            "# comment\n\
+             top = \"no section\"\n\
+             [menu]\n  start = \"Go\"  \n\
+             bare = unquoted\n\
+             [ pause ]\n\
+             quit=\"a = b\"\n",
+        );
+        assert_eq!(table.get(".top"), Some("no section"));
+        assert_eq!(table.get("menu.start"), Some("Go"));
+        assert_eq!(table.get("menu.bare"), Some("unquoted"));
+        assert_eq!(table.get("pause.quit"), Some("a = b"));
+        assert_eq!(table.get("menu.quit"), None);
+    }
+
+    #[test]
+    fn reads_escapes_in_one_pass() {
+        let table = Table::parse(
+            "xx",
+            r#"a = "two\nlines"
+b = "say \"hi\""
+c = "back\\slash"
+d = "not\\n a newline"
+e = "kept \t"
+f = "end\"#,
+        );
+        assert_eq!(table.get(".a"), Some("two\nlines"));
+        assert_eq!(table.get(".b"), Some("say \"hi\""));
+        assert_eq!(table.get(".c"), Some("back\\slash"));
+        assert_eq!(table.get(".d"), Some("not\\n a newline"));
+        assert_eq!(table.get(".e"), Some("kept \\t"));
+        assert_eq!(table.get(".f"), Some("\"end\\"));
+    }
+
+    #[test]
+    fn skips_lines_without_a_value() {
+        let table = Table::parse("xx", "[menu]\nstart\n= \"no key\"\nback = \"Back\"\n");
+        assert_eq!(table.strings.len(), 1);
+        assert_eq!(table.get("menu.back"), Some("Back"));
+    }
+
+    #[test]
+    fn english_has_every_placeholder_it_uses() {
+        let table = Table::parse("en", ENGLISH);
+        assert_eq!(table.get("language.name"), Some("English"));
+        for (key, value) in table.strings.iter() {
+            assert_eq!(
+                value.matches('{').count(),
+                value.matches('}').count(),
+                "{} has unbalanced braces",
+                key
+            );
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use raylib::prelude::*;
+
+use crate::camera::Camera;
+use crate::renderer::Renderer;
+use crate::{Anchor, DrawSystem, World};
+
+const EDGE_MARGIN: f32 = 16.0;
+const ARROW_SIZE: f32 = 10.0;
+/// Distance past the screen edge at which an arrow has fully faded out.
+const FADE_DISTANCE: f32 = 800.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndicatorKind {
+    Enemy,
+    Pickup,
+    Objective,
+}
+
+impl IndicatorKind {
+    fn color(&self) -> Color {
+        match self {
+            IndicatorKind::Enemy => Color::RED,
+            IndicatorKind::Pickup => Color::GREEN,
+            IndicatorKind::Objective => Color::GOLD,
+        }
+    }
+}
+
+/// Points an arrow at the entity from the screen edge while it is off-screen.
+pub struct Indicator {
+    kind: IndicatorKind,
+}
+
+impl Indicator {
+    pub fn new(kind: IndicatorKind) -> Indicator {
+        Indicator { kind }
+    }
+}
+
+impl DrawSystem for Indicator {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        let screen = world.screen.bounds();
+        let edge = screen.inset(EDGE_MARGIN);
+        let center = screen.calc(Anchor::Center);
+        let camera = match Camera::active(world) {
+            Some(c) => c,
+            None => return,
+        };
+        let view = camera.view_bounds(screen.width(), screen.height());
+
+        world
+            .base_components
+            .iter()
+            .filter_map(|b| {
+                world
+                    .indicator_components
+                    .iter()
+                    .find(|i| b.0 == i.0)
+                    .map(|i| (&b.1, &i.1))
+            })
+            .filter(|(b, _)| !b.bounds.overlaps(&view))
+            .for_each(|(b, indicator)| {
+                let target = camera.world_to_screen(b.bounds.center());
+                let tip = edge.clamp_towards(center, target);
+                let dir = (target - center).normalized();
+                let side = Vector2::new(-dir.y, dir.x) * (ARROW_SIZE * 0.6);
+                let back = tip - dir * ARROW_SIZE;
+
+                let alpha = 1.0 - (target.distance_to(tip) / FADE_DISTANCE).min(0.8);
+                d.draw_triangle(
+                    tip,
+                    back - side,
+                    back + side,
+                    world.palette.remap(indicator.kind.color()).fade(alpha),
+                );
+            });
+    }
+}
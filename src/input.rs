@@ -0,0 +1,18 @@
+use raylib::prelude::*;
+
+/// Characters typed since the last frame, in order, for text fields. They
+/// are taken off raylib's queue, so one field gets them each frame; calling
+/// it and dropping the result keeps a key that opened a field out of it.
+pub fn typed(_rl: &RaylibHandle) -> Vec<char> {
+    let mut chars = Vec::new();
+    loop {
+        // SAFETY: GetCharPressed only pops raylib's own fixed-size queue of
+        // typed characters and returns 0 when it's empty. It takes no
+        // pointers, and the handle means the window it's filled from is up.
+        let c = unsafe { ffi::GetCharPressed() };
+        if c <= 0 {
+            return chars;
+        }
+        chars.extend(char::from_u32(c as u32));
+    }
+}
@@ -0,0 +1,384 @@
+use raylib::prelude::*;
+
+use crate::camera::Camera;
+use crate::movement::Velocity;
+use crate::renderer::Renderer;
+use crate::{Base2D, EntityID, Health, World};
+
+const LIST_WIDTH: f32 = 150.0;
+const FIELDS_WIDTH: f32 = 170.0;
+const ROW_HEIGHT: f32 = 12.0;
+const TOP: f32 = 24.0;
+
+/// One editable number of a component.
+pub struct Field {
+    pub name: &'static str,
+    pub value: f32,
+    /// Change per nudge.
+    pub step: f32,
+}
+
+impl Field {
+    fn new(name: &'static str, value: f32, step: f32) -> Field {
+        Field { name, value, step }
+    }
+}
+
+/// Reflection for the inspector: a component's numbers by name, and a way
+/// to write them back.
+pub trait Inspect {
+    fn fields(&self) -> Vec<Field>;
+    fn set_field(&mut self, name: &str, value: f32);
+}
+
+impl Inspect for Base2D {
+    fn fields(&self) -> Vec<Field> {
+        let b = self.bounds;
+        vec![
+            Field::new("x", b.x1, 1.0),
+            Field::new("y", b.y1, 1.0),
+            Field::new("width", b.width(), 1.0),
+            Field::new("height", b.height(), 1.0),
+            Field::new("tint r", self.tint.r as f32, 8.0),
+            Field::new("tint g", self.tint.g as f32, 8.0),
+            Field::new("tint b", self.tint.b as f32, 8.0),
+            Field::new("tint a", self.tint.a as f32, 8.0),
+        ]
+    }
+
+    fn set_field(&mut self, name: &str, value: f32) {
+        let channel = value.clamp(0.0, 255.0) as u8;
+        let b = &mut self.bounds;
+        match name {
+            "x" => b.translate(Vector2::new(value - b.x1, 0.0)),
+            "y" => b.translate(Vector2::new(0.0, value - b.y1)),
+            "width" => b.x2 = b.x1 + value.max(0.0),
+            "height" => b.y2 = b.y1 + value.max(0.0),
+            "tint r" => self.tint.r = channel,
+            "tint g" => self.tint.g = channel,
+            "tint b" => self.tint.b = channel,
+            "tint a" => self.tint.a = channel,
+            _ => (),
+        }
+    }
+}
+
+impl Inspect for Health {
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            Field::new("health", self.health as f32, 1.0),
+            Field::new("max health", self.max_health as f32, 1.0),
+            Field::new("armor", self.armor as f32, 1.0),
+            Field::new("max armor", self.max_armor as f32, 1.0),
+            Field::new("segments", self.segments as f32, 1.0),
+        ]
+    }
+
+    fn set_field(&mut self, name: &str, value: f32) {
+        let value = value.clamp(0.0, u16::MAX as f32) as u16;
+        match name {
+            "health" => self.health = value.min(self.max_health),
+            "max health" => {
+                self.max_health = value;
+                self.health = self.health.min(value);
+            }
+            "armor" => self.armor = value.min(self.max_armor),
+            "max armor" => {
+                self.max_armor = value;
+                self.armor = self.armor.min(value);
+            }
+            "segments" => self.segments = value,
+            _ => (),
+        }
+    }
+}
+
+impl Inspect for Velocity {
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            Field::new("velocity x", self.velocity.x, 10.0),
+            Field::new("velocity y", self.velocity.y, 10.0),
+            Field::new("drag", self.drag, 0.1),
+        ]
+    }
+
+    fn set_field(&mut self, name: &str, value: f32) {
+        match name {
+            "velocity x" => self.velocity.x = value,
+            "velocity y" => self.velocity.y = value,
+            "drag" => self.drag = value.max(0.0),
+            _ => (),
+        }
+    }
+}
+
+fn find<T>(components: &[(EntityID, T)], id: EntityID) -> Option<&T> {
+    components.iter().find(|c| c.0 == id).map(|c| &c.1)
+}
+
+fn find_mut<T>(components: &mut [(EntityID, T)], id: EntityID) -> Option<&mut T> {
+    components.iter_mut().find(|c| c.0 == id).map(|c| &mut c.1)
+}
+
+/// A component type reflection knows how to reach, for the inspector and
+/// world snapshots.
+pub struct Reflected {
+    pub name: &'static str,
+    pub get: fn(&World, EntityID) -> Option<&dyn Inspect>,
+    pub get_mut: fn(&mut World, EntityID) -> Option<&mut dyn Inspect>,
+    /// Entities that have one.
+    pub ids: fn(&World) -> Vec<EntityID>,
+    /// Gives the entity a blank one, for its fields to be set after.
+    pub insert: fn(&mut World, EntityID),
+}
+
+pub const COMPONENTS: [Reflected; 3] = [
+    Reflected {
+        name: "Base2D",
+        get: |w, id| find(&w.base_components, id).map(|c| c as &dyn Inspect),
+        get_mut: |w, id| find_mut(&mut w.base_components, id).map(|c| c as &mut dyn Inspect),
+        ids: |w| w.base_components.iter().map(|c| c.0).collect(),
+        insert: |w, id| {
+            let base = Base2D::new(Vector2::zero(), Vector2::zero());
+            w.base_components.push((id, base));
+        },
+    },
+    Reflected {
+        name: "Health",
+        get: |w, id| find(&w.health_components, id).map(|c| c as &dyn Inspect),
+        get_mut: |w, id| find_mut(&mut w.health_components, id).map(|c| c as &mut dyn Inspect),
+        ids: |w| w.health_components.iter().map(|c| c.0).collect(),
+        insert: |w, id| w.health_components.push((id, Health::new(0))),
+    },
+    Reflected {
+        name: "Velocity",
+        get: |w, id| find(&w.velocity_components, id).map(|c| c as &dyn Inspect),
+        get_mut: |w, id| find_mut(&mut w.velocity_components, id).map(|c| c as &mut dyn Inspect),
+        ids: |w| w.velocity_components.iter().map(|c| c.0).collect(),
+        insert: |w, id| w.velocity_components.push((id, Velocity::new(0.0))),
+    },
+];
+
+/// Debug panel toggled with F6. Lists the entities with a Base2D on the
+/// left, pick one there or by clicking it in the world. Its components
+/// show on the right: click a field, then nudge it with the mouse wheel or
+/// Left and Right, Shift for ten steps at a time.
+pub struct Inspector {
+    open: bool,
+    selected: Option<EntityID>,
+    /// Component and field being edited.
+    field: Option<(&'static str, &'static str)>,
+    /// First entity shown in the list.
+    scroll: usize,
+}
+
+impl Inspector {
+    pub fn new() -> Inspector {
+        Inspector {
+            open: false,
+            selected: None,
+            field: None,
+            scroll: 0,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Shows `id`'s components, for tools that pick entities themselves.
+    pub fn select(&mut self, id: Option<EntityID>) {
+        if self.selected != id {
+            self.selected = id;
+            self.field = None;
+        }
+    }
+
+    fn entities(world: &World) -> Vec<(EntityID, &str)> {
+        world
+            .base_components
+            .iter()
+            .map(|b| (b.0, b.1.name.as_str()))
+            .collect()
+    }
+
+    /// Rows of the selected entity's panel, None for component headers.
+    fn rows(world: &World, id: EntityID) -> Vec<(&'static str, Option<Field>)> {
+        let mut rows = Vec::new();
+        for component in COMPONENTS.iter() {
+            if let Some(c) = (component.get)(world, id) {
+                rows.push((component.name, None));
+                rows.extend(c.fields().into_iter().map(|f| (component.name, Some(f))));
+            }
+        }
+        rows
+    }
+
+    fn list_rows(world: &World) -> usize {
+        ((world.screen.height() - TOP) / ROW_HEIGHT) as usize - 1
+    }
+
+    pub fn input_system(&mut self, world: &mut World, rl: &RaylibHandle) {
+        if rl.is_key_pressed(KeyboardKey::KEY_F6) {
+            self.open = !self.open;
+        }
+        if !self.open {
+            return;
+        }
+        if self
+            .selected
+            .is_some_and(|id| !world.base_components.iter().any(|b| b.0 == id))
+        {
+            self.selected = None;
+            self.field = None;
+        }
+
+        let mouse = world.screen.window_to_canvas(rl.get_mouse_position());
+        let wheel = rl.get_mouse_wheel_move();
+        let in_list = mouse.x < LIST_WIDTH;
+        let fields_x = world.screen.width() - FIELDS_WIDTH;
+        let in_fields = mouse.x > fields_x;
+        let row = ((mouse.y - TOP) / ROW_HEIGHT).floor();
+
+        let count = world.base_components.len();
+        if in_list && wheel != 0.0 {
+            let max = count.saturating_sub(Inspector::list_rows(world));
+            self.scroll = (self.scroll as f32 - wheel).clamp(0.0, max as f32) as usize;
+        }
+
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            if in_list {
+                let index = self.scroll + row.max(0.0) as usize;
+                if let Some((id, _)) = Inspector::entities(world).get(index) {
+                    self.selected = Some(*id);
+                    self.field = None;
+                }
+            } else if in_fields {
+                if let Some(id) = self.selected {
+                    let rows = Inspector::rows(world, id);
+                    if let Some((component, Some(field))) = rows.get(row.max(0.0) as usize) {
+                        self.field = Some((component, field.name));
+                    }
+                }
+            } else {
+                self.pick(world, mouse);
+            }
+        }
+
+        let mut steps = 0.0;
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            steps += 1.0;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+            steps -= 1.0;
+        }
+        if in_fields {
+            steps += wheel;
+        }
+        if rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) {
+            steps *= 10.0;
+        }
+        if steps != 0.0 {
+            self.nudge(world, steps);
+        }
+    }
+
+    /// Selects the smallest entity under the cursor.
+    fn pick(&mut self, world: &World, mouse: Vector2) {
+        let pos = match Camera::active(world) {
+            Some(c) => c.screen_to_world(mouse),
+            None => mouse,
+        };
+        let hit = world
+            .base_components
+            .iter()
+            .filter(|b| b.1.bounds.contains(pos))
+            .min_by(|a, b| {
+                let area = |b: &Base2D| b.bounds.width() * b.bounds.height();
+                area(&a.1).total_cmp(&area(&b.1))
+            });
+        self.selected = hit.map(|b| b.0);
+        self.field = None;
+    }
+
+    fn nudge(&mut self, world: &mut World, steps: f32) {
+        let (id, (component, name)) = match (self.selected, self.field) {
+            (Some(id), Some(field)) => (id, field),
+            _ => return,
+        };
+        let reflected = match COMPONENTS.iter().find(|c| c.name == component) {
+            Some(r) => r,
+            None => return,
+        };
+        if let Some(c) = (reflected.get_mut)(world, id) {
+            if let Some(field) = c.fields().into_iter().find(|f| f.name == name) {
+                c.set_field(name, field.value + field.step * steps);
+            }
+        }
+    }
+
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if !self.open {
+            return;
+        }
+        let h = world.screen.height();
+        d.draw_rect(
+            Rectangle::new(0.0, 0.0, LIST_WIDTH, h),
+            Color::BLACK.fade(0.8),
+        );
+        d.draw_text("Entities", 4, 8, 10, Color::GOLD);
+        let entities = Inspector::entities(world);
+        for (i, (id, name)) in entities
+            .iter()
+            .skip(self.scroll)
+            .take(Inspector::list_rows(world))
+            .enumerate()
+        {
+            let y = (TOP + i as f32 * ROW_HEIGHT) as i32;
+            let color = if self.selected == Some(*id) {
+                Color::YELLOW
+            } else {
+                Color::RAYWHITE
+            };
+            d.draw_text(&format!("{} {}", id, name), 4, y, 10, color);
+        }
+
+        let id = match self.selected {
+            Some(id) => id,
+            None => return,
+        };
+        // outline the selection where it is in the world
+        if let (Some(base), Some(camera)) =
+            (find(&world.base_components, id), Camera::active(world))
+        {
+            let b = base.bounds;
+            let top_left = camera.world_to_screen(Vector2::new(b.x1, b.y1));
+            let bottom_right = camera.world_to_screen(Vector2::new(b.x2, b.y2));
+            let size = bottom_right - top_left;
+            let rect = Rectangle::new(top_left.x, top_left.y, size.x, size.y);
+            d.draw_rect_lines(rect, 1, Color::YELLOW);
+        }
+
+        let x = world.screen.width() - FIELDS_WIDTH;
+        d.draw_rect(
+            Rectangle::new(x, 0.0, FIELDS_WIDTH, h),
+            Color::BLACK.fade(0.8),
+        );
+        d.draw_text(&format!("Entity {}", id), x as i32 + 4, 8, 10, Color::GOLD);
+        for (i, (component, field)) in Inspector::rows(world, id).iter().enumerate() {
+            let y = (TOP + i as f32 * ROW_HEIGHT) as i32;
+            match field {
+                None => d.draw_text(component, x as i32 + 4, y, 10, Color::SKYBLUE),
+                Some(f) => {
+                    let color = if self.field == Some((component, f.name)) {
+                        Color::YELLOW
+                    } else {
+                        Color::RAYWHITE
+                    };
+                    let text = format!("{}: {:.2}", f.name, f.value);
+                    d.draw_text(&text, x as i32 + 12, y, 10, color);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use std::fmt;
+
+/// Arrays and objects deep a document may go, so a file of nothing but `[`
+/// can't run the parser out of stack.
+const MAX_DEPTH: usize = 128;
+
+/// Parsed JSON document. Objects keep their keys in file order, which
+/// matters for formats like Aseprite's where frame order is key order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(value)
+    }
+
+    /// Member of an object, None for missing keys and non-objects.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|m| m.0 == key).map(|m| &m.1),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_f64().map(|n| n as f32)
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_f64().map(|n| n as i64)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Vec<(String, Json)>> {
+        match self {
+            Json::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+/// Compact serialization, parseable again by `Json::parse`. JSON has no
+/// infinity or NaN, those are written as `null`.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if !n.is_finite() => write!(f, "null"),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Arrays and objects the parser is in, see `MAX_DEPTH`.
+    depth: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> String {
+        format!("json: {} at character {}", message, self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("expected '{}'", c)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        for expected in word.chars() {
+            if self.peek() != Some(expected) {
+                return Err(self.error(&format!("expected {}", word)));
+            }
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') | Some('[') => {
+                self.depth += 1;
+                if self.depth > MAX_DEPTH {
+                    return Err(self.error(&format!("nested over {} deep", MAX_DEPTH)));
+                }
+                let value = if self.peek() == Some('{') {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => self.string().map(Json::String),
+            Some('t') => self.literal("true", Json::Bool(true)),
+            Some('f') => self.literal("false", Json::Bool(false)),
+            Some('n') => self.literal("null", Json::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.peek() != Some('"') {
+            return Err(self.error("expected a string"));
+        }
+        self.pos += 1;
+        let mut s = String::new();
+        loop {
+            let c = self.peek().ok_or(self.error("unterminated string"))?;
+            self.pos += 1;
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escaped = self.peek().ok_or(self.error("unterminated string"))?;
+                    self.pos += 1;
+                    match escaped {
+                        '"' | '\\' | '/' => s.push(escaped),
+                        'n' => s.push('\n'),
+                        'r' => s.push('\r'),
+                        't' => s.push('\t'),
+                        'b' => s.push('\u{8}'),
+                        'f' => s.push('\u{c}'),
+                        'u' => {
+                            let hex: String = self.chars.iter().skip(self.pos).take(4).collect();
+                            // from_str_radix alone would take a sign, or fewer digits
+                            if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                                return Err(self.error("bad unicode escape"));
+                            }
+                            let code = u32::from_str_radix(&hex, 16)
+                                .map_err(|_| self.error("bad unicode escape"))?;
+                            self.pos += 4;
+                            // surrogate pairs aren't worth the trouble here
+                            s.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                        }
+                        _ => return Err(self.error("bad escape")),
+                    }
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        match text.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(Json::Number(n)),
+            _ => Err(self.error("bad number")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Json {
+        Json::String(s.to_string())
+    }
+
+    #[test]
+    fn round_trips() {
+        let value = Json::Object(vec![
+            ("z".to_string(), Json::Number(-1.5e-3)),
+            (
+                "a".to_string(),
+                Json::Array(vec![Json::Null, Json::Bool(true)]),
+            ),
+            (
+                "quote \"\\".to_string(),
+                string("line\nreturn\rtab\t\u{1}é😀"),
+            ),
+            ("empty".to_string(), Json::Object(Vec::new())),
+        ]);
+        let text = value.to_string();
+        assert_eq!(Json::parse(&text), Ok(value));
+        // keys stay in file order
+        assert!(text.starts_with("{\"z\":"), "{}", text);
+        assert_eq!(string("\u{1f}").to_string(), "\"\\u001f\"");
+    }
+
+    #[test]
+    fn reads_escapes() {
+        assert_eq!(
+            Json::parse(r#""\"\\\/\n\r\t\b\f""#),
+            Ok(string("\"\\/\n\r\t\u{8}\u{c}"))
+        );
+        assert_eq!(Json::parse(r#""\u0041\u00e9""#), Ok(string("Aé")));
+        // a lone surrogate can't be a char
+        assert_eq!(Json::parse(r#""\ud800""#), Ok(string("\u{fffd}")));
+    }
+
+    #[test]
+    fn rejects_bad_unicode_escapes() {
+        for text in [
+            r#""\u+041""#,
+            r#""\u-041""#,
+            r#""\u41""#,
+            r#""\u00g1""#,
+            r#""\u00"#,
+        ] {
+            assert!(Json::parse(text).is_err(), "{} parsed", text);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        for (text, error) in [
+            ("", "json: unexpected end at character 0"),
+            ("{} x", "json: trailing characters at character 3"),
+            ("[1, 2] ]", "json: trailing characters at character 7"),
+            ("[1 2]", "json: expected ',' or ']' at character 3"),
+            ("{\"a\" 1}", "json: expected ':' at character 5"),
+            ("{\"a\": 1,}", "json: expected a string at character 8"),
+            ("{a: 1}", "json: expected a string at character 1"),
+            ("\"open", "json: unterminated string at character 5"),
+            ("\"\\x\"", "json: bad escape at character 3"),
+            ("tru", "json: expected true at character 3"),
+            ("1.2.3", "json: bad number at character 5"),
+            ("-", "json: bad number at character 1"),
+            ("1e999", "json: bad number at character 5"),
+            ("@", "json: unexpected character at character 0"),
+        ] {
+            assert_eq!(Json::parse(text), Err(error.to_string()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn stops_at_deep_nesting() {
+        let deep = "[".repeat(100_000);
+        assert_eq!(
+            Json::parse(&deep),
+            Err(format!(
+                "json: nested over {} deep at character {}",
+                MAX_DEPTH, MAX_DEPTH
+            ))
+        );
+        let deep = "{\"a\":".repeat(100_000);
+        assert!(Json::parse(&deep).is_err());
+        let fine = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(Json::parse(&fine).is_ok());
+    }
+
+    #[test]
+    fn writes_non_finite_numbers_as_null() {
+        let value = Json::Array(vec![Json::Number(f64::NAN), Json::Number(f64::INFINITY)]);
+        assert_eq!(value.to_string(), "[null,null]");
+        assert!(Json::parse(&value.to_string()).is_ok());
+    }
+}
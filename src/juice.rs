@@ -0,0 +1,79 @@
+use raylib::prelude::*;
+
+use crate::rng::Rng;
+
+/// Update speed while a hitstop is running. Not a full freeze so particles
+/// and animations still creep along.
+const HITSTOP_SCALE: f32 = 0.05;
+
+/// Screen shake and hit-pause. Gameplay code asks for an effect, the main
+/// loop feeds the result into the camera offset and the update timestep.
+pub struct Juice {
+    /// Multiplies every shake, from the screen shake setting.
+    pub intensity: f32,
+    strength: f32,
+    duration: f32,
+    timer: f32,
+    hitstop: u32,
+    offset: Vector2,
+    rng: Rng,
+}
+
+impl Juice {
+    pub fn new(seed: u64) -> Juice {
+        Juice {
+            intensity: 1.0,
+            strength: 0.0,
+            duration: 0.0,
+            timer: 0.0,
+            hitstop: 0,
+            offset: Vector2::zero(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Shakes the camera by up to `strength` pixels, fading out over
+    /// `duration` seconds. A weaker shake never cuts a stronger one short.
+    pub fn shake(&mut self, strength: f32, duration: f32) {
+        if strength >= self.current_strength() {
+            self.strength = strength;
+            self.duration = duration;
+            self.timer = duration;
+        }
+    }
+
+    /// Slows the game down for the next `frames` frames.
+    pub fn hitstop(&mut self, frames: u32) {
+        self.hitstop = self.hitstop.max(frames);
+    }
+
+    /// Camera offset for this frame.
+    pub fn offset(&self) -> Vector2 {
+        self.offset
+    }
+
+    fn current_strength(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        self.strength * (self.timer / self.duration).max(0.0)
+    }
+
+    /// Advances the effects by the real frame time and returns the timestep
+    /// the rest of the update should use.
+    pub fn update(&mut self, dt: f32) -> f32 {
+        self.timer = (self.timer - dt).max(0.0);
+        let strength = self.current_strength() * self.intensity;
+        self.offset = Vector2::new(
+            self.rng.range(-strength, strength),
+            self.rng.range(-strength, strength),
+        );
+
+        if self.hitstop > 0 {
+            self.hitstop -= 1;
+            dt * HITSTOP_SCALE
+        } else {
+            dt
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::json::Json;
+use crate::pack;
+use crate::prefab::{Placement, Prefabs};
+use crate::tilemap::Tilemap;
+use crate::{BoundingBox2D, EntityID, World};
+
+/// Tile layer of a level, placed in world coordinates.
+pub struct LdtkLayer {
+    pub name: String,
+    pub origin: Vector2,
+    /// Tileset image path, relative to the working directory.
+    pub tileset: Option<String>,
+    pub tilemap: Tilemap,
+}
+
+/// Entity instance, `identifier` picks the prefab. Field values end up as
+/// placement properties, strings as they are and everything else as JSON.
+pub struct LdtkEntity {
+    pub identifier: String,
+    pub placement: Placement,
+}
+
+pub struct LdtkLevel {
+    pub name: String,
+    /// Where the level sits in the LDtk world.
+    pub bounds: BoundingBox2D,
+    /// Bottom layer first.
+    pub layers: Vec<LdtkLayer>,
+    pub entities: Vec<LdtkEntity>,
+}
+
+/// LDtk project file (.ldtk), levels saved inline or as separate .ldtkl
+/// files. One LDtk pixel is one world unit and levels keep their world
+/// positions, so the levels of a multi-level world line up.
+///
+/// Tiles, AutoLayer and IntGrid layers become tilemaps, drawing their
+/// placed or auto-layer tiles. IntGrid cells with a value are solid, that's
+/// what marks walls. Tilesets must not use spacing or padding.
+pub struct LdtkProject {
+    pub levels: Vec<LdtkLevel>,
+}
+
+impl LdtkProject {
+    pub fn load(path: &str) -> Result<LdtkProject, String> {
+        let contents =
+            pack::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        LdtkProject::parse(&contents, dir).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Parses the project, external levels and tilesets are resolved
+    /// against `dir`.
+    pub fn parse(contents: &str, dir: &Path) -> Result<LdtkProject, String> {
+        let json = Json::parse(contents)?;
+        let mut levels = Vec::new();
+        for level in json
+            .get("levels")
+            .and_then(Json::as_array)
+            .ok_or("no levels")?
+        {
+            let external = level.get("externalRelPath").and_then(Json::as_str);
+            levels.push(match external {
+                Some(rel) => {
+                    let path = dir.join(rel);
+                    let contents = pack::read_to_string(&path)
+                        .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+                    parse_level(&Json::parse(&contents)?, dir)
+                        .map_err(|e| format!("{}: {}", path.display(), e))?
+                }
+                None => parse_level(level, dir)?,
+            });
+        }
+        Ok(LdtkProject { levels })
+    }
+
+    pub fn level(&self, name: &str) -> Option<&LdtkLevel> {
+        self.levels.iter().find(|l| l.name == name)
+    }
+
+    /// Spawns every level, returns the spawned entities.
+    pub fn spawn(
+        &self,
+        world: &mut World,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) -> Vec<EntityID> {
+        self.levels
+            .iter()
+            .flat_map(|level| level.spawn(world, rl, thread))
+            .collect()
+    }
+}
+
+impl LdtkLevel {
+    /// Adds the level's tile layers behind everything in Background and a
+    /// prefab per entity whose identifier is registered. Returns the
+    /// spawned entities.
+    pub fn spawn(
+        &self,
+        world: &mut World,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) -> Vec<EntityID> {
+        let mut spawned = Vec::new();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let mut tilemap = layer.tilemap.clone();
+            tilemap.tileset = layer
+                .tileset
+                .as_ref()
+                .and_then(|path| world.assets.load_texture(rl, thread, path).ok());
+            let z = i as i32 - self.layers.len() as i32 - 10;
+            let name = format!("{} {}", self.name, layer.name);
+            spawned.push(tilemap.spawn(world, &name, layer.origin, z));
+        }
+        for entity in self.entities.iter() {
+            if let Some(id) = Prefabs::spawn(world, &entity.identifier, &entity.placement) {
+                spawned.push(id);
+            }
+        }
+        spawned
+    }
+
+    /// Center of the first entity called `identifier`, for spawn points.
+    pub fn point(&self, identifier: &str) -> Option<Vector2> {
+        self.entities
+            .iter()
+            .find(|e| e.identifier == identifier)
+            .map(|e| e.placement.bounds.center())
+    }
+}
+
+fn number(json: &Json, key: &str) -> Result<f32, String> {
+    json.get(key)
+        .and_then(Json::as_f32)
+        .ok_or(format!("no {}", key))
+}
+
+/// Two element number array, like LDtk's `px` and `__pivot`.
+fn pair(json: &Json, key: &str) -> Option<Vector2> {
+    match json.get(key)?.as_array()?.as_slice() {
+        [x, y] => Some(Vector2::new(x.as_f32()?, y.as_f32()?)),
+        _ => None,
+    }
+}
+
+fn parse_level(level: &Json, dir: &Path) -> Result<LdtkLevel, String> {
+    let name = level
+        .get("identifier")
+        .and_then(Json::as_str)
+        .ok_or("level without identifier")?
+        .to_string();
+    let world_pos = Vector2::new(number(level, "worldX")?, number(level, "worldY")?);
+    let bounds = BoundingBox2D::new(
+        world_pos.x,
+        world_pos.y,
+        number(level, "pxWid")?,
+        number(level, "pxHei")?,
+    );
+
+    let mut layers = Vec::new();
+    let mut entities = Vec::new();
+    // the file lists layers top first
+    let instances = level
+        .get("layerInstances")
+        .and_then(Json::as_array)
+        .ok_or(format!("level {}: no layerInstances", name))?;
+    for layer in instances.iter().rev() {
+        let layer_name = layer
+            .get("__identifier")
+            .and_then(Json::as_str)
+            .unwrap_or("")
+            .to_string();
+        let offset = Vector2::new(
+            number(layer, "__pxTotalOffsetX").unwrap_or(0.0),
+            number(layer, "__pxTotalOffsetY").unwrap_or(0.0),
+        );
+        let origin = world_pos + offset;
+
+        match layer.get("__type").and_then(Json::as_str) {
+            Some("Entities") => {
+                for entity in layer
+                    .get("entityInstances")
+                    .and_then(Json::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    entities.push(parse_entity(entity, origin)?);
+                }
+            }
+            Some("Tiles") | Some("AutoLayer") | Some("IntGrid") => {
+                let tilemap = parse_tiles(layer)
+                    .map_err(|e| format!("level {} layer {}: {}", name, layer_name, e))?;
+                let tileset = layer
+                    .get("__tilesetRelPath")
+                    .and_then(Json::as_str)
+                    .map(|rel| dir.join(rel).to_string_lossy().into_owned());
+                layers.push(LdtkLayer {
+                    name: layer_name,
+                    origin,
+                    tileset,
+                    tilemap,
+                });
+            }
+            _ => (),
+        }
+    }
+
+    Ok(LdtkLevel {
+        name,
+        bounds,
+        layers,
+        entities,
+    })
+}
+
+fn parse_tiles(layer: &Json) -> Result<Tilemap, String> {
+    let grid = number(layer, "__gridSize")?;
+    let columns = number(layer, "__cWid")? as usize;
+    let rows = number(layer, "__cHei")? as usize;
+    let mut tilemap = Tilemap::new(columns, rows, grid);
+
+    // Tiles layers place gridTiles, the others generate autoLayerTiles
+    let tiles = ["gridTiles", "autoLayerTiles"]
+        .iter()
+        .filter_map(|key| layer.get(key).and_then(Json::as_array))
+        .flatten();
+    for tile in tiles {
+        let (px, t) = match (pair(tile, "px"), tile.get("t").and_then(Json::as_i64)) {
+            (Some(px), Some(t)) => (px, t),
+            _ => continue,
+        };
+        let (x, y) = ((px.x / grid) as usize, (px.y / grid) as usize);
+        // stacked tiles would need more than one tile per cell, keep the top
+        tilemap.set(x, y, Some(t as u16), false);
+    }
+
+    for (i, value) in layer
+        .get("intGridCsv")
+        .and_then(Json::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if value.as_i64().unwrap_or(0) != 0 {
+            let (x, y) = (i % columns.max(1), i / columns.max(1));
+            let tile = tilemap.tile(x, y);
+            tilemap.set(x, y, tile, true);
+        }
+    }
+    Ok(tilemap)
+}
+
+fn parse_entity(entity: &Json, origin: Vector2) -> Result<LdtkEntity, String> {
+    let identifier = entity
+        .get("__identifier")
+        .and_then(Json::as_str)
+        .ok_or("entity without identifier")?
+        .to_string();
+    let px = pair(entity, "px").ok_or(format!("entity {}: no px", identifier))?;
+    let size = Vector2::new(
+        number(entity, "width").unwrap_or(0.0),
+        number(entity, "height").unwrap_or(0.0),
+    );
+    // px is where the pivot sits, not the top left
+    let pivot = pair(entity, "__pivot").unwrap_or(Vector2::zero());
+    let top_left = origin + px - Vector2::new(size.x * pivot.x, size.y * pivot.y);
+
+    let properties = entity
+        .get("fieldInstances")
+        .and_then(Json::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|field| {
+            let name = field.get("__identifier")?.as_str()?;
+            let value = match field.get("__value")? {
+                Json::Null => return None,
+                Json::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            Some((name.to_string(), value))
+        })
+        .collect();
+
+    // LDtk has no separate instance name, a "name" field stands in for it
+    let mut placement = Placement::new(BoundingBox2D::new_v(top_left, size));
+    placement.properties = properties;
+    if let Some(name) = placement.property("name") {
+        placement.name = name.to_string();
+    }
+    Ok(LdtkEntity {
+        identifier,
+        placement,
+    })
+}
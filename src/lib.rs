@@ -0,0 +1,116 @@
+//! A small ECS and 2D engine on top of raylib, and the space shooter
+//! built with it. `game::Game` wires the systems into a frame, the binary
+//! only opens the window and hands that frame to the main loop.
+
+// resources and components are built with new(), some of them take
+// arguments, so Default would only cover half of them
+#![allow(clippy::new_without_default)]
+
+pub mod achievements;
+pub mod animation;
+pub mod aseprite;
+pub mod assets;
+pub mod atlas;
+pub mod audio;
+pub mod beat;
+pub mod bindings;
+pub mod camera;
+pub mod campaign;
+pub mod canvas;
+pub mod cheats;
+pub mod config;
+pub mod console;
+pub mod crash;
+pub mod credits;
+pub mod crosshair;
+pub mod cutscene;
+pub mod damage;
+pub mod damage_number;
+pub mod debug;
+pub mod dev_tools;
+pub mod difficulty;
+pub mod ecs;
+pub mod editor;
+pub mod engine;
+pub mod flash;
+pub mod focus;
+pub mod font;
+pub mod gallery;
+pub mod game;
+pub mod gameover;
+pub mod gameplay;
+pub mod gizmos;
+pub mod grid;
+pub mod group;
+pub mod hazard;
+pub mod heat;
+pub mod hot_reload;
+pub mod hud;
+pub mod i18n;
+pub mod indicator;
+pub mod input;
+pub mod inspector;
+pub mod json;
+pub mod juice;
+pub mod ldtk;
+pub mod lifetime;
+pub mod light;
+pub mod log;
+pub mod menu;
+pub mod minimap;
+pub mod movement;
+pub mod music;
+pub mod nine_slice;
+pub mod observer;
+pub mod pack;
+pub mod palette;
+pub mod particles;
+pub mod path;
+pub mod pause;
+pub mod photo;
+pub mod physics;
+pub mod pilot;
+pub mod player;
+pub mod plugin;
+pub mod pool;
+pub mod portal;
+pub mod prefab;
+#[macro_use]
+pub mod profiler;
+pub mod projectile;
+pub mod regen;
+pub mod render;
+pub mod render_stats;
+pub mod renderer;
+pub mod rng;
+pub mod save;
+pub mod scene;
+pub mod screen;
+pub mod script;
+pub mod sector;
+pub mod settings;
+pub mod sprite;
+pub mod starfield;
+pub mod state;
+pub mod station;
+pub mod stats;
+pub mod team;
+pub mod telegraph;
+pub mod text;
+pub mod tiled;
+pub mod tilemap;
+pub mod time;
+pub mod trail;
+pub mod transition;
+pub mod trigger;
+pub mod tutorial;
+pub mod tween;
+pub mod ui;
+pub mod weapon;
+pub mod widget;
+pub mod xml;
+
+pub use ecs::{DrawSystem, EntityID, UpdateSystem, World};
+pub use engine::{Anchor, Engine, HEIGHT, WIDTH};
+pub use gameplay::{Health, UIBarStyle};
+pub use physics::{Base2D, BoundingBox2D};
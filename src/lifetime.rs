@@ -0,0 +1,40 @@
+use crate::{EntityID, UpdateSystem, World};
+
+/// Despawns the entity once its time is up.
+pub struct Lifetime {
+    remaining: f32,
+    total: f32,
+}
+
+impl Lifetime {
+    pub fn new(seconds: f32) -> Lifetime {
+        Lifetime {
+            remaining: seconds,
+            total: seconds,
+        }
+    }
+
+    /// How far along the entity is, from 0 when spawned to 1 when it expires.
+    pub fn progress(&self) -> f32 {
+        if self.total <= 0.0 {
+            return 1.0;
+        }
+        (1.0 - self.remaining / self.total).clamp(0.0, 1.0)
+    }
+}
+
+impl UpdateSystem for Lifetime {
+    fn update_system(world: &mut World, dt: f32) {
+        let mut expired: Vec<EntityID> = Vec::new();
+        for (id, lifetime) in world.lifetime_components.iter_mut() {
+            lifetime.remaining -= dt;
+            if lifetime.remaining <= 0.0 {
+                expired.push(*id);
+            }
+        }
+
+        for id in expired {
+            world.despawn(id);
+        }
+    }
+}
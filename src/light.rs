@@ -0,0 +1,188 @@
+use raylib::prelude::*;
+
+use crate::lifetime::Lifetime;
+use crate::render;
+use crate::{Base2D, BoundingBox2D, EntityID, UpdateSystem, World};
+
+/// Glow around an entity, drawn additively into the light map.
+pub struct Light {
+    pub radius: f32,
+    pub color: Color,
+    /// Brightness at the center, 1 is the plain color.
+    pub intensity: f32,
+    /// How much the brightness wavers, 0 is steady and 1 can go dark.
+    pub flicker: f32,
+    /// Offset of the light from the entity center, in world units.
+    pub offset: Vector2,
+    time: f32,
+    /// Intensity after flicker and fading, for this frame.
+    current: f32,
+}
+
+impl Light {
+    pub fn new(radius: f32, color: Color, intensity: f32) -> Light {
+        Light {
+            radius,
+            color,
+            intensity,
+            flicker: 0.0,
+            offset: Vector2::zero(),
+            time: 0.0,
+            current: intensity,
+        }
+    }
+
+    pub fn flickering(mut self, flicker: f32) -> Light {
+        self.flicker = flicker.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn offset(mut self, offset: Vector2) -> Light {
+        self.offset = offset;
+        self
+    }
+
+    /// Short bright flash at `pos` that fades out over `duration` seconds,
+    /// for explosions and muzzle flashes.
+    pub fn flash(world: &mut World, pos: Vector2, light: Light, duration: f32) -> EntityID {
+        let id = world.new_entity();
+        let mut base2d = Base2D::new(pos, Vector2::zero());
+        base2d.name = "Light flash".to_string();
+        base2d.tint = Color::BLANK;
+        world.base_components.push((id, base2d));
+        world
+            .lifetime_components
+            .push((id, Lifetime::new(duration)));
+        world.light_components.push((id, light));
+        id
+    }
+}
+
+impl UpdateSystem for Light {
+    fn update_system(world: &mut World, dt: f32) {
+        for (id, light) in world.light_components.iter_mut() {
+            light.time += dt;
+            // a few out of tune sines are enough to read as flicker, the
+            // id shifts the phase so lights don't pulse in sync
+            let t = light.time + *id as f32 * 1.7;
+            let noise = ((t * 13.0).sin() + (t * 29.0).sin() * 0.5 + (t * 7.0).sin() * 0.3) / 1.8;
+            let flicker = 1.0 - light.flicker * (noise * 0.5 + 0.5);
+
+            let fade = world
+                .lifetime_components
+                .iter()
+                .find(|l| l.0 == *id)
+                .map(|l| 1.0 - l.1.progress())
+                .unwrap_or(1.0);
+            light.current = light.intensity * flicker * fade;
+        }
+    }
+}
+
+/// Render target the lights are accumulated in, composited additively over
+/// the world so lights only ever brighten what's below them.
+pub struct LightMap {
+    target: RenderTexture2D,
+}
+
+impl LightMap {
+    pub fn new(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        width: u32,
+        height: u32,
+    ) -> Result<LightMap, String> {
+        let target = rl.load_render_texture(thread, width, height)?;
+        Ok(LightMap { target })
+    }
+
+    /// Draws every light into the map, once per camera viewport. Has to run
+    /// before the canvas is bound, raylib can't nest render targets.
+    pub fn render(&mut self, world: &World, d: &mut RaylibDrawHandle) {
+        let target: &ffi::RenderTexture2D = self.target.as_ref();
+        // SAFETY: the map's target lives as long as the map, and nothing
+        // else is bound yet, see above. Ended at the bottom.
+        unsafe {
+            ffi::BeginTextureMode(*target);
+        }
+        d.clear_background(Color::BLACK);
+        for view in render::views(world) {
+            let bounds = match view.bounds {
+                Some(b) => b,
+                None => continue,
+            };
+            render::begin_viewport(view.viewport);
+            // SAFETY: plain values, ended in reverse order right after.
+            unsafe {
+                ffi::BeginMode2D(view.camera.into());
+                ffi::BeginBlendMode(BlendMode::BLEND_ADDITIVE as i32);
+            }
+            Self::draw_lights(world, d, bounds);
+            // SAFETY: ends the modes begun above, no arguments.
+            unsafe {
+                ffi::EndBlendMode();
+                ffi::EndMode2D();
+            }
+            render::end_viewport(view.viewport);
+        }
+        // SAFETY: ends the texture mode begun at the top.
+        unsafe {
+            ffi::EndTextureMode();
+        }
+    }
+
+    fn draw_lights(world: &World, d: &mut RaylibDrawHandle, view: BoundingBox2D) {
+        for (id, light) in world.light_components.iter() {
+            if light.current <= 0.0 {
+                continue;
+            }
+            let center = match world.base_components.iter().find(|b| b.0 == *id) {
+                Some(b) if b.1.visible => b.1.bounds.center() + light.offset,
+                _ => continue,
+            };
+            // the glow reaches well past the entity, so cull on its radius
+            // instead of the in_view list
+            let reach = BoundingBox2D::new(
+                center.x - light.radius,
+                center.y - light.radius,
+                light.radius * 2.0,
+                light.radius * 2.0,
+            );
+            if !view.overlaps(&reach) {
+                continue;
+            }
+            let inner = scale(world.palette.remap(light.color), light.current);
+            d.draw_circle_gradient(
+                center.x as i32,
+                center.y as i32,
+                light.radius,
+                inner,
+                Color::BLACK,
+            );
+        }
+    }
+
+    /// Adds the light map onto whatever is bound, in screen space.
+    pub fn composite(&self, d: &mut RaylibDrawHandle) {
+        let texture = self.target.texture();
+        let size = Vector2::new(texture.width() as f32, texture.height() as f32);
+        let source = Rectangle::new(0.0, 0.0, size.x, -size.y);
+        let dest = Rectangle::new(0.0, 0.0, size.x, size.y);
+        // SAFETY: a plain value, the blend mode is ended right after.
+        unsafe {
+            ffi::BeginBlendMode(BlendMode::BLEND_ADDITIVE as i32);
+        }
+        d.draw_texture_pro(texture, source, dest, Vector2::zero(), 0.0, Color::WHITE);
+        // SAFETY: ends the blend mode from above, no arguments.
+        unsafe {
+            ffi::EndBlendMode();
+        }
+    }
+}
+
+/// Color brightened or darkened by `factor`. Brightness lives in the color
+/// channels, the gradients run out to black which adds nothing.
+fn scale(color: Color, factor: f32) -> Color {
+    let channel = |c: u8| (c as f32 * factor).clamp(0.0, 255.0) as u8;
+    Color::new(channel(color.r), channel(color.g), channel(color.b), 255)
+}
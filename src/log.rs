@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, TryLockError};
+use std::time::Instant;
+
+use raylib::prelude::*;
+
+use crate::renderer::Renderer;
+use crate::World;
+
+/// Records kept in memory for the log panel and crash reports.
+const RECENT: usize = 500;
+/// Size at which the log file is rotated.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+/// Rotated files kept next to the current one, `game.1.log` is the newest.
+const KEEP_FILES: usize = 3;
+const PANEL_LINES: usize = 20;
+
+/// Severity of a record, most severe first like the log crate's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    pub fn values() -> Vec<Level> {
+        vec![Level::Error, Level::Warn, Level::Info, Level::Debug]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Level::Error => Color::RED,
+            Level::Warn => Color::ORANGE,
+            Level::Info => Color::RAYWHITE,
+            Level::Debug => Color::GRAY,
+        }
+    }
+}
+
+/// One log entry: what happened, in which part of the game and the values
+/// that go with it.
+#[derive(Debug, Clone)]
+pub struct Record {
+    /// Seconds since the logger started.
+    pub time: f32,
+    pub level: Level,
+    pub target: &'static str,
+    pub message: String,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{:>9.3}] {:<5} {}: {}",
+            self.time,
+            self.level.name(),
+            self.target,
+            self.message
+        )?;
+        for (key, value) in self.fields.iter() {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+struct Logger {
+    start: Instant,
+    max_level: Level,
+    path: Option<PathBuf>,
+    file: Option<File>,
+    recent: VecDeque<Record>,
+}
+
+impl Logger {
+    fn new() -> Logger {
+        Logger {
+            start: Instant::now(),
+            max_level: Level::Info,
+            path: None,
+            file: None,
+            recent: VecDeque::with_capacity(RECENT),
+        }
+    }
+
+    /// Moves `game.log` to `game.1.log`, `game.1.log` to `game.2.log` and
+    /// so on, dropping the oldest.
+    fn rotate(&mut self) {
+        let path = match self.path.as_ref() {
+            Some(p) => p.clone(),
+            None => return,
+        };
+        self.file = None;
+        let numbered = |i: usize| path.with_extension(format!("{}.log", i));
+        for i in (1..KEEP_FILES).rev() {
+            fs::rename(numbered(i), numbered(i + 1)).ok();
+        }
+        fs::rename(&path, numbered(1)).ok();
+        self.file = File::create(&path).ok();
+    }
+
+    fn write(&mut self, record: Record) {
+        let too_big = self
+            .file
+            .as_ref()
+            .and_then(|f| f.metadata().ok())
+            .is_some_and(|m| m.len() > MAX_FILE_SIZE);
+        if too_big {
+            self.rotate();
+        }
+        let line = record.to_string();
+        if let Some(file) = self.file.as_mut() {
+            writeln!(file, "{}", line).ok();
+        }
+        if record.level <= Level::Warn {
+            eprintln!("{}", line);
+        }
+        if self.recent.len() == RECENT {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(record);
+    }
+}
+
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+
+fn with_logger<T>(f: impl FnOnce(&mut Logger) -> T) -> T {
+    // a panic while logging poisons the lock, the logger itself is fine
+    let mut guard = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(Logger::new))
+}
+
+/// Starts writing records to `path`, rotating the file left by the last
+/// run. Records before this only go to memory.
+pub fn init(path: impl Into<PathBuf>) {
+    let path = path.into();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).ok();
+    }
+    with_logger(|logger| {
+        logger.path = Some(path.clone());
+        if path.exists() {
+            logger.rotate();
+        } else {
+            logger.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .ok();
+        }
+    });
+}
+
+/// Least severe level that gets recorded.
+pub fn set_max_level(level: Level) {
+    with_logger(|logger| logger.max_level = level);
+}
+
+pub fn enabled(level: Level) -> bool {
+    with_logger(|logger| level <= logger.max_level)
+}
+
+/// Records a message, use the macros instead.
+pub fn write(
+    level: Level,
+    target: &'static str,
+    message: String,
+    fields: Vec<(&'static str, String)>,
+) {
+    with_logger(|logger| {
+        if level > logger.max_level {
+            return;
+        }
+        let record = Record {
+            time: logger.start.elapsed().as_secs_f32(),
+            level,
+            target,
+            message,
+            fields,
+        };
+        logger.write(record);
+    });
+}
+
+/// The last `count` records, oldest first.
+pub fn recent(count: usize) -> Vec<Record> {
+    with_logger(|logger| {
+        let skip = logger.recent.len().saturating_sub(count);
+        logger.recent.iter().skip(skip).cloned().collect()
+    })
+}
+
+/// Like `recent`, but gives nothing instead of waiting when the logger is
+/// busy. For the panic hook, which may run while this thread holds it.
+pub fn try_recent(count: usize) -> Vec<Record> {
+    let guard = match LOGGER.try_lock() {
+        Ok(guard) => guard,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) => return Vec::new(),
+    };
+    guard.as_ref().map_or(Vec::new(), |logger| {
+        let skip = logger.recent.len().saturating_sub(count);
+        logger.recent.iter().skip(skip).cloned().collect()
+    })
+}
+
+/// Records a message under a target, with `key = value` fields after it:
+/// `log!(Level::Info, "assets", "Texture loaded", path = path)`.
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $target:expr, $message:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::log::enabled($level) {
+            $crate::log::write(
+                $level,
+                $target,
+                $message.to_string(),
+                vec![$((stringify!($key), $value.to_string())),*],
+            );
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Error, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Warn, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Info, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Debug, $($arg)*) };
+}
+
+/// Recent log records over the game, toggled with F8. Shift+F8 picks the
+/// least severe level shown, Debug also turns on recording it.
+pub struct LogPanel {
+    open: bool,
+    level: Level,
+}
+
+impl LogPanel {
+    pub fn new() -> LogPanel {
+        LogPanel {
+            open: false,
+            level: Level::Info,
+        }
+    }
+
+    pub fn input_system(&mut self, rl: &RaylibHandle) {
+        if !rl.is_key_pressed(KeyboardKey::KEY_F8) {
+            return;
+        }
+        let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+        if !shift {
+            self.open = !self.open;
+            return;
+        }
+        let levels = Level::values();
+        let i = levels.iter().position(|l| *l == self.level).unwrap_or(0);
+        self.level = levels[(i + 1) % levels.len()];
+        set_max_level(self.level.max(Level::Info));
+        self.open = true;
+    }
+
+    /// Height of the panel, it sits at the bottom of the screen.
+    fn height() -> f32 {
+        (PANEL_LINES + 1) as f32 * 11.0 + 8.0
+    }
+
+    /// Whether `pos` is on the panel while it's open.
+    pub fn covers(&self, world: &World, pos: Vector2) -> bool {
+        self.open && pos.y > world.screen.height() - LogPanel::height()
+    }
+
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if !self.open {
+            return;
+        }
+        let records: Vec<Record> = recent(RECENT)
+            .into_iter()
+            .filter(|r| r.level <= self.level)
+            .collect();
+        let shown = &records[records.len().saturating_sub(PANEL_LINES)..];
+
+        let (w, h) = (world.screen.width(), world.screen.height());
+        let height = LogPanel::height();
+        let top = h - height;
+        d.draw_rect(Rectangle::new(0.0, top, w, height), Color::BLACK.fade(0.8));
+        let title = format!("Log, {} and up (Shift+F8)", self.level.name());
+        d.draw_text(&title, 4, top as i32 + 4, 10, Color::GOLD);
+        for (i, record) in shown.iter().enumerate() {
+            let y = top as i32 + 15 + i as i32 * 11;
+            d.draw_text(&record.to_string(), 4, y, 10, record.level.color());
+        }
+    }
+}
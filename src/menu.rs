@@ -0,0 +1,265 @@
+use raylib::prelude::*;
+
+use crate::assets::Handle;
+use crate::flash::DamageFlash;
+use crate::font::{self, TextStyle};
+use crate::movement::Velocity;
+use crate::pilot::Pilot;
+use crate::renderer::Renderer;
+use crate::rng::Rng;
+use crate::save::SaveSlots;
+use crate::settings::SettingsMenu;
+use crate::state::GameState;
+use crate::t;
+use crate::team::Team;
+use crate::ui::UiElement;
+use crate::weapon::Weapon;
+use crate::widget::{UiEventKind, Widget};
+use crate::{Anchor, Base2D, EntityID, Health, World};
+
+/// Middle of the attract mode dogfight, far away from the level.
+const ARENA: Vector2 = Vector2 {
+    x: -3000.0,
+    y: -3000.0,
+};
+/// Where new ships come in, either side of the arena.
+const ARENA_RADIUS: f32 = 260.0;
+/// Ships straying further than this are taken out of the fight.
+const LEASH: f32 = 700.0;
+const SHIPS_PER_SIDE: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuButton {
+    Start,
+    Gallery,
+    Settings,
+    Credits,
+    Quit,
+}
+
+impl MenuButton {
+    fn values() -> Vec<MenuButton> {
+        vec![
+            MenuButton::Start,
+            MenuButton::Gallery,
+            MenuButton::Settings,
+            MenuButton::Credits,
+            MenuButton::Quit,
+        ]
+    }
+
+    fn label(&self) -> String {
+        match self {
+            MenuButton::Start => t!("menu.start"),
+            MenuButton::Gallery => t!("menu.gallery"),
+            MenuButton::Settings => t!("menu.settings"),
+            MenuButton::Credits => t!("menu.credits"),
+            MenuButton::Quit => t!("menu.quit"),
+        }
+    }
+}
+
+/// Title screen shown in the Menu state: the logo and its buttons over a
+/// dogfight between computer pilots. The cameras are borrowed to watch the
+/// fight and handed back to whatever they followed when the game starts.
+pub struct MainMenu {
+    /// Drawn above the buttons, the game's name in text without it.
+    pub logo: Option<Handle<Texture2D>>,
+    /// Set by the Quit button, the main loop stops on it.
+    pub quit: bool,
+    open: bool,
+    buttons: Vec<(EntityID, MenuButton)>,
+    ships: Vec<EntityID>,
+    cameras: Vec<(EntityID, Option<EntityID>)>,
+    rng: Rng,
+}
+
+impl MainMenu {
+    pub fn new() -> MainMenu {
+        MainMenu {
+            logo: None,
+            quit: false,
+            open: false,
+            buttons: Vec::new(),
+            ships: Vec::new(),
+            cameras: Vec::new(),
+            rng: Rng::new(0x7171),
+        }
+    }
+
+    fn open(&mut self, world: &mut World) {
+        self.open = true;
+        self.spawn_buttons(world);
+        for (id, camera) in world.camera_components.iter_mut() {
+            self.cameras.push((*id, camera.follow.take()));
+            camera.target = ARENA;
+        }
+    }
+
+    fn spawn_buttons(&mut self, world: &mut World) {
+        for (i, button) in MenuButton::values().into_iter().enumerate() {
+            let offset = Vector2::new(0.0, 20.0 + i as f32 * 28.0);
+            let id = Widget::spawn(
+                world,
+                Widget::button(button.label()),
+                UiElement::new(Anchor::Center, offset, Vector2::new(140.0, 24.0)),
+            );
+            self.buttons.push((id, button));
+        }
+        world.focus.focused = self.buttons.first().map(|b| b.0);
+    }
+
+    fn despawn_buttons(&mut self, world: &mut World) {
+        for (id, _) in self.buttons.drain(..) {
+            world.despawn(id);
+        }
+    }
+
+    fn close(&mut self, world: &mut World) {
+        self.open = false;
+        self.despawn_buttons(world);
+        for id in self.ships.drain(..) {
+            world.despawn(id);
+        }
+        // snap back, a pan all the way from the arena would look silly
+        for (id, follow) in self.cameras.drain(..) {
+            let target = follow
+                .and_then(|f| world.base_components.iter().find(|b| b.0 == f))
+                .map(|b| b.1.bounds.center());
+            if let Some(camera) = world.camera_components.iter_mut().find(|c| c.0 == id) {
+                camera.1.follow = follow;
+                if let Some(target) = target {
+                    camera.1.target = target;
+                }
+            }
+        }
+    }
+
+    fn spawn_ship(&mut self, world: &mut World, team: Team) {
+        let side = if team == Team::Friendly { -1.0 } else { 1.0 };
+        let pos = ARENA
+            + Vector2::new(
+                side * ARENA_RADIUS,
+                self.rng.range(-ARENA_RADIUS, ARENA_RADIUS) * 0.5,
+            );
+
+        let id = world.new_entity();
+        let mut base2d = Base2D::new(pos, Vector2::new(24.0, 18.0));
+        base2d.name = "Fighter".to_string();
+        base2d.tint = team.color();
+        world.base_components.push((id, base2d));
+        world.health_components.push((id, Health::new(6)));
+        world.team_components.push((id, team));
+        world.velocity_components.push((id, Velocity::new(1.5)));
+        world
+            .weapon_components
+            .push((id, Weapon::new(2.5, self.rng.next_u64())));
+        world
+            .pilot_components
+            .push((id, Pilot::new(140.0, self.rng.next_u64())));
+        world
+            .flash_components
+            .push((id, DamageFlash::new(Color::WHITE, 0.15)));
+        self.ships.push(id);
+    }
+
+    /// Opens with the Menu state and closes when it's left, reacts to the
+    /// buttons and keeps the dogfight going.
+    pub fn update_system(
+        &mut self,
+        world: &mut World,
+        settings: &mut SettingsMenu,
+        slots: &mut SaveSlots,
+    ) {
+        // the dogfight carries on behind the credits and the gallery
+        let in_menu = world.state.current().is_title();
+        if in_menu && !self.open {
+            self.open(world);
+        } else if !in_menu && self.open {
+            self.close(world);
+        }
+        if !self.open {
+            return;
+        }
+        // the settings and slot panels take the buttons' place while they're up
+        if settings.is_open() || slots.is_open() || !world.state.is(GameState::Menu) {
+            self.despawn_buttons(world);
+        } else if self.buttons.is_empty() {
+            self.spawn_buttons(world);
+        }
+
+        let clicked: Vec<MenuButton> = world
+            .ui_events
+            .iter()
+            .filter(|e| e.kind == UiEventKind::Clicked)
+            .filter_map(|e| self.buttons.iter().find(|b| b.0 == e.widget))
+            .map(|b| b.1)
+            .collect();
+        for button in clicked {
+            match button {
+                MenuButton::Start => slots.open(),
+                MenuButton::Gallery => world.state.set(GameState::Gallery),
+                MenuButton::Settings => settings.open(),
+                MenuButton::Credits => world.state.set(GameState::Credits),
+                MenuButton::Quit => self.quit = true,
+            }
+        }
+
+        // the fallen are despawned by the main loop, stragglers here
+        let strays: Vec<EntityID> = world
+            .base_components
+            .iter()
+            .filter(|b| self.ships.contains(&b.0) && b.1.bounds.center().distance_to(ARENA) > LEASH)
+            .map(|b| b.0)
+            .collect();
+        for id in strays {
+            world.despawn(id);
+        }
+        self.ships
+            .retain(|id| world.base_components.iter().any(|b| b.0 == *id));
+
+        for team in [Team::Friendly, Team::Hostile] {
+            let count = self
+                .ships
+                .iter()
+                .filter(|id| {
+                    world
+                        .team_components
+                        .iter()
+                        .any(|t| t.0 == **id && t.1 == team)
+                })
+                .count();
+            for _ in count..SHIPS_PER_SIDE {
+                self.spawn_ship(world, team);
+            }
+        }
+    }
+
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if !self.open || !world.state.is(GameState::Menu) {
+            return;
+        }
+        let (w, h) = (world.screen.width(), world.screen.height());
+        let center = Vector2::new(w * 0.5, h * 0.3);
+        match self.logo.and_then(|l| world.assets.texture(l)) {
+            Some(logo) => {
+                let size = Vector2::new(logo.width as f32, logo.height as f32);
+                let pos = center - size * 0.5;
+                d.draw_sprite(
+                    logo,
+                    Rectangle::new(0.0, 0.0, size.x, size.y),
+                    Rectangle::new(pos.x, pos.y, size.x, size.y),
+                    Vector2::zero(),
+                    0.0,
+                    Color::WHITE,
+                );
+            }
+            None => {
+                let style = TextStyle::new(40.0, Color::RAYWHITE)
+                    .aligned(Anchor::Center)
+                    .outlined(Color::BLACK);
+                font::draw(&world.assets, d, "SPACEGAME", center, &style);
+            }
+        }
+    }
+}
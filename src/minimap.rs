@@ -0,0 +1,122 @@
+use raylib::prelude::*;
+
+use crate::hazard::Hazard;
+use crate::renderer;
+use crate::team::Team;
+use crate::{t, World};
+
+const SIZE: u32 = 120;
+const MARGIN: f32 = 10.0;
+/// World radius covered by the radar at each zoom level.
+const ZOOM_LEVELS: [f32; 3] = [300.0, 600.0, 1200.0];
+
+/// Corner radar around the player. M toggles it, Z cycles the zoom level.
+pub struct Minimap {
+    target: Option<RenderTexture2D>,
+    visible: bool,
+    zoom: usize,
+}
+
+impl Minimap {
+    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Minimap {
+        Minimap {
+            target: rl.load_render_texture(thread, SIZE, SIZE).ok(),
+            visible: true,
+            zoom: 0,
+        }
+    }
+
+    pub fn input_system(&mut self, rl: &RaylibHandle) {
+        if rl.is_key_pressed(KeyboardKey::KEY_M) {
+            self.visible = !self.visible;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_Z) {
+            self.zoom = (self.zoom + 1) % ZOOM_LEVELS.len();
+        }
+    }
+
+    /// Draws the radar into its texture. Has to happen outside of any other
+    /// texture mode, so before the canvas is started.
+    pub fn render(&mut self, world: &World, d: &mut RaylibDrawHandle, thread: &RaylibThread) {
+        let target = match self.target.as_mut() {
+            Some(t) if self.visible => t,
+            _ => return,
+        };
+        let player = match world.player_components.first() {
+            Some(p) => p.0,
+            None => return,
+        };
+        let origin = match world.base_components.iter().find(|b| b.0 == player) {
+            Some(b) => b.1.bounds.center(),
+            None => return,
+        };
+
+        let radius = SIZE as f32 * 0.5;
+        let scale = radius / ZOOM_LEVELS[self.zoom];
+        let jammed = Hazard::sensors_jammed(world, player);
+
+        {
+            let mut t = d.begin_texture_mode(thread, target);
+            t.clear_background(Color::BLANK);
+            // everything outside this disc stays transparent
+            t.draw_circle(radius as i32, radius as i32, radius, Color::BLACK.fade(0.7));
+
+            if jammed {
+                t.draw_text(
+                    &t!("hud.no_signal"),
+                    22,
+                    radius as i32 - 6,
+                    14,
+                    Color::PURPLE,
+                );
+            } else {
+                for (id, base) in world
+                    .base_components
+                    .iter()
+                    .filter(|b| b.1.visible && b.1.tint.a > 0)
+                {
+                    let offset = (base.bounds.center() - origin) * scale;
+                    if offset.length() > radius - 2.0 {
+                        continue;
+                    }
+                    let color = if *id == player {
+                        Color::WHITE
+                    } else {
+                        world
+                            .team_components
+                            .iter()
+                            .find(|t| t.0 == *id)
+                            .map(|t| world.palette.team(t.1))
+                            .unwrap_or(Team::Neutral.color())
+                    };
+                    let pos = Vector2::new(radius, radius) + offset;
+                    t.draw_circle_v(pos, 2.0, color);
+                }
+            }
+
+            t.draw_circle_lines(radius as i32, radius as i32, radius - 1.0, Color::DARKGREEN);
+        }
+    }
+
+    pub fn draw_system(&self, world: &World, d: &mut dyn renderer::Renderer) {
+        let target = match self.target.as_ref() {
+            Some(t) if self.visible => t,
+            _ => return,
+        };
+        if world.player_components.is_empty() {
+            return;
+        }
+
+        // render textures are stored upside down
+        let x = world.screen.width() - SIZE as f32 - MARGIN;
+        let source = Rectangle::new(0.0, 0.0, SIZE as f32, -(SIZE as f32));
+        d.draw_sprite(
+            target.texture(),
+            source,
+            Rectangle::new(x, MARGIN, SIZE as f32, SIZE as f32),
+            Vector2::zero(),
+            0.0,
+            Color::WHITE,
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use raylib::prelude::*;
+
+use crate::group;
+use crate::{UpdateSystem, World};
+
+pub struct Velocity {
+    pub velocity: Vector2,
+    /// Fraction of velocity lost per second.
+    pub drag: f32,
+}
+
+impl Velocity {
+    pub fn new(drag: f32) -> Velocity {
+        Velocity {
+            velocity: Vector2::zero(),
+            drag,
+        }
+    }
+}
+
+impl UpdateSystem for Velocity {
+    fn update_system(world: &mut World, dt: f32) {
+        world.sort_groups();
+        for (_, vel) in world.velocity_components.iter_mut() {
+            vel.velocity *= (1.0 - vel.drag * dt).max(0.0);
+        }
+        group::join_mut(
+            &mut world.velocity_components,
+            &mut world.base_components,
+            |_, vel, b| b.bounds.translate(vel.velocity * dt),
+        );
+    }
+}
+
+/// Rotates `v` by `radians`. With y pointing down this turns clockwise on screen.
+pub fn rotated(v: Vector2, radians: f32) -> Vector2 {
+    let (sin, cos) = radians.sin_cos();
+    Vector2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
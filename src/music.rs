@@ -0,0 +1,187 @@
+use raylib::prelude::*;
+
+use crate::pack;
+
+/// Seconds a crossfade between tracks takes.
+const CROSSFADE_TIME: f32 = 1.5;
+/// How fast ducking recovers once its hold is over, per second.
+const DUCK_RELEASE: f32 = 0.8;
+
+/// What the music should be doing, one track each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicCue {
+    Menu,
+    Gameplay,
+    Boss,
+}
+
+/// Streamed track, optionally with an intro that plays once before the
+/// body starts looping.
+struct Track {
+    cue: MusicCue,
+    intro: Option<Music>,
+    body: Music,
+    in_intro: bool,
+}
+
+impl Track {
+    fn stream(&mut self) -> &mut Music {
+        match self.intro.as_mut() {
+            Some(intro) if self.in_intro => intro,
+            _ => &mut self.body,
+        }
+    }
+}
+
+/// Plays one track per cue, crossfading when the cue changes. Ducking
+/// lowers the music for a moment to let a big event be heard.
+pub struct MusicPlayer {
+    tracks: Vec<Track>,
+    current: Option<MusicCue>,
+    /// Track being faded out.
+    previous: Option<MusicCue>,
+    /// Crossfade progress, 1 when the current track is at full volume.
+    fade: f32,
+    pub volume: f32,
+    /// Volume multiplier while ducked, 1 when not.
+    duck: f32,
+    duck_hold: f32,
+}
+
+impl MusicPlayer {
+    pub fn new() -> MusicPlayer {
+        MusicPlayer {
+            tracks: Vec::new(),
+            current: None,
+            previous: None,
+            fade: 1.0,
+            volume: 1.0,
+            duck: 1.0,
+            duck_hold: 0.0,
+        }
+    }
+
+    /// Adds the track for `cue`. The intro, when given, plays once each time
+    /// the track starts.
+    pub fn load(
+        &mut self,
+        thread: &RaylibThread,
+        cue: MusicCue,
+        intro: Option<&str>,
+        body: &str,
+    ) -> Result<(), String> {
+        let body = pack::load_music(thread, body)?;
+        let intro = match intro {
+            Some(path) => {
+                let mut intro = pack::load_music(thread, path)?;
+                intro.looping = false;
+                Some(intro)
+            }
+            None => None,
+        };
+        self.tracks.retain(|t| t.cue != cue);
+        // the old track is gone, playing the cue again starts the new one
+        if self.current == Some(cue) {
+            self.current = None;
+        }
+        if self.previous == Some(cue) {
+            self.previous = None;
+        }
+        self.tracks.push(Track {
+            cue,
+            intro,
+            body,
+            in_intro: false,
+        });
+        Ok(())
+    }
+
+    /// Crossfades to the track for `cue`. Staying on the same cue keeps the
+    /// track going, cues without a track fade to silence.
+    pub fn play(&mut self, audio: &mut RaylibAudio, cue: MusicCue) {
+        if self.current == Some(cue) {
+            return;
+        }
+        if let Some(previous) = self.previous.take() {
+            self.stop(audio, previous);
+        }
+        self.previous = self.current;
+        self.current = Some(cue);
+        self.fade = if self.previous.is_some() { 0.0 } else { 1.0 };
+
+        if let Some(track) = self.tracks.iter_mut().find(|t| t.cue == cue) {
+            track.in_intro = track.intro.is_some();
+            let stream = track.stream();
+            audio.play_music_stream(stream);
+            audio.set_music_volume(stream, 0.0);
+        }
+    }
+
+    /// Drops the music to `level` for `seconds`, then lets it swell back.
+    pub fn duck(&mut self, level: f32, seconds: f32) {
+        self.duck = self.duck.min(level);
+        self.duck_hold = self.duck_hold.max(seconds);
+    }
+
+    fn stop(&mut self, audio: &mut RaylibAudio, cue: MusicCue) {
+        if let Some(track) = self.tracks.iter_mut().find(|t| t.cue == cue) {
+            if let Some(intro) = track.intro.as_mut() {
+                audio.stop_music_stream(intro);
+            }
+            audio.stop_music_stream(&mut track.body);
+        }
+    }
+
+    /// Feeds the streams and moves fades along, every frame in real time.
+    pub fn update(&mut self, audio: &mut RaylibAudio, dt: f32) {
+        if self.fade < 1.0 {
+            self.fade = (self.fade + dt / CROSSFADE_TIME).min(1.0);
+            if self.fade >= 1.0 {
+                if let Some(previous) = self.previous.take() {
+                    self.stop(audio, previous);
+                }
+            }
+        }
+        if self.duck_hold > 0.0 {
+            self.duck_hold -= dt;
+        } else {
+            self.duck = (self.duck + DUCK_RELEASE * dt).min(1.0);
+        }
+
+        let gain = self.volume * self.duck;
+        for track in self.tracks.iter_mut() {
+            let level = if Some(track.cue) == self.current {
+                self.fade
+            } else if Some(track.cue) == self.previous {
+                1.0 - self.fade
+            } else {
+                continue;
+            };
+
+            // the intro stops on its own, the body takes over seamlessly
+            // enough at stream granularity
+            let intro_done = track
+                .intro
+                .as_ref()
+                .is_some_and(|intro| track.in_intro && !audio.is_music_playing(intro));
+            if intro_done {
+                track.in_intro = false;
+                audio.play_music_stream(&mut track.body);
+            }
+
+            let stream = track.stream();
+            audio.set_music_volume(stream, level * gain);
+            audio.update_music_stream(stream);
+        }
+    }
+
+    /// Seconds into the looping body of the track for `cue`, None when that
+    /// isn't what's playing.
+    pub fn position(&self, audio: &RaylibAudio, cue: MusicCue) -> Option<f32> {
+        let track = self.tracks.iter().find(|t| t.cue == cue)?;
+        if self.current != Some(cue) || track.in_intro || !audio.is_music_playing(&track.body) {
+            return None;
+        }
+        Some(audio.get_music_time_played(&track.body))
+    }
+}
@@ -0,0 +1,69 @@
+use raylib::prelude::*;
+
+use crate::assets::{Assets, Handle};
+use crate::atlas::Atlas;
+use crate::renderer::Renderer;
+
+/// Texture region cut into a 3x3 grid by its borders. Corners keep their
+/// size, edges stretch along one axis and the center along both, so a small
+/// frame image fits panels and buttons of any size.
+#[derive(Debug, Clone, Copy)]
+pub struct NineSlice {
+    pub texture: Handle<Texture2D>,
+    pub source: Rectangle,
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl NineSlice {
+    /// Whole texture with the same border on every side.
+    pub fn new(assets: &Assets, texture: Handle<Texture2D>, border: i32) -> Option<NineSlice> {
+        let t = assets.texture(texture)?;
+        Some(NineSlice {
+            texture,
+            source: Rectangle::new(0.0, 0.0, t.width() as f32, t.height() as f32),
+            left: border,
+            top: border,
+            right: border,
+            bottom: border,
+        })
+    }
+
+    /// Atlas region with the borders given by its `border` line.
+    pub fn from_atlas(assets: &Assets, atlas: Handle<Atlas>, region: &str) -> Option<NineSlice> {
+        let atlas = assets.atlas(atlas)?;
+        let [left, top, right, bottom] = atlas.border(region)?;
+        Some(NineSlice {
+            texture: atlas.texture,
+            source: atlas.region(region)?,
+            left,
+            top,
+            right,
+            bottom,
+        })
+    }
+
+    pub fn draw(
+        &self,
+        assets: &Assets,
+        d: &mut dyn Renderer,
+        bounds: impl Into<Rectangle>,
+        tint: Color,
+    ) {
+        let texture = match assets.texture(self.texture) {
+            Some(t) => t,
+            None => return,
+        };
+        let info = NPatchInfo {
+            source: self.source,
+            left: self.left,
+            top: self.top,
+            right: self.right,
+            bottom: self.bottom,
+            layout: NPatchLayout::NPATCH_NINE_PATCH,
+        };
+        d.draw_nine_slice(texture, info, bounds.into(), tint);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::damage::{DamageEvent, DeathEvent, HealEvent};
+use crate::weapon::ShotEvent;
+use crate::World;
+
+/// Called once for every event of its type, see `World::on_event`.
+pub type Handler<E> = fn(&mut World, &E);
+
+/// An event queue handlers can subscribe to.
+pub trait Event: Sized + 'static {
+    fn queue(world: &mut World) -> &mut Vec<Self>;
+    fn handlers(observers: &Observers) -> &Vec<Handler<Self>>;
+    fn handlers_mut(observers: &mut Observers) -> &mut Vec<Handler<Self>>;
+}
+
+impl Event for ShotEvent {
+    fn queue(world: &mut World) -> &mut Vec<ShotEvent> {
+        &mut world.shot_events
+    }
+
+    fn handlers(observers: &Observers) -> &Vec<Handler<ShotEvent>> {
+        &observers.shot
+    }
+
+    fn handlers_mut(observers: &mut Observers) -> &mut Vec<Handler<ShotEvent>> {
+        &mut observers.shot
+    }
+}
+
+impl Event for DamageEvent {
+    fn queue(world: &mut World) -> &mut Vec<DamageEvent> {
+        &mut world.damage_events
+    }
+
+    fn handlers(observers: &Observers) -> &Vec<Handler<DamageEvent>> {
+        &observers.damage
+    }
+
+    fn handlers_mut(observers: &mut Observers) -> &mut Vec<Handler<DamageEvent>> {
+        &mut observers.damage
+    }
+}
+
+impl Event for HealEvent {
+    fn queue(world: &mut World) -> &mut Vec<HealEvent> {
+        &mut world.heal_events
+    }
+
+    fn handlers(observers: &Observers) -> &Vec<Handler<HealEvent>> {
+        &observers.heal
+    }
+
+    fn handlers_mut(observers: &mut Observers) -> &mut Vec<Handler<HealEvent>> {
+        &mut observers.heal
+    }
+}
+
+impl Event for DeathEvent {
+    fn queue(world: &mut World) -> &mut Vec<DeathEvent> {
+        &mut world.death_events
+    }
+
+    fn handlers(observers: &Observers) -> &Vec<Handler<DeathEvent>> {
+        &observers.death
+    }
+
+    fn handlers_mut(observers: &mut Observers) -> &mut Vec<Handler<DeathEvent>> {
+        &mut observers.death
+    }
+}
+
+/// Handlers gameplay layers subscribed to the shot, damage, heal and
+/// death events with, so achievements, stats or sounds can follow the fight
+/// without the damage system knowing about them.
+pub struct Observers {
+    shot: Vec<Handler<ShotEvent>>,
+    damage: Vec<Handler<DamageEvent>>,
+    heal: Vec<Handler<HealEvent>>,
+    death: Vec<Handler<DeathEvent>>,
+}
+
+impl Observers {
+    pub fn new() -> Observers {
+        Observers {
+            shot: Vec::new(),
+            damage: Vec::new(),
+            heal: Vec::new(),
+            death: Vec::new(),
+        }
+    }
+
+    /// Hands this frame's events to their handlers, called by
+    /// `World::end_frame` while the dead are still around. Shots go first,
+    /// then damage, heals and deaths, so a death a damage handler pushes
+    /// is still handled. Events pushed to a queue that was already handed out
+    /// are dropped with the rest at the end of the frame.
+    pub fn dispatch(world: &mut World) {
+        dispatch::<ShotEvent>(world);
+        dispatch::<DamageEvent>(world);
+        dispatch::<HealEvent>(world);
+        dispatch::<DeathEvent>(world);
+    }
+}
+
+fn dispatch<E: Event>(world: &mut World) {
+    let handlers = E::handlers(&world.observers).clone();
+    if handlers.is_empty() {
+        return;
+    }
+    let events = std::mem::take(E::queue(world));
+    for event in events.iter() {
+        for handler in handlers.iter() {
+            handler(world, event);
+        }
+    }
+    let pushed = std::mem::replace(E::queue(world), events);
+    E::queue(world).extend(pushed);
+}
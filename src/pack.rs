@@ -0,0 +1,152 @@
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use raylib::prelude::*;
+
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+}
+
+/// The asset files built into the executable by the `embed-assets`
+/// feature, by path, or loose files next to it when there are none. Asset
+/// loading goes through here so a release can be one file, while a
+/// development build keeps picking up edits from disk.
+pub fn get(path: impl AsRef<Path>) -> Option<&'static [u8]> {
+    let key = path.as_ref().to_string_lossy().replace('\\', "/");
+    let key = key.trim_start_matches("./");
+    embedded::EMBEDDED.iter().find(|f| f.0 == key).map(|f| f.1)
+}
+
+/// Whether assets come from the executable rather than the folder.
+pub fn is_embedded() -> bool {
+    !embedded::EMBEDDED.is_empty()
+}
+
+pub fn exists(path: impl AsRef<Path>) -> bool {
+    get(&path).is_some() || path.as_ref().exists()
+}
+
+pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    match get(&path) {
+        Some(bytes) => Ok(bytes.to_vec()),
+        None => fs::read(path),
+    }
+}
+
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    match get(&path) {
+        Some(bytes) => String::from_utf8(bytes.to_vec())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        None => fs::read_to_string(path),
+    }
+}
+
+/// The files directly in `dir`, from the executable when assets are
+/// embedded.
+pub fn files_in(dir: &str) -> Vec<PathBuf> {
+    if is_embedded() {
+        return embedded::EMBEDDED
+            .iter()
+            .map(|f| PathBuf::from(f.0))
+            .filter(|p| p.parent() == Some(Path::new(dir)))
+            .collect();
+    }
+    match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|e| e.ok().map(|e| e.path())).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// raylib's loaders from memory want the file type as `.png`.
+fn file_type(path: &str) -> String {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    format!(".{}", extension)
+}
+
+pub fn load_image(path: &str) -> Result<Image, String> {
+    match get(path) {
+        Some(bytes) => {
+            Image::load_image_from_mem(&file_type(path), &bytes.to_vec(), bytes.len() as i32)
+        }
+        None => Image::load_image(path),
+    }
+}
+
+pub fn load_wave(path: &str) -> Result<Wave, String> {
+    let bytes = match get(path) {
+        Some(bytes) => bytes,
+        None => return Wave::load_wave(path),
+    };
+    let file_type = CString::new(file_type(path)).unwrap();
+    // SAFETY: both pointers are valid for the call, raylib copies what it
+    // decodes and keeps neither.
+    let wave =
+        unsafe { ffi::LoadWaveFromMemory(file_type.as_ptr(), bytes.as_ptr(), bytes.len() as i32) };
+    if wave.data.is_null() {
+        return Err(format!("could not load embedded wave {}", path));
+    }
+    // SAFETY: a freshly loaded wave, handed over once to be freed by its
+    // owner.
+    Ok(unsafe { Wave::from_raw(wave) })
+}
+
+/// Music streams keep reading from the file while they play, which is fine
+/// for embedded ones since those live as long as the game.
+pub fn load_music(thread: &RaylibThread, path: &str) -> Result<Music, String> {
+    let bytes = match get(path) {
+        Some(bytes) => bytes,
+        None => return Music::load_music_stream(thread, path),
+    };
+    let file_type = CString::new(file_type(path)).unwrap();
+    // SAFETY: raylib only reads through the pointer, it's mut for C's
+    // sake. The bytes are embedded, so they outlive the stream reading them.
+    let music = unsafe {
+        ffi::LoadMusicStreamFromMemory(
+            file_type.as_ptr(),
+            bytes.as_ptr() as *mut u8,
+            bytes.len() as i32,
+        )
+    };
+    if music.stream.buffer.is_null() {
+        return Err(format!("could not load embedded music {}", path));
+    }
+    // SAFETY: a freshly loaded stream, handed over once like the wave.
+    Ok(unsafe { Music::from_raw(music) })
+}
+
+/// Rasterizes an outline font at `size` pixels, with the glyphs for
+/// `chars`, or the first 95 characters like `load_font_ex` when empty.
+pub fn load_font(path: &str, size: i32, chars: &[i32]) -> Result<Font, String> {
+    let bytes = get(path).ok_or_else(|| format!("{} is not embedded", path))?;
+    let file_type = CString::new(file_type(path)).unwrap();
+    // SAFETY: the pointers are valid for the call, the chars one is only
+    // read and null when there are none, with the count to match.
+    let font = unsafe {
+        ffi::LoadFontFromMemory(
+            file_type.as_ptr(),
+            bytes.as_ptr(),
+            bytes.len() as i32,
+            size,
+            if chars.is_empty() {
+                std::ptr::null_mut()
+            } else {
+                chars.as_ptr() as *mut i32
+            },
+            if chars.is_empty() {
+                95
+            } else {
+                chars.len() as i32
+            },
+        )
+    };
+    if font.chars.is_null() {
+        return Err(format!("could not load embedded font {}", path));
+    }
+    // SAFETY: a freshly loaded font, handed over once like the wave.
+    Ok(unsafe { Font::from_raw(font) })
+}
@@ -0,0 +1,101 @@
+use raylib::prelude::*;
+
+use crate::team::Team;
+
+/// Colors the game uses to tell things apart, and what each becomes in the
+/// modes for color vision deficiencies. Picked from the Okabe-Ito palette,
+/// which stays distinct for both kinds of red-green blindness.
+const TABLE: [(Color, Color, Color); 5] = [
+    // normal, deuteranopia, protanopia
+    (Color::SKYBLUE, rgb(0, 114, 178), rgb(86, 180, 233)),
+    (Color::RED, rgb(230, 159, 0), rgb(240, 228, 66)),
+    (Color::GREEN, rgb(86, 180, 233), rgb(0, 114, 178)),
+    (Color::GOLD, rgb(240, 228, 66), rgb(230, 159, 0)),
+    (Color::YELLOW, rgb(240, 228, 66), rgb(230, 159, 0)),
+];
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color { r, g, b, a: 255 }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Normal,
+    /// Green-weak, the most common kind.
+    Deuteranopia,
+    /// Red-weak, reds also look darker.
+    Protanopia,
+}
+
+impl ColorMode {
+    pub fn values() -> Vec<ColorMode> {
+        vec![
+            ColorMode::Normal,
+            ColorMode::Deuteranopia,
+            ColorMode::Protanopia,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorMode::Normal => "normal",
+            ColorMode::Deuteranopia => "deuteranopia",
+            ColorMode::Protanopia => "protanopia",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ColorMode> {
+        ColorMode::values().into_iter().find(|m| m.name() == name)
+    }
+}
+
+/// Remaps colors at draw time, so a change applies to everything already
+/// spawned. Draw systems pass team and effect colors through `remap`.
+pub struct Palette {
+    pub mode: ColorMode,
+    /// Bullets drawn white for the player's side and magenta for the
+    /// enemy's, instead of all yellow.
+    pub high_contrast_bullets: bool,
+}
+
+impl Palette {
+    pub fn new() -> Palette {
+        Palette {
+            mode: ColorMode::Normal,
+            high_contrast_bullets: false,
+        }
+    }
+
+    /// The color in the current mode. Colors the table doesn't know are
+    /// left alone, alpha always is.
+    pub fn remap(&self, color: Color) -> Color {
+        let column = |row: &(Color, Color, Color)| match self.mode {
+            ColorMode::Normal => row.0,
+            ColorMode::Deuteranopia => row.1,
+            ColorMode::Protanopia => row.2,
+        };
+        let same = |a: Color, b: Color| a.r == b.r && a.g == b.g && a.b == b.b;
+        match TABLE.iter().find(|row| same(row.0, color)) {
+            Some(row) => Color {
+                a: color.a,
+                ..column(row)
+            },
+            None => color,
+        }
+    }
+
+    pub fn team(&self, team: Team) -> Color {
+        self.remap(team.color())
+    }
+
+    /// Color of a bullet fired by `team`.
+    pub fn bullet(&self, team: Option<Team>) -> Color {
+        if !self.high_contrast_bullets {
+            return self.remap(Color::YELLOW);
+        }
+        match team {
+            Some(Team::Friendly) => Color::WHITE,
+            _ => Color::MAGENTA,
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::f32::consts::PI;
+
+use raylib::prelude::*;
+
+use crate::movement::rotated;
+use crate::renderer::Renderer;
+use crate::rng::Rng;
+use crate::{UpdateSystem, World};
+
+const POOL_SIZE: usize = 4096;
+
+/// Everything that describes how particles of one kind spawn and age.
+#[derive(Debug, Clone, Copy)]
+pub struct EmitterConfig {
+    /// Particles per second while the emitter is active.
+    pub rate: f32,
+    /// Particles spawned at once by a burst.
+    pub burst: u32,
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    /// Center of the velocity cone, in radians.
+    pub direction: f32,
+    /// Full width of the velocity cone, in radians.
+    pub spread: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+    pub start_size: f32,
+    pub end_size: f32,
+    pub gravity: Vector2,
+}
+
+impl EmitterConfig {
+    pub fn thrust() -> EmitterConfig {
+        EmitterConfig {
+            rate: 60.0,
+            burst: 0,
+            lifetime: (0.2, 0.4),
+            speed: (60.0, 120.0),
+            direction: PI * 0.5,
+            spread: 0.5,
+            start_color: Color::SKYBLUE,
+            end_color: Color::DARKBLUE.fade(0.0),
+            start_size: 3.0,
+            end_size: 0.5,
+            gravity: Vector2::zero(),
+        }
+    }
+
+    pub fn explosion() -> EmitterConfig {
+        EmitterConfig {
+            rate: 0.0,
+            burst: 60,
+            lifetime: (0.4, 0.9),
+            speed: (40.0, 220.0),
+            direction: 0.0,
+            spread: PI * 2.0,
+            start_color: Color::YELLOW,
+            end_color: Color::RED.fade(0.0),
+            start_size: 4.0,
+            end_size: 1.0,
+            gravity: Vector2::zero(),
+        }
+    }
+
+    pub fn sparks() -> EmitterConfig {
+        EmitterConfig {
+            rate: 20.0,
+            burst: 12,
+            lifetime: (0.2, 0.5),
+            speed: (80.0, 200.0),
+            direction: -PI * 0.5,
+            spread: PI * 0.6,
+            start_color: Color::ORANGE,
+            end_color: Color::YELLOW.fade(0.0),
+            start_size: 1.5,
+            end_size: 0.5,
+            gravity: Vector2::new(0.0, 300.0),
+        }
+    }
+
+    pub fn impact() -> EmitterConfig {
+        EmitterConfig {
+            rate: 0.0,
+            burst: 8,
+            lifetime: (0.1, 0.25),
+            speed: (30.0, 90.0),
+            direction: 0.0,
+            spread: PI * 2.0,
+            start_color: Color::WHITE,
+            end_color: Color::YELLOW.fade(0.0),
+            start_size: 2.0,
+            end_size: 0.5,
+            gravity: Vector2::zero(),
+        }
+    }
+}
+
+/// Continuously spawns particles from the entity's center while active.
+pub struct Emitter {
+    pub config: EmitterConfig,
+    pub active: bool,
+    accumulator: f32,
+}
+
+impl Emitter {
+    pub fn new(config: EmitterConfig) -> Emitter {
+        Emitter {
+            config,
+            active: true,
+            accumulator: 0.0,
+        }
+    }
+}
+
+struct Particle {
+    pos: Vector2,
+    velocity: Vector2,
+    age: f32,
+    lifetime: f32,
+    config: EmitterConfig,
+}
+
+/// Fixed size particle buffer. Dead particles are swapped out so the live
+/// ones stay packed at the front and nothing is allocated after startup.
+pub struct Particles {
+    pool: Vec<Particle>,
+    rng: Rng,
+}
+
+impl Particles {
+    pub fn new(seed: u64) -> Particles {
+        Particles {
+            pool: Vec::with_capacity(POOL_SIZE),
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn alive(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn spawn(&mut self, config: &EmitterConfig, pos: Vector2) {
+        if self.pool.len() >= POOL_SIZE {
+            return;
+        }
+        let angle = config.direction + self.rng.range(-0.5, 0.5) * config.spread;
+        let speed = self.rng.range(config.speed.0, config.speed.1);
+        self.pool.push(Particle {
+            pos,
+            velocity: rotated(Vector2::new(speed, 0.0), angle),
+            age: 0.0,
+            lifetime: self.rng.range(config.lifetime.0, config.lifetime.1),
+            config: *config,
+        });
+    }
+
+    pub fn burst(&mut self, config: &EmitterConfig, pos: Vector2) {
+        for _ in 0..config.burst {
+            self.spawn(config, pos);
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        let mut i = 0;
+        while i < self.pool.len() {
+            let p = &mut self.pool[i];
+            p.age += dt;
+            if p.age >= p.lifetime {
+                self.pool.swap_remove(i);
+                continue;
+            }
+            p.velocity += p.config.gravity * dt;
+            p.pos += p.velocity * dt;
+            i += 1;
+        }
+    }
+
+    /// Particles are drawn as untextured squares. A quad is 4 vertices
+    /// against dozens for a circle, so thousands of them fit in one batch.
+    pub fn draw(&self, d: &mut dyn Renderer) {
+        for p in self.pool.iter() {
+            let t = p.age / p.lifetime;
+            let c = &p.config;
+            let size = c.start_size + (c.end_size - c.start_size) * t;
+            d.draw_rect(
+                Rectangle::new(p.pos.x - size, p.pos.y - size, size * 2.0, size * 2.0),
+                lerp_color(c.start_color, c.end_color, t),
+            );
+        }
+    }
+}
+
+pub fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t) as u8;
+    Color::new(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), mix(a.a, b.a))
+}
+
+impl UpdateSystem for Emitter {
+    fn update_system(world: &mut World, dt: f32) {
+        for (id, emitter) in world.emitter_components.iter_mut() {
+            if !emitter.active || emitter.config.rate <= 0.0 {
+                emitter.accumulator = 0.0;
+                continue;
+            }
+            let pos = match world.base_components.iter().find(|b| b.0 == *id) {
+                Some(b) => b.1.bounds.center(),
+                None => continue,
+            };
+
+            emitter.accumulator += emitter.config.rate * dt;
+            while emitter.accumulator >= 1.0 {
+                emitter.accumulator -= 1.0;
+                world.particles.spawn(&emitter.config, pos);
+            }
+        }
+
+        world.particles.update(dt);
+    }
+}
@@ -0,0 +1,243 @@
+use raylib::prelude::*;
+
+use crate::json::Json;
+use crate::prefab::vector;
+use crate::tween::Repeat;
+use crate::{EntityID, UpdateSystem, World};
+
+/// Points sampled along each Bezier segment, enough for a smooth curve at
+/// the speeds things move.
+const BEZIER_STEPS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Curve {
+    /// Straight lines from point to point.
+    Waypoints,
+    /// Cubic Bezier segments: a point, two control points, the next point
+    /// and so on. Points left over at the end are ignored.
+    Bezier,
+}
+
+/// A line through the world, sampled so things can move along it at an
+/// even speed whatever the curve.
+#[derive(Debug, Clone)]
+pub struct Path {
+    pub curve: Curve,
+    pub points: Vec<Vector2>,
+    samples: Vec<Vector2>,
+    /// Distance along the path to each sample.
+    lengths: Vec<f32>,
+}
+
+impl Path {
+    pub fn new(curve: Curve, points: Vec<Vector2>) -> Path {
+        let mut path = Path {
+            curve,
+            points,
+            samples: Vec::new(),
+            lengths: Vec::new(),
+        };
+        path.rebuild();
+        path
+    }
+
+    /// Samples the curve again, after `points` or `curve` changed.
+    pub fn rebuild(&mut self) {
+        self.samples = match self.curve {
+            Curve::Waypoints => self.points.clone(),
+            Curve::Bezier => {
+                let mut samples: Vec<Vector2> = self.points.iter().take(1).copied().collect();
+                for segment in self.points.windows(4).step_by(3) {
+                    for step in 1..=BEZIER_STEPS {
+                        let t = step as f32 / BEZIER_STEPS as f32;
+                        samples.push(bezier(segment, t));
+                    }
+                }
+                samples
+            }
+        };
+        let mut total = 0.0;
+        self.lengths = Vec::with_capacity(self.samples.len());
+        for (i, sample) in self.samples.iter().enumerate() {
+            if i > 0 {
+                total += self.samples[i - 1].distance_to(*sample);
+            }
+            self.lengths.push(total);
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    /// The points the path is drawn through, every Bezier step included.
+    pub fn samples(&self) -> &[Vector2] {
+        &self.samples
+    }
+
+    /// Position `distance` along the path, clamped to its ends.
+    pub fn point_at(&self, distance: f32) -> Option<Vector2> {
+        let first = *self.samples.first()?;
+        let next = self.lengths.iter().position(|l| *l > distance);
+        Some(match next {
+            Some(0) => first,
+            Some(i) => {
+                let (a, b) = (self.lengths[i - 1], self.lengths[i]);
+                let t = (distance - a) / (b - a);
+                self.samples[i - 1].lerp(self.samples[i], t)
+            }
+            None => *self.samples.last()?,
+        })
+    }
+}
+
+fn bezier(p: &[Vector2], t: f32) -> Vector2 {
+    let u = 1.0 - t;
+    p[0] * (u * u * u) + p[1] * (3.0 * u * u * t) + p[2] * (3.0 * u * t * t) + p[3] * (t * t * t)
+}
+
+/// Moves its entity's center along a path at `speed` world units per
+/// second, for attack runs and the like. A camera with one rides it as a
+/// rail, its target kept on the path.
+#[derive(Debug, Clone)]
+pub struct PathFollow {
+    pub path: Path,
+    pub speed: f32,
+    pub repeat: Repeat,
+    /// Distance travelled, back and forth counted for ping pong.
+    travelled: f32,
+}
+
+impl PathFollow {
+    pub fn new(path: Path, speed: f32) -> PathFollow {
+        PathFollow {
+            path,
+            speed,
+            repeat: Repeat::Once,
+            travelled: 0.0,
+        }
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> PathFollow {
+        self.repeat = repeat;
+        self
+    }
+
+    /// The same path with every point moved by `offset`, entity definitions
+    /// give theirs relative to where the entity is placed.
+    pub fn offset(mut self, offset: Vector2) -> PathFollow {
+        for point in self.path.points.iter_mut() {
+            *point += offset;
+        }
+        self.path.rebuild();
+        self
+    }
+
+    /// Reads a `path` object of an entity definition or level file:
+    ///
+    /// ```json
+    /// { "points": [[0, 0], [120, 40], [0, 80]], "bezier": false, "speed": 60, "repeat": "Loop" }
+    /// ```
+    pub fn parse(json: &Json) -> Option<PathFollow> {
+        let points = json
+            .get("points")?
+            .as_array()?
+            .iter()
+            .map(vector)
+            .collect::<Option<Vec<_>>>()?;
+        let curve = match json.get("bezier").map(Json::as_bool) {
+            None | Some(Some(false)) => Curve::Waypoints,
+            Some(Some(true)) => Curve::Bezier,
+            Some(None) => return None,
+        };
+        let repeat = match json.get("repeat").map(Json::as_str) {
+            None | Some(Some("Once")) => Repeat::Once,
+            Some(Some("Loop")) => Repeat::Loop,
+            Some(Some("PingPong")) => Repeat::PingPong,
+            Some(_) => return None,
+        };
+        let speed = json.get("speed").and_then(Json::as_f32)?;
+        Some(PathFollow::new(Path::new(curve, points), speed).repeat(repeat))
+    }
+
+    pub fn finished(&self) -> bool {
+        self.repeat == Repeat::Once && self.travelled >= self.path.length()
+    }
+
+    /// Distance along the path it's at now.
+    fn distance(&self) -> f32 {
+        let length = self.path.length();
+        if length <= 0.0 {
+            return 0.0;
+        }
+        match self.repeat {
+            Repeat::Once => self.travelled.min(length),
+            Repeat::Loop => self.travelled % length,
+            Repeat::PingPong => {
+                let d = self.travelled % (length * 2.0);
+                if d > length {
+                    length * 2.0 - d
+                } else {
+                    d
+                }
+            }
+        }
+    }
+
+    /// What the path serializes to, as `parse` reads it.
+    pub fn to_json(&self) -> Json {
+        let points = self
+            .path
+            .points
+            .iter()
+            .map(|p| Json::Array(vec![Json::Number(p.x as f64), Json::Number(p.y as f64)]))
+            .collect();
+        let repeat = match self.repeat {
+            Repeat::Once => "Once",
+            Repeat::Loop => "Loop",
+            Repeat::PingPong => "PingPong",
+        };
+        Json::Object(vec![
+            ("points".to_string(), Json::Array(points)),
+            (
+                "bezier".to_string(),
+                Json::Bool(self.path.curve == Curve::Bezier),
+            ),
+            ("speed".to_string(), Json::Number(self.speed as f64)),
+            ("repeat".to_string(), Json::String(repeat.to_string())),
+        ])
+    }
+}
+
+impl UpdateSystem for PathFollow {
+    fn update_system(world: &mut World, dt: f32) {
+        let mut moves: Vec<(EntityID, Vector2, Vector2)> = Vec::new();
+        for (id, follow) in world.path_components.iter_mut() {
+            if follow.finished() {
+                continue;
+            }
+            let from = follow.path.point_at(follow.distance());
+            follow.travelled += follow.speed * dt;
+            if let (Some(from), Some(pos)) = (from, follow.path.point_at(follow.distance())) {
+                moves.push((*id, from, pos));
+            }
+        }
+
+        for (id, from, pos) in moves {
+            if let Some(b) = world.base_components.iter_mut().find(|b| b.0 == id) {
+                b.1.bounds.set_center(pos);
+            }
+            // runs after Velocity and puts things back on the path, the
+            // velocity only tells look ahead and the like where it's heading
+            if let Some(v) = world.velocity_components.iter_mut().find(|v| v.0 == id) {
+                if dt > 0.0 {
+                    v.1.velocity = (pos - from) / dt;
+                }
+            }
+            if let Some(c) = world.camera_components.iter_mut().find(|c| c.0 == id) {
+                c.1.follow = None;
+                c.1.target = pos;
+            }
+        }
+    }
+}
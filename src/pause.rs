@@ -0,0 +1,101 @@
+use raylib::prelude::*;
+
+use crate::settings::SettingsMenu;
+use crate::state::GameState;
+use crate::t;
+use crate::ui::UiElement;
+use crate::widget::{UiEventKind, Widget};
+use crate::{Anchor, EntityID, World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PauseButton {
+    Resume,
+    Settings,
+    Restart,
+    QuitToMenu,
+}
+
+impl PauseButton {
+    fn values() -> Vec<PauseButton> {
+        vec![
+            PauseButton::Resume,
+            PauseButton::Settings,
+            PauseButton::Restart,
+            PauseButton::QuitToMenu,
+        ]
+    }
+
+    fn label(&self) -> String {
+        match self {
+            PauseButton::Resume => t!("pause.resume"),
+            PauseButton::Settings => t!("menu.settings"),
+            PauseButton::Restart => t!("pause.restart"),
+            PauseButton::QuitToMenu => t!("pause.quit"),
+        }
+    }
+}
+
+/// Buttons shown over the dimmed world while Paused.
+pub struct PauseMenu {
+    /// Set when the game should start over, in Playing for Restart and on
+    /// the title screen for Quit to Menu. The main loop rebuilds the level,
+    /// since that needs the window.
+    pub new_game: Option<GameState>,
+    buttons: Vec<(EntityID, PauseButton)>,
+}
+
+impl PauseMenu {
+    pub fn new() -> PauseMenu {
+        PauseMenu {
+            new_game: None,
+            buttons: Vec::new(),
+        }
+    }
+
+    fn spawn_buttons(&mut self, world: &mut World) {
+        for (i, button) in PauseButton::values().into_iter().enumerate() {
+            let offset = Vector2::new(0.0, i as f32 * 30.0);
+            let id = Widget::spawn(
+                world,
+                Widget::button(button.label()),
+                UiElement::new(Anchor::Center, offset, Vector2::new(160.0, 24.0)),
+            );
+            self.buttons.push((id, button));
+        }
+        world.focus.focused = self.buttons.first().map(|b| b.0);
+    }
+
+    fn despawn_buttons(&mut self, world: &mut World) {
+        for (id, _) in self.buttons.drain(..) {
+            world.despawn(id);
+        }
+    }
+
+    /// Shows the buttons while paused, making way for the settings panel
+    /// when that's opened from here.
+    pub fn update_system(&mut self, world: &mut World, settings: &mut SettingsMenu) {
+        let shown = world.state.is(GameState::Paused) && !settings.is_open();
+        if shown && self.buttons.is_empty() {
+            self.spawn_buttons(world);
+        } else if !shown {
+            self.despawn_buttons(world);
+            return;
+        }
+
+        let clicked: Vec<PauseButton> = world
+            .ui_events
+            .iter()
+            .filter(|e| e.kind == UiEventKind::Clicked)
+            .filter_map(|e| self.buttons.iter().find(|b| b.0 == e.widget))
+            .map(|b| b.1)
+            .collect();
+        for button in clicked {
+            match button {
+                PauseButton::Resume => world.state.set(GameState::Playing),
+                PauseButton::Settings => settings.open(),
+                PauseButton::Restart => self.new_game = Some(GameState::Playing),
+                PauseButton::QuitToMenu => self.new_game = Some(GameState::Menu),
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::ffi::CString;
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::font::{self, TextStyle};
+use crate::info;
+use crate::renderer::Renderer;
+use crate::state::GameState;
+use crate::t;
+use crate::{Anchor, EntityID, World};
+
+/// World units per second the camera pans at, at zoom 1.
+const PAN_SPEED: f32 = 400.0;
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.0;
+const FLASH_TIME: f32 = 0.2;
+
+/// Camera as it was before photo mode took it over.
+struct Borrowed {
+    camera: EntityID,
+    follow: Option<EntityID>,
+    target: Vector2,
+    zoom: f32,
+}
+
+/// Photo mode, toggled with F9 while playing or paused. The world freezes,
+/// the HUD goes away and the camera flies free: WASD or the arrow keys to
+/// pan, Shift to go faster, the mouse wheel to zoom. F12 saves the frame as
+/// photo_<n>.png in the working directory.
+pub struct PhotoMode {
+    /// State to go back to when leaving.
+    previous: GameState,
+    borrowed: Option<Borrowed>,
+    pan: Vector2,
+    zoom: f32,
+    capture: bool,
+    flash: f32,
+}
+
+impl PhotoMode {
+    pub fn new() -> PhotoMode {
+        PhotoMode {
+            previous: GameState::Playing,
+            borrowed: None,
+            pan: Vector2::zero(),
+            zoom: 0.0,
+            capture: false,
+            flash: 0.0,
+        }
+    }
+
+    pub fn input_system(&mut self, world: &mut World, rl: &RaylibHandle) {
+        let state = world.state.current();
+        if rl.is_key_pressed(KeyboardKey::KEY_F9) {
+            match state {
+                GameState::Playing | GameState::Paused => {
+                    self.previous = state;
+                    world.state.set(GameState::Photo);
+                }
+                GameState::Photo => world.state.set(self.previous),
+                _ => (),
+            }
+        }
+        if state != GameState::Photo {
+            return;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            world.state.set(self.previous);
+        }
+
+        let held = |keys: [KeyboardKey; 2]| keys.iter().any(|k| rl.is_key_down(*k));
+        self.pan = Vector2::zero();
+        if held([KeyboardKey::KEY_W, KeyboardKey::KEY_UP]) {
+            self.pan.y -= 1.0;
+        }
+        if held([KeyboardKey::KEY_S, KeyboardKey::KEY_DOWN]) {
+            self.pan.y += 1.0;
+        }
+        if held([KeyboardKey::KEY_A, KeyboardKey::KEY_LEFT]) {
+            self.pan.x -= 1.0;
+        }
+        if held([KeyboardKey::KEY_D, KeyboardKey::KEY_RIGHT]) {
+            self.pan.x += 1.0;
+        }
+        if held([KeyboardKey::KEY_LEFT_SHIFT, KeyboardKey::KEY_RIGHT_SHIFT]) {
+            self.pan *= 3.0;
+        }
+        // the inspector scrolls with the wheel too
+        self.zoom = if world.capture.mouse {
+            0.0
+        } else {
+            rl.get_mouse_wheel_move()
+        };
+        self.capture |= rl.is_key_pressed(KeyboardKey::KEY_F12);
+    }
+
+    /// Borrows the first camera on entering photo mode and hands it back on
+    /// leaving, moves it in between. Runs on real time, the world's is
+    /// stopped.
+    pub fn update_system(&mut self, world: &mut World, dt: f32) {
+        self.flash = (self.flash - dt).max(0.0);
+        let in_photo = world.state.is(GameState::Photo);
+        if in_photo && self.borrowed.is_none() {
+            self.borrowed = world.camera_components.first().map(|(id, c)| Borrowed {
+                camera: *id,
+                follow: c.follow,
+                target: c.target,
+                zoom: c.zoom,
+            });
+        } else if !in_photo {
+            if let Some(b) = self.borrowed.take() {
+                if let Some(c) = world.camera_components.iter_mut().find(|c| c.0 == b.camera) {
+                    c.1.follow = b.follow;
+                    c.1.target = b.target;
+                    c.1.zoom = b.zoom;
+                }
+            }
+            self.capture = false;
+            return;
+        }
+
+        let id = match self.borrowed.as_ref() {
+            Some(b) => b.camera,
+            None => return,
+        };
+        if let Some((_, camera)) = world.camera_components.iter_mut().find(|c| c.0 == id) {
+            camera.follow = None;
+            // zoom in steps of a tenth, relative so it feels the same at any zoom
+            camera.zoom = (camera.zoom * (1.0 + self.zoom * 0.1)).clamp(MIN_ZOOM, MAX_ZOOM);
+            camera.target += self.pan * (PAN_SPEED / camera.zoom * dt);
+        }
+    }
+
+    /// Controls hint, left out of the frame being captured.
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if !world.state.is(GameState::Photo) || self.capture {
+            return;
+        }
+        let (w, h) = (world.screen.width(), world.screen.height());
+        if self.flash > 0.0 {
+            let alpha = self.flash / FLASH_TIME;
+            d.draw_rect(Rectangle::new(0.0, 0.0, w, h), Color::WHITE.fade(alpha));
+        }
+        let style = TextStyle::new(12.0, Color::RAYWHITE)
+            .aligned(Anchor::BottomCenter)
+            .outlined(Color::BLACK);
+        font::draw(
+            &world.assets,
+            d,
+            &t!("photo.help"),
+            Vector2::new(w * 0.5, h - 8.0),
+            &style,
+        );
+    }
+
+    /// Saves the finished frame when a capture was asked for. Call after
+    /// everything is drawn to the window.
+    pub fn capture_system(&mut self, _: &mut RaylibDrawHandle) {
+        if !self.capture {
+            return;
+        }
+        self.capture = false;
+        self.flash = FLASH_TIME;
+        let name = (1..)
+            .map(|i| format!("photo_{:04}.png", i))
+            .find(|name| !Path::new(name).exists())
+            .unwrap_or_default();
+        // the safe wrapper wants the RaylibHandle, which is taken by the
+        // draw handle until the frame ends
+        if let Ok(c_name) = CString::new(name.as_str()) {
+            // SAFETY: the name is a valid C string for the call, raylib
+            // doesn't keep it.
+            unsafe {
+                ffi::TakeScreenshot(c_name.as_ptr());
+            }
+            info!("photo", "photo saved", path = name);
+        }
+    }
+}
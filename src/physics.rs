@@ -0,0 +1,152 @@
+use raylib::prelude::*;
+
+use crate::render;
+use crate::renderer::Renderer;
+use crate::{Anchor, DrawSystem, World};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingBox2D {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+impl BoundingBox2D {
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> BoundingBox2D {
+        BoundingBox2D {
+            x1: x,
+            y1: y,
+            x2: x + w,
+            y2: y + h,
+        }
+    }
+
+    pub fn new_v(pos: Vector2, size: Vector2) -> BoundingBox2D {
+        BoundingBox2D::new(pos.x, pos.y, size.x, size.y)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.x2 - self.x1
+    }
+
+    pub fn height(&self) -> f32 {
+        self.y2 - self.y1
+    }
+
+    pub fn center(&self) -> Vector2 {
+        Vector2::new(self.x1 + self.width() * 0.5, self.y1 + self.height() * 0.5)
+    }
+
+    pub fn overlaps(&self, other: &BoundingBox2D) -> bool {
+        self.x1 < other.x2 && other.x1 < self.x2 && self.y1 < other.y2 && other.y1 < self.y2
+    }
+
+    pub fn contains(&self, pos: Vector2) -> bool {
+        (self.x1..self.x2).contains(&pos.x) && (self.y1..self.y2).contains(&pos.y)
+    }
+
+    pub fn inset(&self, margin: f32) -> BoundingBox2D {
+        BoundingBox2D {
+            x1: self.x1 + margin,
+            y1: self.y1 + margin,
+            x2: self.x2 - margin,
+            y2: self.y2 - margin,
+        }
+    }
+
+    /// Walks from `from` (inside the box) towards `to` and stops at the edge.
+    pub fn clamp_towards(&self, from: Vector2, to: Vector2) -> Vector2 {
+        let dir = to - from;
+        let mut t: f32 = 1.0;
+        if dir.x > 0.0 {
+            t = t.min((self.x2 - from.x) / dir.x);
+        } else if dir.x < 0.0 {
+            t = t.min((self.x1 - from.x) / dir.x);
+        }
+        if dir.y > 0.0 {
+            t = t.min((self.y2 - from.y) / dir.y);
+        } else if dir.y < 0.0 {
+            t = t.min((self.y1 - from.y) / dir.y);
+        }
+        from + dir * t
+    }
+
+    pub fn set_center(&mut self, center: Vector2) {
+        let offset = center - self.center();
+        self.translate(offset);
+    }
+
+    pub fn translate(&mut self, offset: Vector2) {
+        self.x1 += offset.x;
+        self.y1 += offset.y;
+        self.x2 += offset.x;
+        self.y2 += offset.y;
+    }
+
+    pub fn calc(&self, anchor: Anchor) -> Vector2 {
+        match anchor {
+            Anchor::TopLeft => Vector2::new(self.x1, self.y1),
+            Anchor::TopCenter => Vector2::new(self.x1 + self.width() * 0.5, self.y1),
+            Anchor::TopRight => Vector2::new(self.x2, self.y1),
+            Anchor::CenterLeft => Vector2::new(self.x1, self.y1 + self.height() * 0.5),
+            Anchor::Center => self.center(),
+            Anchor::CenterRight => Vector2::new(self.x2, self.y1 + self.height() * 0.5),
+            Anchor::BottomLeft => Vector2::new(self.x1, self.y2),
+            Anchor::BottomCenter => Vector2::new(self.x1 + self.width() * 0.5, self.y2),
+            Anchor::BottomRight => Vector2::new(self.x2, self.y2),
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<ffi::Rectangle> for BoundingBox2D {
+    fn into(self) -> ffi::Rectangle {
+        ffi::Rectangle {
+            x: self.x1,
+            y: self.y1,
+            width: self.x2 - self.x1,
+            height: self.y2 - self.y1,
+        }
+    }
+}
+
+impl From<BoundingBox2D> for Rectangle {
+    fn from(b: BoundingBox2D) -> Rectangle {
+        Rectangle::new(b.x1, b.y1, b.width(), b.height())
+    }
+}
+
+pub struct Base2D {
+    pub name: String,
+    pub bounds: BoundingBox2D,
+    pub tint: Color,
+    pub visible: bool,
+}
+
+impl Base2D {
+    pub fn new(pos: Vector2, size: Vector2) -> Base2D {
+        Base2D {
+            name: "Unnamed".to_string(),
+            bounds: BoundingBox2D::new_v(pos, size),
+            tint: Color::WHITE,
+            visible: true,
+        }
+    }
+}
+
+impl DrawSystem for Base2D {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        // placeholder outlines for everything without a sprite yet
+        world
+            .base_components
+            .iter()
+            .filter(|b| render::in_view(world, b.0))
+            .filter(|b| !world.sprite_components.iter().any(|s| s.0 == b.0))
+            .filter(|b| !world.projectile_components.iter().any(|p| p.0 == b.0))
+            .for_each(|b| {
+                let tint = world.palette.remap(b.1.tint);
+                d.draw_rect_lines(b.1.bounds.into(), 1, tint)
+            });
+    }
+}
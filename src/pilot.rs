@@ -0,0 +1,140 @@
+use raylib::prelude::*;
+
+use crate::movement::rotated;
+use crate::rng::Rng;
+use crate::station::Station;
+use crate::team::Team;
+use crate::telegraph::Telegraph;
+use crate::{EntityID, UpdateSystem, World};
+
+const THRUST: f32 = 360.0;
+/// How far a pilot notices enemies.
+const SIGHT: f32 = 600.0;
+/// Width of the line a winding up pilot warns with.
+const WINDUP_WIDTH: f32 = 6.0;
+
+/// Computer controlled ship. Picks the nearest enemy in sight, closes in
+/// to its preferred range and then circles the target while shooting, the
+/// way Player drives its ship from input. Entities without a team have no
+/// enemies. Pilots hold still and hold their fire while the player is
+/// docked.
+pub struct Pilot {
+    pub firing: bool,
+    /// World position it shoots at.
+    pub aim: Vector2,
+    pub target: Option<EntityID>,
+    /// Distance it tries to keep from its target.
+    pub range: f32,
+    /// Seconds it lines up its shots for, with a telegraph, each time it
+    /// comes in range before it opens fire.
+    pub windup: f32,
+    /// Seconds lined up so far.
+    charge: f32,
+    /// 1 circles clockwise, -1 counterclockwise.
+    orbit: f32,
+}
+
+impl Pilot {
+    pub fn new(range: f32, seed: u64) -> Pilot {
+        let mut rng = Rng::new(seed);
+        Pilot {
+            firing: false,
+            aim: Vector2::zero(),
+            target: None,
+            range,
+            windup: 0.0,
+            charge: 0.0,
+            orbit: if rng.chance(0.5) { 1.0 } else { -1.0 },
+        }
+    }
+
+    pub fn windup(mut self, windup: f32) -> Pilot {
+        self.windup = windup;
+        self
+    }
+}
+
+impl UpdateSystem for Pilot {
+    fn update_system(world: &mut World, dt: f32) {
+        // everything that can be shot at, with where it is and its side
+        let targets: Vec<(EntityID, Vector2, Team)> = world
+            .health_components
+            .iter()
+            .filter_map(|h| {
+                let base = world.base_components.iter().find(|b| b.0 == h.0)?;
+                let team = world.team_components.iter().find(|t| t.0 == h.0)?;
+                Some((h.0, base.1.bounds.center(), team.1))
+            })
+            .collect();
+        let docked = Station::player_docked(world);
+
+        for (id, pilot) in world.pilot_components.iter_mut() {
+            if docked {
+                pilot.firing = false;
+                pilot.charge = 0.0;
+                continue;
+            }
+            let pos = match world.base_components.iter().find(|b| b.0 == *id) {
+                Some(b) => b.1.bounds.center(),
+                None => continue,
+            };
+            let team = world
+                .team_components
+                .iter()
+                .find(|t| t.0 == *id)
+                .map(|t| t.1);
+            let target = targets
+                .iter()
+                .filter(|t| t.0 != *id && Some(t.2) != team && team.is_some())
+                .map(|t| (t.0, t.1, t.1.distance_to(pos)))
+                .filter(|t| t.2 < SIGHT)
+                .min_by(|a, b| a.2.total_cmp(&b.2));
+
+            world.gizmos.circle(pos, SIGHT, Color::DARKGRAY);
+            let (target_id, target_pos, distance) = match target {
+                Some(t) => t,
+                None => {
+                    pilot.target = None;
+                    pilot.firing = false;
+                    pilot.charge = 0.0;
+                    continue;
+                }
+            };
+            pilot.target = Some(target_id);
+            pilot.aim = target_pos;
+            let in_range = distance < pilot.range * 1.5;
+            if !in_range {
+                pilot.charge = 0.0;
+            } else if pilot.charge < pilot.windup {
+                if pilot.charge == 0.0 {
+                    world.telegraph_components.push((
+                        *id,
+                        Telegraph::line(pos, target_pos, WINDUP_WIDTH, pilot.windup),
+                    ));
+                }
+                pilot.charge += dt;
+            }
+            pilot.firing = in_range && pilot.charge >= pilot.windup;
+
+            // head in until in range, then strafe around the target
+            let toward = (target_pos - pos).normalized();
+            let steering = if distance > pilot.range {
+                toward
+            } else {
+                rotated(toward, std::f32::consts::FRAC_PI_2 * pilot.orbit) - toward * 0.3
+            };
+            if let Some(v) = world.velocity_components.iter_mut().find(|v| v.0 == *id) {
+                v.1.velocity += steering.normalized() * THRUST * dt;
+            }
+            let color = if pilot.firing {
+                Color::RED
+            } else {
+                Color::YELLOW
+            };
+            world.gizmos.line(pos, target_pos, color.fade(0.5));
+            world
+                .gizmos
+                .arrow(pos, pos + steering.normalized() * 24.0, Color::SKYBLUE);
+        }
+    }
+}
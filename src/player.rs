@@ -0,0 +1,99 @@
+use raylib::prelude::*;
+
+use crate::animation::AnimationEvent;
+use crate::bindings::Action;
+use crate::camera::Camera;
+use crate::{EntityID, UpdateSystem, World};
+
+const THRUST: f32 = 600.0;
+
+/// Marks the entity driven by local keyboard input.
+pub struct Player {
+    pub firing: bool,
+    pub steering: Vector2,
+    /// World position under the mouse cursor.
+    pub aim: Vector2,
+    pub credits: u32,
+    pub docked_at: Option<EntityID>,
+    /// Seconds left of the grace after a hit, the difficulty's i-frames.
+    pub invulnerable: f32,
+}
+
+impl Player {
+    pub fn new() -> Player {
+        Player {
+            firing: false,
+            steering: Vector2::zero(),
+            aim: Vector2::zero(),
+            credits: 0,
+            docked_at: None,
+            invulnerable: 0.0,
+        }
+    }
+
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        let keys = &world.bindings;
+        let firing = keys.down(rl, Action::Fire);
+
+        let mut steering = Vector2::zero();
+        if keys.down(rl, Action::Up) {
+            steering.y -= 1.0;
+        }
+        if keys.down(rl, Action::Down) {
+            steering.y += 1.0;
+        }
+        if keys.down(rl, Action::Left) {
+            steering.x -= 1.0;
+        }
+        if keys.down(rl, Action::Right) {
+            steering.x += 1.0;
+        }
+
+        let mouse = world.screen.window_to_canvas(rl.get_mouse_position());
+        let aims: Vec<Vector2> = world
+            .player_components
+            .iter()
+            .map(|p| match Camera::of(world, p.0) {
+                Some(c) => c.screen_to_world(mouse),
+                None => mouse,
+            })
+            .collect();
+
+        world
+            .player_components
+            .iter_mut()
+            .zip(aims)
+            .for_each(|(p, aim)| {
+                p.1.aim = aim;
+                // the station menu owns the controls while docked, and a
+                // cutscene can take them too
+                let locked = p.1.docked_at.is_some() || world.cutscene.locks_input();
+                p.1.firing = firing && !locked;
+                p.1.steering = if locked { Vector2::zero() } else { steering };
+            });
+    }
+}
+
+impl UpdateSystem for Player {
+    fn update_system(world: &mut World, dt: f32) {
+        for (id, player) in world.player_components.iter_mut() {
+            player.invulnerable = (player.invulnerable - dt).max(0.0);
+            if let Some(v) = world.velocity_components.iter_mut().find(|v| v.0 == *id) {
+                v.1.velocity += player.steering * THRUST * dt;
+            }
+
+            let thrusting = player.steering.length() > 0.0;
+            if let Some(e) = world.emitter_components.iter_mut().find(|e| e.0 == *id) {
+                // exhaust blows out opposite to the steering direction
+                e.1.active = thrusting;
+                if thrusting {
+                    e.1.config.direction = (-player.steering.y).atan2(-player.steering.x);
+                }
+            }
+            world.animation_events.push(AnimationEvent {
+                entity: *id,
+                clip: if thrusting { "thrust" } else { "idle" },
+            });
+        }
+    }
+}
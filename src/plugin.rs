@@ -0,0 +1,208 @@
+use raylib::prelude::*;
+
+use crate::animation::Animation;
+use crate::camera::Camera;
+use crate::campaign::Campaign;
+use crate::cutscene::Cutscene;
+use crate::damage::Damage;
+use crate::damage_number::DamageNumber;
+use crate::flash::DamageFlash;
+use crate::grid::SpatialGrid;
+use crate::hazard::Hazard;
+use crate::heat::Heat;
+use crate::i18n;
+use crate::lifetime::Lifetime;
+use crate::light::Light;
+use crate::movement::Velocity;
+use crate::particles::Emitter;
+use crate::path::PathFollow;
+use crate::pilot::Pilot;
+use crate::player::Player;
+use crate::portal::Portal;
+use crate::projectile::Projectile;
+use crate::regen::Regen;
+use crate::script::Script;
+use crate::station::Station;
+use crate::telegraph::Telegraph;
+use crate::tilemap::Tilemap;
+use crate::trail::Trail;
+use crate::trigger::Trigger;
+use crate::tween::Tween;
+use crate::weapon::Weapon;
+use crate::{warn, Engine, UpdateSystem, World};
+
+/// A feature bundled up: the systems it runs and what it has to load once
+/// the window is open. Added with `Engine::add_plugin`, systems run in the
+/// order they were added.
+pub trait Plugin {
+    fn build(&self, engine: &mut Engine);
+}
+
+/// Everything the game simulates, in order.
+pub struct GamePlugins;
+
+impl Plugin for GamePlugins {
+    fn build(&self, engine: &mut Engine) {
+        engine
+            .add_plugin(ShipPlugin)
+            .add_plugin(PhysicsPlugin)
+            .add_plugin(CameraPlugin)
+            .add_plugin(LevelPlugin)
+            .add_plugin(CombatPlugin)
+            .add_plugin(EffectsPlugin);
+    }
+}
+
+/// The player's input, the AI pilots and scripts, which steer before
+/// anything moves.
+pub struct ShipPlugin;
+
+impl Plugin for ShipPlugin {
+    fn build(&self, engine: &mut Engine) {
+        engine
+            .add_system("Player", Player::update_system)
+            .add_system("Pilot", Pilot::update_system)
+            .add_system("Script", Script::update_system);
+    }
+}
+
+/// Movement, following paths, pushing entities out of solid tiles, and
+/// sorting everything into the spatial grid where it ended up.
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, engine: &mut Engine) {
+        engine
+            .add_system("Velocity", Velocity::update_system)
+            .add_system("PathFollow", PathFollow::update_system)
+            .add_system("Tilemap", Tilemap::update_system)
+            .add_system("Grid", SpatialGrid::update_system);
+    }
+}
+
+/// Follows the action once everything has moved, with the stars behind.
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, engine: &mut Engine) {
+        engine
+            .add_system("Camera", Camera::update_system)
+            .add_system("Starfield", |world, dt| world.starfield.update(dt));
+    }
+}
+
+/// Areas of the level that do something to what enters them.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, engine: &mut Engine) {
+        engine
+            .add_system("Trigger", Trigger::update_system)
+            .add_system("Hazard", Hazard::update_system)
+            .add_system("Portal", Portal::update_system);
+    }
+}
+
+/// Shooting, getting hit and healing.
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, engine: &mut Engine) {
+        engine
+            .add_system("Station", Station::update_system)
+            .add_system("Weapon", Weapon::update_system)
+            .add_system("Heat", Heat::update_system)
+            .add_system("Projectile", Projectile::update_system)
+            .add_system("Telegraph", Telegraph::update_system)
+            .add_system("Regen", Regen::update_system)
+            .add_system("Damage", Damage::update_system)
+            .add_system("DamageFlash", DamageFlash::update_system);
+    }
+}
+
+/// Animation, particles, lights and the other things that only look good.
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, engine: &mut Engine) {
+        engine
+            .add_system("Animation", Animation::update_system)
+            .add_system("Emitter", Emitter::update_system)
+            .add_system("Trail", Trail::update_system)
+            .add_system("Light", Light::update_system)
+            .add_system("DamageNumber", DamageNumber::update_system)
+            .add_system("Lifetime", Lifetime::update_system)
+            .add_system("Tween", Tween::update_system);
+    }
+}
+
+/// Entity definitions from `assets/entities/`, see `EntityDef`, and the
+/// scripts they run from `assets/scripts/`, see `Program`.
+pub struct PrefabPlugin;
+
+impl Plugin for PrefabPlugin {
+    fn build(&self, engine: &mut Engine) {
+        engine.add_startup_system(|world, _, _| {
+            world.prefabs.load_dir("assets/entities");
+            world.scripts.load_dir("assets/scripts");
+        });
+    }
+}
+
+/// Campaign levels from `assets/levels/`, see `LevelDef`, the waves of
+/// the one being played and its boss's intro.
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, engine: &mut Engine) {
+        engine
+            .add_startup_system(|world, _, _| {
+                world.campaign.load_dir("assets/levels");
+                world.campaign.load_progress("campaign.txt");
+                if let Some(level) = world.config.level {
+                    let last = world.campaign.levels.len().saturating_sub(1);
+                    world.campaign.current = (level - 1).min(last);
+                }
+            })
+            .add_system("Campaign", Campaign::update_system)
+            .add_system("Cutscene", Cutscene::update_system);
+    }
+}
+
+/// The UI font and the UI strings in the configured language. Languages
+/// with a font of their own have it loaded as a fallback, raylib's
+/// built-in font stays the last resort.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, engine: &mut Engine) {
+        engine.add_startup_system(load_ui_font);
+    }
+}
+
+fn load_ui_font(world: &mut World, rl: &mut RaylibHandle, thread: &RaylibThread) {
+    world.assets.ui_font = world
+        .assets
+        .load_font(rl, thread, "assets/fonts/ui.fnt", 16)
+        .ok();
+
+    i18n::load_dir("assets/lang");
+    if !i18n::set_language(&world.config.language) {
+        warn!(
+            "i18n",
+            "unknown language, staying with English",
+            language = world.config.language
+        );
+    }
+    for (path, text) in i18n::fonts() {
+        match world.assets.load_font_for(rl, thread, &path, 16, &text) {
+            Ok(font) => world.assets.fallback_fonts.push(font),
+            Err(e) => warn!(
+                "i18n",
+                "could not load language font",
+                path = path,
+                error = e
+            ),
+        }
+    }
+}
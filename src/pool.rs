@@ -0,0 +1,93 @@
+use crate::damage_number::DamageNumberParts;
+use crate::projectile::BulletParts;
+use crate::{EntityID, World};
+
+/// Components of despawned entities, kept for the next entity of the same
+/// kind. Bullets and damage numbers come and go by the hundred, this way
+/// their names, trails and texts are allocated once and then reused.
+pub struct Pool<T> {
+    pub name: &'static str,
+    free: Vec<T>,
+    /// Most parts kept, the rest of a big burst is dropped.
+    capacity: usize,
+    /// Entities spawned with new parts and with recycled ones.
+    pub created: usize,
+    pub reused: usize,
+}
+
+impl<T> Pool<T> {
+    pub fn new(name: &'static str, capacity: usize) -> Pool<T> {
+        Pool {
+            name,
+            free: Vec::new(),
+            capacity,
+            created: 0,
+            reused: 0,
+        }
+    }
+
+    /// Recycled parts when there are any, else new ones from `new`.
+    pub fn take_or(&mut self, new: impl FnOnce() -> T) -> T {
+        match self.free.pop() {
+            Some(parts) => {
+                self.reused += 1;
+                parts
+            }
+            None => {
+                self.created += 1;
+                new()
+            }
+        }
+    }
+
+    pub fn put(&mut self, parts: T) {
+        if self.free.len() < self.capacity {
+            self.free.push(parts);
+        }
+    }
+
+    /// Parts waiting to be reused.
+    pub fn free(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Share of spawns that got recycled parts, from 0 to 1.
+    pub fn reuse_rate(&self) -> f32 {
+        let total = self.created + self.reused;
+        if total == 0 {
+            return 0.0;
+        }
+        self.reused as f32 / total as f32
+    }
+}
+
+/// Takes `id`'s component out of its storage, keeping the others in order.
+pub fn take<T>(components: &mut Vec<(EntityID, T)>, id: EntityID) -> Option<T> {
+    let index = components.iter().position(|c| c.0 == id)?;
+    Some(components.remove(index).1)
+}
+
+/// The pools the world keeps, see `Pool`.
+pub struct Pools {
+    pub bullets: Pool<BulletParts>,
+    pub damage_numbers: Pool<DamageNumberParts>,
+}
+
+impl Pools {
+    pub fn new() -> Pools {
+        Pools {
+            bullets: Pool::new("bullets", 512),
+            damage_numbers: Pool::new("damage numbers", 64),
+        }
+    }
+
+    /// Moves a pooled kind of entity's components into its pool, called by
+    /// `World::despawn` before the rest go.
+    pub fn recycle(world: &mut World, id: EntityID) {
+        if let Some(parts) = BulletParts::take(world, id) {
+            world.pools.bullets.put(parts);
+        } else if let Some(parts) = DamageNumberParts::take(world, id) {
+            world.pools.damage_numbers.put(parts);
+        }
+    }
+}
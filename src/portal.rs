@@ -0,0 +1,122 @@
+use std::f32::consts::PI;
+
+use raylib::prelude::*;
+
+use crate::movement::rotated;
+use crate::render;
+use crate::renderer::Renderer;
+use crate::trigger::TriggerEventKind;
+use crate::{DrawSystem, EntityID, UpdateSystem, World};
+
+const COOLDOWN: f32 = 1.0;
+const SWIRL_SPEED: f32 = 3.0;
+
+/// One end of a wormhole. Anything entering the trigger volume is moved to
+/// the linked portal, its velocity turned to match the exit's facing.
+pub struct Portal {
+    target: EntityID,
+    /// Direction the portal mouth faces, in radians.
+    facing: f32,
+    /// Travelers that just arrived here and may not leave yet.
+    cooldowns: Vec<(EntityID, f32)>,
+    swirl: f32,
+}
+
+impl Portal {
+    pub fn new(target: EntityID, facing: f32) -> Portal {
+        Portal {
+            target,
+            facing,
+            cooldowns: Vec::new(),
+            swirl: 0.0,
+        }
+    }
+}
+
+impl UpdateSystem for Portal {
+    fn update_system(world: &mut World, dt: f32) {
+        for (_, portal) in world.portal_components.iter_mut() {
+            portal.swirl = (portal.swirl + SWIRL_SPEED * dt) % (PI * 2.0);
+            portal.cooldowns.iter_mut().for_each(|c| c.1 -= dt);
+            portal.cooldowns.retain(|c| c.1 > 0.0);
+        }
+
+        let jumps: Vec<(EntityID, EntityID, EntityID)> = world
+            .trigger_events
+            .iter()
+            .filter(|e| e.kind == TriggerEventKind::Enter)
+            .filter_map(|e| {
+                let portal = world.portal_components.iter().find(|p| p.0 == e.trigger)?;
+                let cooling = portal.1.cooldowns.iter().any(|c| c.0 == e.other);
+                let is_portal = world.portal_components.iter().any(|p| p.0 == e.other);
+                if cooling || is_portal {
+                    return None;
+                }
+                Some((e.other, e.trigger, portal.1.target))
+            })
+            .collect();
+
+        for (traveler, from, to) in jumps {
+            let entry_facing = match world.portal_components.iter().find(|p| p.0 == from) {
+                Some(p) => p.1.facing,
+                None => continue,
+            };
+            let (exit_facing, exit_center) = match (
+                world.portal_components.iter_mut().find(|p| p.0 == to),
+                world.base_components.iter().find(|b| b.0 == to),
+            ) {
+                (Some(p), Some(b)) => {
+                    p.1.cooldowns.push((traveler, COOLDOWN));
+                    (p.1.facing, b.1.bounds.center())
+                }
+                _ => continue,
+            };
+
+            if let Some(b) = world.base_components.iter_mut().find(|b| b.0 == traveler) {
+                b.1.bounds.set_center(exit_center);
+            }
+
+            // entering means moving against the entry facing, leave along the exit facing
+            if let Some(v) = world
+                .velocity_components
+                .iter_mut()
+                .find(|v| v.0 == traveler)
+            {
+                v.1.velocity = rotated(v.1.velocity, exit_facing - (entry_facing + PI));
+            }
+        }
+    }
+}
+
+impl DrawSystem for Portal {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        world
+            .base_components
+            .iter()
+            .filter(|b| render::in_view(world, b.0))
+            .filter_map(|b| {
+                world
+                    .portal_components
+                    .iter()
+                    .find(|p| b.0 == p.0)
+                    .map(|p| (&b.1, &p.1))
+            })
+            .for_each(|(b, portal)| {
+                let center = b.bounds.center();
+                let radius = b.bounds.width().min(b.bounds.height()) * 0.5;
+
+                // three arms of dots spiralling into the center
+                for arm in 0..3 {
+                    for i in 1..=8 {
+                        let t = i as f32 / 8.0;
+                        let angle = portal.swirl + arm as f32 * PI * 2.0 / 3.0 + t * PI;
+                        let offset = rotated(Vector2::new(radius * t, 0.0), angle);
+                        d.draw_circle(center + offset, 1.0 + t * 2.0, b.tint.fade(t));
+                    }
+                }
+
+                let mouth = rotated(Vector2::new(radius, 0.0), portal.facing);
+                d.draw_line(center, center + mouth, 1.0, b.tint);
+            });
+    }
+}
@@ -0,0 +1,595 @@
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::difficulty::Difficulty;
+use crate::flash::DamageFlash;
+use crate::hazard::Hazard;
+use crate::indicator::{Indicator, IndicatorKind};
+use crate::json::Json;
+use crate::movement::Velocity;
+use crate::pack;
+use crate::path::PathFollow;
+use crate::pilot::Pilot;
+use crate::regen::Regen;
+use crate::render::{Layer, RenderLayer};
+use crate::script::Script;
+use crate::team::Team;
+use crate::trigger::Trigger;
+use crate::weapon::Weapon;
+use crate::{info, warn};
+use crate::{Base2D, BoundingBox2D, EntityID, Health, World};
+
+/// Where a prefab goes and how it's tuned, as read from a level file.
+pub struct Placement {
+    /// Name given in the editor, empty when there was none.
+    pub name: String,
+    pub bounds: BoundingBox2D,
+    /// Custom properties, values as written in the file.
+    pub properties: Vec<(String, String)>,
+}
+
+impl Placement {
+    pub fn new(bounds: BoundingBox2D) -> Placement {
+        Placement {
+            name: String::new(),
+            bounds,
+            properties: Vec::new(),
+        }
+    }
+
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|p| p.0 == key)
+            .map(|p| p.1.as_str())
+    }
+
+    /// Property parsed as a number, `default` when missing or malformed.
+    pub fn number(&self, key: &str, default: f32) -> f32 {
+        self.property(key)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    }
+
+    /// Base2D over the placement, named after it when the editor gave it
+    /// a name.
+    fn base(&self, fallback_name: &str, default_size: Vector2, tint: Color) -> Base2D {
+        // point objects have no size of their own
+        let size = if self.bounds.width() > 0.0 && self.bounds.height() > 0.0 {
+            Vector2::new(self.bounds.width(), self.bounds.height())
+        } else {
+            default_size
+        };
+        let mut base2d = Base2D::new(Vector2::zero(), size);
+        base2d.bounds.set_center(self.bounds.center());
+        base2d.name = if self.name.is_empty() {
+            fallback_name.to_string()
+        } else {
+            self.name.clone()
+        };
+        base2d.tint = tint;
+        base2d
+    }
+}
+
+pub type Spawner = fn(&mut World, &Placement) -> EntityID;
+
+/// An entity described in a JSON file in `assets/entities/`, named after
+/// the file. Every key is optional, a component is only added when its key
+/// is there:
+///
+/// ```json
+/// {
+///     "size": [28, 20],
+///     "tint": "#ff7840",
+///     "health": 14,
+///     "armor": 6,
+///     "segments": 5,
+///     "regen": { "per_second": 1, "delay": 3 },
+///     "team": "Hostile",
+///     "velocity": { "drag": 1.5 },
+///     "pilot": { "range": 140, "windup": 0.5 },
+///     "weapon": { "shots_per_second": 1.5 },
+///     "flash": { "color": "#ffffff", "duration": 0.15 },
+///     "indicator": "Enemy",
+///     "layer": "Background",
+///     "trigger": true,
+///     "radiation": { "damage_per_second": 3 },
+///     "script": "spiral",
+///     "path": { "points": [[0, 0], [80, 120], [-80, 120]], "speed": 90, "repeat": "Loop" }
+/// }
+/// ```
+///
+/// A `health` property on the placement overrides the file's. Path points
+/// are relative to where the entity is placed, see `PathFollow`.
+#[derive(Debug, Clone)]
+pub struct EntityDef {
+    pub name: String,
+    pub size: Vector2,
+    pub tint: Color,
+    pub health: Option<u16>,
+    /// Armor and pips of the health, see `Health`.
+    pub armor: u16,
+    pub segments: u16,
+    /// Health per second and seconds after a hit, see `Regen`.
+    pub regen: Option<(f32, f32)>,
+    pub team: Option<Team>,
+    pub drag: Option<f32>,
+    pub pilot_range: Option<f32>,
+    /// Seconds the pilot lines up its shots for, see `Pilot::windup`.
+    pub pilot_windup: f32,
+    pub shots_per_second: Option<f32>,
+    pub flash: Option<(Color, f32)>,
+    pub indicator: Option<IndicatorKind>,
+    pub layer: Option<RenderLayer>,
+    pub trigger: bool,
+    pub radiation: Option<f32>,
+    /// Name of the script it runs, see `Program`.
+    pub script: Option<String>,
+    pub path: Option<PathFollow>,
+}
+
+impl EntityDef {
+    pub fn load(path: &Path) -> Result<EntityDef, String> {
+        let contents = pack::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        EntityDef::parse(name, &contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(name: &str, contents: &str) -> Result<EntityDef, String> {
+        let json = Json::parse(contents)?;
+        let fields = json.as_object().ok_or("expected an object")?;
+        let mut def = EntityDef {
+            name: name.to_string(),
+            size: Vector2::new(32.0, 32.0),
+            tint: Color::WHITE,
+            health: None,
+            armor: 0,
+            segments: 0,
+            regen: None,
+            team: None,
+            drag: None,
+            pilot_range: None,
+            pilot_windup: 0.0,
+            shots_per_second: None,
+            flash: None,
+            indicator: None,
+            layer: None,
+            trigger: false,
+            radiation: None,
+            script: None,
+            path: None,
+        };
+        for (key, value) in fields.iter() {
+            let bad = || format!("bad value for '{}': {}", key, value);
+            match key.as_str() {
+                "size" => def.size = vector(value).ok_or_else(bad)?,
+                "tint" => def.tint = color(value).ok_or_else(bad)?,
+                "health" => def.health = Some(value.as_f32().ok_or_else(bad)? as u16),
+                "armor" => def.armor = value.as_f32().ok_or_else(bad)? as u16,
+                "segments" => def.segments = value.as_f32().ok_or_else(bad)? as u16,
+                "regen" => {
+                    let per_second = number(value, "per_second").ok_or_else(bad)?;
+                    def.regen = Some((per_second, number(value, "delay").unwrap_or(0.0)));
+                }
+                "team" => {
+                    def.team = Some(match value.as_str() {
+                        Some("Friendly") => Team::Friendly,
+                        Some("Hostile") => Team::Hostile,
+                        Some("Neutral") => Team::Neutral,
+                        _ => return Err(bad()),
+                    })
+                }
+                "velocity" => def.drag = Some(number(value, "drag").ok_or_else(bad)?),
+                "pilot" => {
+                    def.pilot_range = Some(number(value, "range").ok_or_else(bad)?);
+                    def.pilot_windup = number(value, "windup").unwrap_or(0.0);
+                }
+                "weapon" => {
+                    let rate = number(value, "shots_per_second").filter(|r| *r > 0.0);
+                    def.shots_per_second = Some(rate.ok_or_else(bad)?);
+                }
+                "flash" => {
+                    let color = value.get("color").and_then(color).ok_or_else(bad)?;
+                    def.flash = Some((color, number(value, "duration").ok_or_else(bad)?));
+                }
+                "indicator" => {
+                    def.indicator = Some(match value.as_str() {
+                        Some("Enemy") => IndicatorKind::Enemy,
+                        Some("Pickup") => IndicatorKind::Pickup,
+                        Some("Objective") => IndicatorKind::Objective,
+                        _ => return Err(bad()),
+                    })
+                }
+                "layer" => {
+                    def.layer = Some(match value.as_str() {
+                        Some("Background") => RenderLayer::Background,
+                        Some("Gameplay") => RenderLayer::Gameplay,
+                        Some("Effects") => RenderLayer::Effects,
+                        Some("Ui") => RenderLayer::Ui,
+                        _ => return Err(bad()),
+                    })
+                }
+                "trigger" => def.trigger = value.as_bool().ok_or_else(bad)?,
+                "radiation" => {
+                    def.radiation = Some(number(value, "damage_per_second").ok_or_else(bad)?)
+                }
+                "script" => def.script = Some(value.as_str().ok_or_else(bad)?.to_string()),
+                "path" => def.path = Some(PathFollow::parse(value).ok_or_else(bad)?),
+                // a typo would otherwise quietly leave a component out
+                _ => return Err(format!("unknown key '{}'", key)),
+            }
+        }
+        Ok(def)
+    }
+
+    fn spawn(&self, world: &mut World, placement: &Placement) -> EntityID {
+        let id = world.new_entity();
+        let base2d = placement.base(&self.name, self.size, self.tint);
+        world.base_components.push((id, base2d));
+        if let Some(health) = self.health {
+            let health = placement.number("health", health as f32) as u16;
+            let health = Health::new(health)
+                .armored(self.armor)
+                .segmented(self.segments);
+            world.health_components.push((id, health));
+        }
+        if let Some((per_second, delay)) = self.regen {
+            world
+                .regen_components
+                .push((id, Regen::new(per_second, delay)));
+        }
+        if let Some(team) = self.team {
+            world.team_components.push((id, team));
+        }
+        if let Some(drag) = self.drag {
+            world.velocity_components.push((id, Velocity::new(drag)));
+        }
+        if let Some(range) = self.pilot_range {
+            let pilot = Pilot::new(range, id ^ world.config.seed.unwrap_or(0));
+            world
+                .pilot_components
+                .push((id, pilot.windup(self.pilot_windup)));
+        }
+        if let Some(rate) = self.shots_per_second {
+            let weapon = Weapon::new(rate, id ^ world.config.seed.unwrap_or(0));
+            world.weapon_components.push((id, weapon));
+        }
+        if let Some((color, duration)) = self.flash {
+            world
+                .flash_components
+                .push((id, DamageFlash::new(color, duration)));
+        }
+        if let Some(kind) = self.indicator {
+            world.indicator_components.push((id, Indicator::new(kind)));
+        }
+        if let Some(layer) = self.layer {
+            world.layer_components.push((id, Layer::new(layer, 0)));
+        }
+        if self.trigger {
+            world.trigger_components.push((id, Trigger::new()));
+        }
+        if let Some(dps) = self.radiation {
+            world.hazard_components.push((id, Hazard::radiation(dps)));
+        }
+        if let Some(script) = self.script.as_ref() {
+            let seed = id ^ world.config.seed.unwrap_or(0);
+            world
+                .script_components
+                .push((id, Script::new(script, seed)));
+        }
+        if let Some(path) = self.path.as_ref() {
+            let follow = path.clone().offset(placement.bounds.center());
+            world.path_components.push((id, follow));
+        }
+        id
+    }
+}
+
+/// Number under `key` of an object.
+pub fn number(json: &Json, key: &str) -> Option<f32> {
+    json.get(key).and_then(Json::as_f32)
+}
+
+/// `[x, y]`.
+pub fn vector(json: &Json) -> Option<Vector2> {
+    match json.as_array()?.as_slice() {
+        [x, y] => Some(Vector2::new(x.as_f32()?, y.as_f32()?)),
+        _ => None,
+    }
+}
+
+/// `#rrggbb` or `#rrggbbaa`.
+pub fn color(json: &Json) -> Option<Color> {
+    let hex = json.as_str()?.strip_prefix('#')?;
+    // from_str_radix would also take a sign
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    match hex.len() {
+        6 => Some(Color::new(channel(0)?, channel(2)?, channel(4)?, 255)),
+        8 => Some(Color::new(
+            channel(0)?,
+            channel(2)?,
+            channel(4)?,
+            channel(6)?,
+        )),
+        _ => None,
+    }
+}
+
+#[derive(Clone)]
+enum Recipe {
+    Code(Spawner),
+    Data(Box<EntityDef>),
+}
+
+/// Named entity recipes, so level files can place things by type name.
+/// Some are code, the rest are read from files by `load_dir`.
+pub struct Prefabs {
+    recipes: Vec<(String, Recipe)>,
+}
+
+impl Prefabs {
+    pub fn new() -> Prefabs {
+        Prefabs {
+            recipes: Vec::new(),
+        }
+    }
+
+    /// The prefabs the game itself knows about.
+    pub fn builtin() -> Prefabs {
+        let mut prefabs = Prefabs::new();
+        prefabs.register("Drone", drone);
+        prefabs.register("Nebula", nebula);
+        prefabs.register("RadiationField", radiation_field);
+        prefabs.register("Trigger", trigger);
+        prefabs
+    }
+
+    /// Adds a prefab, replacing any with the same name.
+    pub fn register(&mut self, name: &str, spawner: Spawner) {
+        self.add(name, Recipe::Code(spawner));
+    }
+
+    /// Adds a prefab described by data, replacing any with its name.
+    pub fn register_def(&mut self, def: EntityDef) {
+        let name = def.name.clone();
+        self.add(&name, Recipe::Data(Box::new(def)));
+    }
+
+    fn add(&mut self, name: &str, recipe: Recipe) {
+        self.recipes.retain(|r| r.0 != name);
+        self.recipes.push((name.to_string(), recipe));
+    }
+
+    /// Registers every `.json` file in `dir` as a prefab named after the
+    /// file, so they can replace the built-in ones. Files that fail to load
+    /// are skipped with a warning. Returns how many were loaded.
+    pub fn load_dir(&mut self, dir: &str) -> usize {
+        let mut paths: Vec<_> = pack::files_in(dir)
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect();
+        // same order everywhere, so duplicates resolve the same way
+        paths.sort();
+
+        let mut loaded = 0;
+        for path in paths {
+            match EntityDef::load(&path) {
+                Ok(def) => {
+                    self.register_def(def);
+                    loaded += 1;
+                }
+                Err(e) => warn!("prefab", "could not load entity", error = e),
+            }
+        }
+        info!("prefab", "entities loaded", dir = dir, count = loaded);
+        loaded
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.recipes.iter().map(|r| r.0.as_str())
+    }
+
+    /// Spawns the prefab called `name`, None when there's no such prefab.
+    /// Hostiles come out as tough as the difficulty makes them.
+    pub fn spawn(world: &mut World, name: &str, placement: &Placement) -> Option<EntityID> {
+        let recipe = world
+            .prefabs
+            .recipes
+            .iter()
+            .find(|r| r.0 == name)?
+            .1
+            .clone();
+        let id = match recipe {
+            Recipe::Code(spawner) => spawner(world, placement),
+            Recipe::Data(def) => def.spawn(world, placement),
+        };
+        if world
+            .team_components
+            .iter()
+            .any(|t| t.0 == id && t.1 == Team::Hostile)
+        {
+            Prefabs::toughen(world, id);
+        }
+        Some(id)
+    }
+
+    /// Scales the health and armor of `id` by the difficulty's enemy health.
+    pub fn toughen(world: &mut World, id: EntityID) {
+        let scale = world.difficulty.enemy_health();
+        if let Some(h) = world.health_components.iter_mut().find(|h| h.0 == id) {
+            h.1.max_health = Difficulty::scale(h.1.max_health, scale);
+            h.1.health = Difficulty::scale(h.1.health, scale);
+            h.1.max_armor = Difficulty::scale(h.1.max_armor, scale);
+            h.1.armor = Difficulty::scale(h.1.armor, scale);
+        }
+    }
+}
+
+fn drone(world: &mut World, placement: &Placement) -> EntityID {
+    let id = world.new_entity();
+    let base2d = placement.base("Drone", Vector2::new(32.0, 24.0), Color::ORANGE);
+    world.base_components.push((id, base2d));
+    let health = placement.number("health", 10.0) as u16;
+    world.health_components.push((id, Health::new(health)));
+    world.team_components.push((id, Team::Hostile));
+    world
+        .flash_components
+        .push((id, DamageFlash::new(Color::WHITE, 0.15)));
+    world
+        .indicator_components
+        .push((id, Indicator::new(IndicatorKind::Enemy)));
+    id
+}
+
+fn nebula(world: &mut World, placement: &Placement) -> EntityID {
+    let id = world.new_entity();
+    let base2d = placement.base("Nebula", Vector2::new(180.0, 140.0), Color::PURPLE);
+    world.base_components.push((id, base2d));
+    world.trigger_components.push((id, Trigger::new()));
+    world.hazard_components.push((id, Hazard::nebula()));
+    world
+        .layer_components
+        .push((id, Layer::new(RenderLayer::Background, 0)));
+    id
+}
+
+fn radiation_field(world: &mut World, placement: &Placement) -> EntityID {
+    let id = world.new_entity();
+    let base2d = placement.base("Radiation field", Vector2::new(120.0, 120.0), Color::LIME);
+    world.base_components.push((id, base2d));
+    world.trigger_components.push((id, Trigger::new()));
+    let dps = placement.number("damage_per_second", 2.0);
+    world.hazard_components.push((id, Hazard::radiation(dps)));
+    world
+        .layer_components
+        .push((id, Layer::new(RenderLayer::Background, 0)));
+    id
+}
+
+/// Invisible area that only raises trigger events, for scripted moments.
+fn trigger(world: &mut World, placement: &Placement) -> EntityID {
+    let id = world.new_entity();
+    let base2d = placement.base("Trigger", Vector2::new(32.0, 32.0), Color::BLANK);
+    world.base_components.push((id, base2d));
+    world.trigger_components.push((id, Trigger::new()));
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(text: &str) -> Json {
+        Json::parse(text).unwrap()
+    }
+
+    #[test]
+    fn reads_every_key() {
+        let def = EntityDef::parse(
+            "drone",
+            r##"{
+                "size": [28, 20], "tint": "#ff784080", "health": 14, "armor": 6,
+                "segments": 5, "regen": { "per_second": 1 }, "team": "Hostile",
+                "velocity": { "drag": 1.5 }, "pilot": { "range": 140, "windup": 0.5 },
+                "weapon": { "shots_per_second": 1.5 },
+                "flash": { "color": "#ffffff", "duration": 0.15 },
+                "indicator": "Enemy", "layer": "Background", "trigger": true,
+                "radiation": { "damage_per_second": 3 }, "script": "spiral"
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(def.name, "drone");
+        assert_eq!(def.size, Vector2::new(28.0, 20.0));
+        assert_eq!(def.tint, Color::new(0xff, 0x78, 0x40, 0x80));
+        assert_eq!((def.health, def.armor, def.segments), (Some(14), 6, 5));
+        assert_eq!(def.regen, Some((1.0, 0.0)));
+        assert_eq!(def.team, Some(Team::Hostile));
+        assert_eq!(def.pilot_range, Some(140.0));
+        assert_eq!(def.pilot_windup, 0.5);
+        assert_eq!(def.shots_per_second, Some(1.5));
+        assert_eq!(def.flash, Some((Color::WHITE, 0.15)));
+        assert!(def.trigger);
+        assert_eq!(def.script.as_deref(), Some("spiral"));
+
+        // nothing given, nothing added
+        let def = EntityDef::parse("empty", "{}").unwrap();
+        assert_eq!(def.health, None);
+        assert_eq!(def.team, None);
+        assert!(!def.trigger);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert_eq!(
+            EntityDef::parse("typo", r#"{ "healht": 10 }"#).err(),
+            Some("unknown key 'healht'".to_string())
+        );
+        assert_eq!(
+            EntityDef::parse("list", "[1, 2]").err(),
+            Some("expected an object".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        for (text, key) in [
+            (r#"{ "size": [1] }"#, "size"),
+            (r#"{ "tint": "red" }"#, "tint"),
+            (r#"{ "team": "Pirates" }"#, "team"),
+            (r#"{ "weapon": { "shots_per_second": 0 } }"#, "weapon"),
+            (r#"{ "pilot": { "windup": 1 } }"#, "pilot"),
+            (r#"{ "trigger": "yes" }"#, "trigger"),
+        ] {
+            let error = EntityDef::parse("bad", text).err();
+            assert!(
+                error
+                    .as_ref()
+                    .is_some_and(|e| e.starts_with(&format!("bad value for '{}'", key))),
+                "{} gave {:?}",
+                text,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn reads_colors() {
+        assert_eq!(
+            color(&json(r##""#102030""##)),
+            Some(Color::new(0x10, 0x20, 0x30, 255))
+        );
+        assert_eq!(
+            color(&json(r##""#A0b0C0d0""##)),
+            Some(Color::new(0xa0, 0xb0, 0xc0, 0xd0))
+        );
+        for bad in [
+            r##""102030""##,
+            r##""#10203""##,
+            r##""#1020304""##,
+            r##""#1g2030""##,
+            r##""#+f2030""##,
+            r##""#-f2030""##,
+            r##""#é20""##,
+            "12",
+        ] {
+            assert_eq!(color(&json(bad)), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn reads_vectors() {
+        assert_eq!(vector(&json("[1.5, -2]")), Some(Vector2::new(1.5, -2.0)));
+        for bad in ["[]", "[1]", "[1, 2, 3]", "[1, \"2\"]", "{\"x\": 1}", "1"] {
+            assert_eq!(vector(&json(bad)), None, "{}", bad);
+        }
+    }
+}
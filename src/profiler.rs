@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use raylib::prelude::*;
+
+use crate::renderer::Renderer;
+use crate::World;
+
+/// Frames of history kept per system.
+const HISTORY: usize = 120;
+const ROW_HEIGHT: i32 = 11;
+const TABLE_WIDTH: f32 = 230.0;
+/// Width of the flame bars for a whole 60 fps frame.
+const FRAME_WIDTH: f32 = 220.0;
+const BUDGET_MS: f32 = 1000.0 / 60.0;
+
+/// Times `$body` as system `$name` of the current stage.
+#[macro_export]
+macro_rules! profile {
+    ($world:expr, $name:expr, $body:expr) => {{
+        let start = std::time::Instant::now();
+        let result = $body;
+        $world.profiler.record($name, start.elapsed());
+        result
+    }};
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortBy {
+    Name,
+    Average,
+    Worst,
+}
+
+struct SystemTimes {
+    stage: &'static str,
+    name: &'static str,
+    /// Milliseconds per frame, newest last. Frames the system didn't run
+    /// count as zero.
+    history: VecDeque<f32>,
+    this_frame: f32,
+}
+
+impl SystemTimes {
+    fn average(&self) -> f32 {
+        if self.history.is_empty() {
+            return 0.0;
+        }
+        self.history.iter().sum::<f32>() / self.history.len() as f32
+    }
+
+    fn worst(&self) -> f32 {
+        self.history.iter().copied().fold(0.0, f32::max)
+    }
+
+    fn last(&self) -> f32 {
+        self.history.back().copied().unwrap_or(0.0)
+    }
+}
+
+/// Time spent per system per main loop stage, over the last couple of
+/// seconds. Systems are timed with the `profile!` macro. F7 shows a table,
+/// sorted by clicking its headers, and flame bars of the last frame.
+pub struct Profiler {
+    pub enabled: bool,
+    stage: &'static str,
+    systems: Vec<SystemTimes>,
+    sort: SortBy,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            enabled: false,
+            stage: "",
+            systems: Vec::new(),
+            sort: SortBy::Average,
+        }
+    }
+
+    /// Systems recorded from now on belong to `stage`.
+    pub fn begin_stage(&mut self, stage: &'static str) {
+        self.stage = stage;
+    }
+
+    pub fn record(&mut self, name: &'static str, time: Duration) {
+        let ms = time.as_secs_f32() * 1000.0;
+        let stage = self.stage;
+        match self
+            .systems
+            .iter_mut()
+            .find(|s| s.stage == stage && s.name == name)
+        {
+            Some(s) => s.this_frame += ms,
+            None => self.systems.push(SystemTimes {
+                stage,
+                name,
+                history: VecDeque::with_capacity(HISTORY),
+                this_frame: ms,
+            }),
+        }
+    }
+
+    /// Moves this frame's times into the history.
+    pub fn end_frame(&mut self) {
+        for system in self.systems.iter_mut() {
+            if system.history.len() == HISTORY {
+                system.history.pop_front();
+            }
+            system.history.push_back(system.this_frame);
+            system.this_frame = 0.0;
+        }
+    }
+
+    fn columns() -> [(SortBy, &'static str, i32); 3] {
+        [
+            (SortBy::Name, "system", 0),
+            (SortBy::Average, "avg ms", 140),
+            (SortBy::Worst, "worst", 190),
+        ]
+    }
+
+    fn origin(world: &World) -> Vector2 {
+        Vector2::new(world.screen.width() - TABLE_WIDTH - 10.0, 32.0)
+    }
+
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        if rl.is_key_pressed(KeyboardKey::KEY_F7) {
+            world.profiler.enabled = !world.profiler.enabled;
+        }
+        if !world.profiler.enabled || !rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            return;
+        }
+        let mouse = world.screen.window_to_canvas(rl.get_mouse_position());
+        let origin = Profiler::origin(world);
+        if !(origin.y..origin.y + ROW_HEIGHT as f32).contains(&mouse.y) {
+            return;
+        }
+        let x = mouse.x - origin.x;
+        if let Some(column) = Profiler::columns()
+            .iter()
+            .rev()
+            .find(|c| x >= c.2 as f32 && x < TABLE_WIDTH)
+        {
+            world.profiler.sort = column.0;
+        }
+    }
+
+    fn stages(&self) -> Vec<&'static str> {
+        let mut stages: Vec<&'static str> = Vec::new();
+        for system in self.systems.iter() {
+            if !stages.contains(&system.stage) {
+                stages.push(system.stage);
+            }
+        }
+        stages
+    }
+
+    /// Where the table is on screen, None while it's hidden.
+    pub fn panel(world: &World) -> Option<Rectangle> {
+        let profiler = &world.profiler;
+        if !profiler.enabled {
+            return None;
+        }
+        let origin = Profiler::origin(world);
+        let rows = profiler.systems.len() + profiler.stages().len() * 2 + 3;
+        Some(Rectangle::new(
+            origin.x - 4.0,
+            origin.y - 4.0,
+            TABLE_WIDTH + 8.0,
+            (rows as i32 * ROW_HEIGHT) as f32 + 8.0,
+        ))
+    }
+
+    pub fn draw_system(world: &World, d: &mut dyn Renderer) {
+        let profiler = &world.profiler;
+        let panel = match Profiler::panel(world) {
+            Some(panel) => panel,
+            None => return,
+        };
+        let origin = Profiler::origin(world);
+        let (x, mut y) = (origin.x as i32, origin.y as i32);
+        let stages = profiler.stages();
+        d.draw_rect(panel, Color::BLACK.fade(0.75));
+
+        for (sort, header, offset) in Profiler::columns() {
+            let color = if sort == profiler.sort {
+                Color::YELLOW
+            } else {
+                Color::GRAY
+            };
+            d.draw_text(header, x + offset, y, 10, color);
+        }
+        y += ROW_HEIGHT + 2;
+
+        let mut systems: Vec<&SystemTimes> = profiler.systems.iter().collect();
+        match profiler.sort {
+            SortBy::Name => systems.sort_by_key(|s| s.name),
+            SortBy::Average => systems.sort_by(|a, b| b.average().total_cmp(&a.average())),
+            SortBy::Worst => systems.sort_by(|a, b| b.worst().total_cmp(&a.worst())),
+        }
+        for stage in stages.iter() {
+            d.draw_text(stage, x, y, 10, Color::SKYBLUE);
+            y += ROW_HEIGHT;
+            for system in systems.iter().filter(|s| s.stage == *stage) {
+                let worst = system.worst();
+                let color = if worst > BUDGET_MS * 0.25 {
+                    Color::ORANGE
+                } else {
+                    Color::RAYWHITE
+                };
+                d.draw_text(system.name, x + 8, y, 10, color);
+                d.draw_text(&format!("{:.3}", system.average()), x + 140, y, 10, color);
+                d.draw_text(&format!("{:.3}", worst), x + 190, y, 10, color);
+                y += ROW_HEIGHT;
+            }
+        }
+
+        // last frame's systems side by side per stage, scaled to the budget
+        y += 4;
+        for stage in stages.iter() {
+            let mut bar_x = x as f32;
+            for (i, system) in profiler
+                .systems
+                .iter()
+                .filter(|s| s.stage == *stage)
+                .enumerate()
+            {
+                let width = system.last() / BUDGET_MS * FRAME_WIDTH;
+                let color = if i % 2 == 0 {
+                    Color::LIME
+                } else {
+                    Color::DARKGREEN
+                };
+                d.draw_rect(
+                    Rectangle::new(bar_x, y as f32, width, ROW_HEIGHT as f32 - 2.0),
+                    color,
+                );
+                bar_x += width;
+            }
+            d.draw_text(stage, x + FRAME_WIDTH as i32 - 40, y, 10, Color::WHITE);
+            y += ROW_HEIGHT;
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use raylib::prelude::*;
+
+use crate::damage::DamageEvent;
+use crate::lifetime::Lifetime;
+use crate::light::Light;
+use crate::movement::Velocity;
+use crate::particles::EmitterConfig;
+use crate::pool;
+use crate::render;
+use crate::renderer::Renderer;
+use crate::trail::Trail;
+use crate::{Base2D, BoundingBox2D, DrawSystem, EntityID, UpdateSystem, World};
+
+const BULLET_SIZE: Vector2 = Vector2 { x: 4.0, y: 10.0 };
+
+pub struct Projectile {
+    owner: EntityID,
+    damage: u16,
+    crit: bool,
+}
+
+impl Projectile {
+    /// Spawns a bullet centered on `pos` that inherits the owner's team.
+    pub fn spawn(
+        world: &mut World,
+        owner: EntityID,
+        pos: Vector2,
+        velocity: Vector2,
+        damage: u16,
+        crit: bool,
+    ) -> EntityID {
+        let bullet = world.new_entity();
+        let mut parts = world.pools.bullets.take_or(BulletParts::new);
+
+        parts.base.bounds = BoundingBox2D::new_v(pos - BULLET_SIZE * 0.5, BULLET_SIZE);
+        parts.base.tint = Color::YELLOW;
+        parts.base.visible = true;
+        world.base_components.push((bullet, parts.base));
+
+        let mut vel = Velocity::new(0.0);
+        vel.velocity = velocity;
+        world.velocity_components.push((bullet, vel));
+
+        let team = world
+            .team_components
+            .iter()
+            .find(|t| t.0 == owner)
+            .map(|t| t.1);
+        if let Some(team) = team {
+            world.team_components.push((bullet, team));
+        }
+        let color = world.palette.bullet(team);
+
+        world.projectile_components.push((
+            bullet,
+            Projectile {
+                owner,
+                damage,
+                crit,
+            },
+        ));
+        world.lifetime_components.push((bullet, Lifetime::new(1.5)));
+        parts.trail.reset(6, 2.0, color);
+        world.trail_components.push((bullet, parts.trail));
+        world
+            .light_components
+            .push((bullet, Light::new(24.0, color, 0.6)));
+        bullet
+    }
+}
+
+/// What a bullet allocates, kept in `world.pools` between bullets.
+pub struct BulletParts {
+    base: Base2D,
+    trail: Trail,
+}
+
+impl BulletParts {
+    fn new() -> BulletParts {
+        let mut base = Base2D::new(Vector2::zero(), BULLET_SIZE);
+        base.name = "Bullet".to_string();
+        BulletParts {
+            base,
+            trail: Trail::new(6, 2.0, Color::YELLOW),
+        }
+    }
+
+    /// Takes a bullet's parts out of the world, None when `id` isn't one.
+    pub fn take(world: &mut World, id: EntityID) -> Option<BulletParts> {
+        pool::take(&mut world.projectile_components, id)?;
+        Some(BulletParts {
+            base: pool::take(&mut world.base_components, id)?,
+            trail: pool::take(&mut world.trail_components, id)?,
+        })
+    }
+}
+
+impl UpdateSystem for Projectile {
+    fn update_system(world: &mut World, _dt: f32) {
+        let mut spent = Vec::new();
+
+        for (id, projectile) in world.projectile_components.iter() {
+            let bounds = match world.base_components.iter().find(|b| b.0 == *id) {
+                Some(b) => b.1.bounds,
+                None => continue,
+            };
+            let team = world
+                .team_components
+                .iter()
+                .find(|t| t.0 == *id)
+                .map(|t| t.1);
+
+            // first damageable entity nearby that isn't the shooter or on its team
+            let hit = world.grid.query(&bounds).into_iter().find(|target| {
+                let friendly = team.is_some()
+                    && world
+                        .team_components
+                        .iter()
+                        .any(|t| t.0 == *target && Some(t.1) == team);
+                *target != projectile.owner
+                    && !friendly
+                    && world.health_components.iter().any(|h| h.0 == *target)
+                    && world
+                        .base_components
+                        .iter()
+                        .any(|b| b.0 == *target && b.1.bounds.overlaps(&bounds))
+            });
+
+            if let Some(target) = hit {
+                world
+                    .particles
+                    .burst(&EmitterConfig::impact(), bounds.center());
+                world.damage_events.push(DamageEvent {
+                    target,
+                    source: Some(projectile.owner),
+                    amount: projectile.damage,
+                    crit: projectile.crit,
+                });
+                spent.push(*id);
+            }
+        }
+
+        for id in spent {
+            world.despawn(id);
+        }
+    }
+}
+
+impl DrawSystem for Projectile {
+    /// Bullets are plain filled quads drawn in one go, so however many are
+    /// flying they share a single batch.
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        for (id, _) in world.projectile_components.iter() {
+            if !render::in_view(world, *id) {
+                continue;
+            }
+            if let Some(b) = world.base_components.iter().find(|b| b.0 == *id) {
+                let team = world.team_components.iter().find(|t| t.0 == *id);
+                d.draw_rect(b.1.bounds.into(), world.palette.bullet(team.map(|t| t.1)));
+            }
+        }
+    }
+}
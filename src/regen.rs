@@ -0,0 +1,157 @@
+use crate::damage::HealEvent;
+use crate::{EntityID, UpdateSystem, World};
+
+/// Seconds between the heals of a regenerating entity, so its health comes
+/// back in readable steps instead of a number every frame.
+const TICK: f32 = 1.0;
+
+/// Heals its entity's hull over time, after a while without being hit.
+pub struct Regen {
+    pub per_second: f32,
+    /// Seconds after a hit before healing starts again.
+    pub delay: f32,
+    since_hit: f32,
+    /// Healing not handed out yet, whole points go out every tick.
+    pending: f32,
+    tick: f32,
+    regenerating: bool,
+}
+
+impl Regen {
+    pub fn new(per_second: f32, delay: f32) -> Regen {
+        Regen {
+            per_second,
+            delay,
+            since_hit: delay,
+            pending: 0.0,
+            tick: 0.0,
+            regenerating: false,
+        }
+    }
+
+    pub fn of(world: &World, id: EntityID) -> Option<&Regen> {
+        world
+            .regen_components
+            .iter()
+            .find(|r| r.0 == id)
+            .map(|r| &r.1)
+    }
+
+    /// Whether it's healing right now, for the glow on the health bar.
+    pub fn regenerating(&self) -> bool {
+        self.regenerating
+    }
+}
+
+impl UpdateSystem for Regen {
+    /// Runs before the damage system, which applies the heals, and after
+    /// everything that deals damage, so a hit this frame holds it off.
+    fn update_system(world: &mut World, dt: f32) {
+        let mut heals = Vec::new();
+        for (id, regen) in world.regen_components.iter_mut() {
+            let (health, max_health) = match world.health_components.iter().find(|h| h.0 == *id) {
+                Some(h) => (h.1.health, h.1.max_health),
+                None => continue,
+            };
+            if world
+                .damage_events
+                .iter()
+                .any(|e| e.target == *id && e.amount > 0)
+            {
+                regen.since_hit = 0.0;
+            }
+            regen.since_hit += dt;
+
+            // the dead stay dead
+            regen.regenerating =
+                health > 0 && health < max_health && regen.since_hit >= regen.delay;
+            if !regen.regenerating {
+                regen.pending = 0.0;
+                regen.tick = 0.0;
+                continue;
+            }
+            regen.pending += regen.per_second * dt;
+            regen.tick += dt;
+            if regen.tick >= TICK && regen.pending >= 1.0 {
+                regen.tick = 0.0;
+                let amount = regen.pending.floor();
+                regen.pending -= amount;
+                heals.push(HealEvent {
+                    target: *id,
+                    source: None,
+                    amount: amount as u16,
+                });
+            }
+        }
+        world.heal_events.extend(heals);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::DamageEvent;
+    use crate::gameplay::Health;
+
+    /// The heals handed out over `frames` updates of a quarter second.
+    fn heals(world: &mut World, frames: usize) -> Vec<u16> {
+        let mut amounts = Vec::new();
+        for _ in 0..frames {
+            Regen::update_system(world, 0.25);
+            amounts.extend(world.heal_events.drain(..).map(|h| h.amount));
+            world.damage_events.clear();
+        }
+        amounts
+    }
+
+    fn hit(world: &mut World, amount: u16) {
+        world.damage_events.push(DamageEvent {
+            target: 1,
+            source: Some(2),
+            amount,
+            crit: false,
+        });
+    }
+
+    #[test]
+    fn heals_in_ticks_after_the_delay() {
+        let mut world = World::new();
+        let mut health = Health::new(10);
+        health.take_damage(4);
+        world.health_components.push((1, health));
+        world.regen_components.push((1, Regen::new(1.5, 2.0)));
+
+        // a hit holds it off for the delay, the frame of the hit included
+        hit(&mut world, 3);
+        assert_eq!(heals(&mut world, 1), []);
+        assert_eq!(heals(&mut world, 6), []);
+        assert!(!Regen::of(&world, 1).unwrap().regenerating());
+        assert_eq!(heals(&mut world, 1), []);
+        assert!(Regen::of(&world, 1).unwrap().regenerating());
+
+        // a point and a half a second, whole points handed out once a tick
+        assert_eq!(heals(&mut world, 3), [1]);
+        assert_eq!(heals(&mut world, 4), [2]);
+        assert_eq!(heals(&mut world, 8), [1, 2]);
+
+        // absorbed hits don't count, real ones start the delay over
+        hit(&mut world, 0);
+        assert_eq!(heals(&mut world, 4), [1]);
+        hit(&mut world, 1);
+        assert_eq!(heals(&mut world, 8), []);
+        assert_eq!(heals(&mut world, 4), [1]);
+    }
+
+    #[test]
+    fn stops_at_full_health_and_when_dead() {
+        let mut world = World::new();
+        world.health_components.push((1, Health::new(10)));
+        world.regen_components.push((1, Regen::new(5.0, 0.0)));
+        assert_eq!(heals(&mut world, 8), []);
+        assert!(!Regen::of(&world, 1).unwrap().regenerating());
+
+        world.health_components[0].1.take_damage(10);
+        assert_eq!(heals(&mut world, 8), []);
+        assert!(!Regen::of(&world, 1).unwrap().regenerating());
+    }
+}
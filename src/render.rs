@@ -0,0 +1,278 @@
+use raylib::prelude::*;
+
+use crate::debug::DebugDraw;
+use crate::focus::Focus;
+use crate::gizmos::Gizmos;
+use crate::hazard::Hazard;
+use crate::heat::Heat;
+use crate::hud::HudItem;
+use crate::indicator::Indicator;
+use crate::light::LightMap;
+use crate::portal::Portal;
+use crate::projectile::Projectile;
+use crate::sprite::Sprite;
+use crate::state::StateMachine;
+use crate::station::Station;
+use crate::telegraph::Telegraph;
+use crate::text::Text2D;
+use crate::tilemap::Tilemap;
+use crate::trail::Trail;
+use crate::widget::Widget;
+use crate::{Base2D, BoundingBox2D, DrawSystem, EntityID, Health, World};
+
+/// Render layers, drawn back to front in declaration order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderLayer {
+    Background,
+    Gameplay,
+    Effects,
+    Ui,
+}
+
+impl RenderLayer {
+    pub fn values() -> Vec<RenderLayer> {
+        vec![
+            RenderLayer::Background,
+            RenderLayer::Gameplay,
+            RenderLayer::Effects,
+            RenderLayer::Ui,
+        ]
+    }
+}
+
+/// Where an entity's drawables go. Entities without one are drawn as
+/// Gameplay with z 0. Higher z draws on top within the same layer.
+#[derive(Debug, Clone, Copy)]
+pub struct Layer {
+    pub layer: RenderLayer,
+    pub z: i32,
+}
+
+impl Layer {
+    pub fn new(layer: RenderLayer, z: i32) -> Layer {
+        Layer { layer, z }
+    }
+
+    pub fn of(world: &World, id: EntityID) -> Layer {
+        world
+            .layer_components
+            .iter()
+            .find(|l| l.0 == id)
+            .map(|l| l.1)
+            .unwrap_or(Layer::new(RenderLayer::Gameplay, 0))
+    }
+
+    /// Entities in `layer` sorted by z, ties broken by entity id so the order
+    /// never depends on component storage order.
+    pub fn sorted(world: &World, layer: RenderLayer) -> Vec<EntityID> {
+        let mut ids: Vec<(i32, EntityID)> = world
+            .base_components
+            .iter()
+            .map(|b| (Layer::of(world, b.0), b.0))
+            .filter(|(l, _)| l.layer == layer)
+            .map(|(l, id)| (l.z, id))
+            .collect();
+        ids.sort();
+        ids.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+/// Extra room around the camera view, so outlines, bars and labels that
+/// stick out of an entity's bounds don't pop at the screen edges.
+const CULL_MARGIN: f32 = 64.0;
+
+/// Collects the entities worth drawing in a view: visible ones whose bounds
+/// touch it, or all visible ones without a view. World draw systems skip
+/// everything else.
+pub fn cull_system(world: &mut World, view: Option<BoundingBox2D>) {
+    let view = view.map(|v| v.inset(-CULL_MARGIN));
+    let mut in_view: Vec<EntityID> = world
+        .base_components
+        .iter()
+        .filter(|b| b.1.visible && view.is_none_or(|v| v.overlaps(&b.1.bounds)))
+        .map(|b| b.0)
+        .collect();
+    in_view.sort_unstable();
+    world.in_view = in_view;
+    world.view = view;
+}
+
+pub fn in_view(world: &World, id: EntityID) -> bool {
+    world.in_view.binary_search(&id).is_ok()
+}
+
+/// What one camera sees and where on the screen it goes.
+pub struct View {
+    pub camera: Camera2D,
+    pub bounds: Option<BoundingBox2D>,
+    pub viewport: Option<Rectangle>,
+}
+
+/// A view per camera, shaken by the juice. Without cameras the world is
+/// drawn unmoved, as one view of everything.
+pub fn views(world: &World) -> Vec<View> {
+    let (w, h) = (world.screen.width(), world.screen.height());
+    let views: Vec<View> = world
+        .camera_components
+        .iter()
+        .map(|(_, c)| {
+            let mut camera = c.as_raylib();
+            camera.offset += world.juice.offset();
+            View {
+                camera,
+                bounds: Some(c.view_bounds(w, h)),
+                viewport: c.viewport,
+            }
+        })
+        .collect();
+    if !views.is_empty() {
+        return views;
+    }
+    vec![View {
+        camera: Camera2D {
+            offset: Vector2::zero(),
+            target: Vector2::zero(),
+            rotation: 0.0,
+            zoom: 1.0,
+        },
+        bounds: None,
+        viewport: None,
+    }]
+}
+
+/// Clips drawing to the viewport until `end_viewport`, nothing to do for
+/// full screen views.
+pub fn begin_viewport(viewport: Option<Rectangle>) {
+    if let Some(v) = viewport {
+        // SAFETY: plain values, raylib clamps them to the window.
+        unsafe {
+            ffi::BeginScissorMode(v.x as i32, v.y as i32, v.width as i32, v.height as i32);
+        }
+    }
+}
+
+pub fn end_viewport(viewport: Option<Rectangle>) {
+    if viewport.is_some() {
+        // SAFETY: no arguments, only called after `begin_viewport`.
+        unsafe {
+            ffi::EndScissorMode();
+        }
+    }
+}
+
+/// Clears the screen and runs every world draw system, layer by layer, once
+/// per camera within its viewport. The light map brightens everything below
+/// the Ui layer, screen-space UI goes on top of all views afterwards.
+pub fn draw_world(world: &mut World, d: &mut RaylibDrawHandle, lights: &LightMap) {
+    d.clear_background(Color::BLACK);
+    world.sort_groups();
+    let views = views(world);
+    let mut sprites = Vec::new();
+    for view in views.iter() {
+        begin_viewport(view.viewport);
+        cull_system(world, view.bounds);
+        world.starfield.draw(
+            d,
+            &world.assets,
+            world.screen.canvas,
+            view.camera.target,
+            world.beat.pulse,
+        );
+        draw_view(world, d, lights, view.camera, &mut sprites);
+        end_viewport(view.viewport);
+    }
+    world.render_stats.sprites = sprites.iter().map(|s| s.0).sum();
+    world.render_stats.sprite_batches = sprites.iter().map(|s| s.1).sum();
+
+    // a thin frame keeps split screen views apart
+    if views.len() > 1 {
+        for viewport in views.iter().filter_map(|v| v.viewport) {
+            d.draw_rectangle_lines_ex(viewport, 1, Color::DARKGRAY);
+        }
+    }
+
+    if world.state.current().shows_hud() {
+        Indicator::draw_system(world, d);
+        Station::draw_system(world, d);
+        profile!(world, "HudItem", HudItem::draw_system(world, d));
+    }
+    StateMachine::draw_system(world, d);
+    profile!(world, "Widget", Widget::draw_system(world, d));
+    Focus::draw_system(world, d);
+}
+
+/// World draw systems through one camera, `sprites` collects the sprite
+/// draw and batch counts.
+fn draw_view(
+    world: &mut World,
+    d: &mut RaylibDrawHandle,
+    lights: &LightMap,
+    camera: Camera2D,
+    sprites: &mut Vec<(usize, usize)>,
+) {
+    // The safe begin_mode2D wrapper hands out a different draw handle type
+    // than the draw systems take, so toggle the mode directly.
+    // SAFETY: the camera is passed by value and the draw handle means a
+    // frame is being drawn. The mode is ended at the bottom.
+    unsafe {
+        ffi::BeginMode2D(camera.into());
+    }
+    for layer in RenderLayer::values() {
+        match layer {
+            RenderLayer::Background => {
+                profile!(world, "Tilemap", Tilemap::draw_system(world, d));
+                Hazard::draw_system(world, d);
+                Telegraph::draw_system(world, d);
+                sprites.push(profile!(
+                    world,
+                    "Sprite",
+                    Sprite::draw_layer(world, d, layer)
+                ));
+            }
+            RenderLayer::Gameplay => {
+                sprites.push(profile!(
+                    world,
+                    "Sprite",
+                    Sprite::draw_layer(world, d, layer)
+                ));
+                Base2D::draw_system(world, d);
+                Projectile::draw_system(world, d);
+            }
+            RenderLayer::Effects => {
+                Portal::draw_system(world, d);
+                Trail::draw_system(world, d);
+                profile!(world, "Particles", world.particles.draw(d));
+                sprites.push(profile!(
+                    world,
+                    "Sprite",
+                    Sprite::draw_layer(world, d, layer)
+                ));
+            }
+            RenderLayer::Ui => {
+                // SAFETY: the light map goes on in screen space, so the
+                // camera's mode is left and taken up again, by value.
+                unsafe {
+                    ffi::EndMode2D();
+                }
+                profile!(world, "LightMap", lights.composite(d));
+                unsafe {
+                    ffi::BeginMode2D(camera.into());
+                }
+                sprites.push(profile!(
+                    world,
+                    "Sprite",
+                    Sprite::draw_layer(world, d, layer)
+                ));
+                Health::draw_system(world, d);
+                Heat::draw_system(world, d);
+                Text2D::draw_system(world, d);
+            }
+        }
+    }
+    DebugDraw::draw_system(world, d);
+    Gizmos::draw_system(world, d);
+    // SAFETY: ends the mode begun at the top.
+    unsafe {
+        ffi::EndMode2D();
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use raylib::prelude::*;
+
+use crate::pool::Pool;
+use crate::renderer::Renderer;
+use crate::{DrawSystem, World};
+
+/// Frames of history in the frame time graph.
+const HISTORY: usize = 120;
+/// Frame time at the top of the graph, in milliseconds.
+const GRAPH_MAX_MS: f32 = 33.3;
+const GRAPH_SIZE: Vector2 = Vector2 { x: 120.0, y: 40.0 };
+
+/// Performance overlay toggled with F4: frame time graph, how much of the
+/// world got drawn and where the frame went.
+pub struct RenderStats {
+    pub enabled: bool,
+    frame_times: VecDeque<f32>,
+    /// Milliseconds per named main loop stage, last frame.
+    stages: Vec<(&'static str, f32)>,
+    /// Sprites drawn and texture switches between them, this frame.
+    pub sprites: usize,
+    pub sprite_batches: usize,
+}
+
+impl RenderStats {
+    pub fn new() -> RenderStats {
+        RenderStats {
+            enabled: false,
+            frame_times: VecDeque::with_capacity(HISTORY),
+            stages: Vec::new(),
+            sprites: 0,
+            sprite_batches: 0,
+        }
+    }
+
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        if rl.is_key_pressed(KeyboardKey::KEY_F4) {
+            world.render_stats.enabled = !world.render_stats.enabled;
+        }
+    }
+
+    /// Records the real, unscaled length of the last frame.
+    pub fn frame(&mut self, dt: f32) {
+        if self.frame_times.len() == HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(dt * 1000.0);
+    }
+
+    pub fn stage(&mut self, name: &'static str, time: Duration) {
+        let ms = time.as_secs_f32() * 1000.0;
+        match self.stages.iter_mut().find(|s| s.0 == name) {
+            Some(s) => s.1 = ms,
+            None => self.stages.push((name, ms)),
+        }
+    }
+}
+
+/// Live entities of a pooled kind plus the spare parts pooled, and how
+/// many spawns got recycled parts.
+fn pool_line<T>(pool: &Pool<T>, live: usize) -> String {
+    format!(
+        "{} {}+{} {:.0}% reused",
+        pool.name,
+        live,
+        pool.free(),
+        pool.reuse_rate() * 100.0
+    )
+}
+
+impl DrawSystem for RenderStats {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        let stats = &world.render_stats;
+        if !stats.enabled {
+            return;
+        }
+
+        let (x, mut y) = (10, 32);
+        let height = (6 + stats.stages.len()) as f32 * 12.0 + GRAPH_SIZE.y + 12.0;
+        let panel = Rectangle::new(x as f32 - 4.0, y as f32 - 4.0, 200.0, height);
+        d.draw_rect(panel, Color::BLACK.fade(0.7));
+
+        let average = if stats.frame_times.is_empty() {
+            0.0
+        } else {
+            stats.frame_times.iter().sum::<f32>() / stats.frame_times.len() as f32
+        };
+        let worst = stats.frame_times.iter().copied().fold(0.0, f32::max);
+        let drawn = world.in_view.len();
+        let culled = world.base_components.len() - drawn;
+        let lines = [
+            format!("frame {:.2}ms  worst {:.2}ms", average, worst),
+            format!("entities {} drawn {} culled", drawn, culled),
+            format!(
+                "sprites {} in {} batches",
+                stats.sprites, stats.sprite_batches
+            ),
+            format!("particles {}", world.particles.alive()),
+            pool_line(&world.pools.bullets, world.projectile_components.len()),
+            pool_line(
+                &world.pools.damage_numbers,
+                world.damage_number_components.len(),
+            ),
+        ];
+        for line in lines.iter() {
+            d.draw_text(line, x, y, 10, Color::WHITE);
+            y += 12;
+        }
+        for (name, ms) in stats.stages.iter() {
+            d.draw_text(
+                &format!("  {:<8}{:.2}ms", name, ms),
+                x,
+                y,
+                10,
+                Color::LIGHTGRAY,
+            );
+            y += 12;
+        }
+
+        // one bar per frame, the line marks a 60 fps frame
+        y += 4;
+        let bottom = y as f32 + GRAPH_SIZE.y;
+        let bar_width = GRAPH_SIZE.x / HISTORY as f32;
+        for (i, ms) in stats.frame_times.iter().enumerate() {
+            let height = (ms / GRAPH_MAX_MS).min(1.0) * GRAPH_SIZE.y;
+            let color = if *ms > 1000.0 / 59.0 {
+                Color::RED
+            } else {
+                Color::LIME
+            };
+            d.draw_rect(
+                Rectangle::new(
+                    x as f32 + i as f32 * bar_width,
+                    bottom - height,
+                    bar_width,
+                    height,
+                ),
+                color,
+            );
+        }
+        let target = bottom - (1000.0 / 60.0) / GRAPH_MAX_MS * GRAPH_SIZE.y;
+        d.draw_line(
+            Vector2::new(x as f32, target),
+            Vector2::new(x as f32 + GRAPH_SIZE.x, target),
+            1.0,
+            Color::YELLOW.fade(0.6),
+        );
+    }
+}
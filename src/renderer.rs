@@ -0,0 +1,237 @@
+use raylib::prelude::*;
+
+/// Lets a borrowed raw font go through raylib's safe drawing functions.
+struct FontRef<'a>(&'a ffi::Font);
+
+impl AsRef<ffi::Font> for FontRef<'_> {
+    fn as_ref(&self) -> &ffi::Font {
+        self.0
+    }
+}
+
+/// Same for a texture, owned or a render target's.
+struct TextureRef<'a>(&'a ffi::Texture2D);
+
+impl AsRef<ffi::Texture2D> for TextureRef<'_> {
+    fn as_ref(&self) -> &ffi::Texture2D {
+        self.0
+    }
+}
+
+/// What draw systems draw with. Every raylib draw handle is one, a
+/// `Recorder` is one without a window. Only the passes that bind render
+/// targets or blend modes themselves take the draw handle: the canvas, the
+/// light map, the minimap's render, the screenshot and `render::draw_world`
+/// around the layers.
+pub trait Renderer {
+    fn draw_rect(&mut self, rect: Rectangle, color: Color);
+    fn draw_rect_lines(&mut self, rect: Rectangle, thickness: i32, color: Color);
+    fn draw_line(&mut self, from: Vector2, to: Vector2, thickness: f32, color: Color);
+    fn draw_circle(&mut self, center: Vector2, radius: f32, color: Color);
+    fn draw_circle_outline(&mut self, center: Vector2, radius: f32, color: Color);
+    /// Corners go counter-clockwise on screen, or nothing shows.
+    fn draw_triangle(&mut self, a: Vector2, b: Vector2, c: Vector2, color: Color);
+    /// Text in raylib's built-in font.
+    fn draw_text(&mut self, text: &str, x: i32, y: i32, size: i32, color: Color);
+    fn draw_text_font(
+        &mut self,
+        font: &ffi::Font,
+        text: &str,
+        pos: Vector2,
+        size: f32,
+        spacing: f32,
+        color: Color,
+    );
+    /// Draws `source` of the texture into `dest`, turned by `rotation`
+    /// degrees around `origin`.
+    fn draw_sprite(
+        &mut self,
+        texture: &ffi::Texture2D,
+        source: Rectangle,
+        dest: Rectangle,
+        origin: Vector2,
+        rotation: f32,
+        tint: Color,
+    );
+    /// Draws the texture stretched into `dest` without stretching the
+    /// borders `info` marks out.
+    fn draw_nine_slice(
+        &mut self,
+        texture: &ffi::Texture2D,
+        info: NPatchInfo,
+        dest: Rectangle,
+        tint: Color,
+    );
+}
+
+impl<D: RaylibDraw> Renderer for D {
+    fn draw_rect(&mut self, rect: Rectangle, color: Color) {
+        self.draw_rectangle_rec(rect, color);
+    }
+
+    fn draw_rect_lines(&mut self, rect: Rectangle, thickness: i32, color: Color) {
+        self.draw_rectangle_lines_ex(rect, thickness, color);
+    }
+
+    fn draw_line(&mut self, from: Vector2, to: Vector2, thickness: f32, color: Color) {
+        self.draw_line_ex(from, to, thickness, color);
+    }
+
+    fn draw_circle(&mut self, center: Vector2, radius: f32, color: Color) {
+        self.draw_circle_v(center, radius, color);
+    }
+
+    fn draw_circle_outline(&mut self, center: Vector2, radius: f32, color: Color) {
+        self.draw_circle_lines(center.x as i32, center.y as i32, radius, color);
+    }
+
+    fn draw_triangle(&mut self, a: Vector2, b: Vector2, c: Vector2, color: Color) {
+        RaylibDraw::draw_triangle(self, a, b, c, color);
+    }
+
+    fn draw_text(&mut self, text: &str, x: i32, y: i32, size: i32, color: Color) {
+        RaylibDraw::draw_text(self, text, x, y, size, color);
+    }
+
+    fn draw_text_font(
+        &mut self,
+        font: &ffi::Font,
+        text: &str,
+        pos: Vector2,
+        size: f32,
+        spacing: f32,
+        color: Color,
+    ) {
+        self.draw_text_ex(FontRef(font), text, pos, size, spacing, color);
+    }
+
+    fn draw_sprite(
+        &mut self,
+        texture: &ffi::Texture2D,
+        source: Rectangle,
+        dest: Rectangle,
+        origin: Vector2,
+        rotation: f32,
+        tint: Color,
+    ) {
+        self.draw_texture_pro(TextureRef(texture), source, dest, origin, rotation, tint);
+    }
+
+    fn draw_nine_slice(
+        &mut self,
+        texture: &ffi::Texture2D,
+        info: NPatchInfo,
+        dest: Rectangle,
+        tint: Color,
+    ) {
+        self.draw_texture_n_patch(TextureRef(texture), info, dest, Vector2::zero(), 0.0, tint);
+    }
+}
+
+/// One call made on a `Recorder`. Textures and fonts are known by their
+/// GPU id.
+#[derive(Debug, Clone)]
+pub enum DrawCommand {
+    Rect(Rectangle, Color),
+    RectLines(Rectangle, i32, Color),
+    Line(Vector2, Vector2, f32, Color),
+    Circle(Vector2, f32, Color),
+    CircleOutline(Vector2, f32, Color),
+    Triangle(Vector2, Vector2, Vector2, Color),
+    Text(String, i32, i32, i32, Color),
+    TextFont(u32, String, Vector2, f32, Color),
+    Sprite(u32, Rectangle, Rectangle, f32, Color),
+    NineSlice(u32, Rectangle, Color),
+}
+
+/// Draws nothing, only writes down what it was asked to draw. For tests
+/// and comparing frames without a window.
+pub struct Recorder {
+    pub commands: Vec<DrawCommand>,
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder {
+            commands: Vec::new(),
+        }
+    }
+}
+
+impl Renderer for Recorder {
+    fn draw_rect(&mut self, rect: Rectangle, color: Color) {
+        self.commands.push(DrawCommand::Rect(rect, color));
+    }
+
+    fn draw_rect_lines(&mut self, rect: Rectangle, thickness: i32, color: Color) {
+        self.commands
+            .push(DrawCommand::RectLines(rect, thickness, color));
+    }
+
+    fn draw_line(&mut self, from: Vector2, to: Vector2, thickness: f32, color: Color) {
+        self.commands
+            .push(DrawCommand::Line(from, to, thickness, color));
+    }
+
+    fn draw_circle(&mut self, center: Vector2, radius: f32, color: Color) {
+        self.commands
+            .push(DrawCommand::Circle(center, radius, color));
+    }
+
+    fn draw_circle_outline(&mut self, center: Vector2, radius: f32, color: Color) {
+        self.commands
+            .push(DrawCommand::CircleOutline(center, radius, color));
+    }
+
+    fn draw_triangle(&mut self, a: Vector2, b: Vector2, c: Vector2, color: Color) {
+        self.commands.push(DrawCommand::Triangle(a, b, c, color));
+    }
+
+    fn draw_text(&mut self, text: &str, x: i32, y: i32, size: i32, color: Color) {
+        self.commands
+            .push(DrawCommand::Text(text.to_string(), x, y, size, color));
+    }
+
+    fn draw_text_font(
+        &mut self,
+        font: &ffi::Font,
+        text: &str,
+        pos: Vector2,
+        size: f32,
+        _spacing: f32,
+        color: Color,
+    ) {
+        self.commands.push(DrawCommand::TextFont(
+            font.texture.id,
+            text.to_string(),
+            pos,
+            size,
+            color,
+        ));
+    }
+
+    fn draw_sprite(
+        &mut self,
+        texture: &ffi::Texture2D,
+        source: Rectangle,
+        dest: Rectangle,
+        _origin: Vector2,
+        rotation: f32,
+        tint: Color,
+    ) {
+        self.commands.push(DrawCommand::Sprite(
+            texture.id, source, dest, rotation, tint,
+        ));
+    }
+
+    fn draw_nine_slice(
+        &mut self,
+        texture: &ffi::Texture2D,
+        _info: NPatchInfo,
+        dest: Rectangle,
+        tint: Color,
+    ) {
+        self.commands
+            .push(DrawCommand::NineSlice(texture.id, dest, tint));
+    }
+}
@@ -0,0 +1,602 @@
+use std::fs;
+use std::path::PathBuf;
+
+use raylib::prelude::*;
+
+use crate::config::Config;
+use crate::crosshair::Crosshair;
+use crate::difficulty::Difficulty;
+use crate::state::GameState;
+use crate::stats::Stats;
+use crate::ui::UiElement;
+use crate::warn;
+use crate::widget::{UiEventKind, Widget};
+use crate::{i18n, t};
+use crate::{Anchor, EntityID, World};
+
+/// Written as the first line of every save. Bump it when the format
+/// changes, older saves load with defaults for whatever they lack.
+const SAVE_VERSION: u32 = 1;
+const SLOTS: usize = 3;
+/// Ships unlocked by a slot's best score.
+const UNLOCKS: [(u32, &str); 3] = [(0, "Scout"), (2500, "Interceptor"), (10000, "Gunship")];
+
+/// Settings that follow the player from slot to slot. Video settings
+/// belong to the machine and stay in the config only.
+fn is_profile_setting(key: &str) -> bool {
+    !matches!(key, "window_mode" | "window_scale" | "target_fps" | "vsync")
+}
+
+/// The player as they were when they last docked at a station.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Checkpoint {
+    position: Vector2,
+    health: u16,
+    credits: u32,
+    bombs: u8,
+}
+
+impl Checkpoint {
+    fn parse(value: &str) -> Option<Checkpoint> {
+        let mut parts = value.split(' ');
+        let mut next = || parts.next();
+        Some(Checkpoint {
+            position: Vector2::new(next()?.parse().ok()?, next()?.parse().ok()?),
+            health: next()?.parse().ok()?,
+            credits: next()?.parse().ok()?,
+            bombs: next()?.parse().ok()?,
+        })
+    }
+}
+
+/// Progress of one save slot, kept on disk as `<key> <value>` lines after a
+/// `version` line. Unknown lines are skipped, so saves from older versions
+/// still load.
+struct SaveGame {
+    version: u32,
+    runs: u32,
+    best_score: u32,
+    /// Seconds spent playing on this slot.
+    play_time: f32,
+    unlocked: Vec<String>,
+    /// Config entries for the profile settings.
+    settings: Vec<(String, String)>,
+    checkpoint: Option<Checkpoint>,
+}
+
+impl SaveGame {
+    fn new(config: &Config) -> SaveGame {
+        let mut save = SaveGame {
+            version: SAVE_VERSION,
+            runs: 0,
+            best_score: 0,
+            play_time: 0.0,
+            unlocked: Vec::new(),
+            settings: Vec::new(),
+            checkpoint: None,
+        };
+        save.unlock();
+        save.keep_settings(config);
+        save
+    }
+
+    /// None when there's no save in the slot yet.
+    fn load(path: &PathBuf) -> Option<SaveGame> {
+        fs::read_to_string(path)
+            .ok()
+            .map(|contents| SaveGame::parse(&contents))
+    }
+
+    fn parse(contents: &str) -> SaveGame {
+        let mut save = SaveGame {
+            // saves from before versioning have no version line
+            version: 0,
+            runs: 0,
+            best_score: 0,
+            play_time: 0.0,
+            unlocked: Vec::new(),
+            settings: Vec::new(),
+            checkpoint: None,
+        };
+        for line in contents.lines() {
+            let (key, value) = match line.split_once(' ') {
+                Some(kv) => kv,
+                None => continue,
+            };
+            match key {
+                "version" => save.version = value.parse().unwrap_or(0),
+                "runs" => save.runs = value.parse().unwrap_or(0),
+                "best_score" => save.best_score = value.parse().unwrap_or(0),
+                "play_time" => save.play_time = value.parse().unwrap_or(0.0),
+                "unlock" => save.unlocked.push(value.to_string()),
+                "setting" => {
+                    if let Some((key, value)) = value.split_once(' ') {
+                        save.settings.push((key.to_string(), value.to_string()));
+                    }
+                }
+                "checkpoint" => save.checkpoint = Checkpoint::parse(value),
+                _ => (),
+            }
+        }
+        // catch up on unlocks added since the save was written
+        save.unlock();
+        save
+    }
+
+    fn save(&mut self, path: &PathBuf) {
+        self.version = SAVE_VERSION;
+        if let Err(e) = fs::write(path, self.contents()) {
+            warn!(
+                "save",
+                "could not save game",
+                path = path.display(),
+                error = e
+            );
+        }
+    }
+
+    fn contents(&self) -> String {
+        let mut contents = format!(
+            "version {}\nruns {}\nbest_score {}\nplay_time {}\n",
+            self.version, self.runs, self.best_score, self.play_time
+        );
+        for ship in self.unlocked.iter() {
+            contents += &format!("unlock {}\n", ship);
+        }
+        for (key, value) in self.settings.iter() {
+            contents += &format!("setting {} {}\n", key, value);
+        }
+        if let Some(c) = self.checkpoint {
+            contents += &format!(
+                "checkpoint {} {} {} {} {}\n",
+                c.position.x, c.position.y, c.health, c.credits, c.bombs
+            );
+        }
+        contents
+    }
+
+    /// Written by a newer version of the game, which might store things
+    /// this one would lose by saving over it.
+    fn is_newer(&self) -> bool {
+        self.version > SAVE_VERSION
+    }
+
+    /// Whether this game may play, and so save over, the slot.
+    fn check(&self) -> Result<(), String> {
+        if self.is_newer() {
+            return Err(format!(
+                "save is version {}, this game only knows up to {}",
+                self.version, SAVE_VERSION
+            ));
+        }
+        Ok(())
+    }
+
+    fn unlock(&mut self) {
+        for (score, ship) in UNLOCKS {
+            if self.best_score >= score && !self.unlocked.iter().any(|s| s == ship) {
+                self.unlocked.push(ship.to_string());
+            }
+        }
+    }
+
+    fn keep_settings(&mut self, config: &Config) {
+        self.settings = config
+            .entries()
+            .into_iter()
+            .filter(|(key, _)| is_profile_setting(key))
+            .collect();
+    }
+
+    fn label(&self, slot: usize) -> String {
+        if self.is_newer() {
+            return t!("slots.newer", slot = slot + 1);
+        }
+        let minutes = (self.play_time / 60.0) as u32;
+        t!(
+            "slots.progress",
+            slot = slot + 1,
+            score = self.best_score,
+            hours = minutes / 60,
+            minutes = format!("{:02}", minutes % 60)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlotButton {
+    Slot(usize),
+    /// Cycles the difficulty an empty slot starts on, a slot in progress
+    /// keeps its own.
+    Difficulty,
+    Back,
+}
+
+fn difficulty_label(difficulty: Difficulty) -> String {
+    let name = match difficulty {
+        Difficulty::Easy => t!("slots.easy"),
+        Difficulty::Normal => t!("slots.normal"),
+        Difficulty::Hard => t!("slots.hard"),
+        Difficulty::Nightmare => t!("slots.nightmare"),
+    };
+    t!("slots.difficulty", difficulty = name)
+}
+
+/// Save slots and the panel to pick one, opened by the title screen's
+/// Start button. The picked slot brings its settings along, resumes from
+/// its checkpoint and is saved whenever the player docks at a station or a
+/// run ends.
+pub struct SaveSlots {
+    /// Set when a slot is picked, like PauseMenu's.
+    pub new_game: Option<GameState>,
+    slots: Vec<(PathBuf, Option<SaveGame>)>,
+    active: Option<usize>,
+    open: bool,
+    panel: Option<EntityID>,
+    buttons: Vec<(EntityID, SlotButton)>,
+    docked: bool,
+    last_state: GameState,
+}
+
+impl SaveSlots {
+    /// Slots are stored as `<prefix>1.txt`, `<prefix>2.txt` and so on.
+    pub fn load(prefix: &str) -> SaveSlots {
+        let slots = (1..=SLOTS)
+            .map(|i| {
+                let path = PathBuf::from(format!("{}{}.txt", prefix, i));
+                let save = SaveGame::load(&path);
+                (path, save)
+            })
+            .collect();
+        SaveSlots {
+            new_game: None,
+            slots,
+            active: None,
+            open: false,
+            panel: None,
+            buttons: Vec::new(),
+            docked: false,
+            last_state: GameState::Menu,
+        }
+    }
+
+    /// Every ship and whether any slot has unlocked it.
+    pub fn ships(&self) -> Vec<(&'static str, bool)> {
+        UNLOCKS
+            .iter()
+            .map(|(_, ship)| {
+                let unlocked = self
+                    .slots
+                    .iter()
+                    .filter_map(|s| s.1.as_ref())
+                    .any(|save| save.unlocked.iter().any(|s| s == ship));
+                (*ship, unlocked)
+            })
+            .collect()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Shows the panel from the next update on.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    fn spawn_panel(&mut self, world: &mut World) {
+        self.panel = Some(Widget::spawn(
+            world,
+            Widget::panel(),
+            UiElement::new(
+                Anchor::Center,
+                Vector2::new(0.0, 64.0),
+                Vector2::new(300.0, 168.0),
+            ),
+        ));
+        for (i, (_, save)) in self.slots.iter().enumerate() {
+            let label = match save {
+                Some(save) => save.label(i),
+                None => t!("slots.empty", slot = i + 1),
+            };
+            let offset = Vector2::new(0.0, i as f32 * 28.0);
+            let id = Widget::spawn(
+                world,
+                Widget::button(label),
+                UiElement::new(Anchor::Center, offset, Vector2::new(260.0, 22.0)),
+            );
+            self.buttons.push((id, SlotButton::Slot(i)));
+        }
+        let difficulty = Widget::spawn(
+            world,
+            Widget::button(difficulty_label(world.config.difficulty)),
+            UiElement::new(
+                Anchor::Center,
+                Vector2::new(0.0, 92.0),
+                Vector2::new(200.0, 20.0),
+            ),
+        );
+        self.buttons.push((difficulty, SlotButton::Difficulty));
+        let back = Widget::spawn(
+            world,
+            Widget::button(t!("menu.back")),
+            UiElement::new(
+                Anchor::Center,
+                Vector2::new(0.0, 124.0),
+                Vector2::new(100.0, 20.0),
+            ),
+        );
+        self.buttons.push((back, SlotButton::Back));
+        world.focus.focused = self.buttons.first().map(|b| b.0);
+    }
+
+    fn close(&mut self, world: &mut World) {
+        self.open = false;
+        if let Some(panel) = self.panel.take() {
+            world.despawn(panel);
+        }
+        for (id, _) in self.buttons.drain(..) {
+            world.despawn(id);
+        }
+    }
+
+    /// Makes `slot` the one being played, starting a new save in an empty
+    /// slot and taking over the settings of an existing one.
+    fn pick(&mut self, slot: usize, world: &mut World) {
+        let (path, save) = &mut self.slots[slot];
+        if let Some(Err(e)) = save.as_ref().map(SaveGame::check) {
+            warn!(
+                "save",
+                "refused to load save",
+                path = path.display(),
+                error = e
+            );
+            return;
+        }
+        let save = save.get_or_insert_with(|| SaveGame::new(&world.config));
+        save.save(path);
+        for (key, value) in save.settings.iter() {
+            world.config.set(key, value);
+        }
+        world.config.save();
+        i18n::set_language(&world.config.language);
+        world.volume = world.config.volume();
+        world.bindings = world.config.bindings.clone();
+        world.juice.intensity = world.config.screen_shake;
+        world.palette.mode = world.config.color_mode;
+        world.palette.high_contrast_bullets = world.config.high_contrast_bullets;
+        world.difficulty = world.config.difficulty;
+        world.crosshair = Crosshair::new(world.config.crosshair, world.config.crosshair_scale);
+
+        self.active = Some(slot);
+        self.new_game = Some(GameState::Playing);
+        self.close(world);
+    }
+
+    fn next_difficulty(&mut self, world: &mut World) {
+        let values = Difficulty::values();
+        let i = values.iter().position(|d| *d == world.config.difficulty);
+        let next = values[i.map(|i| (i + 1) % values.len()).unwrap_or(0)];
+        world.config.difficulty = next;
+        let button = self.buttons.iter().find(|b| b.1 == SlotButton::Difficulty);
+        if let Some(w) =
+            button.and_then(|b| world.widget_components.iter_mut().find(|w| w.0 == b.0))
+        {
+            w.1.set_label(difficulty_label(next));
+        }
+    }
+
+    fn save_active(&mut self, config: &Config) {
+        if let Some((path, Some(save))) = self.active.and_then(|i| self.slots.get_mut(i)) {
+            save.keep_settings(config);
+            save.save(path);
+        }
+    }
+
+    /// Puts the player back where the active slot's checkpoint left them,
+    /// right after the level is built.
+    pub fn restore(&mut self, world: &mut World) {
+        let checkpoint = match self.active.and_then(|i| self.slots[i].1.as_ref()) {
+            Some(save) => save.checkpoint,
+            None => return,
+        };
+        let (checkpoint, player) = match (checkpoint, world.player_components.first()) {
+            (Some(c), Some(p)) => (c, p.0),
+            _ => return,
+        };
+        if let Some(base) = world.base_components.iter_mut().find(|b| b.0 == player) {
+            let offset = checkpoint.position - base.1.bounds.center();
+            base.1.bounds.translate(offset);
+        }
+        if let Some(health) = world.health_components.iter_mut().find(|h| h.0 == player) {
+            health.1.health = checkpoint.health.min(health.1.max_health);
+        }
+        if let Some(p) = world.player_components.iter_mut().find(|p| p.0 == player) {
+            p.1.credits = checkpoint.credits;
+        }
+        if let Some(bombs) = world.bomb_components.iter_mut().find(|b| b.0 == player) {
+            bombs.1.count = checkpoint.bombs.min(bombs.1.capacity);
+        }
+        // no pan across the map to get there
+        for (_, camera) in world.camera_components.iter_mut() {
+            if camera.follow == Some(player) {
+                camera.target = checkpoint.position;
+            }
+        }
+        self.docked = false;
+    }
+
+    fn checkpoint(world: &World) -> Option<Checkpoint> {
+        let (player, p) = world.player_components.first()?;
+        let base = world.base_components.iter().find(|b| b.0 == *player)?;
+        let health = world.health_components.iter().find(|h| h.0 == *player)?;
+        let bombs = world
+            .bomb_components
+            .iter()
+            .find(|b| b.0 == *player)
+            .map(|b| b.1.count)
+            .unwrap_or(0);
+        Some(Checkpoint {
+            position: base.1.bounds.center(),
+            health: health.1.health,
+            credits: p.credits,
+            bombs,
+        })
+    }
+
+    /// Runs the slot panel and keeps the active slot's progress up to date.
+    pub fn update_system(&mut self, world: &mut World, stats: &Stats, dt: f32) {
+        if self.open {
+            if self.panel.is_none() {
+                self.spawn_panel(world);
+            }
+            let clicked: Vec<SlotButton> = world
+                .ui_events
+                .iter()
+                .filter(|e| e.kind == UiEventKind::Clicked)
+                .filter_map(|e| self.buttons.iter().find(|b| b.0 == e.widget))
+                .map(|b| b.1)
+                .collect();
+            for button in clicked {
+                match button {
+                    SlotButton::Slot(slot) => self.pick(slot, world),
+                    SlotButton::Difficulty => self.next_difficulty(world),
+                    SlotButton::Back => self.close(world),
+                }
+            }
+        }
+
+        let active = match self.active {
+            Some(i) => i,
+            None => return,
+        };
+        let state = world.state.current();
+        let entered = state != self.last_state;
+        self.last_state = state;
+        let docked = world
+            .player_components
+            .iter()
+            .any(|p| p.1.docked_at.is_some());
+        let docking = docked && !self.docked;
+        self.docked = docked;
+        let save = match self.slots[active].1.as_mut() {
+            Some(s) => s,
+            None => return,
+        };
+
+        let changed = match state {
+            GameState::Playing => {
+                save.play_time += dt;
+                if docking {
+                    save.checkpoint = SaveSlots::checkpoint(world);
+                }
+                docking
+            }
+            GameState::GameOver if entered => {
+                // a lost run can't be resumed, the next starts from scratch
+                save.runs += 1;
+                save.best_score = save.best_score.max(stats.score());
+                save.checkpoint = None;
+                save.unlock();
+                true
+            }
+            GameState::Menu => entered,
+            _ => false,
+        };
+        if changed {
+            self.save_active(&world.config);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_saves_from_before_versioning() {
+        let save = SaveGame::parse("runs 3\nbest_score 3000\nplay_time 90.5\n");
+        assert_eq!(save.version, 0);
+        assert_eq!(
+            (save.runs, save.best_score, save.play_time),
+            (3, 3000, 90.5)
+        );
+        // unlocks it never wrote down are caught up on
+        assert_eq!(save.unlocked, ["Scout", "Interceptor"]);
+        assert_eq!(save.checkpoint, None);
+        assert!(save.check().is_ok());
+    }
+
+    #[test]
+    fn skips_lines_it_doesnt_know() {
+        let save =
+            SaveGame::parse("version 1\nfuture_thing 12 34\nnonsense\n\nruns 2\nbest_score lots\n");
+        assert_eq!(save.version, 1);
+        assert_eq!(save.runs, 2);
+        assert_eq!(save.best_score, 0);
+    }
+
+    #[test]
+    fn refuses_newer_saves() {
+        let contents = format!("version {}\nruns 7\n", SAVE_VERSION + 1);
+        let save = SaveGame::parse(&contents);
+        assert!(save.is_newer());
+        assert_eq!(
+            save.check(),
+            Err(format!(
+                "save is version {}, this game only knows up to {}",
+                SAVE_VERSION + 1,
+                SAVE_VERSION
+            ))
+        );
+
+        // picking the slot neither starts a game nor writes over it
+        let dir = std::env::temp_dir().join(format!("spacegame-save-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let prefix = dir.join("slot").display().to_string();
+        let path = PathBuf::from(format!("{}1.txt", prefix));
+        fs::write(&path, &contents).unwrap();
+        let mut slots = SaveSlots::load(&prefix);
+        let mut world = World::new();
+        slots.pick(0, &mut world);
+        assert_eq!(slots.new_game, None);
+        assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn checkpoints_round_trip() {
+        let mut save = SaveGame::parse("");
+        save.runs = 4;
+        save.best_score = 2000;
+        save.play_time = 61.25;
+        save.settings = vec![("language".to_string(), "\"de\"".to_string())];
+        save.checkpoint = Some(Checkpoint {
+            position: Vector2::new(-120.5, 48.0),
+            health: 17,
+            credits: 350,
+            bombs: 2,
+        });
+        save.version = SAVE_VERSION;
+
+        let read = SaveGame::parse(&save.contents());
+        assert_eq!(read.version, SAVE_VERSION);
+        assert_eq!(
+            (read.runs, read.best_score, read.play_time),
+            (4, 2000, 61.25)
+        );
+        assert_eq!(read.unlocked, save.unlocked);
+        assert_eq!(read.settings, save.settings);
+        assert_eq!(read.checkpoint, save.checkpoint);
+    }
+
+    #[test]
+    fn drops_a_broken_checkpoint() {
+        assert_eq!(Checkpoint::parse("1 2 3 4"), None);
+        assert_eq!(Checkpoint::parse("1 2 -3 4 5"), None);
+        assert_eq!(Checkpoint::parse("x 2 3 4 5"), None);
+        let save = SaveGame::parse("version 1\ncheckpoint 1 2\nruns 1\n");
+        assert_eq!(save.checkpoint, None);
+        assert_eq!(save.runs, 1);
+    }
+}
@@ -0,0 +1,228 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use raylib::prelude::*;
+
+use crate::font::{self, TextStyle};
+use crate::pack;
+use crate::renderer::Renderer;
+use crate::state::GameState;
+use crate::t;
+use crate::{info, warn};
+use crate::{Anchor, World};
+
+/// Spawns a scene's entities, loading what it needs through world.assets.
+pub type Build = fn(&mut World, &mut RaylibHandle, &RaylibThread);
+
+/// A file read ahead on the loading thread. Images are decoded there too,
+/// leaving only the upload to the GPU for the main thread.
+pub enum Preload {
+    Bytes(Vec<u8>),
+    Image(Image),
+}
+
+// SAFETY: an Image is pixel memory owned by whoever holds it, raylib keeps
+// no pointer to it, so it may move to the main thread
+unsafe impl Send for Preload {}
+
+/// Files read ahead by path, handed to the assets for loading from memory.
+pub type Preloaded = Vec<(String, Preload)>;
+
+/// What the loading thread reports back.
+enum Loading {
+    /// This many files are done.
+    Progress(usize),
+    Done(Preloaded),
+}
+
+fn read_ahead(path: &str) -> Option<Preload> {
+    let bytes = pack::read(path).ok()?;
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    if !["png", "jpg", "bmp", "gif", "qoi"].contains(&extension) {
+        return Some(Preload::Bytes(bytes));
+    }
+    let file_type = format!(".{}", extension);
+    Image::load_image_from_mem(&file_type, &bytes, bytes.len() as i32)
+        .ok()
+        .map(Preload::Image)
+}
+
+/// A level or screen of the game, built from scratch every time it's
+/// entered.
+pub struct Scene {
+    pub name: &'static str,
+    /// State the game goes into once the scene is built.
+    pub state: GameState,
+    /// Files read on a background thread while the loading screen is up.
+    pub preload: Vec<&'static str>,
+    build: Build,
+}
+
+impl Scene {
+    pub fn new(name: &'static str, state: GameState, build: Build) -> Scene {
+        Scene {
+            name,
+            state,
+            preload: Vec::new(),
+            build,
+        }
+    }
+
+    pub fn preloading(mut self, paths: &[&'static str]) -> Scene {
+        self.preload.extend_from_slice(paths);
+        self
+    }
+}
+
+/// Switches between scenes. The current scene owns every entity in the
+/// world and every asset loaded since it started, so switching empties the
+/// world, frees the scene's assets and builds the next scene. Assets loaded
+/// before the first scene, like fonts, are shared by all of them.
+///
+/// The next scene's files are read on a background thread behind a loading
+/// screen, the old scene keeps running underneath until they're in.
+pub struct Scenes {
+    scenes: Vec<Scene>,
+    current: Option<&'static str>,
+    /// Scene being switched to and the thread reading its files.
+    pending: Option<(usize, Receiver<Loading>)>,
+    /// Files of the pending scene read so far, and how many it wants.
+    progress: (usize, usize),
+    loading_time: f32,
+}
+
+impl Scenes {
+    pub fn new() -> Scenes {
+        Scenes {
+            scenes: Vec::new(),
+            current: None,
+            pending: None,
+            progress: (0, 0),
+            loading_time: 0.0,
+        }
+    }
+
+    pub fn add(&mut self, scene: Scene) {
+        self.scenes.push(scene);
+    }
+
+    pub fn current(&self) -> Option<&'static str> {
+        self.current
+    }
+
+    pub fn loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Starts switching to the scene called `name`, even when it's already
+    /// current, which restarts it. Unknown names are ignored.
+    pub fn load(&mut self, name: &str) {
+        let index = match self.scenes.iter().position(|s| s.name == name) {
+            Some(i) => i,
+            None => {
+                warn!("scene", "no scene with that name", name = name);
+                return;
+            }
+        };
+        let paths: Vec<String> = self.scenes[index]
+            .preload
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        self.progress = (0, paths.len());
+        let (sender, receiver) = mpsc::channel();
+        let read = move || {
+            let mut files = Vec::new();
+            for (i, path) in paths.into_iter().enumerate() {
+                // missing files are left for the scene to complain about
+                if let Some(file) = read_ahead(&path) {
+                    files.push((path, file));
+                }
+                sender.send(Loading::Progress(i + 1)).ok();
+            }
+            sender.send(Loading::Done(files)).ok();
+        };
+        // the browser build has no threads, but its files are preloaded
+        // into memory so reading them doesn't block
+        if cfg!(target_os = "emscripten") {
+            read();
+        } else {
+            thread::spawn(read);
+        }
+        self.pending = Some((index, receiver));
+        self.loading_time = 0.0;
+    }
+
+    /// Builds the pending scene once its files are read. Returns its name
+    /// on the frame it's entered.
+    pub fn update_system(
+        &mut self,
+        world: &mut World,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        dt: f32,
+    ) -> Option<&'static str> {
+        let (index, receiver) = self.pending.as_ref()?;
+        self.loading_time += dt;
+        // a reader that died only loses the head start
+        let files = loop {
+            match receiver.try_recv() {
+                Ok(Loading::Progress(done)) => self.progress.0 = done,
+                Ok(Loading::Done(files)) => break files,
+                Err(mpsc::TryRecvError::Empty) => return None,
+                Err(mpsc::TryRecvError::Disconnected) => break Vec::new(),
+            }
+        };
+        let scene = &self.scenes[*index];
+
+        world.assets.end_scene();
+        world.reset();
+        world.assets.begin_scene();
+        world.assets.preloaded = files;
+        (scene.build)(world, rl, thread);
+        world.assets.preloaded.clear();
+        world.state.set(scene.state);
+
+        info!(
+            "scene",
+            "scene loaded",
+            name = scene.name,
+            seconds = format!("{:.2}", self.loading_time)
+        );
+        self.current = Some(scene.name);
+        self.pending = None;
+        self.current
+    }
+
+    /// Loading screen, on top of everything while a scene is pending.
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if !self.loading() {
+            return;
+        }
+        let (w, h) = (world.screen.width(), world.screen.height());
+        d.draw_rect(Rectangle::new(0.0, 0.0, w, h), Color::BLACK);
+        let (done, total) = self.progress;
+        if total > 0 {
+            let bar = Rectangle::new(w * 0.3, h * 0.5 - 3.0, w * 0.4, 6.0);
+            let filled = Rectangle {
+                width: bar.width * done as f32 / total as f32,
+                ..bar
+            };
+            d.draw_rect(filled, Color::RAYWHITE);
+            d.draw_rect_lines(bar, 1, Color::GRAY);
+        }
+        let dots = (self.loading_time * 3.0) as usize % 4;
+        let text = format!("{}{:<3}", t!("scene.loading"), ".".repeat(dots));
+        let style = TextStyle::new(16.0, Color::RAYWHITE).aligned(Anchor::BottomRight);
+        font::draw(
+            &world.assets,
+            d,
+            &text,
+            Vector2::new(w - 12.0, h - 12.0),
+            &style,
+        );
+    }
+}
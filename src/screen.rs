@@ -0,0 +1,138 @@
+use raylib::prelude::*;
+
+use crate::{BoundingBox2D, World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    /// Undecorated window covering the whole monitor.
+    Borderless,
+    /// Exclusive fullscreen at the monitor's resolution.
+    Fullscreen,
+}
+
+impl WindowMode {
+    pub fn values() -> Vec<WindowMode> {
+        vec![
+            WindowMode::Windowed,
+            WindowMode::Borderless,
+            WindowMode::Fullscreen,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "windowed",
+            WindowMode::Borderless => "borderless",
+            WindowMode::Fullscreen => "fullscreen",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<WindowMode> {
+        WindowMode::values().into_iter().find(|m| m.name() == name)
+    }
+}
+
+/// Size of the window and of the canvas the game draws into. Anything laid
+/// out on screen reads the canvas size from here rather than assuming one.
+pub struct ScreenSize {
+    pub canvas: Vector2,
+    pub window: Vector2,
+    pub mode: WindowMode,
+    /// Window size to go back to when leaving a fullscreen mode.
+    windowed: Vector2,
+}
+
+impl ScreenSize {
+    pub fn new(canvas: Vector2, window: Vector2) -> ScreenSize {
+        ScreenSize {
+            canvas,
+            window,
+            mode: WindowMode::Windowed,
+            windowed: window,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.canvas.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.canvas.y
+    }
+
+    /// The canvas as a box, for anchoring and edge clamping.
+    pub fn bounds(&self) -> BoundingBox2D {
+        BoundingBox2D::new_v(Vector2::zero(), self.canvas)
+    }
+
+    /// Scale and top left corner of the canvas inside the window. Whole
+    /// number scales keep pixels square, only windows smaller than the
+    /// canvas fall back to shrinking it smoothly.
+    pub fn letterbox(&self) -> (f32, Vector2) {
+        let fit = (self.window.x / self.canvas.x).min(self.window.y / self.canvas.y);
+        let scale = if fit >= 1.0 { fit.floor() } else { fit };
+        (scale, (self.window - self.canvas * scale) * 0.5)
+    }
+
+    /// Converts a window position, like the mouse, into canvas pixels.
+    pub fn window_to_canvas(&self, pos: Vector2) -> Vector2 {
+        let (scale, offset) = self.letterbox();
+        (pos - offset) / scale
+    }
+
+    pub fn set_mode(&mut self, rl: &mut RaylibHandle, mode: WindowMode) {
+        if mode == self.mode {
+            return;
+        }
+
+        // always pass through windowed so each mode starts from a clean state
+        match self.mode {
+            WindowMode::Fullscreen => rl.toggle_fullscreen(),
+            WindowMode::Borderless => {
+                rl.clear_window_state(WindowState::default().set_window_undecorated(true))
+            }
+            WindowMode::Windowed => {
+                self.windowed =
+                    Vector2::new(rl.get_screen_width() as f32, rl.get_screen_height() as f32)
+            }
+        }
+
+        let monitor = get_current_monitor();
+        let monitor_size = (get_monitor_width(monitor), get_monitor_height(monitor));
+        match mode {
+            WindowMode::Windowed => {
+                rl.set_window_size(self.windowed.x as i32, self.windowed.y as i32);
+            }
+            WindowMode::Borderless => {
+                rl.set_window_state(WindowState::default().set_window_undecorated(true));
+                rl.set_window_position(0, 0);
+                rl.set_window_size(monitor_size.0, monitor_size.1);
+            }
+            WindowMode::Fullscreen => {
+                rl.set_window_size(monitor_size.0, monitor_size.1);
+                rl.toggle_fullscreen();
+            }
+        }
+        self.mode = mode;
+    }
+
+    /// F11 cycles windowed, borderless and fullscreen.
+    pub fn input_system(world: &mut World, rl: &mut RaylibHandle) {
+        if !rl.is_key_pressed(KeyboardKey::KEY_F11) {
+            return;
+        }
+        let next = match world.screen.mode {
+            WindowMode::Windowed => WindowMode::Borderless,
+            WindowMode::Borderless => WindowMode::Fullscreen,
+            WindowMode::Fullscreen => WindowMode::Windowed,
+        };
+        world.screen.set_mode(rl, next);
+    }
+
+    /// Picks up the new window size after a resize or mode change.
+    pub fn update_system(world: &mut World, rl: &RaylibHandle) {
+        world.screen.window =
+            Vector2::new(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+    }
+}
@@ -0,0 +1,1437 @@
+use std::fmt;
+use std::mem;
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::pack;
+use crate::particles::EmitterConfig;
+use crate::prefab::{Placement, Prefabs};
+use crate::projectile::Projectile;
+use crate::rng::Rng;
+use crate::telegraph::Telegraph;
+use crate::{info, warn};
+use crate::{BoundingBox2D, EntityID, UpdateSystem, World};
+
+/// Statements and calls one hook may run before it's stopped, so a script
+/// stuck in a loop can't hang the game.
+const MAX_STEPS: u32 = 10_000;
+/// Calls deep a script may go, so runaway recursion stops before the
+/// game's own stack runs out.
+const MAX_DEPTH: usize = 64;
+/// Blocks, brackets and operators deep the parser goes, so a file of
+/// nothing but `(` can't run it out of stack.
+const MAX_NESTING: usize = 128;
+/// Damage of a bullet from `fire` when the script doesn't say.
+const BULLET_DAMAGE: f32 = 2.0;
+
+/// A value in a script. Numbers are the game's `f32`s, angles are in
+/// degrees like everywhere else.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unit,
+    Number(f32),
+    Bool(bool),
+    Str(String),
+    Vector(Vector2),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "nothing",
+            Value::Number(_) => "number",
+            Value::Bool(_) => "bool",
+            Value::Str(_) => "string",
+            Value::Vector(_) => "vector",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Vector(v) => write!(f, "({}, {})", v.x, v.y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Str(String),
+    Name(String),
+    Symbol(&'static str),
+    End,
+}
+
+/// Two character symbols first, so `==` isn't read as two `=`.
+const SYMBOLS: [&str; 22] = [
+    "==", "!=", "<=", ">=", "&&", "||", "(", ")", "{", "}", ",", ";", ".", "=", "<", ">", "+", "-",
+    "*", "/", "%", "!",
+];
+
+const KEYWORDS: [&str; 8] = [
+    "let", "fn", "if", "else", "while", "return", "true", "false",
+];
+
+/// Splits source into tokens, each with its line.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit()
+                    || chars[i] == '.' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()))
+            {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse()
+                .map_err(|_| format!("line {}: bad number {}", line, text))?;
+            tokens.push((Token::Number(number), line));
+        } else if c == '"' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some('"') => break,
+                    Some('\\') if chars.get(i + 1) == Some(&'n') => {
+                        text.push('\n');
+                        i += 1;
+                    }
+                    Some('\\') if chars.get(i + 1) == Some(&'"') => {
+                        text.push('"');
+                        i += 1;
+                    }
+                    Some('\n') | None => return Err(format!("line {}: unclosed string", line)),
+                    Some(c) => text.push(*c),
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push((Token::Str(text), line));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Name(chars[start..i].iter().collect()), line));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(**s))
+                .ok_or_else(|| format!("line {}: unexpected character '{}'", line, c))?;
+            tokens.push((Token::Symbol(symbol), line));
+            i += symbol.len();
+        }
+    }
+    tokens.push((Token::End, line));
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Var(String),
+    /// `.x` or `.y` of a vector.
+    Field(Box<Expr>, String),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum StmtKind {
+    Let(String, Expr),
+    Assign(String, Expr),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    While(Expr, Vec<Stmt>),
+    Return(Option<Expr>),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone)]
+struct Stmt {
+    line: usize,
+    kind: StmtKind,
+}
+
+#[derive(Debug, Clone)]
+struct Function {
+    name: String,
+    params: Vec<String>,
+    body: Vec<Stmt>,
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// How deep in blocks and expressions it is, see `MAX_NESTING`.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.tokens[self.pos].1, message)
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Goes one level deeper, the caller steps back out with `leave`.
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(self.error(&format!("nested over {} deep", MAX_NESTING)));
+        }
+        Ok(())
+    }
+
+    fn leave(&mut self, levels: usize) {
+        self.depth -= levels;
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Token::Symbol(s) if *s == symbol)
+    }
+
+    fn is_keyword(&self, word: &str) -> bool {
+        matches!(self.peek(), Token::Name(n) if n == word)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found = self.is_symbol(symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if !self.eat(symbol) {
+            return Err(self.error(&format!("expected '{}'", symbol)));
+        }
+        Ok(())
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.peek().clone() {
+            Token::Name(n) if !KEYWORDS.contains(&n.as_str()) => {
+                self.pos += 1;
+                Ok(n)
+            }
+            _ => Err(self.error("expected a name")),
+        }
+    }
+
+    fn function(&mut self) -> Result<Function, String> {
+        self.pos += 1;
+        let name = self.name()?;
+        self.expect("(")?;
+        let mut params = Vec::new();
+        while !self.eat(")") {
+            params.push(self.name()?);
+            if !self.is_symbol(")") {
+                self.expect(",")?;
+            }
+        }
+        Ok(Function {
+            name,
+            params,
+            body: self.block()?,
+        })
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        self.expect("{")?;
+        self.enter()?;
+        let mut body = Vec::new();
+        while !self.eat("}") {
+            if *self.peek() == Token::End {
+                return Err(self.error("expected '}'"));
+            }
+            body.push(self.statement()?);
+        }
+        self.leave(1);
+        Ok(body)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, String> {
+        let line = self.tokens[self.pos].1;
+        let kind = if self.is_keyword("let") {
+            self.pos += 1;
+            let name = self.name()?;
+            self.expect("=")?;
+            let value = self.expr()?;
+            self.expect(";")?;
+            StmtKind::Let(name, value)
+        } else if self.is_keyword("if") {
+            return self.if_statement();
+        } else if self.is_keyword("while") {
+            self.pos += 1;
+            let condition = self.expr()?;
+            StmtKind::While(condition, self.block()?)
+        } else if self.is_keyword("return") {
+            self.pos += 1;
+            let value = if self.is_symbol(";") {
+                None
+            } else {
+                Some(self.expr()?)
+            };
+            self.expect(";")?;
+            StmtKind::Return(value)
+        } else if matches!(self.tokens[self.pos + 1].0, Token::Symbol("="))
+            && matches!(self.peek(), Token::Name(_))
+        {
+            let name = self.name()?;
+            self.pos += 1;
+            let value = self.expr()?;
+            self.expect(";")?;
+            StmtKind::Assign(name, value)
+        } else {
+            let value = self.expr()?;
+            self.expect(";")?;
+            StmtKind::Expr(value)
+        };
+        Ok(Stmt { line, kind })
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, String> {
+        let line = self.tokens[self.pos].1;
+        self.pos += 1;
+        let condition = self.expr()?;
+        let then = self.block()?;
+        let otherwise = if self.is_keyword("else") {
+            self.pos += 1;
+            if self.is_keyword("if") {
+                self.enter()?;
+                let chained = self.if_statement()?;
+                self.leave(1);
+                vec![chained]
+            } else {
+                self.block()?
+            }
+        } else {
+            Vec::new()
+        };
+        Ok(Stmt {
+            line,
+            kind: StmtKind::If(condition, then, otherwise),
+        })
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        self.enter()?;
+        let value = self.binary(0)?;
+        self.leave(1);
+        Ok(value)
+    }
+
+    /// Operators from loosest to tightest binding, each level's operands
+    /// are the next level's expressions.
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        const LEVELS: [&[(&str, Op)]; 5] = [
+            &[("||", Op::Or)],
+            &[("&&", Op::And)],
+            &[
+                ("==", Op::Eq),
+                ("!=", Op::Ne),
+                ("<=", Op::Le),
+                (">=", Op::Ge),
+                ("<", Op::Lt),
+                (">", Op::Gt),
+            ],
+            &[("+", Op::Add), ("-", Op::Sub)],
+            &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+        ];
+        let operand = |parser: &mut Parser| {
+            if level + 1 < LEVELS.len() {
+                parser.binary(level + 1)
+            } else {
+                parser.unary()
+            }
+        };
+        let mut left = operand(self)?;
+        // every operator in a row puts the ones before it a level deeper
+        let mut chained = 0;
+        while let Some((_, op)) = LEVELS[level].iter().find(|o| self.is_symbol(o.0)) {
+            self.pos += 1;
+            self.enter()?;
+            chained += 1;
+            left = Expr::Binary(*op, Box::new(left), Box::new(operand(self)?));
+        }
+        self.leave(chained);
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let negate = self.is_symbol("-");
+        if negate || self.is_symbol("!") {
+            self.pos += 1;
+            self.enter()?;
+            let value = Box::new(self.unary()?);
+            self.leave(1);
+            return Ok(if negate {
+                Expr::Neg(value)
+            } else {
+                Expr::Not(value)
+            });
+        }
+        let mut value = self.primary()?;
+        let mut fields = 0;
+        while self.eat(".") {
+            self.enter()?;
+            fields += 1;
+            value = Expr::Field(Box::new(value), self.name()?);
+        }
+        self.leave(fields);
+        Ok(value)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Token::Number(n) => Ok(Expr::Literal(Value::Number(n))),
+            Token::Str(s) => Ok(Expr::Literal(Value::Str(s))),
+            Token::Name(n) if n == "true" => Ok(Expr::Literal(Value::Bool(true))),
+            Token::Name(n) if n == "false" => Ok(Expr::Literal(Value::Bool(false))),
+            Token::Name(n) if !KEYWORDS.contains(&n.as_str()) => {
+                if !self.eat("(") {
+                    return Ok(Expr::Var(n));
+                }
+                let mut args = Vec::new();
+                while !self.eat(")") {
+                    args.push(self.expr()?);
+                    if !self.is_symbol(")") {
+                        self.expect(",")?;
+                    }
+                }
+                Ok(Expr::Call(n, args))
+            }
+            Token::Symbol("(") => {
+                let value = self.expr()?;
+                self.expect(")")?;
+                Ok(value)
+            }
+            token => {
+                if token != Token::End {
+                    self.pos -= 1;
+                }
+                Err(self.error("expected a value"))
+            }
+        }
+    }
+}
+
+/// A parsed script from `assets/scripts/`, named after its file.
+///
+/// The language is small and looks like Rust: `let` for variables, `fn`,
+/// `if`/`else`, `while` and `return`, numbers, strings, bools and vectors.
+///
+/// ```text
+/// // top level runs when the script starts, its variables last
+/// let spin = 0;
+///
+/// fn update(dt) {
+///     spin = spin + 90 * dt;
+///     if health() < max_health() / 2 {
+///         fire(direction(spin), 200);
+///     }
+/// }
+///
+/// fn on_event(name) {
+///     if name == "enrage" { spin = spin + 180; }
+/// }
+/// ```
+///
+/// `update(dt)` runs every frame and `on_event(name)` for every `emit`.
+/// Scripts only touch the world through these calls, all about the entity
+/// running the script:
+///
+/// - `position()`, `velocity()`, `set_velocity(v)`, `health()`,
+///   `max_health()` and `despawn()`
+/// - `has_player()` and `player_position()`
+/// - `spawn(prefab, pos)`, `fire(direction, speed)` or
+///   `fire(direction, speed, damage)`
+/// - `laser(to, width, windup, damage)` from the entity and
+///   `blast(center, radius, windup, damage)`, attacks that go off after
+///   `windup` seconds of warning, see `Telegraph`
+/// - `emit(name)`, `time()` since the script started and `log(text)`
+/// - `vec(x, y)`, `v.x`, `v.y`, `length(v)`, `normalize(v)`,
+///   `distance(a, b)`, `direction(degrees)`, `angle(v)`
+/// - `sin`, `cos`, `sqrt`, `abs`, `floor`, `min`, `max` and
+///   `random(min, max)`
+pub struct Program {
+    pub name: String,
+    /// Tells a reloaded program from the one a script started with.
+    generation: u32,
+    init: Vec<Stmt>,
+    functions: Vec<Function>,
+}
+
+impl Program {
+    pub fn load(path: &Path) -> Result<Program, String> {
+        let contents = pack::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        Program::parse(name, &contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(name: &str, source: &str) -> Result<Program, String> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+            depth: 0,
+        };
+        let mut program = Program {
+            name: name.to_string(),
+            generation: 0,
+            init: Vec::new(),
+            functions: Vec::new(),
+        };
+        while *parser.peek() != Token::End {
+            if parser.is_keyword("fn") {
+                let function = parser.function()?;
+                if program.function(&function.name).is_some() {
+                    return Err(parser.error(&format!("'{}' defined twice", function.name)));
+                }
+                program.functions.push(function);
+            } else {
+                program.init.push(parser.statement()?);
+            }
+        }
+        Ok(program)
+    }
+
+    fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+}
+
+/// Every loaded script program by name.
+pub struct Scripts {
+    programs: Vec<Program>,
+    generation: u32,
+}
+
+impl Scripts {
+    pub fn new() -> Scripts {
+        Scripts {
+            programs: Vec::new(),
+            generation: 0,
+        }
+    }
+
+    /// Loads every `.script` file in `dir`, returns how many. Files that
+    /// fail to parse are skipped with a warning.
+    pub fn load_dir(&mut self, dir: &str) -> usize {
+        let mut loaded = 0;
+        for path in pack::files_in(dir) {
+            if path.extension().is_none_or(|e| e != "script") {
+                continue;
+            }
+            match Program::load(&path) {
+                Ok(program) => {
+                    self.add(program);
+                    loaded += 1;
+                }
+                Err(e) => warn!("script", "could not load script", error = e),
+            }
+        }
+        info!("script", "scripts loaded", count = loaded);
+        loaded
+    }
+
+    /// Adds a program, replacing any with the same name. Scripts running
+    /// the old one start over with the new one.
+    pub fn add(&mut self, mut program: Program) {
+        self.generation += 1;
+        program.generation = self.generation;
+        self.programs.retain(|p| p.name != program.name);
+        self.programs.push(program);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Program> {
+        self.programs.iter().find(|p| p.name == name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.programs.iter().map(|p| p.name.as_str())
+    }
+}
+
+/// Sent by a script's `emit`, every script's `on_event` hears it the same
+/// frame.
+pub struct ScriptEvent {
+    pub entity: EntityID,
+    pub name: String,
+}
+
+/// Runs a program from `world.scripts` for the entity. A script that runs
+/// into an error stops with a warning, until its file changes.
+pub struct Script {
+    pub name: String,
+    /// Generation of the program it runs, 0 before it started.
+    generation: u32,
+    globals: Vec<(String, Value)>,
+    time: f32,
+    rng: Rng,
+    failed: bool,
+    despawn: bool,
+}
+
+impl Script {
+    pub fn new(name: &str, seed: u64) -> Script {
+        Script {
+            name: name.to_string(),
+            generation: 0,
+            globals: Vec::new(),
+            time: 0.0,
+            rng: Rng::new(seed),
+            failed: false,
+            despawn: false,
+        }
+    }
+
+    /// Runs `hook` if the program has it, stopping the script on an error.
+    fn call(
+        &mut self,
+        world: &mut World,
+        program: &Program,
+        entity: EntityID,
+        hook: &str,
+        args: Vec<Value>,
+    ) {
+        if self.failed {
+            return;
+        }
+        let result = match program.function(hook) {
+            Some(function) => Context::new(world, program, entity, self)
+                .call(function, args)
+                .map(|_| ()),
+            None => Ok(()),
+        };
+        self.fail(result, entity, hook);
+    }
+
+    fn fail(&mut self, result: Result<(), String>, entity: EntityID, hook: &str) {
+        if let Err(e) = result {
+            warn!(
+                "script",
+                "script stopped",
+                script = self.name,
+                entity = entity,
+                hook = hook,
+                error = e
+            );
+            self.failed = true;
+        }
+    }
+}
+
+impl UpdateSystem for Script {
+    fn update_system(world: &mut World, dt: f32) {
+        // out of the world while they run, scripts change the world
+        let programs = mem::replace(&mut world.scripts, Scripts::new());
+        let mut scripts = mem::take(&mut world.script_components);
+        let first_event = world.script_events.len();
+
+        for (id, script) in scripts.iter_mut() {
+            let program = match programs.get(&script.name) {
+                Some(p) => p,
+                None => {
+                    if !script.failed {
+                        warn!("script", "unknown script", script = script.name);
+                        script.failed = true;
+                    }
+                    continue;
+                }
+            };
+            // first frame, or the file changed
+            if script.generation != program.generation {
+                script.generation = program.generation;
+                script.globals.clear();
+                script.time = 0.0;
+                script.failed = false;
+                let result = Context::new(world, program, *id, script)
+                    .block(&program.init)
+                    .map(|_| ());
+                script.fail(result, *id, "start");
+            }
+            script.time += dt;
+            script.call(world, program, *id, "update", vec![Value::Number(dt)]);
+        }
+
+        let events: Vec<String> = world.script_events[first_event..]
+            .iter()
+            .map(|e| e.name.clone())
+            .collect();
+        for name in events {
+            for (id, script) in scripts.iter_mut() {
+                if let Some(program) = programs.get(&script.name) {
+                    script.call(
+                        world,
+                        program,
+                        *id,
+                        "on_event",
+                        vec![Value::Str(name.clone())],
+                    );
+                }
+            }
+        }
+
+        let despawned: Vec<EntityID> = scripts
+            .iter()
+            .filter(|s| s.1.despawn)
+            .map(|s| s.0)
+            .collect();
+        // scripts started by what the scripts spawned
+        scripts.append(&mut world.script_components);
+        world.script_components = scripts;
+        world.scripts = programs;
+        for id in despawned {
+            world.despawn(id);
+        }
+    }
+}
+
+enum Flow {
+    Next,
+    Return(Value),
+}
+
+/// One run of a script's code, with the entity it's about.
+struct Context<'a> {
+    world: &'a mut World,
+    program: &'a Program,
+    entity: EntityID,
+    script: &'a mut Script,
+    /// Variables of the function being run, the top level has none and
+    /// keeps its variables in the script.
+    locals: Vec<(String, Value)>,
+    depth: usize,
+    steps: u32,
+}
+
+impl<'a> Context<'a> {
+    fn new(
+        world: &'a mut World,
+        program: &'a Program,
+        entity: EntityID,
+        script: &'a mut Script,
+    ) -> Context<'a> {
+        Context {
+            world,
+            program,
+            entity,
+            script,
+            locals: Vec::new(),
+            depth: 0,
+            steps: 0,
+        }
+    }
+
+    fn step(&mut self) -> Result<(), String> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return Err(format!("ran over {} steps", MAX_STEPS));
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, function: &Function, args: Vec<Value>) -> Result<Value, String> {
+        if args.len() != function.params.len() {
+            return Err(format!(
+                "{} takes {} arguments, got {}",
+                function.name,
+                function.params.len(),
+                args.len()
+            ));
+        }
+        if self.depth >= MAX_DEPTH {
+            return Err(format!("calls nested over {} deep", MAX_DEPTH));
+        }
+        let locals = function.params.iter().cloned().zip(args).collect();
+        let outer = mem::replace(&mut self.locals, locals);
+        self.depth += 1;
+        let flow = self.block(&function.body);
+        self.depth -= 1;
+        self.locals = outer;
+        match flow? {
+            Flow::Return(value) => Ok(value),
+            Flow::Next => Ok(Value::Unit),
+        }
+    }
+
+    fn block(&mut self, body: &[Stmt]) -> Result<Flow, String> {
+        for stmt in body {
+            let flow = self.statement(stmt).map_err(|e| {
+                // the innermost statement knows the line best
+                if e.starts_with("line ") {
+                    e
+                } else {
+                    format!("line {}: {}", stmt.line, e)
+                }
+            })?;
+            if let Flow::Return(value) = flow {
+                return Ok(Flow::Return(value));
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> Result<Flow, String> {
+        self.step()?;
+        match &stmt.kind {
+            StmtKind::Let(name, value) => {
+                let value = self.eval(value)?;
+                let vars = if self.depth == 0 {
+                    &mut self.script.globals
+                } else {
+                    &mut self.locals
+                };
+                vars.retain(|v| v.0 != *name);
+                vars.push((name.clone(), value));
+            }
+            StmtKind::Assign(name, value) => {
+                let value = self.eval(value)?;
+                let var = self
+                    .locals
+                    .iter_mut()
+                    .chain(self.script.globals.iter_mut())
+                    .find(|v| v.0 == *name)
+                    .ok_or_else(|| format!("unknown variable '{}'", name))?;
+                var.1 = value;
+            }
+            StmtKind::If(condition, then, otherwise) => {
+                return if self.condition(condition)? {
+                    self.block(then)
+                } else {
+                    self.block(otherwise)
+                };
+            }
+            StmtKind::While(condition, body) => {
+                while self.condition(condition)? {
+                    if let Flow::Return(value) = self.block(body)? {
+                        return Ok(Flow::Return(value));
+                    }
+                    self.step()?;
+                }
+            }
+            StmtKind::Return(value) => {
+                let value = match value {
+                    Some(value) => self.eval(value)?,
+                    None => Value::Unit,
+                };
+                return Ok(Flow::Return(value));
+            }
+            StmtKind::Expr(value) => {
+                self.eval(value)?;
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn condition(&mut self, condition: &Expr) -> Result<bool, String> {
+        match self.eval(condition)? {
+            Value::Bool(b) => Ok(b),
+            other => Err(format!("condition is a {}, not a bool", other.type_name())),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value, String> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Var(name) => self
+                .locals
+                .iter()
+                .chain(self.script.globals.iter())
+                .find(|v| v.0 == *name)
+                .map(|v| v.1.clone())
+                .ok_or_else(|| format!("unknown variable '{}'", name)),
+            Expr::Field(value, field) => match (self.eval(value)?, field.as_str()) {
+                (Value::Vector(v), "x") => Ok(Value::Number(v.x)),
+                (Value::Vector(v), "y") => Ok(Value::Number(v.y)),
+                (value, _) => Err(format!("a {} has no .{}", value.type_name(), field)),
+            },
+            Expr::Neg(value) => match self.eval(value)? {
+                Value::Number(n) => Ok(Value::Number(-n)),
+                Value::Vector(v) => Ok(Value::Vector(-v)),
+                value => Err(format!("can't negate a {}", value.type_name())),
+            },
+            Expr::Not(value) => match self.eval(value)? {
+                Value::Bool(b) => Ok(Value::Bool(!b)),
+                value => Err(format!("can't negate a {}", value.type_name())),
+            },
+            // both sides of && and || are only looked at when needed
+            Expr::Binary(Op::And, left, right) => {
+                Ok(Value::Bool(self.condition(left)? && self.condition(right)?))
+            }
+            Expr::Binary(Op::Or, left, right) => {
+                Ok(Value::Bool(self.condition(left)? || self.condition(right)?))
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                binary(*op, left, right)
+            }
+            Expr::Call(name, args) => {
+                self.step()?;
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+                match self.program.function(name) {
+                    Some(function) => self.call(function, values),
+                    None => self.builtin(name, values),
+                }
+            }
+        }
+    }
+
+    /// The calls scripts have into the game.
+    fn builtin(&mut self, name: &str, args: Vec<Value>) -> Result<Value, String> {
+        let args = Args { name, values: args };
+        let world = &mut *self.world;
+        let id = self.entity;
+        let value = match name {
+            "vec" => {
+                args.count(2)?;
+                Value::Vector(Vector2::new(args.number(0)?, args.number(1)?))
+            }
+            "length" => {
+                args.count(1)?;
+                Value::Number(args.vector(0)?.length())
+            }
+            "normalize" => {
+                args.count(1)?;
+                let v = args.vector(0)?;
+                Value::Vector(if v.length() > 0.0 { v.normalized() } else { v })
+            }
+            "distance" => {
+                args.count(2)?;
+                Value::Number(args.vector(0)?.distance_to(args.vector(1)?))
+            }
+            "direction" => {
+                args.count(1)?;
+                let radians = args.number(0)?.to_radians();
+                Value::Vector(Vector2::new(radians.cos(), radians.sin()))
+            }
+            "angle" => {
+                args.count(1)?;
+                let v = args.vector(0)?;
+                Value::Number(v.y.atan2(v.x).to_degrees())
+            }
+            "sin" | "cos" | "sqrt" | "abs" | "floor" => {
+                args.count(1)?;
+                let n = args.number(0)?;
+                Value::Number(match name {
+                    "sin" => n.to_radians().sin(),
+                    "cos" => n.to_radians().cos(),
+                    "sqrt" => n.sqrt(),
+                    "abs" => n.abs(),
+                    _ => n.floor(),
+                })
+            }
+            "min" | "max" => {
+                args.count(2)?;
+                let (a, b) = (args.number(0)?, args.number(1)?);
+                Value::Number(if name == "min" { a.min(b) } else { a.max(b) })
+            }
+            "random" => {
+                args.count(2)?;
+                Value::Number(self.script.rng.range(args.number(0)?, args.number(1)?))
+            }
+            "time" => {
+                args.count(0)?;
+                Value::Number(self.script.time)
+            }
+            "log" => {
+                args.count(1)?;
+                info!("script", args.values[0], script = self.script.name);
+                Value::Unit
+            }
+            "position" => {
+                args.count(0)?;
+                Value::Vector(position(world, id).ok_or("entity has no position")?)
+            }
+            "velocity" => {
+                args.count(0)?;
+                let velocity = world.velocity_components.iter().find(|v| v.0 == id);
+                Value::Vector(velocity.ok_or("entity has no velocity")?.1.velocity)
+            }
+            "set_velocity" => {
+                args.count(1)?;
+                let v = args.vector(0)?;
+                // a NaN would spread to the bounds and everything it touches
+                if !v.x.is_finite() || !v.y.is_finite() {
+                    return Err(format!("velocity ({}, {}) isn't a number", v.x, v.y));
+                }
+                let velocity = world.velocity_components.iter_mut().find(|v| v.0 == id);
+                velocity.ok_or("entity has no velocity")?.1.velocity = v;
+                Value::Unit
+            }
+            "health" | "max_health" => {
+                args.count(0)?;
+                let health = world.health_components.iter().find(|h| h.0 == id);
+                let health = &health.ok_or("entity has no health")?.1;
+                Value::Number(if name == "health" {
+                    health.health
+                } else {
+                    health.max_health
+                } as f32)
+            }
+            "despawn" => {
+                args.count(0)?;
+                self.script.despawn = true;
+                Value::Unit
+            }
+            "has_player" => {
+                args.count(0)?;
+                Value::Bool(player_position(world).is_some())
+            }
+            "player_position" => {
+                args.count(0)?;
+                Value::Vector(player_position(world).ok_or("there is no player")?)
+            }
+            "spawn" => {
+                args.count(2)?;
+                let prefab = args.string(0)?;
+                let pos = args.vector(1)?;
+                let placement = Placement::new(BoundingBox2D::new(pos.x, pos.y, 0.0, 0.0));
+                let child = Prefabs::spawn(world, prefab, &placement)
+                    .ok_or_else(|| format!("unknown prefab '{}'", prefab))?;
+                world.campaign.adopt(id, child);
+                Value::Unit
+            }
+            "fire" => {
+                if args.values.len() != 2 {
+                    args.count(3)?;
+                }
+                let direction = args.vector(0)?;
+                let speed = args.number(1)?;
+                let damage = match args.values.len() {
+                    3 => args.number(2)?,
+                    _ => BULLET_DAMAGE,
+                };
+                let muzzle = position(world, id).ok_or("entity has no position")?;
+                let direction = if direction.length() > 0.0 {
+                    direction.normalized()
+                } else {
+                    Vector2::new(0.0, -1.0)
+                };
+                Projectile::spawn(world, id, muzzle, direction * speed, damage as u16, false);
+                Value::Unit
+            }
+            "laser" => {
+                args.count(4)?;
+                let from = position(world, id).ok_or("entity has no position")?;
+                let telegraph =
+                    Telegraph::line(from, args.vector(0)?, args.number(1)?, args.number(2)?);
+                world.telegraph_components.push((
+                    id,
+                    telegraph
+                        .damage(args.number(3)? as u16)
+                        .burst(EmitterConfig::sparks()),
+                ));
+                Value::Unit
+            }
+            "blast" => {
+                args.count(4)?;
+                let telegraph =
+                    Telegraph::circle(args.vector(0)?, args.number(1)?, args.number(2)?);
+                world.telegraph_components.push((
+                    id,
+                    telegraph
+                        .damage(args.number(3)? as u16)
+                        .burst(EmitterConfig::explosion()),
+                ));
+                Value::Unit
+            }
+            "emit" => {
+                args.count(1)?;
+                let name = args.string(0)?.to_string();
+                world.script_events.push(ScriptEvent { entity: id, name });
+                Value::Unit
+            }
+            _ => return Err(format!("unknown function '{}'", name)),
+        };
+        Ok(value)
+    }
+}
+
+fn position(world: &World, id: EntityID) -> Option<Vector2> {
+    world
+        .base_components
+        .iter()
+        .find(|b| b.0 == id)
+        .map(|b| b.1.bounds.center())
+}
+
+fn player_position(world: &World) -> Option<Vector2> {
+    let player = world.player_components.first()?;
+    position(world, player.0)
+}
+
+fn binary(op: Op, left: Value, right: Value) -> Result<Value, String> {
+    use Value::*;
+    let value = match (op, &left, &right) {
+        (Op::Div | Op::Rem, _, Number(b)) if *b == 0.0 => {
+            return Err("division by zero".to_string())
+        }
+        (Op::Eq, _, _) => Bool(left == right),
+        (Op::Ne, _, _) => Bool(left != right),
+        (Op::Add, Number(a), Number(b)) => Number(a + b),
+        (Op::Sub, Number(a), Number(b)) => Number(a - b),
+        (Op::Mul, Number(a), Number(b)) => Number(a * b),
+        (Op::Div, Number(a), Number(b)) => Number(a / b),
+        (Op::Rem, Number(a), Number(b)) => Number(a.rem_euclid(*b)),
+        (Op::Lt, Number(a), Number(b)) => Bool(a < b),
+        (Op::Le, Number(a), Number(b)) => Bool(a <= b),
+        (Op::Gt, Number(a), Number(b)) => Bool(a > b),
+        (Op::Ge, Number(a), Number(b)) => Bool(a >= b),
+        (Op::Add, Vector(a), Vector(b)) => Vector(*a + *b),
+        (Op::Sub, Vector(a), Vector(b)) => Vector(*a - *b),
+        (Op::Mul, Vector(a), Number(b)) | (Op::Mul, Number(b), Vector(a)) => Vector(*a * *b),
+        (Op::Div, Vector(a), Number(b)) => Vector(*a / *b),
+        (Op::Add, Str(a), _) => Str(format!("{}{}", a, right)),
+        (Op::Add, _, Str(b)) => Str(format!("{}{}", left, b)),
+        _ => {
+            return Err(format!(
+                "can't {:?} a {} and a {}",
+                op,
+                left.type_name(),
+                right.type_name()
+            ))
+        }
+    };
+    let finite = match value {
+        Number(n) => n.is_finite(),
+        Vector(v) => v.x.is_finite() && v.y.is_finite(),
+        _ => true,
+    };
+    if !finite {
+        return Err(format!("{:?} of {} and {} isn't a number", op, left, right));
+    }
+    Ok(value)
+}
+
+/// Arguments of a built-in call, checked as they're taken.
+struct Args<'a> {
+    name: &'a str,
+    values: Vec<Value>,
+}
+
+impl Args<'_> {
+    fn count(&self, count: usize) -> Result<(), String> {
+        if self.values.len() != count {
+            return Err(format!(
+                "{} takes {} arguments, got {}",
+                self.name,
+                count,
+                self.values.len()
+            ));
+        }
+        Ok(())
+    }
+
+    fn wrong(&self, i: usize, expected: &str) -> String {
+        format!(
+            "argument {} of {} should be a {}, not a {}",
+            i + 1,
+            self.name,
+            expected,
+            self.values[i].type_name()
+        )
+    }
+
+    fn number(&self, i: usize) -> Result<f32, String> {
+        match self.values[i] {
+            Value::Number(n) => Ok(n),
+            _ => Err(self.wrong(i, "number")),
+        }
+    }
+
+    fn vector(&self, i: usize) -> Result<Vector2, String> {
+        match self.values[i] {
+            Value::Vector(v) => Ok(v),
+            _ => Err(self.wrong(i, "vector")),
+        }
+    }
+
+    fn string(&self, i: usize) -> Result<&str, String> {
+        match &self.values[i] {
+            Value::Str(s) => Ok(s),
+            _ => Err(self.wrong(i, "string")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the program's top level, then its `main`.
+    fn run_in(world: &mut World, entity: EntityID, source: &str) -> Result<Value, String> {
+        let program = Program::parse("test", source)?;
+        let mut script = Script::new("test", 1);
+        let mut context = Context::new(world, &program, entity, &mut script);
+        context.block(&program.init)?;
+        let main = program.function("main").expect("test program has no main");
+        context.call(main, Vec::new())
+    }
+
+    fn run(source: &str) -> Result<Value, String> {
+        let mut world = World::new();
+        let entity = world.new_entity();
+        run_in(&mut world, entity, source)
+    }
+
+    fn eval(expr: &str) -> Result<Value, String> {
+        run(&format!("fn main() {{ return {}; }}", expr))
+    }
+
+    #[test]
+    fn tokenizes_with_lines() {
+        let tokens = tokenize("a <= 1.5; // gone\n\"x\\n\\\"\" !=\n_b2").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                (Token::Name("a".to_string()), 1),
+                (Token::Symbol("<="), 1),
+                (Token::Number(1.5), 1),
+                (Token::Symbol(";"), 1),
+                (Token::Str("x\n\"".to_string()), 2),
+                (Token::Symbol("!="), 2),
+                (Token::Name("_b2".to_string()), 3),
+                (Token::End, 3),
+            ]
+        );
+        // a dot not followed by a digit is a field access
+        assert_eq!(
+            tokenize("1.x").unwrap()[..3],
+            [
+                (Token::Number(1.0), 1),
+                (Token::Symbol("."), 1),
+                (Token::Name("x".to_string()), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn operators_bind_by_precedence() {
+        let number = |expr| match eval(expr) {
+            Ok(Value::Number(n)) => n,
+            other => panic!("{} gave {:?}", expr, other),
+        };
+        assert_eq!(number("1 + 2 * 3"), 7.0);
+        assert_eq!(number("(1 + 2) * 3"), 9.0);
+        assert_eq!(number("10 - 4 - 3"), 3.0);
+        assert_eq!(number("12 / 2 / 3"), 2.0);
+        assert_eq!(number("-2 * 3 + 1"), -5.0);
+        assert_eq!(number("-7 % 3"), 2.0);
+        assert_eq!(number("vec(3, 4).x + vec(3, 4).y * 2"), 11.0);
+        assert_eq!(eval("1 < 2 && 3 < 2 || 1 == 1"), Ok(Value::Bool(true)));
+        assert_eq!(eval("!true == false"), Ok(Value::Bool(true)));
+        assert_eq!(eval("1 + 1 == 2"), Ok(Value::Bool(true)));
+        assert_eq!(eval("\"hp \" + 3"), Ok(Value::Str("hp 3".to_string())));
+        // the right side of && isn't run when the left decides
+        assert_eq!(eval("false && nothing()"), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn parse_errors_name_the_line() {
+        let error = |source| Program::parse("test", source).err().unwrap();
+        assert_eq!(error("let a = 1;\nlet b = ;"), "line 2: expected a value");
+        assert_eq!(error("let a = 1\nlet b = 2;"), "line 2: expected ';'");
+        assert_eq!(error("\n\nlet s = \"open;\n"), "line 3: unclosed string");
+        assert_eq!(error("let a = 1 @ 2;"), "line 1: unexpected character '@'");
+        assert_eq!(error("fn f() {\n  let a = 1;\n"), "line 3: expected '}'");
+        assert_eq!(error("let 1 = 2;"), "line 1: expected a name");
+        assert_eq!(error("fn f() {}\n\nfn f() {}"), "line 3: 'f' defined twice");
+    }
+
+    #[test]
+    fn run_errors_name_the_line() {
+        assert_eq!(
+            run("fn main() {\n  let a = 1;\n  return a + true;\n}"),
+            Err("line 3: can't Add a number and a bool".to_string())
+        );
+        assert_eq!(
+            run("fn main() {\n  if 1 { }\n}"),
+            Err("line 2: condition is a number, not a bool".to_string())
+        );
+        assert_eq!(
+            run("fn main() {\n  b = 2;\n}"),
+            Err("line 2: unknown variable 'b'".to_string())
+        );
+    }
+
+    #[test]
+    fn endless_loops_run_out_of_steps() {
+        assert_eq!(
+            run("fn main() {\n  while true { }\n}"),
+            Err(format!("line 2: ran over {} steps", MAX_STEPS))
+        );
+        // a loop that ends in time is fine
+        assert_eq!(
+            run("fn main() { let i = 0; while i < 100 { i = i + 1; } return i; }"),
+            Ok(Value::Number(100.0))
+        );
+    }
+
+    #[test]
+    fn runaway_recursion_stops() {
+        let result = run("fn f(n) { return f(n + 1); }\nfn main() { return f(0); }");
+        assert!(
+            result
+                .as_ref()
+                .is_err_and(|e| e.ends_with(&format!("calls nested over {} deep", MAX_DEPTH))),
+            "{:?}",
+            result
+        );
+        assert_eq!(
+            run("fn f(n) { if n == 0 { return 0; } return 1 + f(n - 1); }\nfn main() { return f(20); }"),
+            Ok(Value::Number(20.0))
+        );
+    }
+
+    #[test]
+    fn deep_nesting_is_a_parse_error() {
+        let nested = format!("let a = {}1{};", "(".repeat(10_000), ")".repeat(10_000));
+        let negated = format!("let a = {}1;", "-".repeat(10_000));
+        let chained = format!("let a = 1{};", " + 1".repeat(10_000));
+        let blocks = format!(
+            "fn f() {}{}",
+            "{ if true ".repeat(10_000),
+            "}".repeat(20_000)
+        );
+        for source in [nested, negated, chained, blocks] {
+            let error = Program::parse("test", &source).err();
+            assert!(
+                error
+                    .as_ref()
+                    .is_some_and(|e| e.ends_with(&format!("nested over {} deep", MAX_NESTING))),
+                "{:?}",
+                error
+            );
+        }
+        // well within the limit it parses
+        let source = format!("let a = {}1{};", "(".repeat(50), ")".repeat(50));
+        assert!(Program::parse("test", &source).is_ok());
+    }
+
+    #[test]
+    fn builtins_check_their_arguments() {
+        assert_eq!(
+            eval("vec(1)"),
+            Err("line 1: vec takes 2 arguments, got 1".to_string())
+        );
+        assert_eq!(
+            eval("fire(vec(0, 1), 2, 3, 4)"),
+            Err("line 1: fire takes 3 arguments, got 4".to_string())
+        );
+        assert_eq!(
+            eval("length(1)"),
+            Err("line 1: argument 1 of length should be a vector, not a number".to_string())
+        );
+        assert_eq!(
+            eval("min(1, \"a\")"),
+            Err("line 1: argument 2 of min should be a number, not a string".to_string())
+        );
+        assert_eq!(
+            eval("nothing()"),
+            Err("line 1: unknown function 'nothing'".to_string())
+        );
+        assert_eq!(
+            run("fn f(a) { }\nfn main() {\n  f();\n}"),
+            Err("line 3: f takes 1 arguments, got 0".to_string())
+        );
+    }
+
+    #[test]
+    fn dividing_by_zero_is_an_error() {
+        for expr in ["1 / 0", "5 % 0", "vec(1, 1) / 0", "0 / 0"] {
+            assert_eq!(
+                eval(expr),
+                Err("line 1: division by zero".to_string()),
+                "{}",
+                expr
+            );
+        }
+        assert!(eval("100000000000000000000 * 100000000000000000000").is_err());
+    }
+
+    #[test]
+    fn velocity_is_never_set_to_nan() {
+        let mut world = World::new();
+        let entity = world.new_entity();
+        world
+            .velocity_components
+            .push((entity, crate::movement::Velocity::new(0.0)));
+
+        let result = run_in(
+            &mut world,
+            entity,
+            "fn main() { set_velocity(vec(sqrt(-1), 0)); }",
+        );
+        assert!(result.is_err(), "{:?}", result);
+        assert_eq!(world.velocity_components[0].1.velocity, Vector2::zero());
+
+        run_in(&mut world, entity, "fn main() { set_velocity(vec(3, 4)); }").unwrap();
+        assert_eq!(
+            world.velocity_components[0].1.velocity,
+            Vector2::new(3.0, 4.0)
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::animation::{Animation, Clip};
+use crate::audio::SoundCue;
+use crate::camera::Camera;
+use crate::campaign::Campaign;
+use crate::editor::EditorLevel;
+use crate::flash::DamageFlash;
+use crate::heat::Heat;
+use crate::hud::HudItem;
+use crate::indicator::{Indicator, IndicatorKind};
+use crate::ldtk::LdtkProject;
+use crate::light::Light;
+use crate::movement::Velocity;
+use crate::particles::{Emitter, EmitterConfig};
+use crate::player::Player;
+use crate::portal::Portal;
+use crate::prefab::{Placement, Prefabs};
+use crate::render::{Layer, RenderLayer};
+use crate::scene::{Scene, Scenes};
+use crate::sprite::Sprite;
+use crate::state::GameState;
+use crate::station::{Bombs, Station};
+use crate::team::Team;
+use crate::text::Text2D;
+use crate::tiled::TiledMap;
+use crate::tilemap::Tilemap;
+use crate::trail::Trail;
+use crate::trigger::Trigger;
+use crate::tween::{self, Easing, Tween};
+use crate::ui::UiElement;
+use crate::weapon::Weapon;
+use crate::{Anchor, Base2D, BoundingBox2D, Health, World, HEIGHT, WIDTH};
+
+/// The game's scenes: the title screen, and the sector a game is played in.
+pub fn scenes() -> Scenes {
+    let mut scenes = Scenes::new();
+    scenes.add(Scene::new("title", GameState::Menu, spawn_title));
+    scenes.add(
+        Scene::new("sector", GameState::Playing, spawn_level)
+            .preloading(&["assets/textures/ship.png", "assets/textures/tiles.png"]),
+    );
+    scenes
+}
+
+/// What the level editor saves, spawned into every level.
+pub const EDITOR_LEVEL: &str = "assets/maps/editor.json";
+
+/// Camera for the title screen, the main menu points it at its dogfight.
+pub fn spawn_title(world: &mut World, _: &mut RaylibHandle, _: &RaylibThread) {
+    let camera = world.new_entity();
+    let screen_center = Vector2::new(WIDTH as f32, HEIGHT as f32) * 0.5;
+    world
+        .camera_components
+        .push((camera, Camera::new(screen_center)));
+}
+
+/// Fills the world with the player and the level around them, for a new
+/// game.
+pub fn spawn_level(world: &mut World, rl: &mut RaylibHandle, thread: &RaylibThread) {
+    let player = world.new_entity();
+    world.base_components.push((
+        player,
+        Base2D::new(Vector2::new(100.0, 280.0), Vector2::new(36.0, 48.0)),
+    ));
+    world.health_components.push((player, Health::new(20)));
+    world.player_components.push((player, Player::new()));
+    let mut weapon = Weapon::new(8.0, player ^ world.config.seed.unwrap_or(0));
+    weapon.sound = SoundCue::load(
+        &mut world.assets,
+        &[
+            "assets/sfx/laser_1.wav",
+            "assets/sfx/laser_2.wav",
+            "assets/sfx/laser_3.wav",
+        ],
+    )
+    .map(|cue| cue.volume(0.6).pitch_spread(0.06));
+    world.weapon_components.push((player, weapon));
+    world
+        .heat_components
+        .push((player, Heat::new(100.0, 6.0, 25.0)));
+    world.velocity_components.push((player, Velocity::new(2.0)));
+    world.bomb_components.push((player, Bombs::new(3)));
+    world.team_components.push((player, Team::Friendly));
+    world
+        .flash_components
+        .push((player, DamageFlash::new(Color::RED, 0.25)));
+    world
+        .layer_components
+        .push((player, Layer::new(RenderLayer::Gameplay, 10)));
+    world
+        .emitter_components
+        .push((player, Emitter::new(EmitterConfig::thrust())));
+    world
+        .trail_components
+        .push((player, Trail::new(24, 6.0, Color::SKYBLUE.fade(0.5))));
+    // engine glow, behind the ship where the thrust particles come out
+    world.light_components.push((
+        player,
+        Light::new(48.0, Color::SKYBLUE, 0.8)
+            .flickering(0.25)
+            .offset(Vector2::new(0.0, 24.0)),
+    ));
+    // prefer the artists' Aseprite export, its tags are the clips
+    let ship_sheet = world
+        .assets
+        .load_aseprite(rl, thread, "assets/textures/ship.json");
+    let ship = ship_sheet.ok().and_then(|sheet| {
+        let atlas = world.assets.atlas(sheet)?;
+        let first = atlas.region_names().next()?;
+        let sprite = Sprite::from_atlas(&world.assets, sheet, first)?;
+        Some((sprite, Animation::from_atlas(atlas)?))
+    });
+    if let Some((sprite, animation)) = ship {
+        world.sprite_components.push((player, sprite));
+        world.animation_components.push((player, animation));
+    } else if let Ok(texture) = world
+        .assets
+        .load_texture(rl, thread, "assets/textures/ship.png")
+    {
+        let sprite = Sprite::new(&world.assets, texture);
+        world.sprite_components.push((player, sprite));
+
+        // ship.png is a sheet of 36x48 frames, one row per clip
+        let frame = Vector2::new(36.0, 48.0);
+        let animation = Animation::new(
+            "idle",
+            Clip::from_strip(Vector2::new(0.0, 0.0), frame, 2, 4.0, true),
+        )
+        .with_clip(
+            "thrust",
+            Clip::from_strip(Vector2::new(0.0, 48.0), frame, 4, 12.0, true),
+        )
+        .with_clip(
+            "hit",
+            Clip::from_strip(Vector2::new(0.0, 96.0), frame, 3, 15.0, false),
+        );
+        world.animation_components.push((player, animation));
+    }
+
+    let camera = world.new_entity();
+    let screen_center = Vector2::new(WIDTH as f32, HEIGHT as f32) * 0.5;
+    let mut player_camera = Camera::following(screen_center, player);
+    player_camera.target = screen_center;
+    world.camera_components.push((camera, player_camera));
+    // one camera per local player, a single player keeps the whole screen
+    Camera::split_screen(world);
+
+    let god = world.new_entity();
+    let mut base2d = Base2D::new(Vector2::new(400.0, 380.0), Vector2::new(76.0, 48.0));
+    base2d.tint = Color::YELLOW;
+    world.base_components.push((god, base2d));
+    world.team_components.push((god, Team::Hostile));
+
+    for (prefab, bounds) in [
+        ("Drone", BoundingBox2D::new(260.0, 300.0, 32.0, 24.0)),
+        ("Drone", BoundingBox2D::new(360.0, 300.0, 32.0, 24.0)),
+        ("Nebula", BoundingBox2D::new(220.0, 60.0, 180.0, 140.0)),
+        (
+            "RadiationField",
+            BoundingBox2D::new(460.0, 120.0, 120.0, 120.0),
+        ),
+    ] {
+        Prefabs::spawn(world, prefab, &Placement::new(bounds));
+    }
+
+    // levels authored in Tiled or LDtk, on top of the built-in layout for now
+    let mut player_spawn = None;
+    if let Ok(map) = TiledMap::load("assets/maps/sector.tmx") {
+        map.spawn(world, rl, thread);
+        player_spawn = map.point("PlayerSpawn");
+    }
+    if let Ok(project) = LdtkProject::load("assets/maps/world.ldtk") {
+        // a campaign level names its map, without a campaign --level
+        // starts in that one level alone
+        let single = match world.campaign.level() {
+            Some(level) => level.map.as_deref().and_then(|m| project.level(m)),
+            None => world.config.level.and_then(|l| project.levels.get(l - 1)),
+        };
+        match single {
+            Some(level) => {
+                level.spawn(world, rl, thread);
+                player_spawn = level.point("PlayerSpawn");
+            }
+            None => {
+                project.spawn(world, rl, thread);
+                player_spawn = project.levels.iter().find_map(|l| l.point("PlayerSpawn"));
+            }
+        }
+    }
+    // and what was built in the editor
+    if let Ok(level) = EditorLevel::load(Path::new(EDITOR_LEVEL)) {
+        level.spawn(world);
+    }
+    if let Some(spawn) = player_spawn {
+        if let Some(b) = world.base_components.iter_mut().find(|b| b.0 == player) {
+            b.1.bounds.set_center(spawn);
+        }
+    }
+
+    let wormhole_a = world.new_entity();
+    let wormhole_b = world.new_entity();
+    for (id, pos, target, facing) in [
+        (wormhole_a, Vector2::new(40.0, 380.0), wormhole_b, 0.0),
+        (
+            wormhole_b,
+            Vector2::new(560.0, 40.0),
+            wormhole_a,
+            std::f32::consts::PI,
+        ),
+    ] {
+        let mut base2d = Base2D::new(pos, Vector2::new(48.0, 48.0));
+        base2d.name = "Wormhole".to_string();
+        base2d.tint = Color::SKYBLUE;
+        world.base_components.push((id, base2d));
+        world.trigger_components.push((id, Trigger::new()));
+        world
+            .portal_components
+            .push((id, Portal::new(target, facing)));
+        world
+            .light_components
+            .push((id, Light::new(72.0, Color::SKYBLUE, 0.5).flickering(0.3)));
+        world
+            .layer_components
+            .push((id, Layer::new(RenderLayer::Effects, 0)));
+    }
+
+    // derelict outpost to fly through, the gaps are its airlocks
+    let map = Tilemap::from_rows(
+        &[
+            "############",
+            "#..........#",
+            "#..####....#",
+            "...#..#.....",
+            "...#..#.....",
+            "#......###.#",
+            "#..........#",
+            "############",
+        ],
+        16.0,
+    );
+    let map = match world
+        .assets
+        .load_texture(rl, thread, "assets/textures/tiles.png")
+    {
+        Ok(tileset) => map.with_tileset(tileset),
+        Err(_) => map,
+    };
+    map.spawn(world, "Outpost", Vector2::new(700.0, 200.0), -10);
+
+    let station = world.new_entity();
+    let mut base2d = Base2D::new(Vector2::new(40.0, 40.0), Vector2::new(96.0, 72.0));
+    base2d.name = "Station".to_string();
+    base2d.tint = Color::LIGHTGRAY;
+    world.base_components.push((station, base2d));
+    world.trigger_components.push((station, Trigger::new()));
+    world.station_components.push((station, Station::new()));
+    world.text_components.push((
+        station,
+        Text2D::new("Station", 10, Color::LIGHTGRAY)
+            .anchored(Anchor::BottomCenter, Vector2::new(0.0, 10.0)),
+    ));
+    world.team_components.push((station, Team::Friendly));
+    world
+        .indicator_components
+        .push((station, Indicator::new(IndicatorKind::Objective)));
+
+    // every piece slides in from off screen
+    for (item, anchor, offset, size, slide_in) in [
+        (
+            HudItem::Credits,
+            Anchor::TopRight,
+            Vector2::new(-10.0, 10.0),
+            Vector2::new(120.0, 20.0),
+            Tween::new(tween::ui_offset_x, 140.0, -10.0, 0.6, Easing::BackOut),
+        ),
+        (
+            HudItem::Bombs,
+            Anchor::TopLeft,
+            Vector2::new(10.0, 34.0),
+            Vector2::new(120.0, 16.0),
+            Tween::new(tween::ui_offset_x, -130.0, 10.0, 0.6, Easing::BackOut),
+        ),
+        (
+            HudItem::BossBar,
+            Anchor::TopCenter,
+            Vector2::new(0.0, 10.0),
+            Vector2::new(300.0, 12.0),
+            Tween::new(tween::ui_offset_y, -30.0, 10.0, 0.6, Easing::BackOut),
+        ),
+    ] {
+        let hud = world.new_entity();
+        world
+            .ui_components
+            .push((hud, UiElement::new(anchor, offset, size)));
+        world.hud_components.push((hud, item));
+        world.tween_components.push((hud, slide_in));
+    }
+
+    // the campaign level's waves and backdrops
+    Campaign::begin(world, rl, thread);
+}
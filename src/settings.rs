@@ -0,0 +1,359 @@
+use raylib::prelude::*;
+
+use crate::bindings::{self, Action};
+use crate::palette::ColorMode;
+use crate::screen::WindowMode;
+use crate::ui::UiElement;
+use crate::widget::{UiEventKind, Widget};
+use crate::{i18n, t, Anchor, EntityID, World};
+
+/// Frame caps the setting steps through, 0 is uncapped.
+const FRAME_CAPS: [u32; 5] = [30, 60, 120, 144, 0];
+const MAX_WINDOW_SCALE: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Video,
+    Audio,
+    Controls,
+    Accessibility,
+}
+
+impl Tab {
+    fn values() -> Vec<Tab> {
+        vec![Tab::Video, Tab::Audio, Tab::Controls, Tab::Accessibility]
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Tab::Video => t!("settings.video"),
+            Tab::Audio => t!("settings.audio"),
+            Tab::Controls => t!("settings.controls"),
+            Tab::Accessibility => t!("settings.accessibility"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    Tab(Tab),
+    WindowMode,
+    Resolution,
+    Vsync,
+    FrameCap,
+    Language,
+    Master,
+    Music,
+    Effects,
+    ScreenShake,
+    ColorMode,
+    HighContrast,
+    Key(Action),
+    Back,
+}
+
+impl Setting {
+    /// Rows of a tab, top to bottom.
+    fn rows(tab: Tab) -> Vec<Setting> {
+        match tab {
+            Tab::Video => vec![
+                Setting::WindowMode,
+                Setting::Resolution,
+                Setting::Vsync,
+                Setting::FrameCap,
+                Setting::ScreenShake,
+                Setting::Language,
+            ],
+            Tab::Audio => vec![Setting::Master, Setting::Music, Setting::Effects],
+            Tab::Controls => Action::values().into_iter().map(Setting::Key).collect(),
+            Tab::Accessibility => vec![Setting::ColorMode, Setting::HighContrast],
+        }
+    }
+}
+
+/// Settings panel with Video, Audio, Controls and Accessibility tabs, opened from the
+/// title screen and pause menus or toggled with F10. Changes apply right
+/// away and are written to the config when the panel closes.
+pub struct SettingsMenu {
+    open: bool,
+    panel: Option<EntityID>,
+    tab: Tab,
+    /// Tab buttons, they stay while the rows under them change.
+    tabs: Vec<(EntityID, Setting)>,
+    rows: Vec<(EntityID, Setting)>,
+    /// Action waiting for the next key press to become its key.
+    rebinding: Option<Action>,
+}
+
+impl SettingsMenu {
+    pub fn new() -> SettingsMenu {
+        SettingsMenu {
+            open: false,
+            panel: None,
+            tab: Tab::Video,
+            tabs: Vec::new(),
+            rows: Vec::new(),
+            rebinding: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Shows the panel from the next update on.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    fn spawn_panel(&mut self, world: &mut World) {
+        self.panel = Some(Widget::spawn(
+            world,
+            Widget::panel(),
+            UiElement::new(Anchor::Center, Vector2::zero(), Vector2::new(300.0, 220.0)),
+        ));
+        for (i, tab) in Tab::values().into_iter().enumerate() {
+            let offset = Vector2::new((i as f32 - 1.5) * 72.0, -85.0);
+            let id = Widget::spawn(
+                world,
+                Widget::button(tab.label()),
+                UiElement::new(Anchor::Center, offset, Vector2::new(68.0, 20.0)),
+            );
+            self.tabs.push((id, Setting::Tab(tab)));
+        }
+        self.spawn_rows(world);
+    }
+
+    /// Spawns the current tab's rows and the Back button below them.
+    fn spawn_rows(&mut self, world: &mut World) {
+        let rows = Setting::rows(self.tab);
+        for (i, setting) in rows.into_iter().enumerate() {
+            let label = self.label(setting, world);
+            let widget = match setting {
+                Setting::Vsync => Widget::checkbox(label, world.config.vsync),
+                Setting::Master => Widget::slider(label, 0.0, 1.0, world.volume.master),
+                Setting::Music => Widget::slider(label, 0.0, 1.0, world.volume.music),
+                Setting::Effects => Widget::slider(label, 0.0, 1.0, world.volume.sfx),
+                Setting::HighContrast => {
+                    Widget::checkbox(label, world.palette.high_contrast_bullets)
+                }
+                Setting::ScreenShake => Widget::slider(label, 0.0, 1.0, world.config.screen_shake),
+                _ => Widget::button(label),
+            };
+            let offset = Vector2::new(0.0, -55.0 + i as f32 * 24.0);
+            let id = Widget::spawn(
+                world,
+                widget,
+                UiElement::new(Anchor::Center, offset, Vector2::new(260.0, 20.0)),
+            );
+            self.rows.push((id, setting));
+        }
+        let back = Widget::spawn(
+            world,
+            Widget::button(t!("menu.back")),
+            UiElement::new(
+                Anchor::Center,
+                Vector2::new(0.0, 85.0),
+                Vector2::new(100.0, 20.0),
+            ),
+        );
+        self.rows.push((back, Setting::Back));
+        world.focus.focused = self.rows.first().map(|r| r.0);
+    }
+
+    fn despawn_rows(&mut self, world: &mut World) {
+        for (id, _) in self.rows.drain(..) {
+            world.despawn(id);
+        }
+    }
+
+    fn label(&self, setting: Setting, world: &World) -> String {
+        match setting {
+            Setting::Tab(tab) => tab.label(),
+            Setting::WindowMode => {
+                let mode = match world.screen.mode {
+                    WindowMode::Windowed => t!("settings.windowed"),
+                    WindowMode::Borderless => t!("settings.borderless"),
+                    WindowMode::Fullscreen => t!("settings.fullscreen"),
+                };
+                t!("settings.window", mode = mode)
+            }
+            Setting::Resolution => {
+                let size = world.screen.canvas * world.config.window_scale as f32;
+                t!("settings.resolution", width = size.x, height = size.y)
+            }
+            Setting::Vsync => t!("settings.vsync"),
+            Setting::FrameCap => match world.config.target_fps {
+                0 => t!("settings.frame_cap_off"),
+                fps => t!("settings.frame_cap", fps = fps),
+            },
+            Setting::Language => t!("settings.language", name = i18n::name(&i18n::language())),
+            Setting::Master => t!("settings.master"),
+            Setting::Music => t!("settings.music"),
+            Setting::Effects => t!("settings.effects"),
+            Setting::ScreenShake => t!("settings.shake"),
+            Setting::ColorMode => {
+                let mode = match world.palette.mode {
+                    ColorMode::Normal => t!("settings.colors_normal"),
+                    ColorMode::Deuteranopia => t!("settings.deuteranopia"),
+                    ColorMode::Protanopia => t!("settings.protanopia"),
+                };
+                t!("settings.colors", mode = mode)
+            }
+            Setting::HighContrast => t!("settings.high_contrast"),
+            Setting::Key(action) if self.rebinding == Some(action) => {
+                t!("settings.press_key", action = action.label())
+            }
+            Setting::Key(action) => {
+                let key = bindings::key_name(world.bindings.key(action));
+                t!("settings.key", action = action.label(), key = key)
+            }
+            Setting::Back => t!("menu.back"),
+        }
+    }
+
+    /// Brings every tab's and row's label up to date.
+    fn relabel(&self, world: &mut World) {
+        for (id, setting) in self.tabs.iter().chain(self.rows.iter()) {
+            let label = self.label(*setting, world);
+            if let Some(w) = world.widget_components.iter_mut().find(|w| w.0 == *id) {
+                w.1.set_label(label);
+            }
+        }
+    }
+
+    /// Removes the panel and saves what was changed.
+    pub fn close(&mut self, world: &mut World) {
+        self.open = false;
+        self.rebinding = None;
+        if let Some(panel) = self.panel.take() {
+            world.despawn(panel);
+        }
+        for (id, _) in self.tabs.drain(..) {
+            world.despawn(id);
+        }
+        self.despawn_rows(world);
+        world.config.set_volume(&world.volume);
+        world.config.window_mode = world.screen.mode;
+        world.config.save();
+    }
+
+    /// Toggles the panel with F10 and catches the key for a rebind. Escape
+    /// cancels the rebind.
+    pub fn input_system(&mut self, world: &mut World, rl: &mut RaylibHandle) {
+        if rl.is_key_pressed(KeyboardKey::KEY_F10) {
+            if self.open {
+                self.close(world);
+            } else {
+                self.open();
+            }
+            return;
+        }
+
+        let action = match self.rebinding {
+            Some(a) => a,
+            None => return,
+        };
+        if let Some(key) = rl.get_key_pressed() {
+            if key != KeyboardKey::KEY_ESCAPE {
+                world.bindings.set(action, key);
+                world.config.bindings = world.bindings.clone();
+            }
+            self.rebinding = None;
+            self.relabel(world);
+        }
+    }
+
+    pub fn update_system(&mut self, world: &mut World, rl: &mut RaylibHandle) {
+        if !self.open {
+            return;
+        }
+        if self.panel.is_none() {
+            self.spawn_panel(world);
+        }
+
+        let events: Vec<(Setting, UiEventKind)> = world
+            .ui_events
+            .iter()
+            .filter_map(|e| {
+                let setting = self
+                    .tabs
+                    .iter()
+                    .chain(self.rows.iter())
+                    .find(|w| w.0 == e.widget)?;
+                Some((setting.1, e.kind))
+            })
+            .collect();
+
+        for (setting, kind) in events {
+            match (setting, kind) {
+                (Setting::Tab(tab), UiEventKind::Clicked) if tab != self.tab => {
+                    self.tab = tab;
+                    self.rebinding = None;
+                    self.despawn_rows(world);
+                    self.spawn_rows(world);
+                }
+                (Setting::WindowMode, UiEventKind::Clicked) => {
+                    let modes = WindowMode::values();
+                    let i = modes.iter().position(|m| *m == world.screen.mode);
+                    let next = modes[i.map(|i| (i + 1) % modes.len()).unwrap_or(0)];
+                    world.screen.set_mode(rl, next);
+                    world.config.window_mode = next;
+                }
+                (Setting::Resolution, UiEventKind::Clicked) => {
+                    world.config.window_scale = world.config.window_scale % MAX_WINDOW_SCALE + 1;
+                    // fullscreen modes keep the monitor's size, it applies
+                    // when going back to a window
+                    if world.screen.mode == WindowMode::Windowed {
+                        let size = world.screen.canvas * world.config.window_scale as f32;
+                        rl.set_window_size(size.x as i32, size.y as i32);
+                    }
+                }
+                (Setting::Vsync, UiEventKind::Toggled(on)) => {
+                    world.config.vsync = on;
+                    world.config.apply_video(rl);
+                }
+                (Setting::FrameCap, UiEventKind::Clicked) => {
+                    let i = FRAME_CAPS
+                        .iter()
+                        .position(|f| *f == world.config.target_fps);
+                    world.config.target_fps =
+                        FRAME_CAPS[i.map(|i| (i + 1) % FRAME_CAPS.len()).unwrap_or(0)];
+                    world.config.apply_video(rl);
+                }
+                (Setting::Language, UiEventKind::Clicked) => {
+                    let languages = i18n::languages();
+                    let i = languages.iter().position(|l| *l == i18n::language());
+                    let next = &languages[i.map(|i| (i + 1) % languages.len()).unwrap_or(0)];
+                    i18n::set_language(next);
+                    world.config.language = next.clone();
+                }
+                (Setting::ColorMode, UiEventKind::Clicked) => {
+                    let modes = ColorMode::values();
+                    let i = modes.iter().position(|m| *m == world.palette.mode);
+                    let next = modes[i.map(|i| (i + 1) % modes.len()).unwrap_or(0)];
+                    world.palette.mode = next;
+                    world.config.color_mode = next;
+                }
+                (Setting::HighContrast, UiEventKind::Toggled(on)) => {
+                    world.palette.high_contrast_bullets = on;
+                    world.config.high_contrast_bullets = on;
+                }
+                (Setting::Master, UiEventKind::Changed(v)) => world.volume.master = v,
+                (Setting::Music, UiEventKind::Changed(v)) => world.volume.music = v,
+                (Setting::Effects, UiEventKind::Changed(v)) => world.volume.sfx = v,
+                (Setting::ScreenShake, UiEventKind::Changed(v)) => {
+                    world.config.screen_shake = v;
+                    world.juice.intensity = v;
+                }
+                (Setting::Key(action), UiEventKind::Clicked) => self.rebinding = Some(action),
+                (Setting::Back, UiEventKind::Clicked) => {
+                    self.close(world);
+                    return;
+                }
+                _ => continue,
+            }
+            self.relabel(world);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use raylib::prelude::*;
+
+use crate::assets::{Assets, Handle};
+use crate::atlas::Atlas;
+use crate::group;
+use crate::render::{self, Layer, RenderLayer};
+use crate::renderer::Renderer;
+use crate::{DrawSystem, World};
+
+/// Textured quad stretched over the entity's bounds, tinted with Base2D.tint.
+pub struct Sprite {
+    texture: Handle<Texture2D>,
+    /// Region of the texture to draw, in pixels.
+    pub source: Rectangle,
+    /// Rotation pivot relative to the bounds, (0.5, 0.5) is the center.
+    pub origin: Vector2,
+    /// Rotation in degrees, clockwise.
+    pub rotation: f32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl Sprite {
+    /// Sprite showing the whole texture.
+    pub fn new(assets: &Assets, texture: Handle<Texture2D>) -> Sprite {
+        let source = match assets.texture(texture) {
+            Some(t) => Rectangle::new(0.0, 0.0, t.width() as f32, t.height() as f32),
+            None => Rectangle::new(0.0, 0.0, 0.0, 0.0),
+        };
+        Sprite {
+            texture,
+            source,
+            origin: Vector2::new(0.5, 0.5),
+            rotation: 0.0,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+}
+
+impl Sprite {
+    /// Sprite showing the named region of an atlas, pivoting around the
+    /// atlas pivot when it has one.
+    pub fn from_atlas(assets: &Assets, atlas: Handle<Atlas>, region: &str) -> Option<Sprite> {
+        let atlas = assets.atlas(atlas)?;
+        let source = atlas.region(region)?;
+        Some(Sprite {
+            source,
+            origin: atlas.pivot.unwrap_or(Vector2::new(0.5, 0.5)),
+            ..Sprite::new(assets, atlas.texture)
+        })
+    }
+
+    /// Draws the sprites of one render layer in z order. Sprites on the same
+    /// z are grouped by texture, raylib only has to flush its batch when the
+    /// texture changes so this keeps bullets and the like to a few draws.
+    /// Returns how many sprites were drawn and how many texture switches
+    /// that took. Needs `World::sort_groups` first.
+    pub fn draw_layer(world: &World, d: &mut dyn Renderer, layer: RenderLayer) -> (usize, usize) {
+        let mut batch: Vec<(i32, Handle<Texture2D>, usize, usize)> = Layer::sorted(world, layer)
+            .into_iter()
+            .filter(|id| render::in_view(world, *id))
+            .filter_map(|id| {
+                let s = group::find(&world.sprite_components, id)?;
+                let b = group::find(&world.base_components, id)?;
+                let texture = world.sprite_components[s].1.texture;
+                Some((Layer::of(world, id).z, texture, s, b))
+            })
+            .collect();
+        batch.sort();
+
+        let (mut drawn, mut batches) = (0, 0);
+        let mut last_texture = None;
+        for (_, _, s, b) in batch {
+            let (b, sprite) = (&world.base_components[b].1, &world.sprite_components[s].1);
+            let texture = match world.assets.texture(sprite.texture) {
+                Some(t) => t,
+                None => continue,
+            };
+
+            let mut source = sprite.source;
+            if sprite.flip_x {
+                source.width = -source.width;
+            }
+            if sprite.flip_y {
+                source.height = -source.height;
+            }
+
+            // draw_sprite rotates around the destination's top left
+            // corner shifted by origin, so move the rect onto the pivot
+            let origin = Vector2::new(
+                b.bounds.width() * sprite.origin.x,
+                b.bounds.height() * sprite.origin.y,
+            );
+            let dest = Rectangle::new(
+                b.bounds.x1 + origin.x,
+                b.bounds.y1 + origin.y,
+                b.bounds.width(),
+                b.bounds.height(),
+            );
+            let tint = world.palette.remap(b.tint);
+            d.draw_sprite(texture, source, dest, origin, sprite.rotation, tint);
+            drawn += 1;
+            if last_texture != Some(sprite.texture) {
+                batches += 1;
+                last_texture = Some(sprite.texture);
+            }
+        }
+        (drawn, batches)
+    }
+}
+
+impl DrawSystem for Sprite {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        world.sort_groups();
+        for layer in RenderLayer::values() {
+            Sprite::draw_layer(world, d, layer);
+        }
+    }
+}
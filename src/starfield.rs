@@ -0,0 +1,184 @@
+use std::f32::consts::PI;
+
+use raylib::prelude::*;
+
+use crate::assets::{Assets, Handle};
+use crate::renderer::Renderer;
+use crate::rng::Rng;
+
+/// Size of the square the stars wrap around in, must exceed the screen.
+const TILE: f32 = 2048.0;
+
+struct Star {
+    pos: Vector2,
+    size: f32,
+    brightness: f32,
+    twinkle_speed: f32,
+    phase: f32,
+}
+
+/// Distant body drawn on one of the slower layers.
+enum Feature {
+    Planet {
+        pos: Vector2,
+        radius: f32,
+        color: Color,
+    },
+    Nebula {
+        pos: Vector2,
+        radius: f32,
+        color: Color,
+    },
+}
+
+struct StarLayer {
+    /// How much of the camera movement this layer follows, 1.0 is world speed.
+    parallax: f32,
+    stars: Vec<Star>,
+    features: Vec<Feature>,
+}
+
+/// Image tiled behind the stars, scrolling like a star layer.
+pub struct Backdrop {
+    pub texture: Handle<Texture2D>,
+    /// How much of the camera movement it follows, 1.0 is world speed.
+    pub parallax: f32,
+    pub tint: Color,
+}
+
+/// Procedural background of star layers scrolling slower than the world,
+/// over the level's backdrops.
+pub struct Starfield {
+    layers: Vec<StarLayer>,
+    pub backdrops: Vec<Backdrop>,
+    time: f32,
+}
+
+impl Starfield {
+    pub fn new(seed: u64) -> Starfield {
+        let mut rng = Rng::new(seed);
+        let mut layers = Vec::new();
+
+        for (parallax, count, max_size) in [(0.05, 220, 1.0), (0.15, 140, 1.5), (0.35, 70, 2.2)] {
+            let stars = (0..count)
+                .map(|_| Star {
+                    pos: Vector2::new(rng.range(0.0, TILE), rng.range(0.0, TILE)),
+                    size: rng.range(0.5, max_size),
+                    brightness: rng.range(0.3, 1.0),
+                    twinkle_speed: rng.range(0.5, 3.0),
+                    phase: rng.range(0.0, PI * 2.0),
+                })
+                .collect();
+            layers.push(StarLayer {
+                parallax,
+                stars,
+                features: Vec::new(),
+            });
+        }
+
+        // a few nebulae far back, the odd planet a bit closer
+        for _ in 0..3 {
+            let colors = [Color::PURPLE, Color::DARKBLUE, Color::MAROON];
+            layers[0].features.push(Feature::Nebula {
+                pos: Vector2::new(rng.range(0.0, TILE), rng.range(0.0, TILE)),
+                radius: rng.range(120.0, 260.0),
+                color: colors[rng.index(colors.len())],
+            });
+        }
+        for _ in 0..2 {
+            let colors = [
+                Color::BROWN,
+                Color::DARKGREEN,
+                Color::DARKPURPLE,
+                Color::BEIGE,
+            ];
+            layers[1].features.push(Feature::Planet {
+                pos: Vector2::new(rng.range(0.0, TILE), rng.range(0.0, TILE)),
+                radius: rng.range(18.0, 60.0),
+                color: colors[rng.index(colors.len())],
+            });
+        }
+
+        Starfield {
+            layers,
+            backdrops: Vec::new(),
+            time: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    /// Draws in screen space, scrolled by the camera's world position.
+    /// Nebulae swell and brighten with `pulse`, the music's beat.
+    pub fn draw(
+        &self,
+        d: &mut dyn Renderer,
+        assets: &Assets,
+        view: Vector2,
+        camera_target: Vector2,
+        pulse: f32,
+    ) {
+        for backdrop in self.backdrops.iter() {
+            let texture = match assets.texture(backdrop.texture) {
+                Some(t) => t,
+                None => continue,
+            };
+            let size = Vector2::new(texture.width as f32, texture.height as f32);
+            let scroll = camera_target * backdrop.parallax;
+            let start = Vector2::new(-scroll.x.rem_euclid(size.x), -scroll.y.rem_euclid(size.y));
+            let mut y = start.y;
+            while y < view.y {
+                let mut x = start.x;
+                while x < view.x {
+                    d.draw_sprite(
+                        texture,
+                        Rectangle::new(0.0, 0.0, size.x, size.y),
+                        Rectangle::new(x, y, size.x, size.y),
+                        Vector2::zero(),
+                        0.0,
+                        backdrop.tint,
+                    );
+                    x += size.x;
+                }
+                y += size.y;
+            }
+        }
+        for layer in self.layers.iter() {
+            let scroll = camera_target * layer.parallax;
+            let wrap = |pos: Vector2| {
+                Vector2::new(
+                    (pos.x - scroll.x).rem_euclid(TILE),
+                    (pos.y - scroll.y).rem_euclid(TILE),
+                )
+            };
+
+            for feature in layer.features.iter() {
+                match feature {
+                    Feature::Nebula { pos, radius, color } => {
+                        let p = wrap(*pos);
+                        let radius = radius * (1.0 + 0.04 * pulse);
+                        for i in 0..4 {
+                            let r = radius * (1.0 - i as f32 * 0.2);
+                            d.draw_circle(p, r, color.fade(0.06 + 0.04 * pulse));
+                        }
+                    }
+                    Feature::Planet { pos, radius, color } => {
+                        let p = wrap(*pos);
+                        d.draw_circle(p, *radius, *color);
+                        // shade the far side
+                        let shade = Vector2::new(radius * 0.35, radius * 0.2);
+                        d.draw_circle(p + shade, radius * 0.85, Color::BLACK.fade(0.45));
+                    }
+                }
+            }
+
+            for star in layer.stars.iter() {
+                let twinkle = 0.75 + 0.25 * (self.time * star.twinkle_speed + star.phase).sin();
+                let alpha = (star.brightness * twinkle).clamp(0.0, 1.0);
+                d.draw_circle(wrap(star.pos), star.size, Color::WHITE.fade(alpha));
+            }
+        }
+    }
+}
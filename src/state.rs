@@ -0,0 +1,183 @@
+use raylib::prelude::*;
+
+use crate::font::{self, TextStyle};
+use crate::renderer::Renderer;
+use crate::{info, Anchor, DrawSystem, World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    Menu,
+    Playing,
+    Paused,
+    GameOver,
+    /// Frozen for screenshots, see PhotoMode.
+    Photo,
+    Credits,
+    Gallery,
+    /// Frozen for building levels, see Editor.
+    Editor,
+}
+
+impl GameState {
+    /// Whether the simulation runs, the title screen's attract mode plays
+    /// out in the world too. In every other state the world stays frozen
+    /// underneath and is only drawn.
+    pub fn simulates(self) -> bool {
+        self.is_title() || self == GameState::Playing
+    }
+
+    /// The title screen and the screens reached from it, all in front of
+    /// the attract mode.
+    pub fn is_title(self) -> bool {
+        matches!(
+            self,
+            GameState::Menu | GameState::Credits | GameState::Gallery
+        )
+    }
+
+    /// Whether the HUD and overlays are drawn, the title screens have no
+    /// use for them and photos are better off without.
+    pub fn shows_hud(self) -> bool {
+        !self.is_title() && self != GameState::Photo && self != GameState::Editor
+    }
+}
+
+/// Runs when a state is entered or left.
+pub type Hook = fn(&mut World);
+
+/// Which state the game is in. Changes are queued and applied between
+/// frames by `apply_system`, so a frame never runs half in one state and
+/// half in another.
+pub struct StateMachine {
+    current: GameState,
+    next: Option<GameState>,
+    enter_hooks: Vec<(GameState, Hook)>,
+    exit_hooks: Vec<(GameState, Hook)>,
+}
+
+impl StateMachine {
+    pub fn new(initial: GameState) -> StateMachine {
+        StateMachine {
+            current: initial,
+            next: None,
+            enter_hooks: Vec::new(),
+            exit_hooks: Vec::new(),
+        }
+    }
+
+    pub fn current(&self) -> GameState {
+        self.current
+    }
+
+    pub fn is(&self, state: GameState) -> bool {
+        self.current == state
+    }
+
+    /// Switches to `state` before the next frame. The last request wins.
+    pub fn set(&mut self, state: GameState) {
+        self.next = Some(state);
+    }
+
+    pub fn on_enter(&mut self, state: GameState, hook: Hook) {
+        self.enter_hooks.push((state, hook));
+    }
+
+    pub fn on_exit(&mut self, state: GameState, hook: Hook) {
+        self.exit_hooks.push((state, hook));
+    }
+
+    /// Pauses and resumes with Escape or the gamepad's Start button.
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        let start = rl.is_gamepad_available(0)
+            && rl.is_gamepad_button_pressed(0, GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT);
+        if !rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) && !start {
+            return;
+        }
+        match world.state.current {
+            GameState::Playing => world.state.set(GameState::Paused),
+            GameState::Paused => world.state.set(GameState::Playing),
+            _ => (),
+        }
+    }
+
+    /// The game is over once every player has died.
+    pub fn update_system(world: &mut World) {
+        if !world.state.is(GameState::Playing) || world.player_components.is_empty() {
+            return;
+        }
+        let all_dead = world
+            .player_components
+            .iter()
+            .all(|p| world.death_events.iter().any(|e| e.entity == p.0));
+        if all_dead {
+            world.state.set(GameState::GameOver);
+        }
+    }
+
+    /// Moves to the queued state, running the exit hooks of the old one and
+    /// the enter hooks of the new one. Hooks may queue yet another state,
+    /// that one is entered right after.
+    pub fn apply_system(world: &mut World) {
+        while let Some(next) = world.state.next.take() {
+            let previous = world.state.current;
+            if next == previous {
+                continue;
+            }
+            let exits: Vec<Hook> = world
+                .state
+                .exit_hooks
+                .iter()
+                .filter(|h| h.0 == previous)
+                .map(|h| h.1)
+                .collect();
+            for hook in exits {
+                hook(world);
+            }
+
+            world.state.current = next;
+            info!(
+                "state",
+                "state changed",
+                from = format!("{:?}", previous),
+                to = format!("{:?}", next)
+            );
+            let enters: Vec<Hook> = world
+                .state
+                .enter_hooks
+                .iter()
+                .filter(|h| h.0 == next)
+                .map(|h| h.1)
+                .collect();
+            for hook in enters {
+                hook(world);
+            }
+        }
+    }
+}
+
+impl DrawSystem for StateMachine {
+    /// Dims the frozen world, except for photos and the editor, naming the
+    /// state when paused. Drawn under the widgets so menus stay bright.
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        let clear = [GameState::Photo, GameState::Editor];
+        if world.state.current.simulates() || clear.contains(&world.state.current) {
+            return;
+        }
+        let (w, h) = (world.screen.width(), world.screen.height());
+        d.draw_rect(Rectangle::new(0.0, 0.0, w, h), Color::BLACK.fade(0.5));
+        if !world.state.is(GameState::Paused) {
+            return;
+        }
+        let label = "PAUSED";
+        let style = TextStyle::new(24.0, Color::RAYWHITE)
+            .aligned(Anchor::Center)
+            .outlined(Color::BLACK);
+        font::draw(
+            &world.assets,
+            d,
+            label,
+            Vector2::new(w * 0.5, h * 0.3),
+            &style,
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use raylib::prelude::*;
+
+use crate::renderer::Renderer;
+use crate::t;
+use crate::trigger::TriggerEventKind;
+use crate::{Anchor, DrawSystem, EntityID, UpdateSystem, World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StationOption {
+    Repair,
+    RefillBombs,
+    UpgradeWeapon,
+    Undock,
+}
+
+impl StationOption {
+    fn values() -> Vec<StationOption> {
+        vec![
+            StationOption::Repair,
+            StationOption::RefillBombs,
+            StationOption::UpgradeWeapon,
+            StationOption::Undock,
+        ]
+    }
+
+    fn label(&self) -> String {
+        match self {
+            StationOption::Repair => t!("station.repair"),
+            StationOption::RefillBombs => t!("station.refill"),
+            StationOption::UpgradeWeapon => t!("station.upgrade"),
+            StationOption::Undock => t!("station.undock"),
+        }
+    }
+
+    fn cost(&self) -> u32 {
+        match self {
+            StationOption::Repair => 50,
+            StationOption::RefillBombs => 30,
+            StationOption::UpgradeWeapon => 120,
+            StationOption::Undock => 0,
+        }
+    }
+}
+
+/// Bomb stock, only restocked at stations.
+pub struct Bombs {
+    pub count: u8,
+    pub capacity: u8,
+}
+
+impl Bombs {
+    pub fn new(capacity: u8) -> Bombs {
+        Bombs {
+            count: capacity,
+            capacity,
+        }
+    }
+}
+
+/// A dock the player can enter by coasting slowly into its trigger volume.
+pub struct Station {
+    max_dock_speed: f32,
+    occupants: Vec<EntityID>,
+    /// Players that just undocked and have to leave before docking again.
+    departing: Vec<EntityID>,
+    selected: usize,
+}
+
+impl Station {
+    pub fn new() -> Station {
+        Station {
+            max_dock_speed: 40.0,
+            occupants: Vec::new(),
+            departing: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    /// Pilots and every weapon but the player's hold their fire while this
+    /// is true.
+    pub fn player_docked(world: &World) -> bool {
+        world
+            .player_components
+            .iter()
+            .any(|p| p.1.docked_at.is_some())
+    }
+
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        for (player, p) in world.player_components.iter_mut() {
+            let station = match p
+                .docked_at
+                .and_then(|s| world.station_components.iter_mut().find(|st| st.0 == s))
+            {
+                Some(st) => &mut st.1,
+                None => continue,
+            };
+
+            let options = StationOption::values();
+            if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+                station.selected = (station.selected + options.len() - 1) % options.len();
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+                station.selected = (station.selected + 1) % options.len();
+            }
+            if !rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                continue;
+            }
+
+            let option = options[station.selected];
+            if p.credits < option.cost() {
+                continue;
+            }
+
+            let bought = match option {
+                StationOption::Repair => world
+                    .health_components
+                    .iter_mut()
+                    .find(|h| h.0 == *player && h.1.health < h.1.max_health)
+                    .map(|h| h.1.health = h.1.max_health)
+                    .is_some(),
+                StationOption::RefillBombs => world
+                    .bomb_components
+                    .iter_mut()
+                    .find(|b| b.0 == *player && b.1.count < b.1.capacity)
+                    .map(|b| b.1.count = b.1.capacity)
+                    .is_some(),
+                StationOption::UpgradeWeapon => world
+                    .weapon_components
+                    .iter_mut()
+                    .find(|w| w.0 == *player)
+                    .map(|w| w.1.upgrade())
+                    .unwrap_or(false),
+                StationOption::Undock => {
+                    station.departing.push(*player);
+                    p.docked_at = None;
+                    false
+                }
+            };
+
+            if bought {
+                p.credits -= option.cost();
+            }
+        }
+    }
+}
+
+impl UpdateSystem for Station {
+    fn update_system(world: &mut World, _dt: f32) {
+        for event in world.trigger_events.iter() {
+            if let Some(st) = world
+                .station_components
+                .iter_mut()
+                .find(|st| st.0 == event.trigger)
+            {
+                match event.kind {
+                    TriggerEventKind::Enter => st.1.occupants.push(event.other),
+                    TriggerEventKind::Exit => {
+                        st.1.occupants.retain(|o| *o != event.other);
+                        st.1.departing.retain(|o| *o != event.other);
+                    }
+                }
+            }
+        }
+
+        for (id, station) in world.station_components.iter_mut() {
+            for occupant in station.occupants.iter() {
+                if station.departing.contains(occupant) {
+                    continue;
+                }
+                let player = match world
+                    .player_components
+                    .iter_mut()
+                    .find(|p| p.0 == *occupant && p.1.docked_at.is_none())
+                {
+                    Some(p) => &mut p.1,
+                    None => continue,
+                };
+                let velocity = match world
+                    .velocity_components
+                    .iter_mut()
+                    .find(|v| v.0 == *occupant)
+                {
+                    Some(v) => &mut v.1,
+                    None => continue,
+                };
+
+                if velocity.velocity.length() <= station.max_dock_speed {
+                    velocity.velocity = Vector2::zero();
+                    player.docked_at = Some(*id);
+                    station.selected = 0;
+                }
+            }
+        }
+    }
+}
+
+impl DrawSystem for Station {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        for (player, p) in world.player_components.iter() {
+            let station = match p
+                .docked_at
+                .and_then(|s| world.station_components.iter().find(|st| st.0 == s))
+            {
+                Some(st) => &st.1,
+                None => continue,
+            };
+
+            let w = 260.0;
+            let h = 150.0;
+            let center = world.screen.bounds().center();
+            let panel = Rectangle::new(center.x - w * 0.5, center.y - h * 0.5, w, h);
+            d.draw_rect(panel, Color::BLACK.fade(0.85));
+            d.draw_rect_lines(panel, 1, Color::WHITE);
+
+            let x = panel.x as i32 + 12;
+            let mut y = panel.y as i32 + 10;
+            d.draw_text(&t!("station.docked"), x, y, 20, Color::WHITE);
+            let credits = t!("station.credits", credits = p.credits);
+            d.draw_text(&credits, x + 120, y + 4, 14, Color::GOLD);
+
+            for (i, option) in StationOption::values().iter().enumerate() {
+                y += 26;
+                let color = if i == station.selected {
+                    Color::YELLOW
+                } else if p.credits < option.cost() {
+                    Color::DARKGRAY
+                } else {
+                    Color::LIGHTGRAY
+                };
+                let label = match option.cost() {
+                    0 => option.label(),
+                    cost => format!("{} ({})", option.label(), cost),
+                };
+                d.draw_text(&label, x, y, 16, color);
+            }
+
+            if let Some(b) = world.bomb_components.iter().find(|b| b.0 == *player) {
+                let bombs = t!("station.bombs", count = b.1.count, capacity = b.1.capacity);
+                d.draw_text(
+                    &bombs,
+                    x + 160,
+                    panel.y as i32 + h as i32 - 20,
+                    12,
+                    Color::GRAY,
+                );
+            }
+        }
+
+        // remind the player to slow down while drifting through a dock
+        for (_, station) in world.station_components.iter() {
+            for occupant in station.occupants.iter() {
+                let undocked = world
+                    .player_components
+                    .iter()
+                    .any(|p| p.0 == *occupant && p.1.docked_at.is_none());
+                if !undocked || station.departing.contains(occupant) {
+                    continue;
+                }
+                if let Some(b) = world.base_components.iter().find(|b| b.0 == *occupant) {
+                    let pos = b.1.bounds.calc(Anchor::BottomCenter);
+                    d.draw_text(
+                        &t!("station.slow_down"),
+                        pos.x as i32 - 50,
+                        pos.y as i32 + 6,
+                        12,
+                        Color::GRAY,
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::fs;
+use std::path::PathBuf;
+
+use raylib::prelude::*;
+
+use crate::renderer::Renderer;
+use crate::{t, warn};
+use crate::{EntityID, World};
+
+#[derive(Default)]
+struct Totals {
+    shots_fired: u32,
+    hits: u32,
+    damage_dealt: u32,
+    damage_taken: u32,
+    /// Damage past what the player's kills had left, see `DeathEvent`.
+    overkill: u32,
+    time_survived: f32,
+    kills: Vec<(String, u32)>,
+}
+
+impl Totals {
+    fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.0;
+        }
+        self.hits as f32 / self.shots_fired as f32
+    }
+
+    fn add_kill(&mut self, kind: &str, amount: u32) {
+        match self.kills.iter_mut().find(|k| k.0 == kind) {
+            Some(k) => k.1 += amount,
+            None => self.kills.push((kind.to_string(), amount)),
+        }
+    }
+
+    fn add(&mut self, other: &Totals) {
+        self.shots_fired += other.shots_fired;
+        self.hits += other.hits;
+        self.damage_dealt += other.damage_dealt;
+        self.damage_taken += other.damage_taken;
+        self.overkill += other.overkill;
+        self.time_survived += other.time_survived;
+        for (kind, amount) in other.kills.iter() {
+            self.add_kill(kind, *amount);
+        }
+    }
+}
+
+/// Statistics of the current run, plus lifetime totals kept on disk. Each
+/// line of the file is `<key> <value>`, or `kills <count> <enemy name>`.
+pub struct Stats {
+    path: PathBuf,
+    run: Totals,
+    lifetime: Totals,
+    runs: u32,
+    game_over: bool,
+}
+
+impl Stats {
+    pub fn load(path: impl Into<PathBuf>) -> Stats {
+        let mut stats = Stats {
+            path: path.into(),
+            run: Totals::default(),
+            lifetime: Totals::default(),
+            runs: 0,
+            game_over: false,
+        };
+
+        let contents = fs::read_to_string(&stats.path).unwrap_or_default();
+        for line in contents.lines() {
+            let mut parts = line.splitn(3, ' ');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(k), Some(v)) => (k, v),
+                _ => continue,
+            };
+            let lifetime = &mut stats.lifetime;
+            match key {
+                "runs" => stats.runs = value.parse().unwrap_or(0),
+                "shots_fired" => lifetime.shots_fired = value.parse().unwrap_or(0),
+                "hits" => lifetime.hits = value.parse().unwrap_or(0),
+                "damage_dealt" => lifetime.damage_dealt = value.parse().unwrap_or(0),
+                "damage_taken" => lifetime.damage_taken = value.parse().unwrap_or(0),
+                "overkill" => lifetime.overkill = value.parse().unwrap_or(0),
+                "time_survived" => lifetime.time_survived = value.parse().unwrap_or(0.0),
+                "kills" => {
+                    if let (Ok(amount), Some(kind)) = (value.parse(), parts.next()) {
+                        lifetime.add_kill(kind, amount);
+                    }
+                }
+                _ => (),
+            }
+        }
+        stats
+    }
+
+    pub fn save(&self) {
+        let l = &self.lifetime;
+        let mut contents = format!(
+            "runs {}\nshots_fired {}\nhits {}\ndamage_dealt {}\ndamage_taken {}\noverkill {}\ntime_survived {}\n",
+            self.runs, l.shots_fired, l.hits, l.damage_dealt, l.damage_taken, l.overkill, l.time_survived
+        );
+        for (kind, amount) in l.kills.iter() {
+            contents += &format!("kills {} {}\n", amount, kind);
+        }
+        if let Err(e) = fs::write(&self.path, contents) {
+            warn!(
+                "stats",
+                "could not save stats",
+                path = self.path.display(),
+                error = e
+            );
+        }
+    }
+
+    /// Lifetime kills per kind of enemy, the enemies met so far.
+    pub fn kills(&self) -> Vec<(&str, u32)> {
+        self.lifetime
+            .kills
+            .iter()
+            .map(|(kind, amount)| (kind.as_str(), *amount))
+            .collect()
+    }
+
+    /// Starts counting a new run from zero.
+    pub fn start_run(&mut self) {
+        self.run = Totals::default();
+        self.game_over = false;
+    }
+
+    /// Points for the current run: 10 per damage dealt, 100 per kill, a
+    /// style bonus of 5 per point of overkill and one per second survived.
+    pub fn score(&self) -> u32 {
+        let kills: u32 = self.run.kills.iter().map(|k| k.1).sum();
+        self.run.damage_dealt * 10
+            + kills * 100
+            + self.run.overkill * 5
+            + self.run.time_survived as u32
+    }
+
+    pub fn update_system(&mut self, world: &World, dt: f32) {
+        if self.game_over {
+            return;
+        }
+        let is_player = |id: EntityID| world.player_components.iter().any(|p| p.0 == id);
+
+        self.run.time_survived += dt;
+        self.run.shots_fired += world
+            .shot_events
+            .iter()
+            .filter(|e| is_player(e.weapon))
+            .count() as u32;
+
+        // amounts are what was dealt by now, a shot soaked up by god mode
+        // or invulnerability didn't hit anything
+        for event in world.damage_events.iter().filter(|e| e.amount > 0) {
+            if event.source.is_some_and(is_player) {
+                self.run.hits += 1;
+                self.run.damage_dealt += event.amount as u32;
+            }
+            if is_player(event.target) {
+                self.run.damage_taken += event.amount as u32;
+            }
+        }
+
+        for event in world.death_events.iter() {
+            if is_player(event.entity) {
+                self.game_over = true;
+            } else if event.killer.is_some_and(is_player) {
+                let kind = world
+                    .base_components
+                    .iter()
+                    .find(|b| b.0 == event.entity)
+                    .map(|b| b.1.name.as_str())
+                    .unwrap_or("Unknown");
+                self.run.add_kill(kind, 1);
+                self.run.overkill += event.overkill as u32;
+            }
+        }
+
+        if self.game_over {
+            self.runs += 1;
+            self.lifetime.add(&self.run);
+            self.save();
+        }
+    }
+
+    pub fn draw_system(&self, world: &World, d: &mut dyn Renderer) {
+        if !self.game_over {
+            return;
+        }
+
+        // left of the game over screen's score panel
+        let w = 300.0;
+        let h = 278.0;
+        let center = world.screen.bounds().center();
+        let panel = Rectangle::new(center.x - w - 5.0, center.y - h * 0.5 - 14.0, w, h);
+        d.draw_rect(panel, Color::BLACK.fade(0.9));
+        d.draw_rect_lines(panel, 1, Color::WHITE);
+
+        let x = panel.x as i32 + 14;
+        let mut y = panel.y as i32 + 12;
+        d.draw_text(&t!("stats.game_over"), x, y, 24, Color::RED);
+        y += 34;
+
+        let run = &self.run;
+        let lines = [
+            t!("stats.time", seconds = format!("{:.1}", run.time_survived)),
+            t!("stats.shots", shots = run.shots_fired),
+            t!(
+                "stats.accuracy",
+                percent = format!("{:.0}", run.accuracy() * 100.0)
+            ),
+            t!("stats.dealt", damage = run.damage_dealt),
+            t!("stats.taken", damage = run.damage_taken),
+            t!("stats.overkill", damage = run.overkill),
+        ];
+        for line in lines.iter() {
+            d.draw_text(line, x, y, 14, Color::LIGHTGRAY);
+            y += 18;
+        }
+
+        y += 6;
+        d.draw_text(&t!("stats.kills"), x, y, 14, Color::WHITE);
+        for (kind, amount) in run.kills.iter() {
+            y += 16;
+            d.draw_text(
+                &format!("  {} x{}", kind, amount),
+                x,
+                y,
+                12,
+                Color::LIGHTGRAY,
+            );
+        }
+
+        let lifetime = t!(
+            "stats.lifetime",
+            runs = self.runs,
+            seconds = format!("{:.0}", self.lifetime.time_survived),
+            percent = format!("{:.0}", self.lifetime.accuracy() * 100.0)
+        );
+        d.draw_text(
+            &lifetime,
+            x,
+            panel.y as i32 + h as i32 - 22,
+            12,
+            Color::GRAY,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::DamageEvent;
+    use crate::player::Player;
+
+    #[test]
+    fn counts_only_hits_that_dealt_damage() {
+        let mut world = World::new();
+        world.player_components.push((1, Player::new()));
+        let hit = |target, source, amount| DamageEvent {
+            target,
+            source,
+            amount,
+            crit: false,
+        };
+        world.damage_events = vec![
+            hit(2, Some(1), 5),
+            hit(3, Some(1), 0),
+            hit(1, Some(2), 4),
+            hit(1, Some(3), 0),
+            hit(2, None, 7),
+        ];
+
+        let mut stats = Stats::load(PathBuf::new());
+        stats.update_system(&world, 0.5);
+        assert_eq!(stats.run.hits, 1);
+        assert_eq!(stats.run.damage_dealt, 5);
+        assert_eq!(stats.run.damage_taken, 4);
+    }
+}
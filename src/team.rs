@@ -0,0 +1,18 @@
+use raylib::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Team {
+    Friendly,
+    Hostile,
+    Neutral,
+}
+
+impl Team {
+    pub fn color(&self) -> Color {
+        match self {
+            Team::Friendly => Color::SKYBLUE,
+            Team::Hostile => Color::RED,
+            Team::Neutral => Color::GRAY,
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use raylib::prelude::*;
+
+use crate::damage::DamageEvent;
+use crate::particles::EmitterConfig;
+use crate::renderer::Renderer;
+use crate::{BoundingBox2D, DrawSystem, EntityID, UpdateSystem, World};
+
+/// Distance between the bursts along a line when it goes off.
+const BURST_SPACING: f32 = 24.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// A laser from `from` to `to`, `width` wide.
+    Line {
+        from: Vector2,
+        to: Vector2,
+        width: f32,
+    },
+    /// An area of effect.
+    Circle { center: Vector2, radius: f32 },
+}
+
+impl Shape {
+    /// Box around the shape, a line's width left out.
+    pub fn bounds(&self) -> BoundingBox2D {
+        match *self {
+            Shape::Line { from, to, .. } => BoundingBox2D::new(
+                from.x.min(to.x),
+                from.y.min(to.y),
+                (to.x - from.x).abs(),
+                (to.y - from.y).abs(),
+            ),
+            Shape::Circle { center, radius } => BoundingBox2D::new(
+                center.x - radius,
+                center.y - radius,
+                radius * 2.0,
+                radius * 2.0,
+            ),
+        }
+    }
+
+    /// Whether `bounds` is touched by the shape.
+    fn hits(&self, bounds: &BoundingBox2D) -> bool {
+        match *self {
+            Shape::Line { from, to, width } => {
+                let center = bounds.center();
+                let line = to - from;
+                let t = if line.length_sqr() > 0.0 {
+                    ((center - from).dot(line) / line.length_sqr()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let reach = (width + bounds.width().min(bounds.height())) * 0.5;
+                center.distance_to(from + line * t) <= reach
+            }
+            Shape::Circle { center, radius } => {
+                let closest = Vector2::new(
+                    center.x.clamp(bounds.x1, bounds.x2),
+                    center.y.clamp(bounds.y1, bounds.y2),
+                );
+                closest.distance_to(center) <= radius
+            }
+        }
+    }
+}
+
+/// Warning drawn where an attack is about to land, growing over its wind
+/// up until the attack goes off and hurts whatever is inside. Belongs to
+/// the attacker, which can have several going at once, and goes with it.
+pub struct Telegraph {
+    pub shape: Shape,
+    /// Seconds from the warning to the attack.
+    pub windup: f32,
+    /// Dealt to everything inside that isn't on the attacker's team, none
+    /// for a warning only, like a pilot lining up its shots.
+    pub damage: u16,
+    /// Particles burst where it goes off.
+    pub burst: Option<EmitterConfig>,
+    elapsed: f32,
+}
+
+impl Telegraph {
+    pub fn line(from: Vector2, to: Vector2, width: f32, windup: f32) -> Telegraph {
+        Telegraph::new(Shape::Line { from, to, width }, windup)
+    }
+
+    pub fn circle(center: Vector2, radius: f32, windup: f32) -> Telegraph {
+        Telegraph::new(Shape::Circle { center, radius }, windup)
+    }
+
+    fn new(shape: Shape, windup: f32) -> Telegraph {
+        Telegraph {
+            shape,
+            windup,
+            damage: 0,
+            burst: None,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn damage(mut self, damage: u16) -> Telegraph {
+        self.damage = damage;
+        self
+    }
+
+    pub fn burst(mut self, config: EmitterConfig) -> Telegraph {
+        self.burst = Some(config);
+        self
+    }
+
+    /// How far along the wind up is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.windup > 0.0 {
+            (self.elapsed / self.windup).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Hurts what's inside and bursts its particles.
+    fn go_off(&self, world: &mut World, owner: EntityID) {
+        if let Some(config) = self.burst.as_ref() {
+            match self.shape {
+                Shape::Line { from, to, .. } => {
+                    let bursts = (from.distance_to(to) / BURST_SPACING).ceil().max(1.0) as usize;
+                    for i in 0..=bursts {
+                        let pos = from.lerp(to, i as f32 / bursts as f32);
+                        world.particles.burst(config, pos);
+                    }
+                }
+                Shape::Circle { center, .. } => world.particles.burst(config, center),
+            }
+        }
+        if self.damage == 0 {
+            return;
+        }
+
+        let team = world
+            .team_components
+            .iter()
+            .find(|t| t.0 == owner)
+            .map(|t| t.1);
+        let targets: Vec<EntityID> = world
+            .health_components
+            .iter()
+            .map(|h| h.0)
+            .filter(|target| {
+                let friendly = team.is_some()
+                    && world
+                        .team_components
+                        .iter()
+                        .any(|t| t.0 == *target && Some(t.1) == team);
+                *target != owner
+                    && !friendly
+                    && world
+                        .base_components
+                        .iter()
+                        .any(|b| b.0 == *target && self.shape.hits(&b.1.bounds))
+            })
+            .collect();
+        for target in targets {
+            world.damage_events.push(DamageEvent {
+                target,
+                source: Some(owner),
+                amount: self.damage,
+                crit: false,
+            });
+        }
+    }
+}
+
+impl UpdateSystem for Telegraph {
+    fn update_system(world: &mut World, dt: f32) {
+        let mut done = Vec::new();
+        let mut i = 0;
+        while i < world.telegraph_components.len() {
+            let telegraph = &mut world.telegraph_components[i].1;
+            telegraph.elapsed += dt;
+            if telegraph.elapsed >= telegraph.windup {
+                done.push(world.telegraph_components.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+
+        for (owner, telegraph) in done {
+            telegraph.go_off(world, owner);
+        }
+    }
+}
+
+impl DrawSystem for Telegraph {
+    /// The whole shape outlined faintly, filled from the middle out as the
+    /// attack comes closer, and blinking faster towards the end.
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        for (_, telegraph) in world.telegraph_components.iter() {
+            // the attacker may be off screen while what it aims at isn't
+            if world
+                .view
+                .is_some_and(|v| !v.overlaps(&telegraph.shape.bounds()))
+            {
+                continue;
+            }
+
+            let progress = telegraph.progress();
+            let blink = (progress * progress * 40.0).sin() * 0.15 + 0.85;
+            let color = world.palette.remap(Color::RED);
+            match telegraph.shape {
+                Shape::Line { from, to, width } => {
+                    d.draw_line(from, to, 1.0, color.fade(0.5));
+                    d.draw_line(from, to, width * progress, color.fade(0.5 * blink));
+                }
+                Shape::Circle { center, radius } => {
+                    d.draw_circle_outline(center, radius, color.fade(0.5));
+                    d.draw_circle(center, radius * progress, color.fade(0.3 * blink));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use raylib::prelude::*;
+
+use crate::font::{self, TextStyle};
+use crate::render;
+use crate::renderer::Renderer;
+use crate::{Anchor, DrawSystem, World};
+
+/// Label drawn on a point of the entity's bounds, for names, damage numbers
+/// and world-space prompts. Centered on it unless the style aligns it
+/// otherwise.
+pub struct Text2D {
+    pub text: String,
+    pub style: TextStyle,
+    pub anchor: Anchor,
+    /// Nudge away from the anchor point, in world units.
+    pub offset: Vector2,
+}
+
+impl Text2D {
+    pub fn new(text: impl Into<String>, font_size: i32, color: Color) -> Text2D {
+        Text2D {
+            text: text.into(),
+            style: TextStyle::new(font_size as f32, color).aligned(Anchor::Center),
+            anchor: Anchor::Center,
+            offset: Vector2::zero(),
+        }
+    }
+
+    pub fn anchored(mut self, anchor: Anchor, offset: Vector2) -> Text2D {
+        self.anchor = anchor;
+        self.offset = offset;
+        self
+    }
+
+    pub fn styled(mut self, style: TextStyle) -> Text2D {
+        self.style = style;
+        self
+    }
+}
+
+impl DrawSystem for Text2D {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        for (id, text) in world.text_components.iter() {
+            if !render::in_view(world, *id) {
+                continue;
+            }
+            let bounds = match world.base_components.iter().find(|b| b.0 == *id) {
+                Some(b) => b.1.bounds,
+                None => continue,
+            };
+
+            let pos = bounds.calc(text.anchor) + text.offset;
+            font::draw(&world.assets, d, &text.text, pos, &text.style);
+        }
+    }
+}
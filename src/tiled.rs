@@ -0,0 +1,305 @@
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::json::Json;
+use crate::pack;
+use crate::prefab::{Placement, Prefabs};
+use crate::tilemap::Tilemap;
+use crate::xml::Element;
+use crate::{BoundingBox2D, EntityID, World};
+
+/// Gid bits Tiled uses to flip and rotate tiles. Tilemaps can't do either,
+/// so they're masked off.
+const FLIP_FLAGS: u32 = 0xf000_0000;
+
+/// Object from an object layer. `kind` is its type (class since Tiled 1.9)
+/// and picks the prefab, objects of unknown kinds are only kept as data.
+pub struct TiledObject {
+    pub kind: String,
+    pub placement: Placement,
+    /// Polyline and polygon points in world coordinates, empty for other
+    /// shapes.
+    pub points: Vec<Vector2>,
+}
+
+struct Tileset {
+    first_gid: u32,
+    image: String,
+    /// Local ids of tiles with a `solid` property set to true.
+    solid: Vec<u32>,
+}
+
+/// Orthogonal map saved by the Tiled editor as .tmx, one Tiled pixel per
+/// world unit. Tile layers need the CSV layer format and all tiles have to
+/// come from the first tileset, embedded or external (.tsx or .tsj).
+///
+/// A tile is solid when its tileset gives it a `solid` property, or when
+/// its layer has a `collision` property, both booleans.
+pub struct TiledMap {
+    pub tile_size: f32,
+    /// Tileset image path, relative to the working directory.
+    pub tileset: Option<String>,
+    /// Tile layers in draw order, by name.
+    pub layers: Vec<(String, Tilemap)>,
+    pub objects: Vec<TiledObject>,
+}
+
+impl TiledMap {
+    pub fn load(path: &str) -> Result<TiledMap, String> {
+        let contents =
+            pack::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        TiledMap::parse(&contents, dir).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Parses a .tmx document, external tilesets and images are resolved
+    /// against `dir`.
+    pub fn parse(contents: &str, dir: &Path) -> Result<TiledMap, String> {
+        let map = Element::parse(contents)?;
+        if map.name != "map" {
+            return Err("not a Tiled map".to_string());
+        }
+        if map.attr("orientation").is_some_and(|o| o != "orthogonal") {
+            return Err("only orthogonal maps are supported".to_string());
+        }
+        if map.attr("infinite") == Some("1") {
+            return Err("infinite maps are not supported".to_string());
+        }
+        let tile_size = map.attr_f32("tilewidth").ok_or("no tilewidth")?;
+
+        let mut tilesets = Vec::new();
+        for element in map.children_named("tileset") {
+            tilesets.push(parse_tileset(element, dir)?);
+        }
+        let first = tilesets.first();
+
+        let mut layers = Vec::new();
+        for layer in map.children_named("layer") {
+            let name = layer.attr("name").unwrap_or("").to_string();
+            let columns = layer.attr_u32("width").ok_or("layer width")? as usize;
+            let rows = layer.attr_u32("height").ok_or("layer height")? as usize;
+            let data = layer
+                .child("data")
+                .ok_or(format!("layer {}: no data", name))?;
+            if data.attr("encoding") != Some("csv") {
+                return Err(format!("layer {}: save with the CSV layer format", name));
+            }
+            let collision = properties(layer)
+                .iter()
+                .any(|p| p.0 == "collision" && p.1 == "true");
+
+            let mut tilemap = Tilemap::new(columns, rows, tile_size);
+            for (i, gid) in data.text.split(',').enumerate() {
+                let gid = gid
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| format!("layer {}: bad tile {}", name, gid.trim()))?
+                    & !FLIP_FLAGS;
+                // 0 is an empty cell, later tilesets aren't supported
+                let tileset = match first {
+                    Some(t) if gid >= t.first_gid && owner(&tilesets, gid) == 0 => t,
+                    _ => continue,
+                };
+                let local = gid - tileset.first_gid;
+                let solid = collision || tileset.solid.contains(&local);
+                tilemap.set(i % columns, i / columns, Some(local as u16), solid);
+            }
+            layers.push((name, tilemap));
+        }
+
+        let mut objects = Vec::new();
+        for group in map.children_named("objectgroup") {
+            for object in group.children_named("object") {
+                objects.push(parse_object(object)?);
+            }
+        }
+
+        Ok(TiledMap {
+            tile_size,
+            tileset: first.map(|t| t.image.clone()),
+            layers,
+            objects,
+        })
+    }
+
+    /// Adds the map to the world: an entity per tile layer, drawn behind
+    /// everything else in Background, and a prefab per object whose kind
+    /// is registered. Returns the spawned entities.
+    pub fn spawn(
+        &self,
+        world: &mut World,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) -> Vec<EntityID> {
+        let tileset = self
+            .tileset
+            .as_ref()
+            .and_then(|path| world.assets.load_texture(rl, thread, path).ok());
+
+        let mut spawned = Vec::new();
+        for (i, (name, layer)) in self.layers.iter().enumerate() {
+            let mut tilemap = layer.clone();
+            tilemap.tileset = tileset;
+            let z = i as i32 - self.layers.len() as i32 - 10;
+            spawned.push(tilemap.spawn(world, name, Vector2::zero(), z));
+        }
+
+        for object in self.objects.iter() {
+            if let Some(id) = Prefabs::spawn(world, &object.kind, &object.placement) {
+                spawned.push(id);
+            }
+        }
+        spawned
+    }
+
+    /// Center of the first object of `kind`, for spawn points.
+    pub fn point(&self, kind: &str) -> Option<Vector2> {
+        self.objects
+            .iter()
+            .find(|o| o.kind == kind)
+            .map(|o| o.placement.bounds.center())
+    }
+
+    /// Points of the polyline or polygon called `name`, for patrol paths.
+    pub fn path(&self, name: &str) -> Option<&[Vector2]> {
+        self.objects
+            .iter()
+            .find(|o| o.placement.name == name && !o.points.is_empty())
+            .map(|o| o.points.as_slice())
+    }
+}
+
+/// Index of the tileset `gid` belongs to: the last one starting at or
+/// before it.
+fn owner(tilesets: &[Tileset], gid: u32) -> usize {
+    tilesets
+        .iter()
+        .rposition(|t| t.first_gid <= gid)
+        .unwrap_or(0)
+}
+
+fn parse_tileset(element: &Element, dir: &Path) -> Result<Tileset, String> {
+    let first_gid = element.attr_u32("firstgid").ok_or("tileset firstgid")?;
+    let source = match element.attr("source") {
+        Some(s) => dir.join(s),
+        None => return tileset_from_xml(first_gid, element, dir),
+    };
+    let source_dir = source.parent().unwrap_or(Path::new(""));
+    let contents = pack::read_to_string(&source)
+        .map_err(|e| format!("could not read {}: {}", source.display(), e))?;
+    let tileset = match source.extension().and_then(|e| e.to_str()) {
+        Some("tsj") | Some("json") => tileset_from_json(first_gid, &contents, source_dir),
+        _ => Element::parse(&contents).and_then(|e| tileset_from_xml(first_gid, &e, source_dir)),
+    };
+    tileset.map_err(|e| format!("{}: {}", source.display(), e))
+}
+
+fn tileset_from_xml(first_gid: u32, element: &Element, dir: &Path) -> Result<Tileset, String> {
+    let image = element
+        .child("image")
+        .and_then(|i| i.attr("source"))
+        .ok_or("tileset without a single image")?;
+    let solid = element
+        .children_named("tile")
+        .filter(|t| {
+            properties(t)
+                .iter()
+                .any(|p| p.0 == "solid" && p.1 == "true")
+        })
+        .filter_map(|t| t.attr_u32("id"))
+        .collect();
+    Ok(Tileset {
+        first_gid,
+        image: dir.join(image).to_string_lossy().into_owned(),
+        solid,
+    })
+}
+
+fn tileset_from_json(first_gid: u32, contents: &str, dir: &Path) -> Result<Tileset, String> {
+    let json = Json::parse(contents)?;
+    let image = json
+        .get("image")
+        .and_then(Json::as_str)
+        .ok_or("tileset without a single image")?;
+    let solid = json
+        .get("tiles")
+        .and_then(Json::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|tile| {
+            tile.get("properties")
+                .and_then(Json::as_array)
+                .into_iter()
+                .flatten()
+                .any(|p| {
+                    p.get("name").and_then(Json::as_str) == Some("solid")
+                        && p.get("value").and_then(Json::as_bool) == Some(true)
+                })
+        })
+        .filter_map(|tile| tile.get("id").and_then(Json::as_i64))
+        .map(|id| id as u32)
+        .collect();
+    Ok(Tileset {
+        first_gid,
+        image: dir.join(image).to_string_lossy().into_owned(),
+        solid,
+    })
+}
+
+fn parse_object(object: &Element) -> Result<TiledObject, String> {
+    let pos = Vector2::new(
+        object.attr_f32("x").ok_or("object x")?,
+        object.attr_f32("y").ok_or("object y")?,
+    );
+    let size = Vector2::new(
+        object.attr_f32("width").unwrap_or(0.0),
+        object.attr_f32("height").unwrap_or(0.0),
+    );
+    let kind = object
+        .attr("type")
+        .or(object.attr("class"))
+        .unwrap_or("")
+        .to_string();
+
+    // polyline points are relative to the object's position
+    let points = object
+        .child("polyline")
+        .or(object.child("polygon"))
+        .and_then(|p| p.attr("points"))
+        .map(|points| {
+            points
+                .split_whitespace()
+                .filter_map(|p| {
+                    let (x, y) = p.split_once(',')?;
+                    Some(pos + Vector2::new(x.parse().ok()?, y.parse().ok()?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(TiledObject {
+        kind,
+        placement: Placement {
+            name: object.attr("name").unwrap_or("").to_string(),
+            bounds: BoundingBox2D::new_v(pos, size),
+            properties: properties(object),
+        },
+        points,
+    })
+}
+
+/// Custom properties of a map, layer, tile or object, values as text.
+fn properties(element: &Element) -> Vec<(String, String)> {
+    element
+        .child("properties")
+        .into_iter()
+        .flat_map(|p| p.children_named("property"))
+        .filter_map(|p| {
+            let name = p.attr("name")?;
+            // multiline string values are stored as the element's text
+            let value = p.attr("value").unwrap_or(&p.text);
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
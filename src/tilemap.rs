@@ -0,0 +1,289 @@
+use raylib::prelude::*;
+
+use crate::assets::Handle;
+use crate::particles::EmitterConfig;
+use crate::render::{self, Layer, RenderLayer};
+use crate::renderer::Renderer;
+use crate::{Base2D, BoundingBox2D, DrawSystem, EntityID, UpdateSystem, World};
+
+/// Tiles per chunk side. Chunks outside the view are skipped whole, so big
+/// maps only pay for what's on screen.
+const CHUNK_SIZE: usize = 16;
+
+/// Grid of tiles drawn from a tileset, for station interiors and other
+/// built structures. The entity's Base2D places the map: its top left is
+/// the corner of tile (0, 0) and it should span the whole grid.
+#[derive(Clone)]
+pub struct Tilemap {
+    /// Tiles are cut from this texture left to right, top to bottom. Without
+    /// one the tiles are drawn as flat placeholder squares.
+    pub tileset: Option<Handle<Texture2D>>,
+    /// Side of a tile, both in the tileset and in the world.
+    pub tile_size: f32,
+    pub columns: usize,
+    pub rows: usize,
+    /// Tileset index per cell, None for empty space.
+    tiles: Vec<Option<u16>>,
+    /// Cells nothing can move through.
+    solid: Vec<bool>,
+}
+
+impl Tilemap {
+    pub fn new(columns: usize, rows: usize, tile_size: f32) -> Tilemap {
+        Tilemap {
+            tileset: None,
+            tile_size,
+            columns,
+            rows,
+            tiles: vec![None; columns * rows],
+            solid: vec![false; columns * rows],
+        }
+    }
+
+    /// Map from a text layout, one string per row: `#` is a solid wall
+    /// (tile 0), `.` is floor (tile 1) and anything else is empty space.
+    pub fn from_rows(rows: &[&str], tile_size: f32) -> Tilemap {
+        let columns = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+        let mut map = Tilemap::new(columns, rows.len(), tile_size);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '#' => map.set(x, y, Some(0), true),
+                    '.' => map.set(x, y, Some(1), false),
+                    _ => (),
+                }
+            }
+        }
+        map
+    }
+
+    /// Adds the map to the world as an entity called `name`, its top left
+    /// at `origin` and drawn in Background at `z`.
+    pub fn spawn(self, world: &mut World, name: &str, origin: Vector2, z: i32) -> EntityID {
+        let id = world.new_entity();
+        let mut base2d = Base2D::new(origin, self.size());
+        base2d.name = name.to_string();
+        base2d.tint = Color::BLANK;
+        world.base_components.push((id, base2d));
+        world.tilemap_components.push((id, self));
+        world
+            .layer_components
+            .push((id, Layer::new(RenderLayer::Background, z)));
+        id
+    }
+
+    pub fn with_tileset(mut self, tileset: Handle<Texture2D>) -> Tilemap {
+        self.tileset = Some(tileset);
+        self
+    }
+
+    /// Size of the whole grid in world units.
+    pub fn size(&self) -> Vector2 {
+        Vector2::new(
+            self.columns as f32 * self.tile_size,
+            self.rows as f32 * self.tile_size,
+        )
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.columns && y < self.rows {
+            Some(y * self.columns + x)
+        } else {
+            None
+        }
+    }
+
+    /// Changes a cell, ignored outside the grid.
+    pub fn set(&mut self, x: usize, y: usize, tile: Option<u16>, solid: bool) {
+        if let Some(i) = self.index(x, y) {
+            self.tiles[i] = tile;
+            self.solid[i] = solid;
+        }
+    }
+
+    pub fn tile(&self, x: usize, y: usize) -> Option<u16> {
+        self.index(x, y).and_then(|i| self.tiles[i])
+    }
+
+    /// Outside the grid is open space, so maps don't need a border wall.
+    pub fn is_solid(&self, x: usize, y: usize) -> bool {
+        self.index(x, y).is_some_and(|i| self.solid[i])
+    }
+
+    /// Cells touching `area`, clamped to the grid, as column and row ranges.
+    /// Only these tiles need a closer look against `area`.
+    fn cells(
+        &self,
+        origin: Vector2,
+        area: &BoundingBox2D,
+    ) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let cell = |v: f32, o: f32, max: usize| {
+            (((v - o) / self.tile_size).floor().max(0.0) as usize).min(max)
+        };
+        let x =
+            cell(area.x1, origin.x, self.columns)..cell(area.x2, origin.x, self.columns - 1) + 1;
+        let y = cell(area.y1, origin.y, self.rows)..cell(area.y2, origin.y, self.rows - 1) + 1;
+        (x, y)
+    }
+
+    fn cell_bounds(&self, origin: Vector2, x: usize, y: usize) -> BoundingBox2D {
+        BoundingBox2D::new(
+            origin.x + x as f32 * self.tile_size,
+            origin.y + y as f32 * self.tile_size,
+            self.tile_size,
+            self.tile_size,
+        )
+    }
+
+    /// World bounds of the solid cells overlapping `bounds`, with the map's
+    /// top left at `origin`.
+    pub fn solid_overlaps(&self, origin: Vector2, bounds: &BoundingBox2D) -> Vec<BoundingBox2D> {
+        if self.columns == 0 || self.rows == 0 {
+            return Vec::new();
+        }
+        let (xs, ys) = self.cells(origin, bounds);
+        let mut hits = Vec::new();
+        for y in ys {
+            for x in xs.clone() {
+                let cell = self.cell_bounds(origin, x, y);
+                if self.is_solid(x, y) && cell.overlaps(bounds) {
+                    hits.push(cell);
+                }
+            }
+        }
+        hits
+    }
+
+    fn draw(&self, world: &World, d: &mut dyn Renderer, origin: Vector2) {
+        let tileset = self.tileset.and_then(|t| world.assets.texture(t));
+        let tileset_columns = tileset
+            .map(|t| (t.width() as f32 / self.tile_size) as u16)
+            .unwrap_or(1)
+            .max(1);
+
+        for chunk_y in (0..self.rows).step_by(CHUNK_SIZE) {
+            for chunk_x in (0..self.columns).step_by(CHUNK_SIZE) {
+                let chunk = BoundingBox2D::new(
+                    origin.x + chunk_x as f32 * self.tile_size,
+                    origin.y + chunk_y as f32 * self.tile_size,
+                    CHUNK_SIZE as f32 * self.tile_size,
+                    CHUNK_SIZE as f32 * self.tile_size,
+                );
+                if world.view.is_some_and(|v| !v.overlaps(&chunk)) {
+                    continue;
+                }
+
+                for y in chunk_y..(chunk_y + CHUNK_SIZE).min(self.rows) {
+                    for x in chunk_x..(chunk_x + CHUNK_SIZE).min(self.columns) {
+                        let tile = match self.tile(x, y) {
+                            Some(t) => t,
+                            None => continue,
+                        };
+                        let dest: Rectangle = self.cell_bounds(origin, x, y).into();
+                        match tileset {
+                            Some(texture) => {
+                                let source = Rectangle::new(
+                                    (tile % tileset_columns) as f32 * self.tile_size,
+                                    (tile / tileset_columns) as f32 * self.tile_size,
+                                    self.tile_size,
+                                    self.tile_size,
+                                );
+                                d.draw_sprite(
+                                    texture,
+                                    source,
+                                    dest,
+                                    Vector2::zero(),
+                                    0.0,
+                                    Color::WHITE,
+                                );
+                            }
+                            None if self.is_solid(x, y) => {
+                                d.draw_rect(dest, Color::DARKGRAY);
+                            }
+                            None => d.draw_rect(dest, Color::new(40, 40, 48, 255)),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl UpdateSystem for Tilemap {
+    /// Pushes moving entities back out of solid tiles along the shallowest
+    /// axis and stops them on it. Projectiles break on walls instead.
+    fn update_system(world: &mut World, _dt: f32) {
+        let maps: Vec<(Vector2, &Tilemap)> = world
+            .tilemap_components
+            .iter()
+            .filter_map(|(id, map)| {
+                let b = world.base_components.iter().find(|b| b.0 == *id)?;
+                Some((Vector2::new(b.1.bounds.x1, b.1.bounds.y1), map))
+            })
+            .collect();
+        if maps.is_empty() {
+            return;
+        }
+
+        let mut spent: Vec<EntityID> = Vec::new();
+        for (id, vel) in world.velocity_components.iter_mut() {
+            let base = match world.base_components.iter_mut().find(|b| b.0 == *id) {
+                Some(b) => &mut b.1,
+                None => continue,
+            };
+            let projectile = world.projectile_components.iter().any(|p| p.0 == *id);
+
+            for (origin, map) in maps.iter() {
+                for wall in map.solid_overlaps(*origin, &base.bounds) {
+                    if projectile {
+                        world
+                            .particles
+                            .burst(&EmitterConfig::impact(), base.bounds.center());
+                        spent.push(*id);
+                        break;
+                    }
+                    // earlier pushes may already have cleared this tile
+                    let b = &base.bounds;
+                    if !wall.overlaps(b) {
+                        continue;
+                    }
+                    let push_x = if b.center().x < wall.center().x {
+                        wall.x1 - b.x2
+                    } else {
+                        wall.x2 - b.x1
+                    };
+                    let push_y = if b.center().y < wall.center().y {
+                        wall.y1 - b.y2
+                    } else {
+                        wall.y2 - b.y1
+                    };
+                    if push_x.abs() < push_y.abs() {
+                        base.bounds.translate(Vector2::new(push_x, 0.0));
+                        vel.velocity.x = 0.0;
+                    } else {
+                        base.bounds.translate(Vector2::new(0.0, push_y));
+                        vel.velocity.y = 0.0;
+                    }
+                }
+            }
+        }
+
+        spent.dedup();
+        for id in spent {
+            world.despawn(id);
+        }
+    }
+}
+
+impl DrawSystem for Tilemap {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        for (id, map) in world.tilemap_components.iter() {
+            if !render::in_view(world, *id) {
+                continue;
+            }
+            if let Some(b) = world.base_components.iter().find(|b| b.0 == *id) {
+                map.draw(world, d, Vector2::new(b.1.bounds.x1, b.1.bounds.y1));
+            }
+        }
+    }
+}
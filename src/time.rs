@@ -0,0 +1,189 @@
+use raylib::prelude::*;
+
+use crate::renderer::Renderer;
+use crate::{info, World};
+
+/// Speeds cycled through by the debug hotkey.
+const DEBUG_SCALES: [f32; 3] = [1.0, 0.25, 2.0];
+/// Timestep of a single step in frame-step mode.
+const STEP: f32 = 1.0 / 60.0;
+
+/// Frame timing, advanced once per frame at the top of the main loop.
+/// Update systems get `delta` as their timestep: real time scaled by the
+/// debug speed, slow motion and hitstop, and zero while the world is
+/// frozen. Menus and overlays use `unscaled_delta`.
+pub struct Time {
+    /// Multiplies the timestep, for debugging in slow or fast motion. F5
+    /// cycles it.
+    pub time_scale: f32,
+    /// Frame-step mode: the world holds still until a step is asked for.
+    stepping: bool,
+    step_requested: bool,
+    /// Set on the frames frame-step mode holds the world still.
+    frozen: bool,
+    delta: f32,
+    unscaled_delta: f32,
+    elapsed: f32,
+    unscaled_elapsed: f32,
+    frame: u64,
+    slow_motion: f32,
+    /// Real seconds of slow motion left.
+    slow_motion_timer: f32,
+    slow_motion_duration: f32,
+}
+
+impl Time {
+    pub fn new() -> Time {
+        Time {
+            time_scale: 1.0,
+            stepping: false,
+            step_requested: false,
+            frozen: false,
+            delta: 0.0,
+            unscaled_delta: 0.0,
+            elapsed: 0.0,
+            unscaled_elapsed: 0.0,
+            frame: 0,
+            slow_motion: 1.0,
+            slow_motion_timer: 0.0,
+            slow_motion_duration: 0.0,
+        }
+    }
+
+    /// Game time passed this frame.
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// Real time passed this frame.
+    pub fn unscaled_delta(&self) -> f32 {
+        self.unscaled_delta
+    }
+
+    /// Game time since the game started.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Real time since the game started.
+    pub fn unscaled_elapsed(&self) -> f32 {
+        self.unscaled_elapsed
+    }
+
+    /// Frames since the game started.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Everything the timestep is multiplied by right now, except hitstop.
+    pub fn scale(&self) -> f32 {
+        self.time_scale * self.current_slow_motion()
+    }
+
+    /// Slows the game to `scale` for `duration` real seconds, easing back
+    /// to full speed towards the end. The slowest request wins.
+    pub fn slow_motion(&mut self, scale: f32, duration: f32) {
+        if scale <= self.current_slow_motion() {
+            self.slow_motion = scale;
+            self.slow_motion_timer = duration;
+            self.slow_motion_duration = duration;
+        }
+    }
+
+    fn current_slow_motion(&self) -> f32 {
+        if self.slow_motion_timer <= 0.0 {
+            return 1.0;
+        }
+        // full effect for the first half, then eases out
+        let t = (self.slow_motion_timer / (self.slow_motion_duration * 0.5)).min(1.0);
+        1.0 + (self.slow_motion - 1.0) * t
+    }
+
+    pub fn stepping(&self) -> bool {
+        self.stepping
+    }
+
+    /// Turns frame-step mode on or off, forgetting a step asked for.
+    pub fn set_stepping(&mut self, stepping: bool) {
+        self.stepping = stepping;
+        self.step_requested = false;
+    }
+
+    /// Asks for one step in frame-step mode, taken next frame.
+    pub fn step(&mut self) {
+        self.step_requested = self.stepping;
+    }
+
+    /// Whether frame-step mode holds the world still this frame. No update
+    /// system runs then, not even the ones that don't care about the
+    /// timestep, so nothing gets hit or triggered between steps.
+    pub fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// How far the world may move this frame, before hitstop and scaling.
+    /// In frame-step mode that's one fixed step when one was asked for and
+    /// None otherwise, so effects like hitstop wait as well.
+    pub fn simulation_dt(&mut self, dt: f32) -> Option<f32> {
+        let dt = if !self.stepping {
+            Some(dt)
+        } else if std::mem::take(&mut self.step_requested) {
+            Some(STEP)
+        } else {
+            None
+        };
+        self.frozen = dt.is_none();
+        dt
+    }
+
+    /// Starts a new frame that took `dt` real seconds. `game_dt` is what
+    /// the world gets before scaling: `dt` slowed by hitstop, or zero when
+    /// nothing simulates.
+    pub fn advance(&mut self, dt: f32, game_dt: f32) {
+        self.frame += 1;
+        self.unscaled_delta = dt;
+        self.unscaled_elapsed += dt;
+        self.delta = game_dt * self.scale();
+        self.elapsed += self.delta;
+        if game_dt > 0.0 {
+            self.slow_motion_timer = (self.slow_motion_timer - dt).max(0.0);
+        }
+    }
+
+    /// F5 cycles the simulation speed between normal, quarter and double.
+    /// F2 toggles frame-step mode, in which Period advances the world by
+    /// one fixed update.
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        let time = &mut world.time;
+        if rl.is_key_pressed(KeyboardKey::KEY_F2) {
+            time.set_stepping(!time.stepping);
+            info!("time", "frame stepping", enabled = time.stepping);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_PERIOD) {
+            time.step();
+        }
+        if !rl.is_key_pressed(KeyboardKey::KEY_F5) {
+            return;
+        }
+        let i = DEBUG_SCALES
+            .iter()
+            .position(|s| *s == time.time_scale)
+            .map_or(0, |i| (i + 1) % DEBUG_SCALES.len());
+        time.time_scale = DEBUG_SCALES[i];
+        info!("time", "time scale changed", scale = time.time_scale);
+    }
+
+    /// Shows the speed while it isn't normal, and the frame while stepping.
+    pub fn draw_system(world: &World, d: &mut dyn Renderer) {
+        let time = &world.time;
+        let text = if time.stepping {
+            format!("Frame step at {:.3}s (Period)", time.elapsed)
+        } else if time.time_scale != 1.0 {
+            format!("{}x speed", time.time_scale)
+        } else {
+            return;
+        };
+        let x = world.screen.width() as i32 / 2 - measure_text(&text, 10) / 2;
+        d.draw_text(&text, x, 4, 10, Color::YELLOW);
+    }
+}
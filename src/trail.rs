@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+
+use raylib::prelude::*;
+
+use crate::particles::lerp_color;
+use crate::render;
+use crate::renderer::Renderer;
+use crate::{DrawSystem, UpdateSystem, World};
+
+/// Moves longer than this in one frame are teleports, not motion, so the
+/// trail starts over instead of streaking across the map.
+const MAX_STEP: f32 = 200.0;
+
+/// Fading line strip behind the entity's center. Width and color run from
+/// the `start_` values at the entity to the `end_` values at the tail.
+pub struct Trail {
+    /// Points kept, more makes a longer trail.
+    pub length: usize,
+    /// Distance the entity has to move before a new point is recorded.
+    pub spacing: f32,
+    pub start_width: f32,
+    pub end_width: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+    /// Newest point first.
+    points: VecDeque<Vector2>,
+}
+
+impl Trail {
+    pub fn new(length: usize, width: f32, color: Color) -> Trail {
+        Trail {
+            length,
+            spacing: 4.0,
+            start_width: width,
+            end_width: 0.0,
+            start_color: color,
+            end_color: color.fade(0.0),
+            points: VecDeque::with_capacity(length + 1),
+        }
+    }
+
+    /// Starts over like `Trail::new` would, keeping the points' memory.
+    pub fn reset(&mut self, length: usize, width: f32, color: Color) {
+        self.length = length;
+        self.spacing = 4.0;
+        self.start_width = width;
+        self.end_width = 0.0;
+        self.start_color = color;
+        self.end_color = color.fade(0.0);
+        self.points.clear();
+    }
+}
+
+impl UpdateSystem for Trail {
+    fn update_system(world: &mut World, _dt: f32) {
+        for (id, trail) in world.trail_components.iter_mut() {
+            let pos = match world.base_components.iter().find(|b| b.0 == *id) {
+                Some(b) => b.1.bounds.center(),
+                None => continue,
+            };
+
+            match trail.points.front() {
+                Some(last) if last.distance_to(pos) > MAX_STEP => trail.points.clear(),
+                Some(last) if last.distance_to(pos) < trail.spacing => continue,
+                _ => (),
+            }
+            trail.points.push_front(pos);
+            trail.points.truncate(trail.length);
+        }
+    }
+}
+
+impl DrawSystem for Trail {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        for (id, trail) in world.trail_components.iter() {
+            if !render::in_view(world, *id) {
+                continue;
+            }
+            let segments = trail.points.len().saturating_sub(1);
+            for (i, (a, b)) in trail
+                .points
+                .iter()
+                .zip(trail.points.iter().skip(1))
+                .enumerate()
+            {
+                let t = i as f32 / segments as f32;
+                let width = trail.start_width + (trail.end_width - trail.start_width) * t;
+                let color = lerp_color(trail.start_color, trail.end_color, t);
+                let color = world.palette.remap(color);
+                d.draw_line(*a, *b, width.max(1.0), color);
+            }
+        }
+    }
+}
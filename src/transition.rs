@@ -0,0 +1,144 @@
+use raylib::prelude::*;
+
+use crate::renderer::Renderer;
+use crate::tween::Easing;
+use crate::{DrawSystem, UpdateSystem, World};
+
+/// Most blocky the pixelate transition gets, in canvas pixels per block.
+const MAX_PIXELATION: f32 = 16.0;
+
+/// Runs while the screen is fully covered, to swap states or levels.
+pub type Action = fn(&mut World);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionKind {
+    /// Fades to black and back.
+    Fade,
+    /// A black curtain sweeps in from the left and off to the right.
+    Wipe,
+    /// The picture breaks into ever bigger blocks while it darkens.
+    Pixelate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Idle,
+    /// Covering up the old screen.
+    Out,
+    /// Revealing the new one.
+    In,
+}
+
+/// Screen transition. Covers the screen, runs its action once nothing is
+/// visible, then uncovers it again. Whatever the action switched to should
+/// hold off until `busy` is false, so it never starts half hidden.
+pub struct Transition {
+    kind: TransitionKind,
+    /// Seconds for each half.
+    duration: f32,
+    timer: f32,
+    phase: Phase,
+    action: Option<Action>,
+}
+
+impl Transition {
+    pub fn new() -> Transition {
+        Transition {
+            kind: TransitionKind::Fade,
+            duration: 0.0,
+            timer: 0.0,
+            phase: Phase::Idle,
+            action: None,
+        }
+    }
+
+    /// Starts a transition taking `duration` seconds each way. Ignored while
+    /// another one is running, so mashing a button can't skip the action.
+    pub fn start(&mut self, kind: TransitionKind, duration: f32, action: Action) {
+        if self.busy() {
+            return;
+        }
+        self.kind = kind;
+        self.duration = duration;
+        self.timer = 0.0;
+        self.phase = Phase::Out;
+        self.action = Some(action);
+    }
+
+    pub fn busy(&self) -> bool {
+        self.phase != Phase::Idle
+    }
+
+    /// How much of the screen is hidden, 0 is none and 1 all of it.
+    pub fn cover(&self) -> f32 {
+        let t = if self.duration > 0.0 {
+            Easing::QuadInOut.apply(self.timer / self.duration)
+        } else {
+            1.0
+        };
+        match self.phase {
+            Phase::Idle => 0.0,
+            Phase::Out => t,
+            Phase::In => 1.0 - t,
+        }
+    }
+
+    /// Block size the canvas should be presented at, 1 is untouched.
+    pub fn pixelation(&self) -> u32 {
+        match self.kind {
+            TransitionKind::Pixelate if self.busy() => {
+                1 + (self.cover() * (MAX_PIXELATION - 1.0)) as u32
+            }
+            _ => 1,
+        }
+    }
+}
+
+impl UpdateSystem for Transition {
+    fn update_system(world: &mut World, dt: f32) {
+        let transition = &mut world.transition;
+        if !transition.busy() {
+            return;
+        }
+        transition.timer += dt;
+        if transition.timer < transition.duration {
+            return;
+        }
+
+        transition.timer = 0.0;
+        match transition.phase {
+            Phase::Out => {
+                transition.phase = Phase::In;
+                if let Some(action) = transition.action.take() {
+                    action(world);
+                }
+            }
+            _ => transition.phase = Phase::Idle,
+        }
+    }
+}
+
+impl DrawSystem for Transition {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        let transition = &world.transition;
+        if !transition.busy() {
+            return;
+        }
+        let (w, h) = (world.screen.width(), world.screen.height());
+        let cover = transition.cover();
+
+        match transition.kind {
+            TransitionKind::Fade | TransitionKind::Pixelate => {
+                d.draw_rect(Rectangle::new(0.0, 0.0, w, h), Color::BLACK.fade(cover));
+            }
+            TransitionKind::Wipe => {
+                // the curtain keeps moving right, so it leaves the way it came
+                let rect = match transition.phase {
+                    Phase::Out => Rectangle::new(0.0, 0.0, w * cover, h),
+                    _ => Rectangle::new(w * (1.0 - cover), 0.0, w * cover, h),
+                };
+                d.draw_rect(rect, Color::BLACK);
+            }
+        }
+    }
+}
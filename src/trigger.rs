@@ -0,0 +1,66 @@
+use crate::{EntityID, UpdateSystem, World};
+
+/// Turns the entity's Base2D bounds into a volume that reports entities
+/// entering and leaving it.
+pub struct Trigger {
+    inside: Vec<EntityID>,
+}
+
+impl Trigger {
+    pub fn new() -> Trigger {
+        Trigger { inside: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerEventKind {
+    Enter,
+    Exit,
+}
+
+pub struct TriggerEvent {
+    pub trigger: EntityID,
+    pub other: EntityID,
+    pub kind: TriggerEventKind,
+}
+
+impl UpdateSystem for Trigger {
+    fn update_system(world: &mut World, _dt: f32) {
+        for (id, trigger) in world.trigger_components.iter_mut() {
+            let bounds = match world.base_components.iter().find(|b| b.0 == *id) {
+                Some(b) => b.1.bounds,
+                None => continue,
+            };
+
+            let overlapping: Vec<EntityID> = world
+                .grid
+                .query(&bounds)
+                .into_iter()
+                .filter(|other| {
+                    *other != *id
+                        && world
+                            .base_components
+                            .iter()
+                            .any(|b| b.0 == *other && b.1.bounds.overlaps(&bounds))
+                })
+                .collect();
+
+            for other in overlapping.iter().filter(|o| !trigger.inside.contains(o)) {
+                world.trigger_events.push(TriggerEvent {
+                    trigger: *id,
+                    other: *other,
+                    kind: TriggerEventKind::Enter,
+                });
+            }
+            for other in trigger.inside.iter().filter(|o| !overlapping.contains(o)) {
+                world.trigger_events.push(TriggerEvent {
+                    trigger: *id,
+                    other: *other,
+                    kind: TriggerEventKind::Exit,
+                });
+            }
+
+            trigger.inside = overlapping;
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::fs;
+use std::path::PathBuf;
+
+use raylib::prelude::*;
+
+use crate::font::TextStyle;
+use crate::render::{Layer, RenderLayer};
+use crate::t;
+use crate::text::Text2D;
+use crate::warn;
+use crate::{Anchor, Base2D, EntityID, World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lesson {
+    Move,
+    Fire,
+    Dock,
+    StationMenu,
+}
+
+impl Lesson {
+    fn values() -> Vec<Lesson> {
+        vec![
+            Lesson::Move,
+            Lesson::Fire,
+            Lesson::Dock,
+            Lesson::StationMenu,
+        ]
+    }
+
+    fn id(&self) -> &'static str {
+        match self {
+            Lesson::Move => "move",
+            Lesson::Fire => "fire",
+            Lesson::Dock => "dock",
+            Lesson::StationMenu => "station_menu",
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Lesson::Move => t!("tutorial.move"),
+            Lesson::Fire => t!("tutorial.fire"),
+            Lesson::Dock => t!("tutorial.dock"),
+            Lesson::StationMenu => t!("tutorial.station"),
+        }
+    }
+
+    /// Whether the mechanic is usable right now.
+    fn available(&self, world: &World, player: EntityID) -> bool {
+        match self {
+            Lesson::Move | Lesson::Fire => true,
+            Lesson::Dock => world.trigger_events.iter().any(|e| {
+                e.other == player && world.station_components.iter().any(|s| s.0 == e.trigger)
+            }),
+            Lesson::StationMenu => Lesson::docked(world, player),
+        }
+    }
+
+    /// Whether the player just used the mechanic.
+    fn used(&self, world: &World, player: EntityID) -> bool {
+        match self {
+            Lesson::Move => world
+                .player_components
+                .iter()
+                .any(|p| p.0 == player && p.1.steering.length() > 0.0),
+            Lesson::Fire => world.shot_events.iter().any(|e| e.weapon == player),
+            Lesson::Dock => Lesson::docked(world, player),
+            Lesson::StationMenu => !Lesson::docked(world, player),
+        }
+    }
+
+    fn docked(world: &World, player: EntityID) -> bool {
+        world
+            .player_components
+            .iter()
+            .any(|p| p.0 == player && p.1.docked_at.is_some())
+    }
+}
+
+/// On-screen hint that follows the player until the lesson has been used.
+/// Drawn through its Text2D, with bounds kept on top of the player's.
+pub struct TutorialPrompt {
+    lesson: Lesson,
+    player: EntityID,
+}
+
+/// Decides which prompts to show and remembers the ones already learned in a
+/// text file with one lesson id per line.
+pub struct Tutorial {
+    path: PathBuf,
+    learned: Vec<Lesson>,
+}
+
+impl Tutorial {
+    pub fn load(path: impl Into<PathBuf>) -> Tutorial {
+        let path = path.into();
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+        let learned = Lesson::values()
+            .into_iter()
+            .filter(|l| contents.lines().any(|line| line.trim() == l.id()))
+            .collect();
+        Tutorial { path, learned }
+    }
+
+    fn save(&self) {
+        let contents: String = self
+            .learned
+            .iter()
+            .map(|l| format!("{}\n", l.id()))
+            .collect();
+        if let Err(e) = fs::write(&self.path, contents) {
+            warn!(
+                "tutorial",
+                "could not save tutorial progress",
+                path = self.path.display(),
+                error = e
+            );
+        }
+    }
+
+    pub fn update_system(&mut self, world: &mut World) {
+        for (id, prompt) in world.tutorial_components.iter() {
+            let bounds = match world.base_components.iter().find(|b| b.0 == prompt.player) {
+                Some(b) => b.1.bounds,
+                None => continue,
+            };
+            if let Some(b) = world.base_components.iter_mut().find(|b| b.0 == *id) {
+                b.1.bounds = bounds;
+            }
+        }
+
+        let mut finished = Vec::new();
+        for (id, prompt) in world.tutorial_components.iter() {
+            if prompt.lesson.used(world, prompt.player) {
+                finished.push((*id, prompt.lesson));
+            }
+        }
+        for (id, lesson) in finished {
+            world.despawn(id);
+            if !self.learned.contains(&lesson) {
+                self.learned.push(lesson);
+                self.save();
+            }
+        }
+
+        // one prompt at a time keeps the screen readable
+        if !world.tutorial_components.is_empty() {
+            return;
+        }
+        let player = match world.player_components.first() {
+            Some(p) => p.0,
+            None => return,
+        };
+        let next = Lesson::values()
+            .into_iter()
+            .find(|l| !self.learned.contains(l) && l.available(world, player));
+        if let Some(lesson) = next {
+            let bounds = match world.base_components.iter().find(|b| b.0 == player) {
+                Some(b) => b.1.bounds,
+                None => return,
+            };
+            let prompt = world.new_entity();
+            let mut base2d = Base2D::new(Vector2::zero(), Vector2::zero());
+            base2d.name = "Tutorial prompt".to_string();
+            base2d.bounds = bounds;
+            base2d.tint = Color::BLANK;
+            world.base_components.push((prompt, base2d));
+            world.text_components.push((
+                prompt,
+                Text2D::new(lesson.text(), 12, Color::WHITE)
+                    .anchored(Anchor::BottomCenter, Vector2::new(0.0, 14.0))
+                    .styled(
+                        TextStyle::new(12.0, Color::WHITE)
+                            .aligned(Anchor::Center)
+                            .shadowed(Vector2::new(1.0, 1.0), Color::BLACK),
+                    ),
+            ));
+            world
+                .layer_components
+                .push((prompt, Layer::new(RenderLayer::Ui, 0)));
+            world
+                .tutorial_components
+                .push((prompt, TutorialPrompt { lesson, player }));
+        }
+    }
+}
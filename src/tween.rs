@@ -0,0 +1,211 @@
+use std::f32::consts::PI;
+
+use crate::{EntityID, UpdateSystem, World};
+
+/// Writes the tweened value into whatever field the tween drives.
+pub type Setter = fn(&mut World, EntityID, f32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicOut,
+    SineInOut,
+    /// Overshoots the target a little before settling, good for slide-ins.
+    BackOut,
+}
+
+impl Easing {
+    /// Maps linear progress in [0, 1] onto the curve.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) * 0.5
+                }
+            }
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::SineInOut => -((PI * t).cos() - 1.0) * 0.5,
+            Easing::BackOut => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    /// Stops at `to` and removes itself.
+    Once,
+    /// Jumps back to `from` and starts over.
+    Loop,
+    /// Goes back and forth between `from` and `to`.
+    PingPong,
+}
+
+/// Animates one value of its entity from `from` to `to`. An entity can have
+/// several tweens running at once.
+pub struct Tween {
+    pub from: f32,
+    pub to: f32,
+    pub duration: f32,
+    pub easing: Easing,
+    pub repeat: Repeat,
+    /// Runs on real time, past slow motion and hitstop, see `unscaled`.
+    pub unscaled: bool,
+    setter: Setter,
+    elapsed: f32,
+}
+
+impl Tween {
+    pub fn new(setter: Setter, from: f32, to: f32, duration: f32, easing: Easing) -> Tween {
+        Tween {
+            from,
+            to,
+            duration,
+            easing,
+            repeat: Repeat::Once,
+            unscaled: false,
+            setter,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Tween {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Keeps the tween on real time while the game slows down, so a camera
+    /// effect takes as long during a boss's slow motion death as any other
+    /// time. It still stops when the game does.
+    pub fn unscaled(mut self) -> Tween {
+        self.unscaled = true;
+        self
+    }
+
+    pub fn finished(&self) -> bool {
+        self.repeat == Repeat::Once && self.elapsed >= self.duration
+    }
+
+    fn value(&self) -> f32 {
+        let mut t = if self.duration > 0.0 {
+            self.elapsed / self.duration
+        } else {
+            1.0
+        };
+        match self.repeat {
+            Repeat::Once => t = t.min(1.0),
+            Repeat::Loop => t = t.fract(),
+            Repeat::PingPong => {
+                t %= 2.0;
+                if t > 1.0 {
+                    t = 2.0 - t;
+                }
+            }
+        }
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+}
+
+impl UpdateSystem for Tween {
+    fn update_system(world: &mut World, dt: f32) {
+        let unscaled_dt = if dt > 0.0 {
+            world.time.unscaled_delta()
+        } else {
+            0.0
+        };
+        let mut writes = Vec::new();
+        for (id, tween) in world.tween_components.iter_mut() {
+            tween.elapsed += if tween.unscaled { unscaled_dt } else { dt };
+            writes.push((*id, tween.setter, tween.value()));
+        }
+        world.tween_components.retain(|t| !t.1.finished());
+
+        for (id, setter, value) in writes {
+            setter(world, id, value);
+        }
+    }
+}
+
+/// Removes `id`'s tweens driven by `setter`, before starting one that
+/// would fight them.
+pub fn cancel(world: &mut World, id: EntityID, setter: Setter) {
+    world
+        .tween_components
+        .retain(|t| t.0 != id || !std::ptr::fn_addr_eq(t.1.setter, setter));
+}
+
+pub fn camera_zoom(world: &mut World, id: EntityID, value: f32) {
+    if let Some(c) = world.camera_components.iter_mut().find(|c| c.0 == id) {
+        c.1.zoom = value;
+    }
+}
+
+pub fn camera_punch(world: &mut World, id: EntityID, value: f32) {
+    if let Some(c) = world.camera_components.iter_mut().find(|c| c.0 == id) {
+        c.1.punch = value;
+    }
+}
+
+/// Moves the camera from where its pan started to the entity it pans to,
+/// 0 being the start. Arrived, it follows the entity from there.
+pub fn camera_pan(world: &mut World, id: EntityID, value: f32) {
+    let pan = match world.camera_components.iter().find(|c| c.0 == id) {
+        Some(c) => match c.1.pan {
+            Some(pan) => pan,
+            None => return,
+        },
+        None => return,
+    };
+    let to = world
+        .base_components
+        .iter()
+        .find(|b| b.0 == pan.to)
+        .map(|b| b.1.bounds.center());
+    let camera = match world.camera_components.iter_mut().find(|c| c.0 == id) {
+        Some(c) => &mut c.1,
+        None => return,
+    };
+    match to {
+        Some(to) => {
+            camera.target = pan.from.lerp(to, value);
+            if value >= 1.0 {
+                camera.pan = None;
+                camera.follow = Some(pan.to);
+            }
+        }
+        // gone before the camera got there, stay put
+        None => camera.pan = None,
+    }
+}
+
+pub fn ui_offset_x(world: &mut World, id: EntityID, value: f32) {
+    if let Some(u) = world.ui_components.iter_mut().find(|u| u.0 == id) {
+        u.1.offset.x = value;
+        u.1.relayout();
+    }
+}
+
+pub fn ui_offset_y(world: &mut World, id: EntityID, value: f32) {
+    if let Some(u) = world.ui_components.iter_mut().find(|u| u.0 == id) {
+        u.1.offset.y = value;
+        u.1.relayout();
+    }
+}
+
+pub fn tint_alpha(world: &mut World, id: EntityID, value: f32) {
+    if let Some(b) = world.base_components.iter_mut().find(|b| b.0 == id) {
+        b.1.tint = b.1.tint.fade(value);
+    }
+}
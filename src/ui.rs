@@ -0,0 +1,53 @@
+use raylib::prelude::*;
+
+use crate::{Anchor, BoundingBox2D, World};
+
+/// Screen-space element placed relative to a corner, edge or the center of
+/// the window. `bounds` is recomputed whenever the window size changes.
+pub struct UiElement {
+    pub anchor: Anchor,
+    /// Added after anchoring, in pixels.
+    pub offset: Vector2,
+    pub size: Vector2,
+    pub bounds: BoundingBox2D,
+    /// Screen size the bounds were last computed for.
+    screen: Vector2,
+}
+
+impl UiElement {
+    pub fn new(anchor: Anchor, offset: Vector2, size: Vector2) -> UiElement {
+        UiElement {
+            anchor,
+            offset,
+            size,
+            bounds: BoundingBox2D::new_v(offset, size),
+            screen: Vector2::zero(),
+        }
+    }
+
+    /// Lines the element's own anchor point up with the screen's, so a
+    /// TopRight element hugs the top right corner and a Center element is
+    /// centered.
+    pub fn layout(&mut self, screen: Vector2) {
+        let point = BoundingBox2D::new_v(Vector2::zero(), screen).calc(self.anchor);
+        let pivot = BoundingBox2D::new_v(Vector2::zero(), self.size).calc(self.anchor);
+        self.bounds = BoundingBox2D::new_v(point - pivot + self.offset, self.size);
+        self.screen = screen;
+    }
+
+    /// Lays the element out again for the same screen, after changing its
+    /// offset or size.
+    pub fn relayout(&mut self) {
+        self.layout(self.screen);
+    }
+
+    /// Lays out new elements and everything after a resize.
+    pub fn layout_system(world: &mut World) {
+        let screen = world.screen.canvas;
+        for (_, element) in world.ui_components.iter_mut() {
+            if element.screen != screen {
+                element.layout(screen);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use raylib::prelude::*;
+
+use crate::audio::SoundCue;
+use crate::projectile::Projectile;
+use crate::rng::Rng;
+use crate::station::Station;
+use crate::{EntityID, UpdateSystem, World};
+
+const MAX_LEVEL: u8 = 3;
+const BULLET_SPEED: f32 = 500.0;
+const BULLET_DAMAGE: u16 = 2;
+const CRIT_CHANCE: f32 = 0.1;
+const CRIT_MULTIPLIER: u16 = 3;
+
+pub struct Weapon {
+    /// Played on every shot.
+    pub sound: Option<SoundCue>,
+    fire_interval: f32,
+    cooldown: f32,
+    level: u8,
+    rng: Rng,
+}
+
+impl Weapon {
+    /// `seed` picks which shots crit, give each weapon its own.
+    pub fn new(shots_per_second: f32, seed: u64) -> Weapon {
+        Weapon {
+            sound: None,
+            fire_interval: 1.0 / shots_per_second,
+            cooldown: 0.0,
+            level: 0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Raises the fire rate by a quarter, returns false once maxed out.
+    pub fn upgrade(&mut self) -> bool {
+        if self.level >= MAX_LEVEL {
+            return false;
+        }
+        self.level += 1;
+        self.fire_interval /= 1.25;
+        true
+    }
+}
+
+/// Emitted for every shot that actually leaves the barrel.
+pub struct ShotEvent {
+    pub weapon: EntityID,
+}
+
+impl UpdateSystem for Weapon {
+    fn update_system(world: &mut World, dt: f32) {
+        let mut shots = Vec::new();
+        let docked = Station::player_docked(world);
+
+        for (id, weapon) in world.weapon_components.iter_mut() {
+            weapon.cooldown = (weapon.cooldown - dt).max(0.0);
+
+            let player = world.player_components.iter().find(|p| p.0 == *id);
+            let firing = player.is_some_and(|p| p.1.firing)
+                || world
+                    .pilot_components
+                    .iter()
+                    .any(|p| p.0 == *id && p.1.firing);
+            // nothing shoots at a docked player
+            if docked && player.is_none() {
+                continue;
+            }
+            // everyone holds fire while a cutscene has the controls
+            if !firing || weapon.cooldown > 0.0 || world.cutscene.locks_input() {
+                continue;
+            }
+
+            // a jammed weapon refuses to fire until it has vented
+            if let Some(heat) = world.heat_components.iter_mut().find(|h| h.0 == *id) {
+                if !heat.1.add_shot() {
+                    continue;
+                }
+            }
+
+            weapon.cooldown = weapon.fire_interval;
+            world.shot_events.push(ShotEvent { weapon: *id });
+            if let Some(mut event) = weapon.sound.as_mut().and_then(SoundCue::play) {
+                event.position = world
+                    .base_components
+                    .iter()
+                    .find(|b| b.0 == *id)
+                    .map(|b| b.1.bounds.center());
+                world.sound_events.push(event);
+            }
+            shots.push((*id, weapon.rng.chance(CRIT_CHANCE)));
+        }
+
+        for (id, crit) in shots {
+            let muzzle = match world.base_components.iter().find(|b| b.0 == id) {
+                Some(b) => b.1.bounds.center(),
+                None => continue,
+            };
+            // players shoot at the mouse, pilots at their target and
+            // anything else straight ahead
+            let aim = world
+                .player_components
+                .iter()
+                .find(|p| p.0 == id)
+                .map(|p| p.1.aim)
+                .or(world
+                    .pilot_components
+                    .iter()
+                    .find(|p| p.0 == id)
+                    .map(|p| p.1.aim));
+            let direction = aim
+                .map(|aim| aim - muzzle)
+                .filter(|d| d.length() > 0.0)
+                .unwrap_or(Vector2::new(0.0, -1.0))
+                .normalized();
+            let velocity = direction * BULLET_SPEED;
+            let damage = if crit {
+                BULLET_DAMAGE * CRIT_MULTIPLIER
+            } else {
+                BULLET_DAMAGE
+            };
+            Projectile::spawn(world, id, muzzle, velocity, damage, crit);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use raylib::prelude::*;
+
+use crate::font::{self, TextStyle};
+use crate::nine_slice::NineSlice;
+use crate::renderer::Renderer;
+use crate::ui::UiElement;
+use crate::{Anchor, BoundingBox2D, DrawSystem, EntityID, World};
+
+const FONT_SIZE: f32 = 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetState {
+    Idle,
+    Hovered,
+    /// The mouse went down on the widget and hasn't been released yet.
+    Pressed,
+}
+
+pub enum WidgetKind {
+    /// Backdrop for other widgets.
+    Panel,
+    Button {
+        label: String,
+    },
+    Slider {
+        label: String,
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+    Checkbox {
+        label: String,
+        checked: bool,
+    },
+}
+
+/// Interactive piece of UI, laid out by the entity's UiElement.
+pub struct Widget {
+    pub kind: WidgetKind,
+    pub state: WidgetState,
+    /// Frame texture for panels and buttons, plain rectangles without one.
+    pub skin: Option<NineSlice>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiEventKind {
+    Clicked,
+    /// New slider value.
+    Changed(f32),
+    /// New checkbox value.
+    Toggled(bool),
+}
+
+/// Emitted by widgets for menu systems to react to.
+pub struct UiEvent {
+    pub widget: EntityID,
+    pub kind: UiEventKind,
+}
+
+impl Widget {
+    fn new(kind: WidgetKind) -> Widget {
+        Widget {
+            kind,
+            state: WidgetState::Idle,
+            skin: None,
+        }
+    }
+
+    pub fn with_skin(mut self, skin: NineSlice) -> Widget {
+        self.skin = Some(skin);
+        self
+    }
+
+    pub fn panel() -> Widget {
+        Widget::new(WidgetKind::Panel)
+    }
+
+    pub fn button(label: impl Into<String>) -> Widget {
+        Widget::new(WidgetKind::Button {
+            label: label.into(),
+        })
+    }
+
+    pub fn slider(label: impl Into<String>, min: f32, max: f32, value: f32) -> Widget {
+        Widget::new(WidgetKind::Slider {
+            label: label.into(),
+            value: value.clamp(min, max),
+            min,
+            max,
+        })
+    }
+
+    pub fn checkbox(label: impl Into<String>, checked: bool) -> Widget {
+        Widget::new(WidgetKind::Checkbox {
+            label: label.into(),
+            checked,
+        })
+    }
+
+    /// Changes the text of a button, slider or checkbox.
+    pub fn set_label(&mut self, text: impl Into<String>) {
+        match &mut self.kind {
+            WidgetKind::Panel => (),
+            WidgetKind::Button { label }
+            | WidgetKind::Slider { label, .. }
+            | WidgetKind::Checkbox { label, .. } => *label = text.into(),
+        }
+    }
+
+    pub fn spawn(world: &mut World, widget: Widget, element: UiElement) -> EntityID {
+        let id = world.new_entity();
+        world.ui_components.push((id, element));
+        world.widget_components.push((id, widget));
+        id
+    }
+
+    pub fn input_system(world: &mut World, rl: &RaylibHandle) {
+        let mouse = world.screen.window_to_canvas(rl.get_mouse_position());
+        // a developer panel over the widgets gets the mouse instead
+        let captured = world.capture.mouse;
+        let pressed = !captured && rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
+        let down = !captured && rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON);
+
+        for (id, widget) in world.widget_components.iter_mut() {
+            let bounds = match world.ui_components.iter().find(|u| u.0 == *id) {
+                Some(u) => u.1.bounds,
+                None => continue,
+            };
+            let hovered = !captured && contains(&bounds, mouse);
+            let was_pressed = widget.state == WidgetState::Pressed;
+
+            // a press has to start on the widget, but keeps it captured
+            // until the button is released wherever the mouse is
+            widget.state = if (was_pressed || (hovered && pressed)) && down {
+                WidgetState::Pressed
+            } else if hovered {
+                WidgetState::Hovered
+            } else {
+                WidgetState::Idle
+            };
+            let released_on = was_pressed && !down && hovered;
+
+            let event = match &mut widget.kind {
+                WidgetKind::Panel => {
+                    widget.state = WidgetState::Idle;
+                    None
+                }
+                WidgetKind::Button { .. } if released_on => Some(UiEventKind::Clicked),
+                WidgetKind::Checkbox { checked, .. } if released_on => {
+                    *checked = !*checked;
+                    Some(UiEventKind::Toggled(*checked))
+                }
+                WidgetKind::Slider {
+                    value, min, max, ..
+                } if widget.state == WidgetState::Pressed => {
+                    let track = slider_track(&bounds);
+                    let t = ((mouse.x - track.x) / track.width).clamp(0.0, 1.0);
+                    let new_value = *min + (*max - *min) * t;
+                    if new_value != *value {
+                        *value = new_value;
+                        Some(UiEventKind::Changed(new_value))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+
+            if let Some(kind) = event {
+                world.ui_events.push(UiEvent { widget: *id, kind });
+            }
+        }
+    }
+}
+
+fn contains(bounds: &BoundingBox2D, point: Vector2) -> bool {
+    point.x >= bounds.x1 && point.x < bounds.x2 && point.y >= bounds.y1 && point.y < bounds.y2
+}
+
+/// Sliders put their label on the left and the track on the right.
+fn slider_track(bounds: &BoundingBox2D) -> Rectangle {
+    let label_width = bounds.width() * 0.4;
+    Rectangle::new(
+        bounds.x1 + label_width,
+        bounds.center().y - 2.0,
+        bounds.width() - label_width,
+        4.0,
+    )
+}
+
+fn state_color(state: WidgetState) -> Color {
+    match state {
+        WidgetState::Idle => Color::DARKGRAY,
+        WidgetState::Hovered => Color::GRAY,
+        WidgetState::Pressed => Color::LIGHTGRAY,
+    }
+}
+
+/// Skins keep their own colors, so states only shade them.
+fn skin_tint(state: WidgetState) -> Color {
+    match state {
+        WidgetState::Idle => Color::LIGHTGRAY,
+        WidgetState::Hovered => Color::WHITE,
+        WidgetState::Pressed => Color::GRAY,
+    }
+}
+
+impl DrawSystem for Widget {
+    fn draw_system(world: &mut World, d: &mut dyn Renderer) {
+        for (id, widget) in world.widget_components.iter() {
+            let bounds = match world.ui_components.iter().find(|u| u.0 == *id) {
+                Some(u) => u.1.bounds,
+                None => continue,
+            };
+            let label_style = TextStyle::new(FONT_SIZE, Color::WHITE).aligned(Anchor::CenterLeft);
+
+            match &widget.kind {
+                WidgetKind::Panel => match &widget.skin {
+                    Some(skin) => skin.draw(&world.assets, d, bounds, Color::WHITE),
+                    None => {
+                        d.draw_rect(bounds.into(), Color::BLACK.fade(0.85));
+                        d.draw_rect_lines(bounds.into(), 1, Color::WHITE);
+                    }
+                },
+                WidgetKind::Button { label } => {
+                    match &widget.skin {
+                        Some(skin) => skin.draw(&world.assets, d, bounds, skin_tint(widget.state)),
+                        None => {
+                            d.draw_rect(bounds.into(), state_color(widget.state));
+                            d.draw_rect_lines(bounds.into(), 1, Color::WHITE);
+                        }
+                    }
+                    let style = label_style.aligned(Anchor::Center);
+                    font::draw(&world.assets, d, label, bounds.center(), &style);
+                }
+                WidgetKind::Slider {
+                    label,
+                    value,
+                    min,
+                    max,
+                } => {
+                    let pos = bounds.calc(Anchor::CenterLeft);
+                    font::draw(&world.assets, d, label, pos, &label_style);
+                    let track = slider_track(&bounds);
+                    d.draw_rect(track, Color::DARKGRAY);
+                    let t = if max > min {
+                        (value - min) / (max - min)
+                    } else {
+                        0.0
+                    };
+                    let knob = Vector2::new(track.x + track.width * t, track.y + 2.0);
+                    d.draw_circle(knob, 7.0, state_color(widget.state));
+                    d.draw_circle_outline(knob, 7.0, Color::WHITE);
+                }
+                WidgetKind::Checkbox { label, checked } => {
+                    let size = bounds.height();
+                    let check = Rectangle::new(bounds.x1, bounds.y1, size, size);
+                    d.draw_rect(check, state_color(widget.state));
+                    d.draw_rect_lines(check, 1, Color::WHITE);
+                    if *checked {
+                        d.draw_rect(
+                            Rectangle::new(check.x + 4.0, check.y + 4.0, size - 8.0, size - 8.0),
+                            Color::WHITE,
+                        );
+                    }
+                    let pos = bounds.calc(Anchor::CenterLeft) + Vector2::new(size + 8.0, 0.0);
+                    font::draw(&world.assets, d, label, pos, &label_style);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,337 @@
+/// Elements deep a document may go, so a file of nothing but `<a>` can't
+/// run the parser out of stack.
+const MAX_DEPTH: usize = 128;
+
+/// Parsed XML element. Enough of XML for editor formats like Tiled's:
+/// elements, attributes, text, CDATA, the five predefined entities and
+/// numeric character references. Comments,
+/// the declaration and doctypes are skipped, namespaces are kept verbatim.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    /// Text directly inside the element, trimmed.
+    pub text: String,
+}
+
+impl Element {
+    /// Parses a document and returns its root element.
+    pub fn parse(text: &str) -> Result<Element, String> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+        parser.skip_misc()?;
+        let root = parser.element()?;
+        parser.skip_misc()?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(root)
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.0 == name)
+            .map(|a| a.1.as_str())
+    }
+
+    pub fn attr_f32(&self, name: &str) -> Option<f32> {
+        self.attr(name).and_then(|v| v.trim().parse().ok())
+    }
+
+    pub fn attr_u32(&self, name: &str) -> Option<u32> {
+        self.attr(name).and_then(|v| v.trim().parse().ok())
+    }
+
+    /// First child called `name`.
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Elements the parser is in, see `MAX_DEPTH`.
+    depth: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> String {
+        format!("xml: {} at character {}", message, self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Moves past the next `end`.
+    fn skip_past(&mut self, end: &str) -> Result<(), String> {
+        while !self.starts_with(end) {
+            if self.peek().is_none() {
+                return Err(self.error(&format!("expected '{}'", end)));
+            }
+            self.pos += 1;
+        }
+        self.pos += end.chars().count();
+        Ok(())
+    }
+
+    /// Whitespace, comments, the declaration and doctypes.
+    fn skip_misc(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || "_-.:".contains(c))
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("expected a name"));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("expected '{}'", c)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn element(&mut self) -> Result<Element, String> {
+        self.expect('<')?;
+        let name = self.name()?;
+        let mut element = Element {
+            name,
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+        };
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('/') => {
+                    self.pos += 1;
+                    self.expect('>')?;
+                    return Ok(element);
+                }
+                Some('>') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => {
+                    let key = self.name()?;
+                    self.expect('=')?;
+                    self.skip_whitespace();
+                    let quote = match self.peek() {
+                        Some(q) if q == '"' || q == '\'' => q,
+                        _ => return Err(self.error("expected a quoted value")),
+                    };
+                    self.pos += 1;
+                    let start = self.pos;
+                    while self.peek().is_some_and(|c| c != quote) {
+                        self.pos += 1;
+                    }
+                    if self.peek().is_none() {
+                        return Err(self.error("unterminated attribute"));
+                    }
+                    let raw: String = self.chars[start..self.pos].iter().collect();
+                    self.pos += 1;
+                    element.attributes.push((key, unescape(&raw)));
+                }
+                None => return Err(self.error("unexpected end")),
+            }
+        }
+
+        // plain text is unescaped a run at a time, CDATA is kept as it is
+        let mut text = String::new();
+        let mut raw = String::new();
+        loop {
+            if self.starts_with("</") {
+                self.pos += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(self.error(&format!("expected </{}>", element.name)));
+                }
+                self.expect('>')?;
+                text.push_str(&unescape(&raw));
+                element.text = text.trim().to_string();
+                return Ok(element);
+            } else if self.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.starts_with("<![CDATA[") {
+                self.pos += 9;
+                let start = self.pos;
+                self.skip_past("]]>")?;
+                text.push_str(&unescape(&std::mem::take(&mut raw)));
+                text.extend(&self.chars[start..self.pos - 3]);
+            } else if self.peek() == Some('<') {
+                self.depth += 1;
+                if self.depth >= MAX_DEPTH {
+                    return Err(self.error(&format!("nested over {} deep", MAX_DEPTH)));
+                }
+                element.children.push(self.element()?);
+                self.depth -= 1;
+            } else if let Some(c) = self.peek() {
+                raw.push(c);
+                self.pos += 1;
+            } else {
+                return Err(self.error(&format!("unclosed <{}>", element.name)));
+            }
+        }
+    }
+}
+
+/// Replaces entities in one pass, so what one turns into is never read as
+/// another: `&amp;lt;` is `&lt;`. Unknown ones are left as they are.
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let c = entity.and_then(|entity| match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "amp" => Some('&'),
+            _ => char_reference(entity),
+        });
+        match (c, entity) {
+            (Some(c), Some(entity)) => {
+                out.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `#65` or `#x41` as the character they stand for.
+fn char_reference(entity: &str) -> Option<char> {
+    let (digits, radix) = match entity.strip_prefix("#x") {
+        Some(hex) => (hex, 16),
+        None => (entity.strip_prefix('#')?, 10),
+    };
+    // from_str_radix would also take a sign
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    char::from_u32(u32::from_str_radix(digits, radix).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_a_document() {
+        let root = Element::parse(
+            "<?xml version=\"1.0\"?>\n<!DOCTYPE map>\n<!-- made by hand -->\n\
+             <map width='2' ns:kind=\"a&amp;b\">\n  <layer id=\"1\"/>\n  \
+             <data> 1,2 <!-- skip --> 3 </data>\n</map>\n",
+        )
+        .unwrap();
+        assert_eq!(root.name, "map");
+        assert_eq!(root.attr_u32("width"), Some(2));
+        assert_eq!(root.attr("ns:kind"), Some("a&b"));
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.child("layer").and_then(|l| l.attr("id")), Some("1"));
+        assert_eq!(root.child("data").unwrap().text, "1,2  3");
+    }
+
+    #[test]
+    fn replaces_entities_once() {
+        let root = Element::parse("<a t=\"&amp;lt;\">&lt;&gt;&quot;&apos;&amp;amp;</a>").unwrap();
+        assert_eq!(root.attr("t"), Some("&lt;"));
+        assert_eq!(root.text, "<>\"'&amp;");
+        let root = Element::parse("<a>&#65;&#x42;&#x1F600;</a>").unwrap();
+        assert_eq!(root.text, "AB😀");
+        // what isn't an entity is left alone
+        let root = Element::parse("<a>&nope; & &#+65; &#x; &#xD800;</a>").unwrap();
+        assert_eq!(root.text, "&nope; & &#+65; &#x; &#xD800;");
+    }
+
+    #[test]
+    fn keeps_cdata_as_it_is() {
+        let root = Element::parse("<a>x &amp; <![CDATA[<b> &amp; ]]]]> y</a>").unwrap();
+        assert_eq!(root.text, "x & <b> &amp; ]] y");
+        assert!(root.children.is_empty());
+    }
+
+    #[test]
+    fn stops_at_deep_nesting() {
+        let deep = "<a>".repeat(100_000);
+        assert!(Element::parse(&deep)
+            .is_err_and(|e| e.starts_with(&format!("xml: nested over {} deep", MAX_DEPTH))));
+        let fine = format!("{}{}", "<a>".repeat(MAX_DEPTH), "</a>".repeat(MAX_DEPTH));
+        assert!(Element::parse(&fine).is_ok());
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        for (text, error) in [
+            ("", "xml: expected '<' at character 0"),
+            ("<a></a> x", "xml: trailing characters at character 8"),
+            ("<a></a><b/>", "xml: trailing characters at character 7"),
+            ("<a></b>", "xml: expected </a> at character 6"),
+            ("<a>", "xml: unclosed <a> at character 3"),
+            ("<a x=1/>", "xml: expected a quoted value at character 5"),
+            ("<a x=\"1/>", "xml: unterminated attribute at character 9"),
+            ("<a><!-- open</a>", "xml: expected '-->' at character 16"),
+            (
+                "<a><![CDATA[ open</a>",
+                "xml: expected ']]>' at character 21",
+            ),
+            ("< a/>", "xml: expected a name at character 1"),
+        ] {
+            assert_eq!(Element::parse(text), Err(error.to_string()), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use raylib::prelude::*;
+use spacegame::font::TextStyle;
+use spacegame::heat::Heat;
+use spacegame::hud::HudItem;
+use spacegame::player::Player;
+use spacegame::render;
+use spacegame::renderer::{DrawCommand, Recorder};
+use spacegame::station::Bombs;
+use spacegame::text::Text2D;
+use spacegame::ui::UiElement;
+use spacegame::{Anchor, Base2D, DrawSystem, Health, UIBarStyle, World};
+
+/// Compares the recorded frame with `tests/golden/<name>.txt`, one draw
+/// command per line. Run with `UPDATE_GOLDEN=1` to write the files again
+/// after a change that's meant to look different, and commit them.
+fn check(name: &str, commands: &[DrawCommand]) {
+    let frame: String = commands.iter().map(|c| format!("{:?}\n", c)).collect();
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name]
+        .iter()
+        .collect::<PathBuf>()
+        .with_extension("txt");
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &frame).expect("could not write the golden frame");
+        return;
+    }
+    let golden = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "no golden frame at {}, run with UPDATE_GOLDEN=1 to write it",
+            path.display()
+        )
+    });
+    let mut golden_lines = golden.lines();
+    let mut frame_lines = frame.lines();
+    for line in 1.. {
+        match (golden_lines.next(), frame_lines.next()) {
+            (None, None) => return,
+            (expected, actual) if expected == actual => (),
+            (expected, actual) => panic!(
+                "{} differs from its golden frame at line {}\nexpected: {}\n  actual: {}",
+                name,
+                line,
+                expected.unwrap_or("<end of frame>"),
+                actual.unwrap_or("<end of frame>")
+            ),
+        }
+    }
+}
+
+fn record<T: DrawSystem>(world: &mut World) -> Vec<DrawCommand> {
+    let mut recorder = Recorder::new();
+    T::draw_system(world, &mut recorder);
+    recorder.commands
+}
+
+#[test]
+fn hud_layout() {
+    let mut world = World::new();
+    let player = world.new_entity();
+    let mut p = Player::new();
+    p.credits = 1250;
+    world.player_components.push((player, p));
+    world.bomb_components.push((player, Bombs::new(3)));
+
+    let boss = world.new_entity();
+    let mut base = Base2D::new(Vector2::new(300.0, 100.0), Vector2::new(64.0, 64.0));
+    base.name = "Mothership".to_string();
+    world.base_components.push((boss, base));
+    let mut health = Health::new(200);
+    health.health = 150;
+    health.bar_style = UIBarStyle::BOSS;
+    world.health_components.push((boss, health));
+
+    // laid out like the game's own HUD, at the end of its slide in
+    for (item, anchor, offset, size) in [
+        (
+            HudItem::Credits,
+            Anchor::TopRight,
+            Vector2::new(-10.0, 10.0),
+            Vector2::new(120.0, 20.0),
+        ),
+        (
+            HudItem::Bombs,
+            Anchor::TopLeft,
+            Vector2::new(10.0, 34.0),
+            Vector2::new(120.0, 16.0),
+        ),
+        (
+            HudItem::BossBar,
+            Anchor::TopCenter,
+            Vector2::new(0.0, 10.0),
+            Vector2::new(300.0, 12.0),
+        ),
+    ] {
+        let hud = world.new_entity();
+        world
+            .ui_components
+            .push((hud, UiElement::new(anchor, offset, size)));
+        world.hud_components.push((hud, item));
+    }
+    UiElement::layout_system(&mut world);
+
+    check("hud_layout", &record::<HudItem>(&mut world));
+}
+
+#[test]
+fn health_and_heat_bars() {
+    let mut world = World::new();
+    for (i, health) in [40, 25, 1].into_iter().enumerate() {
+        let ship = world.new_entity();
+        let pos = Vector2::new(60.0 + i as f32 * 120.0, 120.0);
+        world
+            .base_components
+            .push((ship, Base2D::new(pos, Vector2::new(32.0, 24.0))));
+        let mut h = Health::new(40);
+        h.health = health;
+        world.health_components.push((ship, h));
+        world
+            .heat_components
+            .push((ship, Heat::new(100.0, 10.0, 20.0)));
+    }
+    // a boss draws its bar in the HUD, not over itself
+    let boss = world.new_entity();
+    world.base_components.push((
+        boss,
+        Base2D::new(Vector2::new(400.0, 240.0), Vector2::new(64.0, 64.0)),
+    ));
+    let mut h = Health::new(500);
+    h.bar_style = UIBarStyle::BOSS;
+    world.health_components.push((boss, h));
+    render::cull_system(&mut world, None);
+
+    let mut commands = record::<Health>(&mut world);
+    commands.extend(record::<Heat>(&mut world));
+    check("health_and_heat_bars", &commands);
+}
+
+#[test]
+fn segmented_and_armored_bars() {
+    let mut world = World::new();
+    // into the hull, part way through the armor, and through it
+    let bars = [
+        (Health::new(50).segmented(5), 14),
+        (Health::new(30).armored(10), 6),
+        (Health::new(30).armored(10).segmented(3), 14),
+    ];
+    for (i, (mut health, damage)) in bars.into_iter().enumerate() {
+        let ship = world.new_entity();
+        let pos = Vector2::new(60.0 + i as f32 * 120.0, 120.0);
+        world
+            .base_components
+            .push((ship, Base2D::new(pos, Vector2::new(32.0, 24.0))));
+        health.take_damage(damage);
+        world.health_components.push((ship, health));
+    }
+    render::cull_system(&mut world, None);
+
+    check("segmented_and_armored_bars", &record::<Health>(&mut world));
+}
+
+#[test]
+fn text_anchors() {
+    let mut world = World::new();
+    let panel = world.new_entity();
+    world.base_components.push((
+        panel,
+        Base2D::new(Vector2::new(100.0, 60.0), Vector2::new(240.0, 160.0)),
+    ));
+    world.text_components.push((
+        panel,
+        Text2D::new("anchored", 10, Color::WHITE).anchored(Anchor::Center, Vector2::zero()),
+    ));
+    // every anchor of a box, each label hugging its own corner or edge
+    for anchor in Anchor::values() {
+        let label = world.new_entity();
+        world.base_components.push((
+            label,
+            Base2D::new(Vector2::new(100.0, 60.0), Vector2::new(240.0, 160.0)),
+        ));
+        let style = TextStyle::new(10.0, Color::LIME).aligned(anchor);
+        let text = Text2D::new(format!("{:?}", anchor), 10, Color::LIME)
+            .anchored(anchor, Vector2::zero())
+            .styled(style);
+        world.text_components.push((label, text));
+    }
+    render::cull_system(&mut world, None);
+
+    check("text_anchors", &record::<Text2D>(&mut world));
+}
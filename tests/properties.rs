@@ -0,0 +1,259 @@
+use raylib::prelude::*;
+use spacegame::camera::Camera;
+use spacegame::damage::DamageEvent;
+use spacegame::grid::SpatialGrid;
+use spacegame::group;
+use spacegame::movement::Velocity;
+use spacegame::pilot::Pilot;
+use spacegame::player::Player;
+use spacegame::plugin::GamePlugins;
+use spacegame::prefab::{Placement, Prefabs};
+use spacegame::projectile::Projectile;
+use spacegame::rng::Rng;
+use spacegame::team::Team;
+use spacegame::weapon::Weapon;
+use spacegame::UpdateSystem;
+use spacegame::{Base2D, BoundingBox2D, Engine, EntityID, Health, World};
+
+const DT: f32 = 1.0 / 60.0;
+/// Random worlds run, each for `FRAMES` fixed updates.
+const CASES: u64 = 64;
+const FRAMES: u32 = 300;
+
+fn spawn_ship(world: &mut World, rng: &mut Rng, team: Team) -> EntityID {
+    let ship = world.new_entity();
+    let pos = Vector2::new(rng.range(-400.0, 400.0), rng.range(-400.0, 400.0));
+    world
+        .base_components
+        .push((ship, Base2D::new(pos, Vector2::new(20.0, 20.0))));
+    world
+        .health_components
+        .push((ship, Health::new(rng.range(1.0, 40.0) as u16)));
+    world.velocity_components.push((ship, Velocity::new(1.0)));
+    world.team_components.push((ship, team));
+    world.weapon_components.push((ship, Weapon::new(4.0, 1)));
+    ship
+}
+
+/// Something random happening to the world: a ship, drone or stray bullet
+/// turning up, or damage landing on a random entity. Most frames nothing
+/// does.
+fn happen(world: &mut World, rng: &mut Rng) {
+    if rng.chance(0.05) {
+        let team = if rng.chance(0.5) {
+            Team::Hostile
+        } else {
+            Team::Friendly
+        };
+        let ship = spawn_ship(world, rng, team);
+        let pilot = Pilot::new(rng.range(40.0, 200.0), rng.next_u64());
+        world.pilot_components.push((ship, pilot));
+    }
+    if rng.chance(0.03) {
+        let pos = Vector2::new(rng.range(-400.0, 400.0), rng.range(-400.0, 400.0));
+        let placement = Placement::new(BoundingBox2D::new(pos.x, pos.y, 0.0, 0.0));
+        Prefabs::spawn(world, "Drone", &placement);
+    }
+    if rng.chance(0.1) && !world.base_components.is_empty() {
+        let owner = world.base_components[rng.index(world.base_components.len())].0;
+        let pos = Vector2::new(rng.range(-400.0, 400.0), rng.range(-400.0, 400.0));
+        let velocity = Vector2::new(rng.range(-600.0, 600.0), rng.range(-600.0, 600.0));
+        let damage = rng.range(0.0, 20.0) as u16;
+        Projectile::spawn(world, owner, pos, velocity, damage, rng.chance(0.2));
+    }
+    if rng.chance(0.2) && !world.health_components.is_empty() {
+        let target = world.health_components[rng.index(world.health_components.len())].0;
+        // now and then far more than anything has
+        let amount = if rng.chance(0.1) {
+            u16::MAX
+        } else {
+            rng.range(0.0, 30.0) as u16
+        };
+        world.damage_events.push(DamageEvent {
+            target,
+            source: None,
+            amount,
+            crit: false,
+        });
+    }
+}
+
+fn exists(world: &World, id: EntityID) -> bool {
+    world.base_components.iter().any(|b| b.0 == id)
+}
+
+/// What has to hold after every fixed update, whatever happened.
+fn check(world: &mut World, case: u64, frame: u32) {
+    let at = format!("case {} frame {}", case, frame);
+
+    for (id, health) in world.health_components.iter() {
+        assert!(
+            health.health <= health.max_health,
+            "{}: entity {} has {} of {} health",
+            at,
+            id,
+            health.health,
+            health.max_health
+        );
+    }
+
+    for (id, base) in world.base_components.iter() {
+        let b = base.bounds;
+        assert!(
+            [b.x1, b.y1, b.x2, b.y2].iter().all(|n| n.is_finite()),
+            "{}: entity {} has bounds {:?}",
+            at,
+            id,
+            b
+        );
+        assert!(
+            b.x1 <= b.x2 && b.y1 <= b.y2,
+            "{}: entity {} is inside out: {:?}",
+            at,
+            id,
+            b
+        );
+    }
+
+    for (id, camera) in world.camera_components.iter() {
+        if let Some(followed) = camera.follow {
+            assert!(
+                exists(world, followed),
+                "{}: camera {} follows {}, which is gone",
+                at,
+                id,
+                followed
+            );
+        }
+    }
+    for (id, pilot) in world.pilot_components.iter() {
+        if let Some(target) = pilot.target {
+            assert!(
+                exists(world, target),
+                "{}: pilot {} targets {}, which is gone",
+                at,
+                id,
+                target
+            );
+        }
+    }
+    for (id, player) in world.player_components.iter() {
+        if let Some(station) = player.docked_at {
+            assert!(
+                exists(world, station),
+                "{}: player {} is docked at {}, which is gone",
+                at,
+                id,
+                station
+            );
+        }
+    }
+
+    // one component per entity per storage, and never from the future
+    let mut ids: Vec<EntityID> = world.health_components.iter().map(|h| h.0).collect();
+    ids.sort();
+    let count = ids.len();
+    ids.dedup();
+    assert_eq!(ids.len(), count, "{}: an entity has two Health", at);
+    assert!(
+        world
+            .base_components
+            .iter()
+            .all(|b| b.0 <= world.last_entity),
+        "{}: a Base2D belongs to an id never handed out",
+        at
+    );
+
+    // the sorted storages join to exactly the entities that have both
+    world.sort_groups();
+    let joined: Vec<EntityID> = group::join(&world.base_components, &world.velocity_components)
+        .map(|(id, _, _)| id)
+        .collect();
+    let scanned: Vec<EntityID> = world
+        .base_components
+        .iter()
+        .map(|b| b.0)
+        .filter(|id| world.velocity_components.iter().any(|v| v.0 == *id))
+        .collect();
+    assert_eq!(
+        joined, scanned,
+        "{}: the Base2D and Velocity join is off",
+        at
+    );
+    for (id, base) in world.base_components.iter() {
+        let found = group::find(&world.base_components, *id);
+        assert!(
+            found.is_some_and(|i| world.base_components[i].1.bounds.x1 == base.bounds.x1),
+            "{}: entity {} isn't found where it is",
+            at,
+            id
+        );
+    }
+
+    // the spatial grid, rebuilt where things are now, holds each entity in
+    // the cells it covers and finds everything a scan of the world would
+    SpatialGrid::update_system(world, 0.0);
+    for (id, base) in world.base_components.iter() {
+        if world.grid.large().contains(id) {
+            continue;
+        }
+        let ((x1, x2), (y1, y2)) = SpatialGrid::cells_of(&base.bounds);
+        for y in y1..=y2 {
+            for x in x1..=x2 {
+                assert!(
+                    world.grid.cell(x, y).iter().filter(|c| *c == id).count() == 1,
+                    "{}: entity {} at {:?} isn't once in cell {} {}",
+                    at,
+                    id,
+                    base.bounds,
+                    x,
+                    y
+                );
+            }
+        }
+        let nearby = world.grid.query(&base.bounds);
+        for (other, _) in world
+            .base_components
+            .iter()
+            .filter(|o| o.1.bounds.overlaps(&base.bounds))
+        {
+            assert!(
+                nearby.contains(other),
+                "{}: the grid misses {} overlapping {}",
+                at,
+                other,
+                id
+            );
+        }
+    }
+}
+
+#[test]
+fn random_worlds_keep_their_invariants() {
+    let mut engine = Engine::new();
+    engine.add_plugin(GamePlugins);
+
+    for case in 0..CASES {
+        let mut rng = Rng::new(0xf022 ^ case);
+        let mut world = World::new();
+        world.config.seed = Some(case);
+
+        let player = spawn_ship(&mut world, &mut rng, Team::Friendly);
+        world.player_components.push((player, Player::new()));
+        let camera = world.new_entity();
+        world
+            .camera_components
+            .push((camera, Camera::following(Vector2::zero(), player)));
+
+        for frame in 0..FRAMES {
+            // random steering and firing, like a player mashing keys
+            if let Some((_, p)) = world.player_components.first_mut() {
+                p.steering = Vector2::new(rng.range(-1.0, 1.0), rng.range(-1.0, 1.0));
+                p.firing = rng.chance(0.5);
+            }
+            happen(&mut world, &mut rng);
+            engine.simulate(&mut world, 1, DT);
+            check(&mut world, case, frame);
+        }
+    }
+}
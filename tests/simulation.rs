@@ -0,0 +1,208 @@
+use raylib::prelude::*;
+use spacegame::cheats::GodMode;
+use spacegame::damage::{Damage, DamageEvent};
+use spacegame::engine::{self, Engine};
+use spacegame::movement::Velocity;
+use spacegame::pilot::Pilot;
+use spacegame::player::Player;
+use spacegame::plugin::GamePlugins;
+use spacegame::projectile::Projectile;
+use spacegame::station::Station;
+use spacegame::team::Team;
+use spacegame::trigger::Trigger;
+use spacegame::weapon::Weapon;
+use spacegame::{Base2D, EntityID, Health, UpdateSystem, World};
+
+const DT: f32 = 1.0 / 60.0;
+
+fn spawn_ship(world: &mut World, pos: Vector2, team: Team, health: u16) -> EntityID {
+    let ship = world.new_entity();
+    world
+        .base_components
+        .push((ship, Base2D::new(pos, Vector2::new(20.0, 20.0))));
+    world.health_components.push((ship, Health::new(health)));
+    world.velocity_components.push((ship, Velocity::new(1.0)));
+    world.team_components.push((ship, team));
+    ship
+}
+
+fn position(world: &World, id: EntityID) -> Option<Vector2> {
+    world
+        .base_components
+        .iter()
+        .find(|b| b.0 == id)
+        .map(|b| b.1.bounds.center())
+}
+
+#[test]
+fn velocity_moves_entities() {
+    let mut world = World::new();
+    let ship = world.new_entity();
+    world
+        .base_components
+        .push((ship, Base2D::new(Vector2::zero(), Vector2::new(10.0, 10.0))));
+    let mut velocity = Velocity::new(0.0);
+    velocity.velocity = Vector2::new(60.0, -30.0);
+    world.velocity_components.push((ship, velocity));
+
+    engine::simulate(&mut world, 60, DT);
+
+    let pos = position(&world, ship).unwrap();
+    assert!((pos.x - 65.0).abs() < 0.01, "x was {}", pos.x);
+    assert!((pos.y + 25.0).abs() < 0.01, "y was {}", pos.y);
+}
+
+#[test]
+fn projectiles_kill_hostiles() {
+    let mut world = World::new();
+    let gunner = spawn_ship(&mut world, Vector2::new(0.0, 0.0), Team::Friendly, 10);
+    let target = spawn_ship(&mut world, Vector2::new(0.0, -100.0), Team::Hostile, 2);
+    Projectile::spawn(
+        &mut world,
+        gunner,
+        Vector2::new(10.0, -10.0),
+        Vector2::new(0.0, -400.0),
+        2,
+        false,
+    );
+
+    engine::simulate(&mut world, 30, DT);
+
+    assert!(position(&world, target).is_none(), "target survived");
+    assert!(world.health_components.iter().any(|h| h.0 == gunner));
+    assert!(world.projectile_components.is_empty());
+}
+
+#[test]
+fn simulation_is_deterministic() {
+    let run = || {
+        let mut world = World::new();
+        for i in 0..4 {
+            let pos = Vector2::new(i as f32 * 60.0, 0.0);
+            let ship = spawn_ship(&mut world, pos, Team::Hostile, 6);
+            world.pilot_components.push((ship, Pilot::new(80.0, i)));
+        }
+        spawn_ship(&mut world, Vector2::new(90.0, 200.0), Team::Friendly, 6);
+        engine::simulate(&mut world, 240, DT);
+        world
+            .base_components
+            .iter()
+            .map(|b| (b.0, b.1.bounds.x1.to_bits(), b.1.bounds.y1.to_bits()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn damage_events_carry_what_was_dealt() {
+    let mut world = World::new();
+    let god = spawn_ship(&mut world, Vector2::zero(), Team::Friendly, 10);
+    world.god_mode_components.push((god, GodMode));
+    let target = spawn_ship(&mut world, Vector2::new(100.0, 0.0), Team::Hostile, 3);
+    let dead = spawn_ship(&mut world, Vector2::new(200.0, 0.0), Team::Hostile, 3);
+    world
+        .health_components
+        .iter_mut()
+        .find(|h| h.0 == dead)
+        .unwrap()
+        .1
+        .health = 0;
+    for (target, amount) in [(god, 5), (target, u16::MAX), (dead, 5)] {
+        world.damage_events.push(DamageEvent {
+            target,
+            source: None,
+            amount,
+            crit: false,
+        });
+    }
+
+    Damage::update_system(&mut world, DT);
+
+    let amounts: Vec<u16> = world.damage_events.iter().map(|e| e.amount).collect();
+    assert_eq!(amounts, vec![0, 3, 0]);
+    assert_eq!(world.death_events.len(), 1);
+    assert_eq!(world.death_events[0].overkill, u16::MAX - 3);
+}
+
+#[test]
+fn frame_stepping_holds_the_world_still() {
+    let mut world = World::new();
+    let gunner = spawn_ship(&mut world, Vector2::new(0.0, 0.0), Team::Friendly, 10);
+    let target = spawn_ship(&mut world, Vector2::new(0.0, -100.0), Team::Hostile, 5);
+    // inside the target, and still after the step moves it
+    Projectile::spawn(
+        &mut world,
+        gunner,
+        Vector2::new(10.0, -84.0),
+        Vector2::new(0.0, -400.0),
+        2,
+        false,
+    );
+    let zone = world.new_entity();
+    world.base_components.push((
+        zone,
+        Base2D::new(Vector2::new(-50.0, -150.0), Vector2::new(100.0, 100.0)),
+    ));
+    world.trigger_components.push((zone, Trigger::new()));
+
+    let mut engine = Engine::new();
+    engine.add_plugin(GamePlugins);
+    world.time.set_stepping(true);
+    let mut events = 0;
+    for _ in 0..30 {
+        engine.frame(&mut world, DT);
+        events += world.damage_events.len() + world.trigger_events.len();
+        world.end_frame();
+    }
+    assert_eq!(events, 0);
+    assert_eq!(world.projectile_components.len(), 1);
+
+    world.time.step();
+    engine.frame(&mut world, DT);
+    assert!(world.damage_events.iter().any(|e| e.target == target));
+    assert!(!world.trigger_events.is_empty());
+}
+
+/// Health the player has left after a hostile fighter had three seconds
+/// at them, sitting in a station or out in the open.
+fn player_health_after_an_attack(in_station: bool) -> u16 {
+    let mut engine = Engine::new();
+    engine.add_plugin(GamePlugins);
+    let mut world = World::new();
+    let player = spawn_ship(&mut world, Vector2::zero(), Team::Friendly, 100);
+    world.player_components.push((player, Player::new()));
+    if in_station {
+        let station = world.new_entity();
+        world.base_components.push((
+            station,
+            Base2D::new(Vector2::new(-20.0, -20.0), Vector2::new(60.0, 60.0)),
+        ));
+        world.trigger_components.push((station, Trigger::new()));
+        world.station_components.push((station, Station::new()));
+    }
+    let fighter = spawn_ship(&mut world, Vector2::new(0.0, -150.0), Team::Hostile, 10);
+    world.pilot_components.push((fighter, Pilot::new(120.0, 7)));
+    world.weapon_components.push((fighter, Weapon::new(4.0, 7)));
+
+    // the player coasts in and docks on the first frames
+    engine.simulate(&mut world, 2, DT);
+    assert_eq!(
+        Station::player_docked(&world),
+        in_station,
+        "player docked: {}",
+        Station::player_docked(&world)
+    );
+    engine.simulate(&mut world, 180, DT);
+    world
+        .health_components
+        .iter()
+        .find(|h| h.0 == player)
+        .map_or(0, |h| h.1.health)
+}
+
+#[test]
+fn docked_players_take_no_damage() {
+    assert!(player_health_after_an_attack(false) < 100);
+    assert_eq!(player_health_after_an_attack(true), 100);
+}